@@ -1,25 +1,92 @@
+use std::collections::HashSet;
 use std::io::{self, Stdout};
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant, SystemTime};
 
 use color_eyre::Result;
 use crossterm::{
-    event::{self, Event, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, KeyEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
+use ratatui::{
+    Terminal,
+    backend::{Backend, CrosstermBackend},
+    buffer::Cell,
+    layout::Rect,
+};
 
-use crate::config::Config;
-use crate::core::{Index, Vault};
+use crate::config::{AppState, Config, Workspace};
+use crate::core::crypt::VaultKey;
+use crate::core::export::{self, ConvertJob, ExportFormat};
+use crate::core::git::{self, SyncOutcome};
+use crate::core::note::word_count;
+use crate::core::query::Query;
+use crate::core::spell::SpellChecker;
+use crate::core::{Exclude, Index, Vault, snapshot, text, transclude, undo_log};
 use crate::input::InputHandler;
+use crate::ui::image::{GraphicsProtocol, ImagePreview};
 use crate::ui::theme::Theme;
-use crate::ui::{self, Focus};
+use crate::ui::{self, EditorMode, Focus, ToastLevel};
 
 /// State for the create note dialog
 pub struct CreateNoteState {
     pub filename: String,    // User-typed name (without .md)
     pub parent_dir: PathBuf, // Directory to create in
+    pub body: String,        // Text placed under the title (e.g. from the scratch buffer)
+}
+
+/// State for the move dialog
+pub struct MoveState {
+    pub path: PathBuf,       // Relative path of the note or directory to move
+    pub is_dir: bool,        // Whether the entry is a directory
+    pub destination: String, // Folder to move it into, relative to the root
+}
+
+/// State for the rename dialog
+pub struct RenameState {
+    pub path: PathBuf, // Relative path of the note or directory to rename
+    pub is_dir: bool,  // Whether the entry is a directory
+    pub name: String,  // New name, without .md for notes
+}
+
+/// Keeping a note's first heading and file name alike, offered after one
+/// of them changes
+pub enum TitleSync {
+    FileName(String), // Rename the file to this name, without .md
+    Heading(String),  // Change the first heading to this title
+}
+
+/// State for the title sync prompt
+pub struct TitleSyncState {
+    pub path: PathBuf, // Relative path of the note
+    pub offer: TitleSync,
+}
+
+/// State for the prompt shown before a key that would drop unsaved edits
+pub struct UnsavedState {
+    pub name: String,  // File name of the note with unsaved text
+    pub key: KeyEvent, // Key to carry out once the text is saved or discarded
+}
+
+/// Which save found the open note changed on disk, to carry on with if the
+/// user chooses to overwrite it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingSave {
+    Buffer,   // Ctrl+s, staying in edit mode
+    ExitEdit, // Leaving edit mode
+    InPlace,  // An edit made in read mode
+}
+
+/// State for the prompt shown when a save would overwrite changes another
+/// program made to the note
+pub struct DiskChangeState {
+    pub path: PathBuf,
+    pub name: String,
+    pub save: PendingSave,
+    pub diff: Option<Vec<String>>, // Disk against buffer, once asked for
+    pub scroll: u16,
 }
 
 /// State for the delete confirmation dialog
@@ -30,6 +97,65 @@ pub struct DeleteConfirmState {
     pub note_count: usize, // Number of notes inside (directories only)
 }
 
+/// State for the sync conflict dialog
+pub struct SyncConflictState {
+    pub files: Vec<PathBuf>, // Unmerged paths reported by git
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    Idle,
+    Running,
+    Synced,
+    Failed(String),
+}
+
+/// Bookkeeping for the periodic background git sync
+pub struct SyncState {
+    pub status: SyncStatus,
+    pub last_run: Option<Instant>,
+    pending: Option<Receiver<Result<SyncOutcome>>>,
+    /// A pull changed the vault while there were unsaved edits; it is
+    /// reloaded once they are saved or dropped
+    stale: bool,
+}
+
+/// Progress reported by the background export worker
+pub enum ExportEvent {
+    Converting {
+        index: usize,
+        total: usize,
+        title: String,
+    },
+    Finished {
+        written: Vec<PathBuf>,
+        failures: Vec<String>,
+    },
+}
+
+/// Progress reported by the thread loading the vault at startup
+pub enum LoadEvent {
+    Progress { loaded: usize, total: usize },
+    Finished(Result<Box<Vault>>),
+}
+
+/// A second note pinned beside the preview. The side with focus is always
+/// the main viewer; switching sides swaps the two viewer states.
+pub struct SplitView {
+    pub path: PathBuf,
+    pub viewer_state: ui::ViewerState,
+    pub scroll: u16,
+    pub on_left: bool, // whether the pinned note is drawn left of the main viewer
+}
+
+/// A note kept open in the tab bar, with the viewer position to return to.
+pub struct Tab {
+    pub path: PathBuf,
+    read_cursor: ui::Position,
+    selected_link: usize,
+    scroll: u16,
+}
+
 pub struct App {
     pub config: Config,
     pub theme: Theme,
@@ -41,24 +167,112 @@ pub struct App {
     pub viewer_scroll: u16,
     pub viewer_state: ui::ViewerState,
     pub backlinks_state: ui::BacklinksState,
-    pub show_help: bool,
+    pub help_state: Option<ui::HelpState>,
     pub create_note_state: Option<CreateNoteState>,
     pub delete_confirm_state: Option<DeleteConfirmState>,
+    pub move_state: Option<MoveState>,
+    pub rename_state: Option<RenameState>,
+    pub title_sync_state: Option<TitleSyncState>,
+    pub unsaved_state: Option<UnsavedState>,
+    pub disk_change_state: Option<DiskChangeState>,
     pub tag_filter_state: Option<ui::TagFilterState>,
     pub active_tag_filter: Option<String>,
+    /// Type-ahead filter narrowing the browser, from `f`
+    pub browser_filter: Option<ui::BrowserFilter>,
+    /// The `[smart_folders]` atop the browser, each followed by the notes
+    /// its query matches
+    pub smart_folders: Vec<crate::core::TreeEntry>,
     pub search_state: Option<ui::SearchState>,
     pub finder_state: Option<ui::FinderState>,
     pub graph_view_state: Option<ui::GraphViewState>,
     pub find_in_note_state: Option<ui::FindInNoteState>,
+    pub history_state: Option<ui::HistoryState>,
+    pub history_diff: Option<ui::DiffView>,
+    pub sync_state: SyncState,
+    pub sync_conflict_state: Option<SyncConflictState>,
+    pub export_menu_state: Option<ui::ExportMenuState>,
+    pub changelog_state: Option<ui::ChangelogState>,
+    /// Bookkeeping saved between runs, such as how often notes are opened
+    pub app_state: AppState,
+    /// The note whose visit was counted last, so staying on it counts once
+    last_visit: Option<PathBuf>,
+    pub timeline_state: Option<ui::TimelineState>,
+    pub agenda_state: Option<ui::AgendaState>,
+    pub hover_preview: ui::HoverPreview,
+    /// The text or CSV attachment selected in the browser, as last read
+    pub text_preview: ui::TextPreview,
+    pub split: Option<SplitView>,
+    /// Note whose preview stays on its last line as other programs append
+    pub following: Option<PathBuf>,
+    /// The open note changed on disk while the viewer held unsaved text
+    pub disk_changed: bool,
+    /// The selected note's word count, with its path and the modification
+    /// time it was counted at
+    status_words: Option<(PathBuf, SystemTime, usize)>,
+    pub maximized: bool,  // the focused pane fills the content area
+    pub raw_source: bool, // READ mode shows the note's markdown source
+    pub tabs: Vec<Tab>,
+    pub spell: Option<SpellChecker>,
+    pub spell_state: Option<ui::SpellState>,
+    pub theme_picker_state: Option<ui::ThemePickerState>,
+    pub workspace_picker_state: Option<ui::WorkspacePickerState>,
+    pub copy_menu_state: Option<ui::CopyMenuState>,
+    pub link_menu_state: Option<ui::LinkMenuState>,
+    pub pending_g: bool, // `g` was pressed in the viewer, waiting for `g`/`t`/`T`/`c`
+    pub pending_count: usize, // digits typed in the viewer before a motion, 0 when none
+    pub scratch: ui::ScratchState,
+    pub show_scratch: bool,
+    export_pending: Option<Receiver<ExportEvent>>,
+    load_pending: Option<Receiver<LoadEvent>>,
+    /// Notes read so far and in total while the vault loads
+    pub loading: Option<(usize, usize)>,
+    pub status_message: Option<String>,
+    pub toasts: ui::Toasts,
     pub viewer_area_height: u16,
+    pub browser_area_height: u16,
+    pub browser_area: Rect,
+    pub backlinks_area: Rect,
+    pub viewer_area: Rect,
+    pub breadcrumb_area: Rect,
+    pub image_preview: ImagePreview,
 }
 
 impl App {
-    pub fn new(config: Config) -> Result<Self> {
-        let vault = Vault::open(&config.vault.path)?;
+    pub fn new(config: Config, key: Option<VaultKey>) -> Result<Self> {
+        // The first frame draws an empty vault; notes appear once the
+        // loader thread has read them
+        let (exclude, exclude_error) = Self::vault_exclude(&config);
+        let vault = Vault::placeholder(&config.vault.path, key.clone());
+        let load_pending = Some(Self::load_vault(&config.vault.path, key, exclude));
         let index = Index::build(&vault);
         let browser_state = ui::BrowserState::new(&vault);
-        let theme = Theme::from_config(&config.ui);
+        let mut viewer_state = ui::ViewerState::new();
+        // Undo logs hold note text in the clear, so encrypted vaults keep
+        // their history in memory only
+        if !vault.is_encrypted() {
+            viewer_state.undo_dir = undo_log::vault_dir(&vault.root);
+        }
+        let (theme, theme_error) = Theme::from_config(&config.ui);
+        let mut app_state = AppState::load();
+        let changelog_state = Self::release_notes(&config, &mut app_state);
+        let image_preview = ImagePreview::new(
+            GraphicsProtocol::detect(&config.ui.image_preview),
+            config.ui.image_max_rows,
+        );
+
+        // A missing dictionary shouldn't stop the app from starting
+        let (spell, spell_error) = if config.spell.enabled {
+            match SpellChecker::load(
+                &config.spell.language,
+                &config.spell.dictionary_dirs,
+                &config.vault.path,
+            ) {
+                Ok(spell) => (Some(spell), None),
+                Err(e) => (None, Some(format!("Spell check off: {}", e))),
+            }
+        } else {
+            (None, None)
+        };
 
         Ok(Self {
             config,
@@ -69,34 +283,144 @@ impl App {
             should_quit: false,
             browser_state,
             viewer_scroll: 0,
-            viewer_state: ui::ViewerState::new(),
+            viewer_state,
             backlinks_state: ui::BacklinksState::new(),
-            show_help: false,
+            help_state: None,
             create_note_state: None,
             delete_confirm_state: None,
+            move_state: None,
+            rename_state: None,
+            title_sync_state: None,
+            unsaved_state: None,
+            disk_change_state: None,
             tag_filter_state: None,
             active_tag_filter: None,
+            browser_filter: None,
+            smart_folders: Vec::new(),
             search_state: None,
             finder_state: None,
             graph_view_state: None,
             find_in_note_state: None,
+            history_state: None,
+            history_diff: None,
+            sync_state: SyncState {
+                status: SyncStatus::Idle,
+                last_run: None,
+                pending: None,
+                stale: false,
+            },
+            sync_conflict_state: None,
+            export_menu_state: None,
+            changelog_state,
+            app_state,
+            last_visit: None,
+            timeline_state: None,
+            agenda_state: None,
+            hover_preview: ui::HoverPreview::new(),
+            text_preview: ui::TextPreview::default(),
+            split: None,
+            following: None,
+            disk_changed: false,
+            status_words: None,
+            maximized: false,
+            raw_source: false,
+            tabs: Vec::new(),
+            spell,
+            spell_state: None,
+            theme_picker_state: None,
+            workspace_picker_state: None,
+            copy_menu_state: None,
+            link_menu_state: None,
+            pending_g: false,
+            pending_count: 0,
+            scratch: ui::ScratchState::new(),
+            show_scratch: false,
+            export_pending: None,
+            load_pending,
+            loading: Some((0, 0)),
+            status_message: theme_error.or(exclude_error).or(spell_error),
+            toasts: ui::Toasts::default(),
             viewer_area_height: 0,
+            browser_area_height: 0,
+            browser_area: Rect::default(),
+            backlinks_area: Rect::default(),
+            viewer_area: Rect::default(),
+            breadcrumb_area: Rect::default(),
+            image_preview,
         })
     }
 
+    /// Release notes to show on the first launch of a new version, plus any
+    /// config migrations. Fresh installs start without them.
+    fn release_notes(config: &Config, state: &mut AppState) -> Option<ui::ChangelogState> {
+        let current = env!("CARGO_PKG_VERSION");
+        let previous = state.last_version.clone();
+        let upgraded = previous.as_deref() != Some(current);
+        if upgraded {
+            state.last_version = Some(current.to_string());
+            let _ = state.save();
+        }
+
+        let report = &config.load_report;
+        if report.created || (!upgraded && report.migrations.is_empty()) {
+            return None;
+        }
+
+        let mut notes = ui::ChangelogState::new(
+            previous.as_deref(),
+            report.migrations.clone(),
+            report.backup.clone(),
+        );
+        if !upgraded {
+            // Same version with a migrated config: only report the migration
+            notes.releases.clear();
+        }
+        (!notes.is_empty()).then_some(notes)
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let mut terminal = self.setup_terminal()?;
 
         let result = self.event_loop(&mut terminal).await;
 
         self.restore_terminal(&mut terminal)?;
+        let _ = self.app_state.save();
+
+        if self.config.snapshot.enabled {
+            self.snapshot_vault();
+        }
         result
     }
 
+    /// Archives the vault after the terminal is restored. Failures are
+    /// reported but never stop the app from exiting.
+    fn snapshot_vault(&self) {
+        let settings = &self.config.snapshot;
+        let created = snapshot::create(
+            &self.vault.root,
+            &settings.dir,
+            settings.changed_only,
+            settings.keep,
+        )
+        .and_then(|created| {
+            snapshot::prune(&self.vault.root, &settings.dir, settings.keep)?;
+            Ok(created)
+        });
+
+        match created {
+            Ok(Some((path, _))) => eprintln!("tui-jot: snapshot saved to {}", path.display()),
+            Ok(None) => {}
+            Err(e) => eprintln!("tui-jot: snapshot failed: {}", e),
+        }
+    }
+
     fn setup_terminal(&self) -> Result<Terminal<CrosstermBackend<Stdout>>> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
+        if self.config.ui.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
         Ok(terminal)
@@ -104,7 +428,11 @@ impl App {
 
     fn restore_terminal(&self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
         disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        execute!(
+            terminal.backend_mut(),
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        )?;
         Ok(())
     }
 
@@ -112,17 +440,42 @@ impl App {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     ) -> Result<()> {
+        let mut dirty = true;
         loop {
-            terminal.draw(|frame| ui::render(frame, self))?;
+            // Redraw only when something changed, rather than on every wake-up
+            if dirty {
+                self.sync_tabs();
+                let frame = terminal.draw(|frame| ui::render(frame, self))?;
+                let uncovered = self.image_preview.uncovered_cells(frame.buffer);
+                self.draw_images(terminal, &uncovered)?;
+                dirty = false;
+            }
 
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
+            let hovering = self.hover_preview.waiting().is_some();
+            if event::poll(self.poll_timeout())? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
                         InputHandler::handle(self, key, terminal)?;
+                        dirty = true;
                     }
+                    Event::Key(_) => {}
+                    Event::Mouse(mouse) => {
+                        InputHandler::handle_mouse(self, mouse);
+                        dirty = true;
+                    }
+                    _ => dirty = true,
                 }
+            } else if hovering {
+                // The hover delay ran out, so the preview can appear
+                dirty = true;
             }
 
+            dirty |= self.tick_load();
+            dirty |= self.tick_sync()?;
+            dirty |= self.tick_export();
+            dirty |= self.toasts.prune();
+            dirty |= self.tick_disk();
+
             if self.should_quit {
                 break;
             }
@@ -131,35 +484,574 @@ impl App {
         Ok(())
     }
 
+    /// How long to wait for input before checking on background work. Short
+    /// while something is running, a hover preview is pending or a toast is
+    /// due to expire, so progress shows promptly; otherwise long enough that
+    /// an idle app stays asleep.
+    fn poll_timeout(&self) -> Duration {
+        if let Some(remaining) = self.hover_preview.waiting() {
+            return remaining;
+        }
+        let busy = self.load_pending.is_some()
+            || self.sync_state.pending.is_some()
+            || self.export_pending.is_some();
+        let timeout = if busy {
+            Duration::from_millis(100)
+        } else {
+            Duration::from_secs(1)
+        };
+        // Wake up in time to take down the next toast
+        match self.toasts.next_expiry() {
+            Some(expiry) => timeout.min(expiry),
+            None => timeout,
+        }
+    }
+
+    /// Draws preview images over the frame just rendered, when they moved,
+    /// first writing `uncovered` back over the ones going away.
+    fn draw_images(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        uncovered: &[(u16, u16, Cell)],
+    ) -> Result<()> {
+        if !self.image_preview.is_stale() {
+            return Ok(());
+        }
+        let backend = terminal.backend_mut();
+        backend.draw(uncovered.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+        self.image_preview.flush(backend)
+    }
+
+    /// Whether a popup or dialog covers part of the main panes.
+    pub fn has_overlay(&self) -> bool {
+        self.help_state.is_some()
+            || self.create_note_state.is_some()
+            || self.delete_confirm_state.is_some()
+            || self.move_state.is_some()
+            || self.rename_state.is_some()
+            || self.title_sync_state.is_some()
+            || self.unsaved_state.is_some()
+            || self.disk_change_state.is_some()
+            || self.sync_conflict_state.is_some()
+            || self.tag_filter_state.is_some()
+            || self.search_state.is_some()
+            || self.finder_state.is_some()
+            || self.graph_view_state.is_some()
+            || self.history_state.is_some()
+            || self.export_menu_state.is_some()
+            || self.changelog_state.is_some()
+            || self.timeline_state.is_some()
+            || self.agenda_state.is_some()
+            || self.spell_state.is_some()
+            || self.theme_picker_state.is_some()
+            || self.workspace_picker_state.is_some()
+            || self.copy_menu_state.is_some()
+            || self.link_menu_state.is_some()
+            || self.show_scratch
+    }
+
+    /// Collects a finished background sync and starts the next one when due.
+    /// Syncs are skipped while editing so a pull never races unsaved changes.
+    /// Returns whether the screen needs redrawing.
+    fn tick_sync(&mut self) -> Result<bool> {
+        if let Some(rx) = &self.sync_state.pending {
+            let result = match rx.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return Ok(false),
+                Err(TryRecvError::Disconnected) => {
+                    Err(color_eyre::eyre::eyre!("sync worker exited unexpectedly"))
+                }
+            };
+            self.sync_state.pending = None;
+            self.sync_state.last_run = Some(Instant::now());
+
+            match result {
+                Ok(SyncOutcome::Synced { pulled }) => {
+                    self.sync_state.status = SyncStatus::Synced;
+                    self.sync_state.stale |= pulled;
+                }
+                Ok(SyncOutcome::Conflicts(files)) => {
+                    self.sync_state.status = SyncStatus::Failed("merge conflict".to_string());
+                    self.sync_conflict_state = Some(SyncConflictState { files });
+                }
+                Err(e) => {
+                    self.sync_state.status = SyncStatus::Failed(e.to_string());
+                }
+            }
+            return Ok(true);
+        }
+
+        // Reloading now would replace the unsaved buffer with the pulled note
+        let editing = self.viewer_state.mode == EditorMode::Edit || self.viewer_state.dirty;
+        if self.sync_state.stale && !editing {
+            self.sync_state.stale = false;
+            self.refresh_vault()?;
+            return Ok(true);
+        }
+
+        let sync = &self.config.sync;
+        let due = self
+            .sync_state
+            .last_run
+            .is_none_or(|t| t.elapsed() >= Duration::from_secs(sync.interval_secs));
+
+        // git add, commit, pull and push all write to a vault opened read-only
+        if sync.enabled
+            && !self.config.vault.read_only
+            && due
+            && !editing
+            && self.sync_conflict_state.is_none()
+        {
+            let (tx, rx) = mpsc::channel();
+            let root = self.vault.root.clone();
+            let remote = sync.remote.clone();
+            let message = sync.commit_message.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(git::sync(&root, &remote, &message));
+            });
+            self.sync_state.pending = Some(rx);
+            self.sync_state.status = SyncStatus::Running;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
     /// Returns visible entries filtered by the active tag filter (if any).
     /// When a tag filter is active, only shows notes that have that tag
-    /// (plus their parent directories to preserve tree structure).
+    /// (plus their parent directories to preserve tree structure). The
+    /// browser's type-ahead filter narrows them further by name, looking
+    /// inside collapsed folders too.
     pub fn filtered_visible_entries(&self) -> Vec<&crate::core::TreeEntry> {
-        let entries = self.vault.visible_entries();
-
-        let tag = match &self.active_tag_filter {
-            Some(tag) => tag,
-            None => return entries,
+        let filter = self
+            .browser_filter
+            .as_ref()
+            .filter(|filter| !filter.query.is_empty());
+        let mut entries = match filter {
+            Some(_) => self.vault.tree.iter().collect(),
+            None => self.vault.visible_entries(),
         };
 
-        let matching_paths = match self.index.notes_with_tag(tag) {
-            Some(paths) => paths,
-            None => return Vec::new(),
-        };
+        if let Some(tag) = &self.active_tag_filter {
+            let Some(matching_paths) = self.index.notes_with_tag(tag) else {
+                return Vec::new();
+            };
+            entries = keep_with_parents(entries, |entry| matching_paths.contains(&entry.path));
+        }
 
-        // Include entries whose path matches the tag, or directories that are
-        // ancestors of matching entries
-        entries
-            .into_iter()
-            .filter(|entry| {
+        if let Some(filter) = filter {
+            entries = keep_with_parents(entries, |entry| filter.matches(&entry.name));
+        }
+
+        // Smart folders stand apart from the tree, so filters pass them by
+        if self.active_tag_filter.is_none() && filter.is_none() {
+            let mut open = false;
+            let smart = self.smart_folders.iter().filter(|entry| {
                 if entry.is_dir {
-                    // Keep directory if any matching note is under it
-                    matching_paths.iter().any(|p| p.starts_with(&entry.path))
-                } else {
-                    matching_paths.contains(&entry.path)
+                    open = entry.expanded;
+                }
+                entry.is_dir || open
+            });
+            entries.splice(0..0, smart);
+        }
+
+        entries
+    }
+
+    /// Rebuilds the index from the vault, and with it the smart folders.
+    pub fn rebuild_index(&mut self) {
+        self.index = Index::build(&self.vault);
+        self.refresh_smart_folders();
+    }
+
+    /// Lists the notes each `[smart_folders]` query matches, along with the
+    /// saved searches when `ui.saved_searches_in_browser` is on. Folders
+    /// that were open stay open.
+    pub fn refresh_smart_folders(&mut self) {
+        let open: HashSet<String> = self
+            .smart_folders
+            .iter()
+            .filter(|entry| entry.is_dir && entry.expanded)
+            .map(|entry| entry.name.clone())
+            .collect();
+
+        let mut folders: Vec<(&str, &str)> = self
+            .config
+            .smart_folders
+            .iter()
+            .map(|(name, query)| (name.as_str(), query.as_str()))
+            .collect();
+        folders.sort_by(|a, b| text::collate(a.0, b.0));
+        if self.config.ui.saved_searches_in_browser {
+            folders.extend(
+                self.app_state
+                    .saved_searches(&self.vault.root)
+                    .iter()
+                    .map(|search| (search.name.as_str(), search.query.as_str())),
+            );
+        }
+
+        let mut entries = Vec::new();
+        for (name, query) in folders {
+            let query = Query::parse(query);
+            let mut notes: Vec<&crate::core::Note> = self
+                .vault
+                .notes
+                .values()
+                // Bodies are only read when there is text to find, and then
+                // without crowding the note cache, as this runs on every save
+                .filter(|note| {
+                    query.matches(&note.path, &self.index)
+                        && (query.text.is_empty() || query.matches_text(&note.scan()))
+                })
+                .collect();
+            notes.sort_by(|a, b| text::collate(&a.title, &b.title));
+
+            entries.push(crate::core::TreeEntry {
+                path: PathBuf::from(name),
+                name: name.to_string(),
+                is_dir: true,
+                depth: 0,
+                expanded: open.contains(name),
+                is_virtual: true,
+            });
+            entries.extend(notes.into_iter().map(|note| {
+                crate::core::TreeEntry {
+                    path: note.path.clone(),
+                    name: note
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    is_dir: false,
+                    depth: 1,
+                    expanded: false,
+                    is_virtual: true,
                 }
+            }));
+        }
+        self.smart_folders = entries;
+    }
+
+    /// Opens or closes the smart folder called `name`.
+    pub fn toggle_smart_folder(&mut self, name: &Path) {
+        if let Some(entry) = self
+            .smart_folders
+            .iter_mut()
+            .find(|entry| entry.is_dir && entry.path == name)
+        {
+            entry.expanded = !entry.expanded;
+        }
+    }
+
+    /// Selects a note in the browser, expanding collapsed folders above it,
+    /// and shows it in the preview. Returns false if the browser can't show
+    /// it, e.g. because the tag filter hides it.
+    pub fn select_note(&mut self, path: &Path) -> bool {
+        self.vault.reveal(path);
+        let Some(index) = self
+            .filtered_visible_entries()
+            .iter()
+            .position(|e| e.path == path && !e.is_virtual)
+        else {
+            return false;
+        };
+
+        self.browser_state.select(index);
+        self.viewer_scroll = 0;
+        self.backlinks_state.reset();
+        if let Some(note) = self.vault.get_note(path) {
+            self.viewer_state.update_links(note);
+        }
+        true
+    }
+
+    /// Selects the folder `dir` in the browser, opening the folders above
+    /// it, and moves focus there. The vault root goes to the top of the tree.
+    pub fn focus_folder(&mut self, dir: &Path) {
+        self.focus = Focus::Browser;
+        if dir.as_os_str().is_empty() {
+            self.browser_state.move_to_top();
+            return;
+        }
+        self.vault.reveal(dir);
+        let index = self
+            .filtered_visible_entries()
+            .iter()
+            .position(|e| e.is_dir && !e.is_virtual && e.path == dir);
+        match index {
+            Some(index) => self.browser_state.select(index),
+            None => self.status_message = Some("That folder is hidden by the filter".to_string()),
+        }
+    }
+
+    /// What the vault scan leaves out: `vault.exclude`, plus the vault's
+    /// ignore files unless `vault.respect_gitignore` is off. Also describes
+    /// the first pattern that didn't parse.
+    pub fn vault_exclude(config: &Config) -> (Exclude, Option<String>) {
+        let (exclude, error) = Exclude::new(&config.vault.exclude);
+        if config.vault.respect_gitignore {
+            (exclude.with_ignore_files(&config.vault.path), error)
+        } else {
+            (exclude, error)
+        }
+    }
+
+    /// The text area of the preview pane, inside its border and padding.
+    pub fn viewer_content_area(&self) -> Rect {
+        ui::viewer::content_area(self.viewer_area, self.config.ui.pane("viewer").padding)
+    }
+
+    /// Pins the selected note in a split beside the preview, replacing any
+    /// note already pinned there.
+    pub fn open_split(&mut self) {
+        let Some(note) = self.selected_note() else {
+            self.status_message = Some("Select a note to open in a split".to_string());
+            return;
+        };
+        let message = format!("Opened {} in a split", note.title);
+        self.split = Some(self.split_view(note));
+        self.status_message = Some(message);
+    }
+
+    fn split_view(&self, note: &crate::core::Note) -> SplitView {
+        let mut viewer_state = ui::ViewerState::new();
+        viewer_state.undo_dir = self.viewer_state.undo_dir.clone();
+        viewer_state.update_links(note);
+        SplitView {
+            path: note.path.clone(),
+            viewer_state,
+            scroll: 0,
+            on_left: false,
+        }
+    }
+
+    /// The panes, open notes and filters as they are now, to save as `name`.
+    pub fn workspace(&self, name: &str) -> Workspace {
+        Workspace {
+            name: name.to_string(),
+            layout: self.config.ui.layout,
+            tree_width: self.config.ui.tree_width,
+            show_backlinks: self.config.ui.show_backlinks,
+            focus: self.focus,
+            maximized: self.maximized,
+            note: self.selected_note().map(|note| note.path.clone()),
+            tabs: self.tabs.iter().map(|tab| tab.path.clone()).collect(),
+            split: self.split.as_ref().map(|split| split.path.clone()),
+            split_on_left: self.split.as_ref().is_some_and(|split| split.on_left),
+            tag_filter: self.active_tag_filter.clone(),
+            browser_filter: self
+                .browser_filter
+                .as_ref()
+                .map(|filter| filter.query.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Arranges the screen the way `workspace` was saved, leaving out notes
+    /// deleted since.
+    pub fn apply_workspace(&mut self, workspace: &Workspace) {
+        self.config.ui.layout = workspace.layout;
+        self.config.ui.tree_width = workspace.tree_width;
+        self.config.ui.show_backlinks = workspace.show_backlinks;
+        self.active_tag_filter = workspace.tag_filter.clone();
+        self.browser_filter = (!workspace.browser_filter.is_empty()).then(|| ui::BrowserFilter {
+            query: workspace.browser_filter.clone(),
+            typing: false,
+        });
+
+        self.tabs = workspace
+            .tabs
+            .iter()
+            .filter(|path| self.vault.notes.contains_key(*path))
+            .map(|path| Tab {
+                path: path.clone(),
+                read_cursor: ui::Position { line: 0, col: 0 },
+                selected_link: 0,
+                scroll: 0,
+            })
+            .collect();
+        let split = workspace
+            .split
+            .as_deref()
+            .and_then(|path| self.vault.get_note(path))
+            .map(|note| SplitView {
+                on_left: workspace.split_on_left,
+                ..self.split_view(note)
+            });
+        self.split = split;
+
+        self.browser_state.move_to_top();
+        self.viewer_scroll = 0;
+        if let Some(path) = &workspace.note {
+            self.select_note(path);
+        }
+        self.focus = workspace.focus;
+        self.maximized = workspace.maximized;
+    }
+
+    /// Moves focus to the other side of the split: the pinned note becomes
+    /// the main viewer and the current note is pinned in its place.
+    pub fn switch_split(&mut self) {
+        let Some(current) = self.selected_note().map(|n| n.path.clone()) else {
+            return;
+        };
+        let Some(split) = self.split.take() else {
+            return;
+        };
+        let current_scroll = self.viewer_scroll;
+        if !self.select_note(&split.path) {
+            self.status_message = Some(if self.vault.get_note(&split.path).is_some() {
+                "The other split is hidden by the tag filter".to_string()
+            } else {
+                "The other split's note no longer exists".to_string()
+            });
+            self.split = Some(split);
+            return;
+        }
+
+        // select_note reset the viewer; bring back the other side's cursor and scroll
+        let SplitView {
+            path,
+            mut viewer_state,
+            scroll,
+            on_left,
+        } = split;
+        if let Some(note) = self.vault.get_note(&path) {
+            viewer_state.reload(note);
+        }
+        viewer_state.clipboard = self.viewer_state.clipboard.take();
+
+        let previous = std::mem::replace(&mut self.viewer_state, viewer_state);
+        self.viewer_scroll = scroll;
+        self.split = Some(SplitView {
+            path: current,
+            viewer_state: previous,
+            scroll: current_scroll,
+            on_left: !on_left,
+        });
+        self.focus = Focus::Viewer;
+    }
+
+    /// Keeps the tab bar in step with the viewer: a note opened in the viewer
+    /// gets a tab, and the tab in view remembers its cursor and scroll.
+    fn sync_tabs(&mut self) {
+        self.tabs
+            .retain(|tab| self.vault.notes.contains_key(&tab.path));
+
+        // Browsing the tree previews notes without opening them
+        if self.focus != Focus::Viewer || self.viewer_state.mode != EditorMode::Read {
+            return;
+        }
+        let Some(path) = self.selected_note().map(|n| n.path.clone()) else {
+            return;
+        };
+        if self.last_visit.as_ref() != Some(&path) {
+            self.app_state.record_visit(&self.vault.root.join(&path));
+            self.last_visit = Some(path.clone());
+        }
+        let index = match self.tabs.iter().position(|tab| tab.path == path) {
+            Some(index) => index,
+            None => {
+                self.tabs.push(Tab {
+                    path,
+                    read_cursor: ui::Position { line: 0, col: 0 },
+                    selected_link: 0,
+                    scroll: 0,
+                });
+                self.tabs.len() - 1
+            }
+        };
+
+        let tab = &mut self.tabs[index];
+        tab.read_cursor = self.viewer_state.read_cursor.clone();
+        tab.selected_link = self.viewer_state.selected_link;
+        tab.scroll = self.viewer_scroll;
+    }
+
+    /// Whether follow mode is on for the note in the viewer.
+    pub fn is_following(&self) -> bool {
+        self.following.is_some() && self.following == self.viewer_state.current_note_path
+    }
+
+    /// Whether the viewer holds changes to the selected note that aren't on
+    /// disk yet.
+    pub fn buffer_unsaved(&self) -> bool {
+        self.viewer_state.dirty
+            && self.selected_note().is_some_and(|note| {
+                self.viewer_state.current_note_path.as_ref() == Some(&note.path)
             })
-            .collect()
+    }
+
+    /// Index of the tab showing the selected note, if it has one.
+    pub fn active_tab(&self) -> Option<usize> {
+        let path = &self.selected_note()?.path;
+        self.tabs.iter().position(|tab| &tab.path == path)
+    }
+
+    /// Switches to the next tab, or the previous one when `forward` is false.
+    pub fn cycle_tab(&mut self, forward: bool) {
+        let len = self.tabs.len();
+        if len == 0 {
+            return;
+        }
+        let index = match self.active_tab() {
+            Some(i) if forward => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+            None => 0,
+        };
+        self.show_tab(index);
+    }
+
+    /// Closes the tab in view and moves to its neighbour.
+    pub fn close_tab(&mut self) {
+        let Some(index) = self.active_tab() else {
+            return;
+        };
+        self.tabs.remove(index);
+        if self.tabs.is_empty() {
+            self.focus = Focus::Browser;
+        } else {
+            self.show_tab(index.min(self.tabs.len() - 1));
+        }
+    }
+
+    fn show_tab(&mut self, index: usize) {
+        let tab = &self.tabs[index];
+        let (path, read_cursor, selected_link, scroll) = (
+            tab.path.clone(),
+            tab.read_cursor.clone(),
+            tab.selected_link,
+            tab.scroll,
+        );
+        if !self.select_note(&path) {
+            self.status_message = Some("That tab is hidden by the tag filter".to_string());
+            return;
+        }
+        self.viewer_state.read_cursor = self.viewer_state.clamp_position(&read_cursor);
+        self.viewer_state.selected_link =
+            selected_link.min(self.viewer_state.visible_links.len().saturating_sub(1));
+        self.viewer_scroll = scroll;
+        self.focus = Focus::Viewer;
+    }
+
+    /// The selected browser entry when it is an attachment rather than a note.
+    pub fn selected_attachment(&self) -> Option<&crate::core::TreeEntry> {
+        self.filtered_visible_entries()
+            .get(self.browser_state.selected)
+            .copied()
+            .filter(|e| e.is_attachment())
+    }
+
+    /// The text of a note about to be created, with a `created:` field when
+    /// `editor.stamp_created` is on.
+    pub fn new_note_content(&self, content: String) -> String {
+        if self.config.editor.stamp_created {
+            let now = chrono::Local::now().format("%Y-%m-%dT%H:%M").to_string();
+            crate::core::frontmatter::with_field(&content, "created", &now)
+        } else {
+            content
+        }
     }
 
     pub fn selected_note(&self) -> Option<&crate::core::Note> {
@@ -170,7 +1062,179 @@ impl App {
             .and_then(|entry| self.vault.get_note(&entry.path))
     }
 
+    /// Word count of the selected note for the status bar. Expanding
+    /// transclusions reads every embedded note, so the count is kept until
+    /// the note, or with transclusions on any note, changes.
+    pub fn selected_word_count(&mut self) -> Option<usize> {
+        let note = self.selected_note()?;
+        let expand = self.config.vault.expand_transclusions;
+        let stamp = if expand {
+            self.vault
+                .notes
+                .values()
+                .map(|n| n.modified)
+                .max()
+                .unwrap_or(note.modified)
+        } else {
+            note.modified
+        };
+        if let Some((path, at, words)) = &self.status_words
+            && *path == note.path
+            && *at == stamp
+        {
+            return Some(*words);
+        }
+
+        let path = note.path.clone();
+        let words = if expand {
+            word_count(&transclude::expand(&self.vault, note))
+        } else {
+            word_count(&note.content())
+        };
+        self.status_words = Some((path, stamp, words));
+        Some(words)
+    }
+
+    /// What the backlinks pane lists for the selected note: the notes linking
+    /// to it, or on the outgoing tab the targets of its links in order. Each
+    /// has a name and the note's path, which broken links lack.
+    pub fn linked_notes(&self) -> Vec<(String, Option<PathBuf>)> {
+        let Some(note) = self.selected_note() else {
+            return Vec::new();
+        };
+        let stem = |path: &Path| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        match self.backlinks_state.tab {
+            ui::LinksTab::Backlinks => {
+                let mut sources: Vec<&crate::core::Note> = self
+                    .index
+                    .get_backlinks(&note.path)
+                    .iter()
+                    .filter_map(|path| self.vault.get_note(path))
+                    .collect();
+                match self.backlinks_state.sort {
+                    ui::BacklinkSort::Name => {
+                        sources.sort_by(|a, b| text::collate(&stem(&a.path), &stem(&b.path)))
+                    }
+                    ui::BacklinkSort::Recent => {
+                        sources.sort_by_key(|source| std::cmp::Reverse(source.modified))
+                    }
+                    ui::BacklinkSort::Count => sources.sort_by_cached_key(|source| {
+                        let count = source
+                            .links
+                            .iter()
+                            .filter(|link| {
+                                self.vault
+                                    .resolve_link(&link.target)
+                                    .is_some_and(|target| target.path == note.path)
+                            })
+                            .count();
+                        std::cmp::Reverse(count)
+                    }),
+                }
+                sources
+                    .into_iter()
+                    .map(|source| (stem(&source.path), Some(source.path.clone())))
+                    .collect()
+            }
+            ui::LinksTab::Outgoing => {
+                let mut seen = HashSet::new();
+                note.links
+                    .iter()
+                    .filter(|link| seen.insert(link.target.to_lowercase()))
+                    .map(|link| {
+                        let path = self
+                            .vault
+                            .resolve_link(&link.target)
+                            .map(|n| n.path.clone());
+                        (link.target.clone(), path)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The history diff for the currently selected note, if one is open.
+    pub fn active_history_diff(&self) -> Option<&ui::DiffView> {
+        let diff = self.history_diff.as_ref()?;
+        let note = self.selected_note()?;
+        (diff.note_path == note.path).then_some(diff)
+    }
+
+    /// Reads the vault on a worker thread, reporting progress as it goes.
+    fn load_vault(path: &Path, key: Option<VaultKey>, exclude: Exclude) -> Receiver<LoadEvent> {
+        let path = path.to_path_buf();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let vault = Vault::open_with_progress(&path, key, exclude, |loaded, total| {
+                // A message per note would only flood the channel
+                if loaded % 50 == 0 || loaded == total {
+                    let _ = tx.send(LoadEvent::Progress { loaded, total });
+                }
+            });
+            let _ = tx.send(LoadEvent::Finished(vault.map(Box::new)));
+        });
+        rx
+    }
+
+    /// Swaps in the vault once the startup load finishes. Returns whether
+    /// there is new progress to show.
+    fn tick_load(&mut self) -> bool {
+        let Some(rx) = &self.load_pending else {
+            return false;
+        };
+
+        let mut changed = false;
+        loop {
+            match rx.try_recv() {
+                Ok(LoadEvent::Progress { loaded, total }) => {
+                    self.loading = Some((loaded, total));
+                    changed = true;
+                }
+                Ok(LoadEvent::Finished(result)) => {
+                    self.load_pending = None;
+                    self.loading = None;
+                    match result {
+                        Ok(vault) => self.install_vault(*vault),
+                        Err(e) => self.toasts.push(
+                            ToastLevel::Error,
+                            format!("Could not load the vault: {}", e),
+                        ),
+                    }
+                    return true;
+                }
+                Err(TryRecvError::Empty) => return changed,
+                Err(TryRecvError::Disconnected) => {
+                    self.toasts
+                        .push(ToastLevel::Error, "Could not load the vault: loader exited");
+                    self.load_pending = None;
+                    self.loading = None;
+                    return true;
+                }
+            }
+        }
+    }
+
     pub fn refresh_vault(&mut self) -> Result<()> {
+        // Anything the startup load would bring in is read here too
+        self.load_pending = None;
+        self.loading = None;
+
+        let vault = Vault::open(
+            &self.config.vault.path,
+            self.vault.key().cloned(),
+            Self::vault_exclude(&self.config).0,
+        )?;
+        self.install_vault(vault);
+        Ok(())
+    }
+
+    /// Replaces the vault with a freshly read one, keeping the selection and
+    /// browser settings.
+    fn install_vault(&mut self, vault: Vault) {
         // Preserve the currently selected path before refreshing
         let selected_path = {
             let entries = self.filtered_visible_entries();
@@ -179,28 +1243,43 @@ impl App {
                 .map(|e| e.path.clone())
         };
 
-        self.vault = Vault::open(&self.config.vault.path)?;
-        self.index = Index::build(&self.vault);
+        let show_archived = self.vault.shows_archived();
+        self.vault = vault;
+        self.vault
+            .set_show_attachments(self.config.ui.show_attachments);
+        self.vault.set_archive_dir(&self.config.vault.archive_dir);
+        self.vault.set_show_archived(show_archived);
+        self.vault.set_sort(self.config.ui.sort);
+        if let Err(e) = self
+            .vault
+            .load_bibliography(&self.config.citations.bibliography)
+        {
+            self.toasts.push(
+                ToastLevel::Warn,
+                format!("Could not read the bibliography: {}", e),
+            );
+        }
+        self.rebuild_index();
+        self.image_preview.clear_cache();
         self.browser_state = ui::BrowserState::new(&self.vault);
         self.backlinks_state.reset();
 
         // Restore selection if the path still exists
-        if let Some(path) = selected_path {
-            if let Some(index) = self
-                .vault
-                .visible_entries()
+        if let Some(path) = selected_path
+            && let Some(index) = self
+                .filtered_visible_entries()
                 .iter()
-                .position(|e| e.path == path)
+                .position(|e| e.path == path && !e.is_virtual)
+        {
+            self.browser_state.select(index);
+            // Also update viewer state to reflect the reloaded note, unless
+            // that would drop unsaved edits to it
+            if !self.viewer_state.dirty
+                && let Some(note) = self.vault.get_note(&path)
             {
-                self.browser_state.select(index);
-                // Also update viewer state to reflect the reloaded note
-                if let Some(note) = self.vault.get_note(&path) {
-                    self.viewer_state.update_links(note);
-                }
+                self.viewer_state.update_links(note);
             }
         }
-
-        Ok(())
     }
 
     pub fn open_in_editor(
@@ -232,4 +1311,234 @@ impl App {
         }
         Ok(())
     }
+
+    /// Opens a vault-relative attachment with the configured viewer. The viewer
+    /// runs detached so the TUI stays responsive.
+    pub fn open_attachment(&mut self, path: &Path) {
+        let full_path = self.vault.root.join(path);
+        let viewer = &self.config.editor.viewer;
+        let result = std::process::Command::new(viewer)
+            .arg(&full_path)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.status_message = Some(match result {
+            Ok(mut child) => {
+                // Reap the viewer when it exits
+                std::thread::spawn(move || child.wait());
+                format!("Opened {} with {}", name, viewer)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                format!("Viewer `{}` not found", viewer)
+            }
+            Err(e) => format!("Could not open {}: {}", name, e),
+        });
+    }
+
+    /// Exports notes in the given scope. HTML and OPML are written in-process;
+    /// other formats are handed to the external converter on a worker thread.
+    pub fn start_export(&mut self, format: ExportFormat, scope: ui::ExportScope) {
+        if self.export_pending.is_some() {
+            self.status_message = Some("An export is already running".to_string());
+            return;
+        }
+
+        let notes: Vec<&crate::core::Note> = match scope {
+            ui::ExportScope::CurrentNote => self.selected_note().into_iter().collect(),
+            ui::ExportScope::VisibleNotes => self
+                .filtered_visible_entries()
+                .into_iter()
+                .filter(|e| !e.is_dir)
+                .filter_map(|e| self.vault.get_note(&e.path))
+                .collect(),
+        };
+        if notes.is_empty() {
+            self.status_message = Some("Nothing to export".to_string());
+            return;
+        }
+
+        let dir = &self.config.export.dir;
+        let names = export::file_names(&self.vault, &notes);
+        if !format.uses_converter() {
+            let mut written = Vec::new();
+            let mut failures = Vec::new();
+            let transclude = self.config.vault.expand_transclusions;
+            for &note in &notes {
+                let result = match format {
+                    ExportFormat::Opml => {
+                        export::export_opml(&self.vault, note, &names, dir, transclude)
+                    }
+                    _ => export::export_html(
+                        &self.vault,
+                        note,
+                        &names,
+                        dir,
+                        self.config.export.link_preview,
+                        transclude,
+                    ),
+                };
+                match result {
+                    Ok(path) => written.push(path),
+                    Err(e) => failures.push(format!("{}: {}", note.title, e)),
+                }
+            }
+            self.notify_export(&written, &failures);
+            return;
+        }
+
+        let jobs: Vec<ConvertJob> = notes
+            .into_iter()
+            .map(|note| {
+                export::prepare_job(
+                    &self.vault,
+                    note,
+                    &names,
+                    dir,
+                    format,
+                    self.config.vault.expand_transclusions,
+                )
+            })
+            .collect();
+        let command = self.config.export.converter.clone();
+        let args = self.config.export.converter_args.clone();
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let total = jobs.len();
+            let mut written = Vec::new();
+            let mut failures = Vec::new();
+            for (index, job) in jobs.iter().enumerate() {
+                let _ = tx.send(ExportEvent::Converting {
+                    index,
+                    total,
+                    title: job.title.clone(),
+                });
+                match export::run_converter(job, &command, &args) {
+                    Ok(path) => written.push(path),
+                    Err(e) => failures.push(format!("{}: {}", job.title, e)),
+                }
+            }
+            let _ = tx.send(ExportEvent::Finished { written, failures });
+        });
+        self.export_pending = Some(rx);
+        self.status_message = Some(format!("Exporting to {}…", format.label()));
+    }
+
+    /// Reports a finished export as a toast, warning when only some notes
+    /// could be written.
+    fn notify_export(&mut self, written: &[PathBuf], failures: &[String]) {
+        let level = match (written, failures) {
+            (_, []) => ToastLevel::Info,
+            ([], _) => ToastLevel::Error,
+            _ => ToastLevel::Warn,
+        };
+        self.toasts.push(level, export_summary(written, failures));
+    }
+
+    /// Notices another program writing the open note. A clean buffer is
+    /// refreshed in place; unsaved text is kept, with a banner saying the
+    /// file moved on underneath it. Returns whether anything changed.
+    fn tick_disk(&mut self) -> bool {
+        let on_disk = self
+            .viewer_state
+            .current_note_path
+            .as_ref()
+            .and_then(|path| self.vault.modified_on_disk(path));
+        // A deleted file is left to the next save to write again
+        if on_disk.is_none() || on_disk == self.viewer_state.disk_mtime {
+            return std::mem::take(&mut self.disk_changed);
+        }
+        if self.viewer_state.dirty {
+            return !std::mem::replace(&mut self.disk_changed, true);
+        }
+
+        let Some(path) = self.viewer_state.current_note_path.clone() else {
+            return false;
+        };
+        self.vault.reload_note(&path);
+        self.rebuild_index();
+        if let Some(note) = self.vault.get_note(&path) {
+            // An editor with nothing typed yet keeps its place
+            let cursor = self.viewer_state.cursor.clone();
+            self.viewer_state.reload(note);
+            self.viewer_state.cursor = self.viewer_state.clamp_position(&cursor);
+        }
+        if self.is_following() && self.viewer_state.mode == EditorMode::Read {
+            InputHandler::jump_to_end(self);
+        }
+        true
+    }
+
+    /// Reports progress from a running export in the status bar. Returns
+    /// whether the status changed.
+    fn tick_export(&mut self) -> bool {
+        let Some(rx) = &self.export_pending else {
+            return false;
+        };
+
+        let mut changed = false;
+        loop {
+            match rx.try_recv() {
+                Ok(ExportEvent::Converting {
+                    index,
+                    total,
+                    title,
+                }) => {
+                    self.status_message =
+                        Some(format!("Exporting {} ({}/{})…", title, index + 1, total));
+                    changed = true;
+                }
+                Ok(ExportEvent::Finished { written, failures }) => {
+                    self.status_message = None;
+                    self.notify_export(&written, &failures);
+                    self.export_pending = None;
+                    return true;
+                }
+                Err(TryRecvError::Empty) => return changed,
+                Err(TryRecvError::Disconnected) => {
+                    self.status_message = None;
+                    self.toasts
+                        .push(ToastLevel::Error, "Export failed: worker exited");
+                    self.export_pending = None;
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+fn export_summary(written: &[PathBuf], failures: &[String]) -> String {
+    match (written, failures) {
+        ([path], []) => format!("Exported to {}", path.display()),
+        (_, []) => format!("Exported {} notes", written.len()),
+        ([], [failure]) => format!("Export failed: {}", failure),
+        _ => format!(
+            "Exported {}, {} failed: {}",
+            written.len(),
+            failures.len(),
+            failures[0]
+        ),
+    }
+}
+
+/// The notes among `entries` that match, plus the directories above them so
+/// the tree keeps its shape. A directory whose own name matches stays too.
+fn keep_with_parents(
+    entries: Vec<&crate::core::TreeEntry>,
+    matches: impl Fn(&crate::core::TreeEntry) -> bool,
+) -> Vec<&crate::core::TreeEntry> {
+    let mut keep: HashSet<&Path> = HashSet::new();
+    for entry in &entries {
+        if !entry.is_dir && matches(entry) {
+            keep.extend(entry.path.ancestors());
+        }
+    }
+
+    entries
+        .into_iter()
+        .filter(|entry| keep.contains(entry.path.as_path()) || (entry.is_dir && matches(entry)))
+        .collect()
 }
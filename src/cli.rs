@@ -0,0 +1,596 @@
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use color_eyre::{
+    Result,
+    eyre::{bail, eyre},
+};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use serde::Serialize;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::core::crypt::{self, VaultKey};
+use crate::core::{
+    Exclude, Index, Vault, attachments, clip, doctor, frontmatter, io_error, opml, snapshot, text,
+};
+
+/// Runs a non-interactive subcommand. Returns `Ok(false)` when `args` does
+/// not name a subcommand and the TUI should start instead.
+pub fn run(config: &Config, args: &[String]) -> Result<bool> {
+    match args.first().map(String::as_str) {
+        Some("doctor") => {
+            doctor(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("gc") => {
+            gc(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("snapshot") => {
+            snapshot(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("clip") => {
+            clip(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("dump") => {
+            dump(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("import") => {
+            import(config, &args[1..])?;
+            Ok(true)
+        }
+        Some("encrypt") => {
+            encrypt(config)?;
+            Ok(true)
+        }
+        Some("decrypt") => {
+            decrypt(config)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// `tui-jot doctor [--fix]` — reports vault integrity problems and offers
+/// to apply the fixes that are safe to automate.
+fn doctor(config: &Config, args: &[String]) -> Result<()> {
+    let vault = Vault::open(&config.vault.path, vault_key(config)?, exclude(config))?;
+    let issues = doctor::check(&vault);
+
+    if issues.is_empty() {
+        println!("No problems found in {}", vault.root.display());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let marker = if issue.fix.is_some() {
+            " [fixable]"
+        } else {
+            ""
+        };
+        println!(
+            "{:<22} {}: {}{}",
+            issue.kind.label(),
+            issue.path.display(),
+            issue.detail,
+            marker
+        );
+    }
+
+    let fixable: Vec<&doctor::Issue> = issues.iter().filter(|i| i.fix.is_some()).collect();
+    println!(
+        "\n{} problem{} found, {} fixable",
+        issues.len(),
+        if issues.len() == 1 { "" } else { "s" },
+        fixable.len()
+    );
+
+    if fixable.is_empty() {
+        return Ok(());
+    }
+
+    let apply = args.iter().any(|a| a == "--fix") || confirm("Apply safe fixes?")?;
+    if !apply {
+        return Ok(());
+    }
+
+    for issue in fixable {
+        if let Some(ref fix) = issue.fix {
+            match doctor::apply_fix(&vault, &issue.path, fix) {
+                Ok(()) => println!("fixed  {}", issue.path.display()),
+                Err(e) => println!("failed {}: {}", issue.path.display(), e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `tui-jot gc [--yes]` — lists attachments that no note references, and
+/// with `--yes` moves them into the vault trash.
+fn gc(config: &Config, args: &[String]) -> Result<()> {
+    // Links from excluded notes count too, but excluded files are left alone
+    let vault = Vault::open(&config.vault.path, vault_key(config)?, Exclude::default())?;
+    let index = Index::build(&vault);
+    let excluded = exclude(config);
+    let orphans: Vec<_> = attachments::find_orphans(&vault, &index)
+        .into_iter()
+        .filter(|path| !excluded.covers(path))
+        .collect();
+
+    if orphans.is_empty() {
+        println!("No unreferenced attachments in {}", vault.root.display());
+        return Ok(());
+    }
+
+    let mut total: u64 = 0;
+    for path in &orphans {
+        let size = std::fs::metadata(vault.root.join(path))
+            .map(|m| m.len())
+            .unwrap_or(0);
+        total += size;
+        println!("{:>10}  {}", format_size(size), path.display());
+    }
+    println!(
+        "
+{} unreferenced attachment{} ({})",
+        orphans.len(),
+        if orphans.len() == 1 { "" } else { "s" },
+        format_size(total)
+    );
+
+    if !args.iter().any(|a| a == "--yes") {
+        println!(
+            "Run `tui-jot gc --yes` to move them to {}/",
+            attachments::TRASH_DIR
+        );
+        return Ok(());
+    }
+
+    for path in &orphans {
+        match attachments::move_to_trash(&vault.root, path) {
+            Ok(_) => println!("trashed {}", path.display()),
+            Err(e) => println!("failed  {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+/// `tui-jot clip <url>...` — saves the readable part of each web page as a
+/// note in `vault.clippings_dir`, with its title, address and the date in
+/// the frontmatter.
+fn clip(config: &Config, args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        bail!("Usage: tui-jot clip <url>...");
+    }
+    let vault = Vault::open(&config.vault.path, vault_key(config)?, exclude(config))?;
+    let mut failed = 0;
+    for url in args {
+        match clip_page(config, &vault, url) {
+            Ok(note) => println!("clipped {} as {}", url, note.display()),
+            Err(e) => {
+                println!("failed  {}: {}", url, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} pages could not be clipped", failed, args.len());
+    }
+    Ok(())
+}
+
+/// Fetches the page at `url` and writes it as a new note, returning its
+/// path in the vault. An existing note is never overwritten.
+fn clip_page(config: &Config, vault: &Vault, url: &str) -> Result<PathBuf> {
+    let html = clip::fetch(url)?;
+    let clipping = clip::to_markdown(&html, url);
+    if clipping.markdown.trim().is_empty() {
+        bail!("no readable content");
+    }
+
+    // Untitled pages are named after their site, or failing that their
+    // file name
+    let address = url.split("://").last().unwrap_or(url);
+    let address = address.split(['?', '#']).next().unwrap_or(address);
+    let fallback = match address.split('/').next() {
+        Some(host) if !host.is_empty() => host.replace('.', "-"),
+        _ => Path::new(address)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let title = match clipping.title.trim() {
+        "" => fallback.as_str(),
+        title => title,
+    };
+    let name = match text::file_name_for(title) {
+        name if name.is_empty() => match text::file_name_for(&fallback) {
+            name if name.is_empty() => "Clipping".to_string(),
+            name => name,
+        },
+        name => name,
+    };
+    let relative = config
+        .vault
+        .clippings_dir
+        .join(format!("{}.{}", name, config.vault.default_extension));
+    let full_path = vault.root.join(&relative);
+    if full_path.exists() {
+        bail!("{} already exists", relative.display());
+    }
+
+    let today = chrono::Local::now().format("%Y-%m-%d");
+    let content = format!(
+        "---\ntitle: {}\nsource: {}\ndate: {}\n---\n# {}\n\n{}",
+        frontmatter::quote(title),
+        frontmatter::quote(url),
+        today,
+        title,
+        clipping.markdown
+    );
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    }
+    vault.write_note(&relative, &content)?;
+    Ok(relative)
+}
+
+/// One note in `tui-jot dump --json`.
+#[derive(Serialize)]
+struct DumpedNote {
+    path: PathBuf,
+    title: String,
+    tags: Vec<String>,
+    links: Vec<DumpedLink>,
+    backlinks: Vec<PathBuf>,
+    /// RFC 3339, in UTC
+    mtime: String,
+}
+
+/// A wiki-link as written, and the note it resolves to when there is one.
+#[derive(Serialize)]
+struct DumpedLink {
+    target: String,
+    path: Option<PathBuf>,
+}
+
+/// `tui-jot dump --json` — prints every note's metadata as a JSON array,
+/// sorted by path, for scripts that build on the vault index.
+fn dump(config: &Config, args: &[String]) -> Result<()> {
+    if !args.iter().any(|a| a == "--json") {
+        bail!("Usage: tui-jot dump --json");
+    }
+    let vault = Vault::open(&config.vault.path, vault_key(config)?, exclude(config))?;
+    let index = Index::build(&vault);
+
+    let mut notes: Vec<_> = vault.notes.values().collect();
+    notes.sort_by(|a, b| a.path.cmp(&b.path));
+    let dumped: Vec<DumpedNote> = notes
+        .into_iter()
+        .map(|note| {
+            let mut tags: Vec<String> = note.tags.iter().cloned().collect();
+            tags.sort();
+            let links = note
+                .links
+                .iter()
+                .map(|link| DumpedLink {
+                    target: link.target.clone(),
+                    path: vault.resolve_link(&link.target).map(|n| n.path.clone()),
+                })
+                .collect();
+            let mtime: DateTime<Utc> = note.modified.into();
+            DumpedNote {
+                path: note.path.clone(),
+                title: note.title.clone(),
+                tags,
+                links,
+                backlinks: index.get_backlinks(&note.path),
+                mtime: mtime.to_rfc3339_opts(SecondsFormat::Secs, true),
+            }
+        })
+        .collect();
+
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &dumped)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// `tui-jot import <file.opml>...` — turns OPML outlines into notes in
+/// `vault.inbox_dir`, each named after its outline's title.
+fn import(config: &Config, args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        bail!("Usage: tui-jot import <file.opml>...");
+    }
+    let vault = Vault::open(&config.vault.path, vault_key(config)?, exclude(config))?;
+    let mut failed = 0;
+    for file in args {
+        let path = Path::new(file);
+        match import_opml(config, &vault, path) {
+            Ok(note) => println!("imported {} as {}", path.display(), note.display()),
+            Err(e) => {
+                eprintln!("failed   {}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!(
+            "{} of {} outlines could not be imported",
+            failed,
+            args.len()
+        );
+    }
+    Ok(())
+}
+
+/// Writes the outline in the OPML file at `path` as a new note, returning
+/// its path in the vault. An existing note is never overwritten.
+fn import_opml(config: &Config, vault: &Vault, path: &Path) -> Result<PathBuf> {
+    let source = std::fs::read_to_string(path)?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let (title, markdown) = opml::to_markdown(&source, &stem)?;
+
+    let name = match text::file_name_for(&title) {
+        name if name.is_empty() => text::file_name_for(&stem),
+        name => name,
+    };
+    // Nothing left would make a hidden `.md` file
+    if name.is_empty() {
+        bail!("no title or file name to name the note after");
+    }
+    let relative = config
+        .vault
+        .inbox_dir
+        .join(format!("{}.{}", name, config.vault.default_extension));
+    let full_path = vault.root.join(&relative);
+    if full_path.exists() {
+        bail!("{} already exists", relative.display());
+    }
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    }
+    vault.write_note(&relative, &markdown)?;
+    Ok(relative)
+}
+
+/// `tui-jot snapshot [--changed]` — archives the vault now, as the app does
+/// on quit when `snapshot.enabled` is set.
+fn snapshot(config: &Config, args: &[String]) -> Result<()> {
+    let settings = &config.snapshot;
+    let changed_only = settings.changed_only || args.iter().any(|a| a == "--changed");
+
+    match snapshot::create(
+        &config.vault.path,
+        &settings.dir,
+        changed_only,
+        settings.keep,
+    )? {
+        Some((path, count)) => println!(
+            "Archived {} file{} to {}",
+            count,
+            if count == 1 { "" } else { "s" },
+            path.display()
+        ),
+        None => println!("Nothing changed since the last snapshot"),
+    }
+
+    let removed = snapshot::prune(&config.vault.path, &settings.dir, settings.keep)?;
+    if removed > 0 {
+        println!(
+            "Removed {} old snapshot{}",
+            removed,
+            if removed == 1 { "" } else { "s" }
+        );
+    }
+
+    Ok(())
+}
+
+/// `tui-jot encrypt` — sets a passphrase if the vault has none, seals every
+/// note still stored in plain text and turns on `vault.encrypted`.
+fn encrypt(config: &Config) -> Result<()> {
+    let root = &config.vault.path;
+    let key = if VaultKey::exists(root) {
+        unlock(root)?
+    } else {
+        new_passphrase(root)?
+    };
+    // Excluded notes are converted too, so none is left behind
+    let vault = Vault::open(root, Some(key.clone()), Exclude::default())?;
+
+    let mut count = 0;
+    for (path, note) in &vault.notes {
+        if !crypt::is_encrypted(&std::fs::read(vault.root.join(path))?) {
+            let content = note
+                .read()
+                .ok_or_else(|| eyre!("{}: could not be read", path.display()))?;
+            vault.write_note(path, &content)?;
+            count += 1;
+        }
+    }
+    println!(
+        "Encrypted {} note{} in {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        vault.root.display()
+    );
+
+    if !config.vault.encrypted {
+        Config::persist("vault.encrypted", true)?;
+    }
+    Ok(())
+}
+
+/// `tui-jot decrypt` — writes every note back in plain text, then removes
+/// the passphrase and turns off `vault.encrypted`.
+fn decrypt(config: &Config) -> Result<()> {
+    let root = &config.vault.path;
+    if !VaultKey::exists(root) {
+        bail!("{} is not encrypted", root.display());
+    }
+    // Excluded notes are converted too, so none is left behind
+    let vault = Vault::open(root, Some(unlock(root)?), Exclude::default())?;
+
+    let mut count = 0;
+    for (path, note) in &vault.notes {
+        let full_path = vault.root.join(path);
+        if crypt::is_encrypted(&std::fs::read(&full_path)?) {
+            let content = note
+                .read()
+                .ok_or_else(|| eyre!("{}: could not be decrypted", path.display()))?;
+            std::fs::write(&full_path, content.as_bytes())?;
+            count += 1;
+        }
+    }
+    println!(
+        "Decrypted {} note{} in {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        vault.root.display()
+    );
+
+    // Notes that would not decrypt still need the key, so keep it
+    let stuck: Vec<_> = vault
+        .tree
+        .iter()
+        .filter(|e| !e.is_dir && !e.is_attachment())
+        .filter(|e| {
+            std::fs::read(vault.root.join(&e.path)).is_ok_and(|bytes| crypt::is_encrypted(&bytes))
+        })
+        .collect();
+    if !stuck.is_empty() {
+        for entry in &stuck {
+            println!("failed  {}", entry.path.display());
+        }
+        bail!("Some notes could not be decrypted; the passphrase was kept");
+    }
+
+    std::fs::remove_file(crypt::key_path(root))?;
+    if config.vault.encrypted {
+        Config::persist("vault.encrypted", false)?;
+    }
+    Ok(())
+}
+
+/// The `vault.exclude` patterns, warning about any that don't parse.
+fn exclude(config: &Config) -> Exclude {
+    let (exclude, error) = App::vault_exclude(config);
+    if let Some(error) = error {
+        eprintln!("{}", error);
+    }
+    exclude
+}
+
+/// The key for an encrypted vault, asking for its passphrase, or for a new
+/// one the first time. `None` when `vault.encrypted` is off.
+pub fn vault_key(config: &Config) -> Result<Option<VaultKey>> {
+    if !config.vault.encrypted {
+        return Ok(None);
+    }
+    let root = &config.vault.path;
+    if VaultKey::exists(root) {
+        unlock(root).map(Some)
+    } else {
+        println!("Setting a passphrase for {}", root.display());
+        new_passphrase(root).map(Some)
+    }
+}
+
+/// Asks for the vault passphrase, allowing a few mistyped attempts.
+fn unlock(root: &std::path::Path) -> Result<VaultKey> {
+    let mut attempts = 0;
+    loop {
+        let passphrase = read_passphrase("Passphrase")?;
+        match VaultKey::unlock(root, &passphrase) {
+            Ok(key) => return Ok(key),
+            Err(e) => {
+                attempts += 1;
+                if attempts == 3 {
+                    return Err(e);
+                }
+                println!("{}", e);
+            }
+        }
+    }
+}
+
+fn new_passphrase(root: &std::path::Path) -> Result<VaultKey> {
+    let passphrase = read_passphrase("New passphrase")?;
+    if passphrase.is_empty() {
+        bail!("The passphrase can't be empty");
+    }
+    if read_passphrase("Repeat passphrase")? != passphrase {
+        bail!("Passphrases don't match");
+    }
+    VaultKey::create(root, &passphrase)
+}
+
+/// Reads a line from the terminal without echoing it.
+fn read_passphrase(prompt: &str) -> Result<String> {
+    print!("{}: ", prompt);
+    io::stdout().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let result = read_hidden_line();
+    terminal::disable_raw_mode()?;
+    println!();
+    result
+}
+
+fn read_hidden_line() -> Result<String> {
+    let mut line = String::new();
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return Ok(line),
+            // A newline typed ahead before raw mode was on arrives as Ctrl+J
+            KeyCode::Char('j') if control => return Ok(line),
+            KeyCode::Char('c') if control => bail!("Cancelled"),
+            KeyCode::Esc => bail!("Cancelled"),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Char(c) if !control => line.push(c),
+            _ => {}
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
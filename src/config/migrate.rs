@@ -0,0 +1,49 @@
+use toml::{Table, Value};
+
+/// Config keys that moved, as dotted `section.key` paths from old to new.
+/// Add an entry here whenever a setting is renamed so existing configs keep
+/// working; the old key is moved over on the next launch.
+const RENAMED_KEYS: &[(&str, &str)] = &[];
+
+/// Upgrades a parsed config file in place. Returns a description of each
+/// change made, empty when the file was already current.
+pub fn apply(config: &mut Table) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for (old, new) in RENAMED_KEYS {
+        let Some(value) = take(config, old) else {
+            continue;
+        };
+        // An explicit value under the new name wins over the old one
+        if get(config, new).is_none() {
+            set(config, new, value);
+            changes.push(format!("Renamed `{}` to `{}`", old, new));
+        } else {
+            changes.push(format!("Removed `{}`, superseded by `{}`", old, new));
+        }
+    }
+
+    changes
+}
+
+fn get<'a>(config: &'a Table, path: &str) -> Option<&'a Value> {
+    let (section, key) = path.split_once('.')?;
+    config.get(section)?.as_table()?.get(key)
+}
+
+fn take(config: &mut Table, path: &str) -> Option<Value> {
+    let (section, key) = path.split_once('.')?;
+    config.get_mut(section)?.as_table_mut()?.remove(key)
+}
+
+fn set(config: &mut Table, path: &str, value: Value) {
+    let Some((section, key)) = path.split_once('.') else {
+        return;
+    };
+    let section = config
+        .entry(section)
+        .or_insert_with(|| Value::Table(Table::new()));
+    if let Some(table) = section.as_table_mut() {
+        table.insert(key.to_string(), value);
+    }
+}
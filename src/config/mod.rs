@@ -1,3 +1,6 @@
+mod migrate;
 mod settings;
+mod state;
 
-pub use settings::{Config, UiConfig};
+pub use settings::{BorderStyle, Config, EditorConfig, PaneChrome, PaneLayout, UiConfig};
+pub use state::{AppState, SavedSearch, Workspace};
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use color_eyre::Result;
+use color_eyre::{Result, eyre::eyre};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::migrate;
+use crate::core::TreeSort;
+use crate::core::daily::Period;
+use crate::core::export::LinkPreview;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
     #[serde(default)]
     pub vault: VaultConfig,
@@ -13,6 +19,36 @@ pub struct Config {
     pub ui: UiConfig,
     #[serde(default)]
     pub editor: EditorConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
+    #[serde(default)]
+    pub spell: SpellConfig,
+    #[serde(default)]
+    pub daily: DailyConfig,
+    #[serde(default)]
+    pub citations: CitationConfig,
+    /// Browser folders listing the notes a query matches, by name:
+    /// `Inbox = "tag:#inbox"`, `Open projects = "path:projects/ AND task:open"`
+    #[serde(default)]
+    pub smart_folders: HashMap<String, String>,
+    /// How the file was loaded this run; never written back
+    #[serde(skip)]
+    pub load_report: LoadReport,
+}
+
+/// What `Config::load` had to do to produce a usable config.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// No config existed, so defaults were written
+    pub created: bool,
+    /// Migrations applied to an older config file
+    pub migrations: Vec<String>,
+    /// Copy of the config as it was before migrating
+    pub backup: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +56,32 @@ pub struct VaultConfig {
     pub path: PathBuf,
     #[serde(default = "default_extension")]
     pub default_extension: String,
+    /// Include `![[Note]]` embeds in word counts, search and export
+    #[serde(default)]
+    pub expand_transclusions: bool,
+    /// Store notes encrypted on disk, unlocked with a passphrase on startup
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Folder, relative to the vault, that archived notes are moved into
+    #[serde(default = "default_archive_dir")]
+    pub archive_dir: PathBuf,
+    /// Folder, relative to the vault, that notes created from the finder go in
+    #[serde(default)]
+    pub inbox_dir: PathBuf,
+    /// Folder, relative to the vault, that `tui-jot clip` saves pages in
+    #[serde(default = "default_clippings_dir")]
+    pub clippings_dir: PathBuf,
+    /// Glob patterns, relative to the vault, of files and folders to leave
+    /// out of the tree, index and graph
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Also leave out whatever `.gitignore` and `.ignore` files list
+    #[serde(default = "default_true")]
+    pub respect_gitignore: bool,
+    /// Refuse to edit, create, delete, move or rename anything in the vault.
+    /// Also turned on for one session by `--read-only`
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,26 +92,275 @@ pub struct UiConfig {
     pub tree_width: u16,
     #[serde(default = "default_true")]
     pub show_backlinks: bool,
+    /// Share of the side column, in percent, the backlinks pane takes below
+    /// the browser
+    #[serde(default = "default_backlinks_height")]
+    pub backlinks_height: u16,
+    #[serde(default)]
+    pub layout: PaneLayout,
     #[serde(default = "default_theme")]
     pub theme: String,
     #[serde(default)]
     pub theme_overrides: HashMap<String, String>,
+    #[serde(default = "default_image_preview")]
+    pub image_preview: String, // "auto", "kitty", "iterm2" or "off"
+    #[serde(default = "default_image_max_rows")]
+    pub image_max_rows: u16,
+    /// List PDFs, images and other non-markdown files in the browser
+    #[serde(default)]
+    pub show_attachments: bool,
+    /// Show when each note was last modified and how many notes each
+    /// folder holds, right-aligned in the browser
+    #[serde(default)]
+    pub show_tree_details: bool,
+    /// Show `%%comments%%` in the rendered preview instead of hiding them
+    #[serde(default)]
+    pub show_comments: bool,
+    /// Order of entries within each folder of the browser
+    #[serde(default)]
+    pub sort: TreeSort,
+    /// Capture the mouse for clicking and scrolling panes. Turn off to use
+    /// the terminal's own text selection.
+    #[serde(default = "default_true")]
+    pub mouse: bool,
+    /// Draw Nerd Font icons; off falls back to plain ASCII
+    #[serde(default = "default_true")]
+    pub nerd_font: bool,
+    /// Border drawn around panes and popups
+    #[serde(default)]
+    pub border: BorderStyle,
+    /// Title and padding of the `browser`, `viewer` and `backlinks` panes
+    #[serde(default)]
+    pub panes: HashMap<String, PaneChrome>,
+    /// After a prefix key such as `g`, list the keys that can follow it
+    #[serde(default = "default_true")]
+    pub which_key: bool,
+    /// List saved searches in the browser as smart folders too
+    #[serde(default)]
+    pub saved_searches_in_browser: bool,
+    /// Show the folders above the open note in a row over the preview
+    #[serde(default = "default_true")]
+    pub breadcrumbs: bool,
+}
+
+impl UiConfig {
+    /// Chrome for the pane called `name`, defaulting to a titled, unpadded pane.
+    pub fn pane(&self, name: &str) -> PaneChrome {
+        self.panes.get(name).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderStyle {
+    #[default]
+    Rounded,
+    Plain,
+    Double,
+    Thick,
+    /// Blank space where the border would be
+    None,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PaneChrome {
+    #[serde(default = "default_true")]
+    pub title: bool,
+    /// Blank columns inside the border on each side
+    #[serde(default)]
+    pub padding: u16,
+}
+
+impl Default for PaneChrome {
+    fn default() -> Self {
+        Self {
+            title: true,
+            padding: 0,
+        }
+    }
+}
+
+/// Arrangement of the browser, backlinks and preview panes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaneLayout {
+    /// Browser and backlinks in a column left of the preview.
+    #[default]
+    Left,
+    /// Browser and backlinks in a column right of the preview.
+    Right,
+    /// Browser and backlinks in a row above the preview, for narrow terminals.
+    Stacked,
+    /// `Left` on wide terminals, `Stacked` below 80 columns.
+    Auto,
+}
+
+impl PaneLayout {
+    pub fn next(self) -> Self {
+        match self {
+            PaneLayout::Left => PaneLayout::Right,
+            PaneLayout::Right => PaneLayout::Stacked,
+            PaneLayout::Stacked => PaneLayout::Auto,
+            PaneLayout::Auto => PaneLayout::Left,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaneLayout::Left => "browser left",
+            PaneLayout::Right => "browser right",
+            PaneLayout::Stacked => "stacked",
+            PaneLayout::Auto => "auto",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditorConfig {
     #[serde(default = "default_editor")]
     pub external: String,
+    /// Program that opens attachments selected in the browser
+    #[serde(default = "default_viewer")]
+    pub viewer: String,
+    /// Close `[`, `(` and backticks as they are typed
+    #[serde(default = "default_true")]
+    pub auto_pair: bool,
+    /// Spaces added or removed by Tab and Shift+Tab in the editor
+    #[serde(default = "default_indent_width")]
+    pub indent_width: usize,
+    /// Trigger words that Tab expands into a body, with `$1`, `$2`, … `$0` tab stops
+    #[serde(default)]
+    pub snippets: HashMap<String, String>,
+    /// Offer to rename the file after a note's first heading changes, and to
+    /// change the heading after the file is renamed
+    #[serde(default)]
+    pub sync_title: bool,
+    /// Record the time in a `created:` frontmatter field of new notes, so it
+    /// survives copies and syncs that reset file times
+    #[serde(default)]
+    pub stamp_created: bool,
+    /// Save the note when Esc leaves edit mode. Off, Esc keeps the changes
+    /// unsaved until Ctrl+s
+    #[serde(default = "default_true")]
+    pub save_on_exit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_sync_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_sync_remote")]
+    pub remote: String,
+    #[serde(default = "default_sync_message")]
+    pub commit_message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportConfig {
+    #[serde(default = "default_export_dir")]
+    pub dir: PathBuf,
+    #[serde(default)]
+    pub link_preview: LinkPreview,
+    #[serde(default = "default_converter")]
+    pub converter: String,
+    #[serde(default = "default_converter_args")]
+    pub converter_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Archive the vault to `dir` when the app quits
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_snapshot_dir")]
+    pub dir: PathBuf,
+    /// Number of snapshots to retain; 0 keeps them all
+    #[serde(default = "default_snapshot_keep")]
+    pub keep: usize,
+    /// Archive only files modified since the previous snapshot
+    #[serde(default)]
+    pub changed_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellConfig {
+    /// Underline misspelled words in the preview and editor
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hunspell dictionary name, loaded from `<language>.aff` and `<language>.dic`
+    #[serde(default = "default_spell_language")]
+    pub language: String,
+    /// Directories searched in order for the dictionary files
+    #[serde(default = "default_dictionary_dirs")]
+    pub dictionary_dirs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyConfig {
+    /// chrono format a note's file name must match to count as a daily note
+    #[serde(default = "default_daily_format")]
+    pub format: String,
+    /// Same for weekly notes; `%G`/`%V` are the ISO year and week
+    #[serde(default = "default_weekly_format")]
+    pub weekly_format: String,
+    #[serde(default = "default_monthly_format")]
+    pub monthly_format: String,
+    /// Vault folder that notes opened for today, this week or this month
+    /// are created in
+    #[serde(default)]
+    pub folder: PathBuf,
+    /// Vault-relative notes copied into new daily, weekly and monthly
+    /// notes, with `{{date}}` replaced by the new note's name; empty for a
+    /// plain heading
+    #[serde(default)]
+    pub template: PathBuf,
+    #[serde(default)]
+    pub weekly_template: PathBuf,
+    #[serde(default)]
+    pub monthly_template: PathBuf,
+    /// Create the adjacent period's note when stepping onto one without a
+    /// note, instead of skipping to the nearest existing note
+    #[serde(default)]
+    pub create_missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CitationConfig {
+    /// BibTeX file whose entries `[@key]` citations refer to, relative to
+    /// the vault or absolute; empty for none
+    #[serde(default)]
+    pub bibliography: PathBuf,
+    /// Vault folder that literature notes for citations are created in
+    #[serde(default = "default_literature_dir")]
+    pub notes_dir: PathBuf,
 }
 
 fn default_extension() -> String {
     "md".to_string()
 }
 
+fn default_archive_dir() -> PathBuf {
+    PathBuf::from("archive")
+}
+
+fn default_literature_dir() -> PathBuf {
+    PathBuf::from("literature")
+}
+
+fn default_clippings_dir() -> PathBuf {
+    PathBuf::from("clippings")
+}
+
 fn default_tree_width() -> u16 {
     25
 }
 
+fn default_backlinks_height() -> u16 {
+    30
+}
+
 fn default_true() -> bool {
     true
 }
@@ -58,10 +369,125 @@ fn default_theme() -> String {
     "gruvbox-dark".to_string()
 }
 
+fn default_image_preview() -> String {
+    "auto".to_string()
+}
+
+fn default_image_max_rows() -> u16 {
+    15
+}
+
+fn default_sync_interval() -> u64 {
+    300
+}
+
+fn default_sync_remote() -> String {
+    "origin".to_string()
+}
+
+fn default_sync_message() -> String {
+    "tui-jot: sync notes".to_string()
+}
+
+fn default_export_dir() -> PathBuf {
+    directories::UserDirs::new()
+        .map(|d| {
+            d.document_dir()
+                .unwrap_or_else(|| d.home_dir())
+                .join("tui-jot-exports")
+        })
+        .unwrap_or_else(|| PathBuf::from("exports"))
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path.to_path_buf();
+    };
+    match directories::UserDirs::new() {
+        Some(user) => user.home_dir().join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+fn default_converter() -> String {
+    "pandoc".to_string()
+}
+
+fn default_converter_args() -> Vec<String> {
+    ["--from", "markdown", "--standalone", "-o", "{output}"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_snapshot_dir() -> PathBuf {
+    ProjectDirs::from("com", "tui-jot", "tui-jot")
+        .map(|dirs| dirs.data_dir().join("snapshots"))
+        .unwrap_or_else(|| PathBuf::from("snapshots"))
+}
+
+fn default_snapshot_keep() -> usize {
+    10
+}
+
+fn default_daily_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_weekly_format() -> String {
+    "%G-W%V".to_string()
+}
+
+fn default_monthly_format() -> String {
+    "%Y-%m".to_string()
+}
+
+fn default_spell_language() -> String {
+    "en_US".to_string()
+}
+
+fn default_dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(project) = ProjectDirs::from("com", "tui-jot", "tui-jot") {
+        dirs.push(project.config_dir().join("dictionaries"));
+    }
+    dirs.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/share/myspell/dicts",
+            "/usr/local/share/hunspell",
+            "/opt/homebrew/share/hunspell",
+            "/Library/Spelling",
+        ]
+        .into_iter()
+        .map(PathBuf::from),
+    );
+    if let Some(user) = directories::UserDirs::new() {
+        dirs.push(user.home_dir().join("Library/Spelling"));
+    }
+    dirs
+}
+
+fn default_indent_width() -> usize {
+    2
+}
+
 fn default_editor() -> String {
     std::env::var("EDITOR").unwrap_or_else(|_| "nvim".to_string())
 }
 
+fn default_viewer() -> String {
+    if cfg!(target_os = "macos") {
+        "open".to_string()
+    } else if cfg!(windows) {
+        "explorer".to_string()
+    } else {
+        "xdg-open".to_string()
+    }
+}
+
 impl Default for VaultConfig {
     fn default() -> Self {
         let home = directories::UserDirs::new()
@@ -70,6 +496,14 @@ impl Default for VaultConfig {
         Self {
             path: home.join("notes"),
             default_extension: default_extension(),
+            expand_transclusions: false,
+            encrypted: false,
+            archive_dir: default_archive_dir(),
+            inbox_dir: PathBuf::new(),
+            clippings_dir: default_clippings_dir(),
+            exclude: Vec::new(),
+            respect_gitignore: true,
+            read_only: false,
         }
     }
 }
@@ -80,8 +514,23 @@ impl Default for UiConfig {
             show_hidden: false,
             tree_width: default_tree_width(),
             show_backlinks: default_true(),
+            backlinks_height: default_backlinks_height(),
+            layout: PaneLayout::default(),
             theme: default_theme(),
             theme_overrides: HashMap::new(),
+            image_preview: default_image_preview(),
+            image_max_rows: default_image_max_rows(),
+            show_attachments: false,
+            show_tree_details: false,
+            show_comments: false,
+            sort: TreeSort::default(),
+            mouse: default_true(),
+            nerd_font: default_true(),
+            border: BorderStyle::default(),
+            panes: HashMap::new(),
+            which_key: default_true(),
+            saved_searches_in_browser: false,
+            breadcrumbs: default_true(),
         }
     }
 }
@@ -90,31 +539,134 @@ impl Default for EditorConfig {
     fn default() -> Self {
         Self {
             external: default_editor(),
+            viewer: default_viewer(),
+            auto_pair: default_true(),
+            indent_width: default_indent_width(),
+            snippets: HashMap::new(),
+            sync_title: false,
+            stamp_created: false,
+            save_on_exit: default_true(),
         }
     }
 }
 
-impl Default for Config {
+impl Default for SyncConfig {
     fn default() -> Self {
         Self {
-            vault: VaultConfig::default(),
-            ui: UiConfig::default(),
-            editor: EditorConfig::default(),
+            enabled: false,
+            interval_secs: default_sync_interval(),
+            remote: default_sync_remote(),
+            commit_message: default_sync_message(),
         }
     }
 }
 
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_export_dir(),
+            link_preview: LinkPreview::default(),
+            converter: default_converter(),
+            converter_args: default_converter_args(),
+        }
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: default_snapshot_dir(),
+            keep: default_snapshot_keep(),
+            changed_only: false,
+        }
+    }
+}
+
+impl Default for SpellConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            language: default_spell_language(),
+            dictionary_dirs: default_dictionary_dirs(),
+        }
+    }
+}
+
+impl Default for DailyConfig {
+    fn default() -> Self {
+        Self {
+            format: default_daily_format(),
+            weekly_format: default_weekly_format(),
+            monthly_format: default_monthly_format(),
+            folder: PathBuf::new(),
+            template: PathBuf::new(),
+            weekly_template: PathBuf::new(),
+            monthly_template: PathBuf::new(),
+            create_missing: false,
+        }
+    }
+}
+
+impl Default for CitationConfig {
+    fn default() -> Self {
+        Self {
+            bibliography: PathBuf::new(),
+            notes_dir: default_literature_dir(),
+        }
+    }
+}
+
+impl DailyConfig {
+    pub fn format(&self, period: Period) -> &str {
+        match period {
+            Period::Day => &self.format,
+            Period::Week => &self.weekly_format,
+            Period::Month => &self.monthly_format,
+        }
+    }
+
+    /// The template for `period`'s notes, if one is set.
+    pub fn template(&self, period: Period) -> Option<&Path> {
+        let template = match period {
+            Period::Day => &self.template,
+            Period::Week => &self.weekly_template,
+            Period::Month => &self.monthly_template,
+        };
+        (!template.as_os_str().is_empty()).then_some(template.as_path())
+    }
+}
+
 impl Config {
     pub fn load() -> Result<Self> {
         let config_path = Self::config_path();
 
         if config_path.exists() {
             let contents = std::fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&contents)?;
+            let mut table: toml::Table = toml::from_str(&contents)?;
+            let migrations = migrate::apply(&mut table);
+
+            // Keep the original next to the upgraded file before rewriting it
+            let mut backup = None;
+            if !migrations.is_empty() {
+                let path = config_path.with_extension("toml.bak");
+                std::fs::write(&path, &contents)?;
+                std::fs::write(&config_path, toml::to_string_pretty(&table)?)?;
+                backup = Some(path);
+            }
+
+            let mut config: Config = table.try_into()?;
+            config.expand_home_dirs();
+            config.load_report = LoadReport {
+                created: false,
+                migrations,
+                backup,
+            };
             Ok(config)
         } else {
             // Create default config
-            let config = Config::default();
+            let mut config = Config::default();
+            config.load_report.created = true;
             if let Some(parent) = config_path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
@@ -124,6 +676,45 @@ impl Config {
         }
     }
 
+    /// Writes one `section.key` setting back to the config file, leaving the
+    /// rest of the file, comments and key order included, as it was.
+    pub fn persist(path: &str, value: impl Into<toml_edit::Value>) -> Result<()> {
+        let Some((section, key)) = path.split_once('.') else {
+            return Ok(());
+        };
+        let config_path = Self::config_path();
+        let mut doc: DocumentMut = match std::fs::read_to_string(&config_path) {
+            Ok(contents) => contents.parse()?,
+            Err(_) => DocumentMut::new(),
+        };
+        let table = doc
+            .entry(section)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| eyre!("`{}` in the config file is not a table", section))?;
+
+        // Keep a trailing comment on the old value
+        let mut value = value.into();
+        if let Some(old) = table.get(key).and_then(Item::as_value) {
+            *value.decor_mut() = old.decor().clone();
+        }
+        table.insert(key, Item::Value(value));
+        std::fs::write(&config_path, doc.to_string())?;
+        Ok(())
+    }
+
+    /// Resolves a leading `~` in the settings that name places outside the
+    /// vault, as the documented `path = "~/notes"` expects.
+    fn expand_home_dirs(&mut self) {
+        self.vault.path = expand_home(&self.vault.path);
+        self.export.dir = expand_home(&self.export.dir);
+        self.snapshot.dir = expand_home(&self.snapshot.dir);
+        self.citations.bibliography = expand_home(&self.citations.bibliography);
+        for dir in &mut self.spell.dictionary_dirs {
+            *dir = expand_home(dir);
+        }
+    }
+
     fn config_path() -> PathBuf {
         ProjectDirs::from("com", "tui-jot", "tui-jot")
             .map(|dirs| dirs.config_dir().join("config.toml"))
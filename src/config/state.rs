@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::PaneLayout;
+use crate::ui::Focus;
+
+/// Bookkeeping the app keeps between runs, stored next to the snapshots in
+/// the data directory rather than in the user-edited config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppState {
+    /// Version that last ran, used to show release notes after an upgrade
+    #[serde(default)]
+    pub last_version: Option<String>,
+    /// How often and how recently each note was opened, by absolute path
+    #[serde(default)]
+    pub visits: HashMap<String, Visit>,
+    /// Named search queries, by the absolute path of their vault
+    #[serde(default)]
+    pub saved_searches: HashMap<String, Vec<SavedSearch>>,
+    /// Named screen arrangements, by the absolute path of their vault
+    #[serde(default)]
+    pub workspaces: HashMap<String, Vec<Workspace>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Panes, open notes and filters saved under a name to return to. Note
+/// paths are relative to the vault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    pub layout: PaneLayout,
+    pub tree_width: u16,
+    pub show_backlinks: bool,
+    pub focus: Focus,
+    #[serde(default)]
+    pub maximized: bool,
+    /// The note selected in the browser
+    #[serde(default)]
+    pub note: Option<PathBuf>,
+    #[serde(default)]
+    pub tabs: Vec<PathBuf>,
+    /// The note pinned beside the preview
+    #[serde(default)]
+    pub split: Option<PathBuf>,
+    #[serde(default)]
+    pub split_on_left: bool,
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    #[serde(default)]
+    pub browser_filter: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Visit {
+    pub count: u32,
+    /// Unix time of the latest visit, in seconds
+    pub last: i64,
+}
+
+/// Visits kept at most; the least recent are forgotten first.
+const MAX_VISITS: usize = 1000;
+
+impl Visit {
+    /// Open count weighted by how long ago the note was last opened, so a
+    /// note opened daily outranks one opened often long ago.
+    pub fn frecency(&self, now: i64) -> u32 {
+        let days = (now - self.last).max(0) / 86_400;
+        let weight = match days {
+            0..=3 => 100,
+            4..=14 => 70,
+            15..=31 => 50,
+            32..=90 => 30,
+            _ => 10,
+        };
+        self.count.saturating_mul(weight)
+    }
+}
+
+impl AppState {
+    /// Reads the state file, falling back to defaults if it is missing or unreadable.
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Counts a visit to the note at `path`.
+    pub fn record_visit(&mut self, path: &Path) {
+        let now = chrono::Local::now().timestamp();
+        let visit = self
+            .visits
+            .entry(path.to_string_lossy().into_owned())
+            .or_default();
+        visit.count = visit.count.saturating_add(1);
+        visit.last = now;
+
+        if self.visits.len() > MAX_VISITS {
+            let mut lasts: Vec<i64> = self.visits.values().map(|v| v.last).collect();
+            lasts.sort_unstable();
+            let cutoff = lasts[self.visits.len() - MAX_VISITS];
+            self.visits.retain(|_, visit| visit.last >= cutoff);
+        }
+    }
+
+    /// The searches saved for the vault at `root`, in the order they were saved.
+    pub fn saved_searches(&self, root: &Path) -> &[SavedSearch] {
+        self.saved_searches
+            .get(root.to_string_lossy().as_ref())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Saves `query` under `name` for the vault at `root`, replacing a
+    /// search of the same name.
+    pub fn save_search(&mut self, root: &Path, name: &str, query: &str) {
+        let searches = self
+            .saved_searches
+            .entry(root.to_string_lossy().into_owned())
+            .or_default();
+        let search = SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+        };
+        match searches.iter_mut().find(|s| s.name == name) {
+            Some(existing) => *existing = search,
+            None => searches.push(search),
+        }
+    }
+
+    pub fn delete_search(&mut self, root: &Path, name: &str) {
+        let key = root.to_string_lossy();
+        if let Some(searches) = self.saved_searches.get_mut(key.as_ref()) {
+            searches.retain(|s| s.name != name);
+            if searches.is_empty() {
+                self.saved_searches.remove(key.as_ref());
+            }
+        }
+    }
+
+    /// The workspaces saved for the vault at `root`, in the order they were saved.
+    pub fn workspaces(&self, root: &Path) -> &[Workspace] {
+        self.workspaces
+            .get(root.to_string_lossy().as_ref())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Saves `workspace` for the vault at `root`, replacing one of the same name.
+    pub fn save_workspace(&mut self, root: &Path, workspace: Workspace) {
+        let workspaces = self
+            .workspaces
+            .entry(root.to_string_lossy().into_owned())
+            .or_default();
+        match workspaces.iter_mut().find(|w| w.name == workspace.name) {
+            Some(existing) => *existing = workspace,
+            None => workspaces.push(workspace),
+        }
+    }
+
+    pub fn delete_workspace(&mut self, root: &Path, name: &str) {
+        let key = root.to_string_lossy();
+        if let Some(workspaces) = self.workspaces.get_mut(key.as_ref()) {
+            workspaces.retain(|w| w.name != name);
+            if workspaces.is_empty() {
+                self.workspaces.remove(key.as_ref());
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn path() -> PathBuf {
+        ProjectDirs::from("com", "tui-jot", "tui-jot")
+            .map(|dirs| dirs.data_dir().join("state.toml"))
+            .unwrap_or_else(|| PathBuf::from("state.toml"))
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use walkdir::WalkDir;
+
+use super::{Index, Vault};
+
+/// Hidden directory inside the vault that receives trashed files.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Lists every non-note file in the vault, skipping hidden and excluded
+/// entries.
+pub fn list(vault: &Vault) -> Vec<PathBuf> {
+    WalkDir::new(&vault.root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !vault.skips(e))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().is_none_or(|ext| ext != "md"))
+        .map(|e| {
+            e.path()
+                .strip_prefix(&vault.root)
+                .unwrap_or(e.path())
+                .to_path_buf()
+        })
+        .collect()
+}
+
+/// Attachments that no note links to or embeds.
+pub fn find_orphans(vault: &Vault, index: &Index) -> Vec<PathBuf> {
+    let mut orphans: Vec<PathBuf> = list(vault)
+        .into_iter()
+        .filter(|path| !index.is_attachment_referenced(path))
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+/// Moves a vault-relative file into the vault trash, keeping its directory
+/// layout so it can be restored by hand. Returns the new location.
+pub fn move_to_trash(root: &Path, relative: &Path) -> Result<PathBuf> {
+    let mut dest = root.join(TRASH_DIR).join(relative);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Never overwrite an earlier trashed file of the same name
+    let mut n = 1;
+    while dest.exists() {
+        let stem = relative
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let ext = relative
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        dest.set_file_name(format!("{}.{}{}", stem, n, ext));
+        n += 1;
+    }
+
+    std::fs::rename(root.join(relative), &dest)?;
+    Ok(dest)
+}
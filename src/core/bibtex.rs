@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::Result;
+
+use super::io_error;
+
+/// One `@article{key, ...}` (or book, inproceedings, ...) entry.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub kind: String,
+    /// Authors, or editors when there are none, as "First Last"
+    pub authors: Vec<String>,
+    surnames: Vec<String>,
+    /// Field values with braces and LaTeX accents resolved, by lowercase name
+    fields: HashMap<String, String>,
+}
+
+impl Entry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    pub fn title(&self) -> &str {
+        self.field("title").unwrap_or("")
+    }
+
+    /// The `year` field, or the year of the biblatex `date` field.
+    pub fn year(&self) -> Option<&str> {
+        self.field("year")
+            .or_else(|| self.field("date").and_then(|date| date.get(..4)))
+    }
+
+    /// `Smith`, `Smith & Jones` or `Smith et al.`, falling back to the key
+    /// for entries without authors or editors.
+    pub fn short_authors(&self) -> String {
+        match self.surnames.as_slice() {
+            [] => self.key.clone(),
+            [one] => one.clone(),
+            [first, second] => format!("{} & {}", first, second),
+            [first, ..] => format!("{} et al.", first),
+        }
+    }
+
+    /// The author–year label a citation of this entry is shown as, such as
+    /// `Smith & Jones, 2020`.
+    pub fn label(&self) -> String {
+        format!(
+            "{}, {}",
+            self.short_authors(),
+            self.year().unwrap_or("n.d.")
+        )
+    }
+}
+
+/// The entries of a `.bib` file, by cite key.
+#[derive(Debug, Default)]
+pub struct Bibliography {
+    entries: Vec<Entry>,
+    by_key: HashMap<String, usize>,
+}
+
+impl Bibliography {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+        Ok(Self::parse(&source))
+    }
+
+    /// Entries in `source`, sorted by key. Malformed entries are skipped,
+    /// and `@string` abbreviations are expanded in the rest.
+    pub fn parse(source: &str) -> Self {
+        let mut entries = parse_entries(source);
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries.dedup_by(|a, b| a.key == b.key);
+        let by_key = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.key.clone(), i))
+            .collect();
+        Self { entries, by_key }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.by_key.get(key).map(|&i| &self.entries[i])
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// One work cited in a `[@key, p. 4; @other]` citation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cite {
+    pub key: String,
+    /// What follows the key, such as a page number
+    pub locator: String,
+}
+
+/// The works cited by the text between a citation's brackets, `@key` items
+/// separated by semicolons. `None` when it isn't a citation.
+pub fn parse_citation(inner: &str) -> Option<Vec<Cite>> {
+    let mut cites = Vec::new();
+    for item in inner.split(';') {
+        let item = item.trim().strip_prefix('@')?;
+        let key_len = item.find(|c: char| !is_key_char(c)).unwrap_or(item.len());
+        // Keys may end in punctuation only inside, as in `smith:2020`
+        let key = item[..key_len].trim_end_matches(['.', ':', '/']);
+        if key.is_empty() {
+            return None;
+        }
+        let locator = item[key.len()..].trim_start_matches(',').trim();
+        cites.push(Cite {
+            key: key.to_string(),
+            locator: locator.to_string(),
+        });
+    }
+    (!cites.is_empty()).then_some(cites)
+}
+
+/// Every `[@key]` citation in `line`, with the byte range it spans.
+pub fn citations(line: &str) -> Vec<(std::ops::Range<usize>, Vec<Cite>)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = line[from..].find("[@").map(|i| from + i) {
+        let Some(end) = line[start..].find(']').map(|i| start + i) else {
+            break;
+        };
+        if let Some(cites) = parse_citation(&line[start + 1..end]) {
+            found.push((start..end + 1, cites));
+        }
+        from = start + 2;
+    }
+    found
+}
+
+/// The file name, without extension, of the literature note for `key`:
+/// the key with the punctuation file names can't hold turned into dashes.
+pub fn note_name(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// A new literature note for `entry`: its details in the frontmatter, its
+/// title as the heading and a citation of it below.
+pub fn literature_note(entry: &Entry) -> String {
+    let title = match entry.title() {
+        "" => &entry.key,
+        title => title,
+    };
+    let mut yaml = format!("citekey: {}\n", entry.key);
+    if !entry.title().is_empty() {
+        yaml.push_str(&format!("title: {}\n", quoted(title)));
+    }
+    if !entry.authors.is_empty() {
+        yaml.push_str("authors:\n");
+        for author in &entry.authors {
+            yaml.push_str(&format!("  - {}\n", quoted(author)));
+        }
+    }
+    if let Some(year) = entry.year() {
+        yaml.push_str(&format!("year: {}\n", year));
+    }
+    yaml.push_str(&format!("type: {}\n", entry.kind));
+    format!("---\n{}---\n# {}\n\n[@{}]\n\n", yaml, title, entry.key)
+}
+
+/// A YAML double-quoted string.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-:./+".contains(c)
+}
+
+fn parse_entries(source: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut strings: HashMap<String, String> = HashMap::new();
+    let mut rest = source;
+
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let kind_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let kind = rest[..kind_len].to_ascii_lowercase();
+        let after = rest[kind_len..].trim_start();
+        let Some(open) = after.chars().next().filter(|&c| c == '{' || c == '(') else {
+            continue;
+        };
+        let close = if open == '{' { '}' } else { ')' };
+        let body_start = &after[1..];
+        let Some(body_len) = balanced_end(body_start, close) else {
+            break;
+        };
+        let body = &body_start[..body_len];
+        rest = &body_start[body_len + 1..];
+
+        match kind.as_str() {
+            "comment" | "preamble" => {}
+            "string" => {
+                let defined: Vec<_> = fields(body, &strings).collect();
+                strings.extend(defined);
+            }
+            _ => {
+                let Some((key, body)) = body.split_once(',') else {
+                    continue;
+                };
+                let key = key.trim();
+                if key.is_empty() {
+                    continue;
+                }
+                let raw: HashMap<String, String> = fields(body, &strings).collect();
+                let names = raw
+                    .get("author")
+                    .or_else(|| raw.get("editor"))
+                    .map(|names| split_names(names))
+                    .unwrap_or_default();
+                entries.push(Entry {
+                    key: key.to_string(),
+                    kind,
+                    authors: names.iter().map(|(full, _)| full.clone()).collect(),
+                    surnames: names.into_iter().map(|(_, surname)| surname).collect(),
+                    fields: raw
+                        .iter()
+                        .map(|(name, value)| (name.clone(), clean(value)))
+                        .collect(),
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// The byte index of the `close` that ends a group whose opening bracket
+/// came just before `text`, skipping nested braces.
+fn balanced_end(text: &str, close: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            c if c == close && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `name = value` pairs of an entry body, with names lowercased and
+/// values still holding their braces. `#` joins pieces of a value, and
+/// bare words are looked up in `strings`.
+fn fields<'a>(
+    body: &'a str,
+    strings: &'a HashMap<String, String>,
+) -> impl Iterator<Item = (String, String)> + 'a {
+    let mut rest = body;
+    std::iter::from_fn(move || {
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            let (name, after) = rest.split_once('=')?;
+            let name = name.trim().to_ascii_lowercase();
+            rest = after;
+
+            let mut value = String::new();
+            loop {
+                rest = rest.trim_start();
+                let piece_len = match rest.chars().next() {
+                    Some('{') => balanced_end(&rest[1..], '}').map(|i| i + 2),
+                    Some('"') => rest[1..].find('"').map(|i| i + 2),
+                    Some(_) => Some(
+                        rest.find(|c: char| c == ',' || c == '#' || c.is_whitespace())
+                            .unwrap_or(rest.len()),
+                    ),
+                    None => None,
+                };
+                let Some(piece_len) = piece_len else {
+                    rest = "";
+                    break;
+                };
+                let piece = &rest[..piece_len];
+                value.push_str(match piece.chars().next() {
+                    Some('{' | '"') => &piece[1..piece.len() - 1],
+                    _ => strings
+                        .get(&piece.to_ascii_lowercase())
+                        .map_or(piece, String::as_str),
+                });
+                rest = rest[piece_len..].trim_start();
+                match rest.strip_prefix('#') {
+                    Some(after) => rest = after,
+                    None => break,
+                }
+            }
+            if !name.is_empty() {
+                return Some((name, value));
+            }
+        }
+    })
+}
+
+/// `author` field names as ("First Last", "Last"). Names are separated by
+/// `and` outside braces; a braced name such as `{World Health Organization}`
+/// is kept whole.
+fn split_names(names: &str) -> Vec<(String, String)> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in names.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ if depth == 0
+                && c.is_whitespace()
+                && names
+                    .get(i + 1..i + 4)
+                    .is_some_and(|word| word.eq_ignore_ascii_case("and"))
+                && names[i + 4..].starts_with(char::is_whitespace) =>
+            {
+                parts.push(&names[start..i]);
+                start = i + 4;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&names[start..]);
+
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "others")
+        .map(|name| {
+            if name.starts_with('{') && balanced_end(&name[1..], '}') == Some(name.len() - 2) {
+                let whole = clean(name);
+                return (whole.clone(), whole);
+            }
+            match name.split_once(',') {
+                // "Last, First" or "Last, Jr, First"
+                Some((last, first)) => {
+                    let first = first.rsplit(',').next().unwrap_or(first);
+                    let (last, first) = (clean(last), clean(first));
+                    (format!("{} {}", first, last).trim().to_string(), last)
+                }
+                None => {
+                    let full = clean(name);
+                    let last = full.rsplit(' ').next().unwrap_or(&full).to_string();
+                    (full, last)
+                }
+            }
+        })
+        .collect()
+}
+
+/// A field value as plain text: braces dropped, LaTeX accents, escapes and
+/// dashes turned into the characters they stand for, whitespace collapsed.
+fn clean(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => {}
+            '~' => out.push(' '),
+            '\\' => {
+                let Some(command) = chars.next() else {
+                    break;
+                };
+                // `\c` and `\v` are accents only before a brace or space,
+                // as in `\c{c}` or `\v s`
+                let is_accent = "\"'`^~=.".contains(command)
+                    || ("cv".contains(command) && matches!(chars.peek(), Some('{' | ' ')));
+                if is_accent {
+                    // An accent takes the next letter, braced or not
+                    let mut letter = chars.next();
+                    if command.is_ascii_alphabetic() && letter == Some(' ') {
+                        letter = chars.next();
+                    }
+                    if letter == Some('{') {
+                        letter = chars.next();
+                        if chars.peek() == Some(&'}') {
+                            chars.next();
+                        }
+                    }
+                    out.extend(letter.map(|letter| accented(command, letter)));
+                } else if !command.is_ascii_alphabetic() {
+                    // An escaped character such as \& or \%
+                    out.push(command);
+                } else {
+                    // A word command such as \emph or \textit: keep its argument
+                    let mut word = String::from(command);
+                    while let Some(&next) = chars.peek() {
+                        if !next.is_ascii_alphabetic() {
+                            break;
+                        }
+                        word.push(next);
+                        chars.next();
+                    }
+                    match word.as_str() {
+                        "ss" => out.push('ß'),
+                        "o" => out.push('ø'),
+                        "O" => out.push('Ø'),
+                        "ae" => out.push('æ'),
+                        "aa" => out.push('å'),
+                        "l" => out.push('ł'),
+                        "i" => out.push('i'),
+                        _ => {}
+                    }
+                    // The space ending a symbol command isn't part of the text
+                    if chars.peek() == Some(&' ') && word.len() <= 2 {
+                        chars.next();
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    let out = out.replace("---", "—").replace("--", "–");
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `letter` with the accent LaTeX's `\<command>` puts on it, or the bare
+/// letter when there is no such character.
+fn accented(command: char, letter: char) -> char {
+    let (plain, marked) = match command {
+        '\'' => ("aeiouyAEIOUYcnszCNSZ", "áéíóúýÁÉÍÓÚÝćńśźĆŃŚŹ"),
+        '`' => ("aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        '^' => ("aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        '"' => ("aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+        '~' => ("anoANO", "ãñõÃÑÕ"),
+        'c' => ("csCS", "çşÇŞ"),
+        'v' => ("cszrnCSZRN", "čšžřňČŠŽŘŇ"),
+        '=' => ("aeiouAEIOU", "āēīōūĀĒĪŌŪ"),
+        '.' => ("zZ", "żŻ"),
+        _ => ("", ""),
+    };
+    plain
+        .chars()
+        .position(|c| c == letter)
+        .and_then(|i| marked.chars().nth(i))
+        .unwrap_or(letter)
+}
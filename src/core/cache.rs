@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use color_eyre::Result;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::note::{Link, Note};
+use super::undo_log::checksum;
+
+/// What was parsed from one note, and the modification time of the file it
+/// was parsed from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedNote {
+    pub modified: SystemTime,
+    pub created: SystemTime,
+    pub title: String,
+    pub tags: HashSet<String>,
+    pub links: Vec<Link>,
+    pub embeds: Vec<String>,
+    pub aliases: Vec<String>,
+    pub locked: bool,
+    pub properties: Vec<(String, String)>,
+    pub tasks: (usize, usize),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    /// The release that wrote the cache; a new one may parse notes differently
+    version: String,
+    notes: HashMap<PathBuf, CachedNote>,
+}
+
+/// Parsed note metadata from the last time the vault was opened, so notes
+/// that haven't changed since needn't be read again.
+#[derive(Default)]
+pub struct MetadataCache {
+    notes: HashMap<PathBuf, CachedNote>,
+}
+
+impl MetadataCache {
+    /// Reads the cache for the vault at `root`. A missing, unreadable or
+    /// outdated cache is treated as empty.
+    pub fn load(root: &Path) -> Self {
+        let notes = cache_path(root)
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|file| file.version == env!("CARGO_PKG_VERSION"))
+            .map(|file| file.notes)
+            .unwrap_or_default();
+        Self { notes }
+    }
+
+    /// The cached metadata for `relative`, if the file hasn't been modified
+    /// since it was parsed.
+    pub fn take(&mut self, relative: &Path, modified: SystemTime) -> Option<CachedNote> {
+        self.notes
+            .remove(relative)
+            .filter(|cached| cached.modified == modified)
+    }
+
+    /// Whether every cached note was taken, meaning none has since been
+    /// deleted or moved.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Writes the metadata of `notes` as the cache for the vault at `root`.
+    pub fn save(root: &Path, notes: &HashMap<PathBuf, Note>) -> Result<()> {
+        let Some(path) = cache_path(root) else {
+            return Ok(());
+        };
+        let file = CacheFile {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            notes: notes
+                .iter()
+                .map(|(path, note)| (path.clone(), note.to_cached()))
+                .collect(),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec(&file)?)?;
+        Ok(())
+    }
+}
+
+/// One cache file per vault under the data directory, beside the undo logs.
+fn cache_path(root: &Path) -> Option<PathBuf> {
+    ProjectDirs::from("com", "tui-jot", "tui-jot").map(|dirs| {
+        dirs.data_dir()
+            .join("cache")
+            .join(format!("{}.json", checksum(&root.to_string_lossy())))
+    })
+}
@@ -0,0 +1,821 @@
+use std::process::{Command, Stdio};
+
+use color_eyre::{Result, eyre::eyre};
+
+use super::opml;
+
+/// Elements left out of a clipping along with everything inside them.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "nav", "header", "footer", "aside", "form",
+    "button", "svg", "iframe", "select", "title",
+];
+
+/// Elements that start and end a paragraph of their own.
+const BLOCKS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "figure",
+    "figcaption",
+    "dl",
+    "dt",
+    "dd",
+    "details",
+    "summary",
+    "address",
+];
+
+/// A web page as a note: its title and readable content as markdown.
+pub struct Clipping {
+    pub title: String,
+    pub markdown: String,
+}
+
+/// Downloads the page at `url` with `curl`, following redirects. curl's
+/// last line of stderr is surfaced on failure.
+pub fn fetch(url: &str) -> Result<String> {
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--compressed",
+            "--max-time",
+            "30",
+            "--user-agent",
+            "Mozilla/5.0 (compatible; tui-jot)",
+            // --url, since a bare argument starting with `-` is an option
+            "--url",
+            url,
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => eyre!("`curl` not found; it is needed to fetch pages"),
+            _ => eyre!("failed to start `curl`: {}", e),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("no error output");
+        return Err(eyre!(
+            "curl exited with {}: {}",
+            output.status,
+            reason.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The readable part of `html` as markdown: the first `<article>`, else
+/// `<main>`, else the whole body, without navigation, headers, footers,
+/// sidebars, forms and scripts. Relative links and images are made
+/// absolute against `url`. The title is the page's `og:title`, else its
+/// `<title>`, else its first heading.
+pub fn to_markdown(html: &str, url: &str) -> Clipping {
+    let tokens = tokenize(html);
+    let title = page_title(&tokens).unwrap_or_default();
+
+    let container = ["article", "main", "body"].into_iter().find(|name| {
+        tokens
+            .iter()
+            .any(|t| matches!(t, Token::Open { name: n, .. } if n == name))
+    });
+
+    let mut writer = Writer::new(url);
+    // Until the container opens, and after it closes, nothing is kept
+    let mut inside = container.is_none();
+    let mut depth = 0;
+    let mut skip: Option<(String, usize)> = None;
+
+    for token in &tokens {
+        if let Some((name, nested)) = &mut skip {
+            match token {
+                Token::Open { name: n, .. } if n == name => *nested += 1,
+                Token::Close(n) if n == name => {
+                    *nested -= 1;
+                    if *nested == 0 {
+                        skip = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match token {
+            Token::Open { name, tag } => {
+                if !inside {
+                    if Some(name.as_str()) == container {
+                        inside = true;
+                        depth = 1;
+                    }
+                    continue;
+                }
+                if Some(name.as_str()) == container {
+                    depth += 1;
+                }
+                if SKIPPED.contains(&name.as_str()) && !writer.in_pre() {
+                    if !tag.ends_with("/>") {
+                        skip = Some((name.clone(), 1));
+                    }
+                    continue;
+                }
+                writer.open(name, tag);
+            }
+            Token::Close(name) => {
+                if !inside {
+                    continue;
+                }
+                if Some(name.as_str()) == container {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                writer.close(name);
+            }
+            Token::Text(text) if inside => writer.text(text),
+            Token::Text(_) => {}
+        }
+    }
+
+    let mut markdown = writer.finish();
+    let mut title = title;
+    // The page usually repeats its title as the first heading
+    if let Some((first, rest)) = markdown.split_once('\n')
+        && let Some(heading) = first.strip_prefix("# ")
+        && (title.is_empty() || heading == title)
+    {
+        title = heading.to_string();
+        markdown = rest.trim_start().to_string();
+    }
+
+    Clipping { title, markdown }
+}
+
+enum Token<'a> {
+    Open { name: String, tag: &'a str },
+    Close(String),
+    Text(&'a str),
+}
+
+/// Splits `html` into tags and the text between them. Comments, doctypes
+/// and the contents of `<script>` and `<style>` are dropped.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    // ASCII lowercasing keeps byte offsets, so it can be searched in place
+    let lower = html.to_ascii_lowercase();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let Some(start) = html[pos..].find('<').map(|i| pos + i) else {
+            tokens.push(Token::Text(&html[pos..]));
+            break;
+        };
+        if start > pos {
+            tokens.push(Token::Text(&html[pos..start]));
+        }
+        let rest = &html[start..];
+
+        if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(html.len(), |i| start + i + 3);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            pos = rest.find('>').map_or(html.len(), |i| start + i + 1);
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name_len = rest[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len() - name_start);
+        if name_len == 0 {
+            // A lone `<` in text
+            tokens.push(Token::Text(&html[start..start + 1]));
+            pos = start + 1;
+            continue;
+        }
+        let name = lower[start + name_start..start + name_start + name_len].to_string();
+        let end = tag_end(rest).map_or(html.len(), |i| start + i + 1);
+        let tag = &html[start..end];
+        pos = end;
+
+        if closing {
+            tokens.push(Token::Close(name));
+            continue;
+        }
+        let raw_text = name == "script" || name == "style";
+        tokens.push(Token::Open {
+            name: name.clone(),
+            tag,
+        });
+        if raw_text {
+            let close = format!("</{}", name);
+            pos = lower[pos..].find(&close).map_or(html.len(), |i| pos + i);
+        }
+    }
+    tokens
+}
+
+/// The index of the `>` that ends the tag at the start of `rest`, skipping
+/// any inside quoted attribute values.
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The value of attribute `name` in an opening tag, quoted or not.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let mut rest = inner.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return None;
+        }
+        let key_len = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after[1..].find(q).map_or(after.len(), |i| i + 1);
+                    value = &after[1..end];
+                    rest = after.get(end + 1..).unwrap_or("");
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    value = &after[..end];
+                    rest = &after[end..];
+                }
+            }
+        }
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode(value));
+        }
+    }
+}
+
+/// The page's `og:title`, else its `<title>`.
+fn page_title(tokens: &[Token]) -> Option<String> {
+    let og_title = tokens.iter().find_map(|token| match token {
+        Token::Open { name, tag } if name == "meta" => {
+            let property = attribute(tag, "property").or_else(|| attribute(tag, "name"))?;
+            (property == "og:title")
+                .then(|| attribute(tag, "content"))
+                .flatten()
+        }
+        _ => None,
+    });
+    let title = || {
+        let start = tokens
+            .iter()
+            .position(|t| matches!(t, Token::Open { name, .. } if name == "title"))?;
+        let text: String = tokens[start + 1..]
+            .iter()
+            .map_while(|t| match t {
+                Token::Text(text) => Some(*text),
+                _ => None,
+            })
+            .collect();
+        Some(decode(&text))
+    };
+    og_title
+        .or_else(title)
+        .map(|title| collapse(&title).trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+/// Decodes HTML's character references: the XML ones, numeric ones, and
+/// the named ones common in article text.
+fn decode(text: &str) -> String {
+    let mut text = text.to_string();
+    for (entity, c) in [
+        ("&nbsp;", " "),
+        ("&mdash;", "—"),
+        ("&ndash;", "–"),
+        ("&hellip;", "…"),
+        ("&lsquo;", "‘"),
+        ("&rsquo;", "’"),
+        ("&ldquo;", "“"),
+        ("&rdquo;", "”"),
+        ("&laquo;", "«"),
+        ("&raquo;", "»"),
+        ("&copy;", "©"),
+        ("&reg;", "®"),
+        ("&trade;", "™"),
+        ("&times;", "×"),
+        ("&middot;", "·"),
+        ("&bull;", "•"),
+    ] {
+        if text.contains(entity) {
+            text = text.replace(entity, c);
+        }
+    }
+    opml::unescape(&text).replace('\u{a0}', " ")
+}
+
+/// Runs of whitespace as single spaces.
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.starts_with(char::is_whitespace) && !out.is_empty() {
+        out.insert(0, ' ');
+    }
+    if text.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out
+}
+
+/// An element whose content is collected separately and rewritten when it
+/// closes.
+enum Kind {
+    Root,
+    /// `**`, `*`, `~~` or `` ` `` around the content
+    Wrap(&'static str),
+    Link(Option<String>),
+    Heading(usize),
+    List {
+        ordered: bool,
+        next: usize,
+    },
+    Item,
+    Quote,
+    /// With the language a `language-*` class on its `<code>` names
+    Pre(Option<String>),
+    Table(Vec<Vec<String>>),
+    Row(Vec<String>),
+    Cell,
+}
+
+struct Frame {
+    tag: String,
+    kind: Kind,
+    out: String,
+}
+
+/// Builds markdown from the tags and text of a page, one element at a time.
+struct Writer<'a> {
+    url: &'a str,
+    stack: Vec<Frame>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(url: &'a str) -> Self {
+        Self {
+            url,
+            stack: vec![Frame {
+                tag: String::new(),
+                kind: Kind::Root,
+                out: String::new(),
+            }],
+        }
+    }
+
+    fn in_pre(&self) -> bool {
+        self.stack.iter().any(|f| matches!(f.kind, Kind::Pre(_)))
+    }
+
+    fn out(&mut self) -> &mut String {
+        &mut self.stack.last_mut().expect("root frame").out
+    }
+
+    fn push(&mut self, tag: &str, kind: Kind) {
+        self.stack.push(Frame {
+            tag: tag.to_string(),
+            kind,
+            out: String::new(),
+        });
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = decode(text);
+        if self.in_pre() {
+            self.out().push_str(&text);
+            return;
+        }
+        let text = collapse(&text);
+        let out = self.out();
+        let at_start = out.is_empty() || out.ends_with(char::is_whitespace);
+        out.push_str(if at_start { text.trim_start() } else { &text });
+    }
+
+    fn open(&mut self, name: &str, tag: &str) {
+        if self.in_pre() {
+            if name == "code"
+                && let Some(Frame {
+                    kind: Kind::Pre(language @ None),
+                    ..
+                }) = self.stack.last_mut()
+            {
+                *language = attribute(tag, "class").and_then(|class| {
+                    class.split_whitespace().find_map(|c| {
+                        c.strip_prefix("language-")
+                            .or_else(|| c.strip_prefix("lang-"))
+                            .map(str::to_string)
+                    })
+                });
+            }
+            return;
+        }
+        match name {
+            "br" => {
+                trim_end_spaces(self.out());
+                self.out().push('\n');
+            }
+            "hr" => {
+                block(self.out());
+                self.out().push_str("---");
+                block(self.out());
+            }
+            "img" => {
+                let src = attribute(tag, "src").and_then(|src| absolute(self.url, &src));
+                if let Some(src) = src {
+                    let alt = collapse(&attribute(tag, "alt").unwrap_or_default());
+                    self.out().push_str(&format!("![{}]({})", alt.trim(), src));
+                }
+            }
+            "strong" | "b" => self.push(name, Kind::Wrap("**")),
+            "em" | "i" => self.push(name, Kind::Wrap("*")),
+            "del" | "s" | "strike" => self.push(name, Kind::Wrap("~~")),
+            "code" | "kbd" | "samp" => self.push(name, Kind::Wrap("`")),
+            "a" => {
+                let href = attribute(tag, "href").and_then(|href| absolute(self.url, &href));
+                self.push(name, Kind::Link(href));
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                self.push(name, Kind::Heading(level));
+            }
+            "ul" | "ol" => {
+                let next = attribute(tag, "start")
+                    .and_then(|start| start.parse().ok())
+                    .unwrap_or(1);
+                let ordered = name == "ol";
+                self.push(name, Kind::List { ordered, next });
+            }
+            "li" => {
+                self.close_implied(name, &["ul", "ol"]);
+                self.push(name, Kind::Item);
+            }
+            "blockquote" => self.push(name, Kind::Quote),
+            "pre" => self.push(name, Kind::Pre(None)),
+            "table" => self.push(name, Kind::Table(Vec::new())),
+            "tr" => {
+                self.close_implied(name, &["table"]);
+                self.push(name, Kind::Row(Vec::new()));
+            }
+            "td" | "th" => {
+                self.close_implied("td", &["tr", "table"]);
+                self.close_implied("th", &["tr", "table"]);
+                self.push(name, Kind::Cell);
+            }
+            _ if BLOCKS.contains(&name) => block(self.out()),
+            _ => {}
+        }
+    }
+
+    /// Closes an open `name` element that another one starting ends, as a
+    /// new `<li>` ends the previous item, unless it sits outside the
+    /// innermost of `within`.
+    fn close_implied(&mut self, name: &str, within: &[&str]) {
+        let open = self.stack.iter().rposition(|f| f.tag == name);
+        let container = self
+            .stack
+            .iter()
+            .rposition(|f| within.contains(&f.tag.as_str()));
+        if let Some(i) = open
+            && container.is_none_or(|c| c < i)
+        {
+            while self.stack.len() > i {
+                self.finish_frame();
+            }
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.in_pre() && name != "pre" {
+            return;
+        }
+        // Unclosed elements inside this one end with it
+        match self.stack.iter().rposition(|f| f.tag == name) {
+            Some(i) if i > 0 => {
+                while self.stack.len() > i {
+                    self.finish_frame();
+                }
+            }
+            _ if BLOCKS.contains(&name) => block(self.out()),
+            _ => {}
+        }
+    }
+
+    /// Closes the innermost element, writing its content into its parent.
+    fn finish_frame(&mut self) {
+        let frame = self.stack.pop().expect("an open element");
+        let content = frame.out;
+        match frame.kind {
+            Kind::Root => unreachable!("the root frame is never closed"),
+            Kind::Wrap(marker) => {
+                let text = if marker == "`" {
+                    collapse(&content)
+                } else {
+                    content
+                };
+                self.inline(&text, |inner| format!("{}{}{}", marker, inner, marker));
+            }
+            Kind::Link(href) => match href {
+                Some(href) => self.inline(&content, |inner| format!("[{}]({})", inner, href)),
+                None => self.inline(&content, str::to_string),
+            },
+            Kind::Heading(level) => {
+                let text = collapse(&content.replace('\n', " "));
+                let out = self.out();
+                block(out);
+                if !text.trim().is_empty() {
+                    out.push_str(&format!("{} {}", "#".repeat(level), text.trim()));
+                    block(out);
+                }
+            }
+            Kind::List { .. } => {
+                let out = self.out();
+                block(out);
+                out.push_str(content.trim_end());
+                block(out);
+            }
+            Kind::Item => {
+                let marker = match self.stack.iter_mut().rev().find_map(|f| match &mut f.kind {
+                    Kind::List { ordered, next } => Some((ordered, next)),
+                    _ => None,
+                }) {
+                    Some((true, next)) => {
+                        *next += 1;
+                        format!("{}. ", *next - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                let indent = " ".repeat(marker.len());
+                let body = content
+                    .trim()
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .enumerate()
+                    .map(|(i, line)| {
+                        if i == 0 {
+                            line.to_string()
+                        } else {
+                            format!("{}{}", indent, line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let out = self.out();
+                trim_end_spaces(out);
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&format!("{}{}\n", marker, body));
+            }
+            Kind::Quote => {
+                let quoted: Vec<String> = content
+                    .trim()
+                    .lines()
+                    .map(|line| match line.trim_end() {
+                        "" => ">".to_string(),
+                        line => format!("> {}", line),
+                    })
+                    .collect();
+                let out = self.out();
+                block(out);
+                out.push_str(&quoted.join("\n"));
+                block(out);
+            }
+            Kind::Pre(language) => {
+                let out = self.out();
+                block(out);
+                out.push_str(&format!(
+                    "```{}\n{}\n```",
+                    language.unwrap_or_default(),
+                    content.trim_matches('\n')
+                ));
+                block(out);
+            }
+            Kind::Cell => {
+                let cell = collapse(&content).trim().replace('|', "\\|");
+                match self.stack.last_mut() {
+                    Some(Frame {
+                        kind: Kind::Row(cells),
+                        ..
+                    }) => cells.push(cell),
+                    _ => self.inline(&cell, str::to_string),
+                }
+            }
+            Kind::Row(cells) => {
+                let table = self.stack.iter_mut().rev().find_map(|f| match &mut f.kind {
+                    Kind::Table(rows) => Some(rows),
+                    _ => None,
+                });
+                if let Some(rows) = table
+                    && !cells.is_empty()
+                {
+                    rows.push(cells);
+                }
+            }
+            Kind::Table(rows) => {
+                let out = self.out();
+                block(out);
+                out.push_str(&table(&rows));
+                block(out);
+            }
+        }
+    }
+
+    /// Writes an inline element's `content` through `wrap`, keeping the
+    /// spaces around it outside the markup. Empty elements are dropped.
+    fn inline(&mut self, content: &str, wrap: impl Fn(&str) -> String) {
+        let inner = content.trim();
+        let out = self.out();
+        if inner.is_empty() {
+            if content.starts_with(' ') && !out.is_empty() && !out.ends_with(char::is_whitespace) {
+                out.push(' ');
+            }
+            return;
+        }
+        if content.starts_with(char::is_whitespace)
+            && !out.is_empty()
+            && !out.ends_with(char::is_whitespace)
+        {
+            out.push(' ');
+        }
+        out.push_str(&wrap(inner));
+        if content.ends_with(char::is_whitespace) {
+            out.push(' ');
+        }
+    }
+
+    /// The whole document, with unclosed elements closed and blank lines
+    /// squeezed.
+    fn finish(mut self) -> String {
+        while self.stack.len() > 1 {
+            self.finish_frame();
+        }
+        let out = self.stack.pop().expect("root frame").out;
+
+        let mut markdown = String::with_capacity(out.len());
+        let mut blank = 0;
+        let mut in_code = false;
+        for line in out.lines() {
+            let line = if in_code { line } else { line.trim_end() };
+            if line.starts_with("```") {
+                in_code = !in_code;
+            }
+            if line.trim().is_empty() && !in_code {
+                blank += 1;
+                continue;
+            }
+            if blank > 0 && !markdown.is_empty() {
+                markdown.push('\n');
+            }
+            blank = 0;
+            markdown.push_str(line);
+            markdown.push('\n');
+        }
+        markdown
+    }
+}
+
+/// Ends the paragraph being written, leaving a blank line after it.
+fn block(out: &mut String) {
+    trim_end_spaces(out);
+    if out.is_empty() {
+        return;
+    }
+    while !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+fn trim_end_spaces(out: &mut String) {
+    let len = out.trim_end_matches([' ', '\t']).len();
+    out.truncate(len);
+}
+
+/// A markdown table with the first row as its header.
+fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let row = |cells: &[String]| {
+        let cells: Vec<&str> = (0..columns)
+            .map(|i| cells.get(i).map_or("", String::as_str))
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![row(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|cells| row(cells)));
+    lines.join("\n")
+}
+
+/// `href` made absolute against the page at `base`. In-page anchors and
+/// `javascript:` and `data:` URLs have nowhere useful to point, so are
+/// `None`.
+fn absolute(base: &str, href: &str) -> Option<String> {
+    let href = href.trim();
+    let lower = href.to_ascii_lowercase();
+    if href.is_empty()
+        || href.starts_with('#')
+        || lower.starts_with("javascript:")
+        || lower.starts_with("data:")
+    {
+        return None;
+    }
+    if href.contains("://") || lower.starts_with("mailto:") {
+        return Some(href.to_string());
+    }
+
+    let base = base.split('#').next().unwrap_or(base);
+    let scheme_end = base.find("://").map_or(0, |i| i + 3);
+    let origin_end = base[scheme_end..]
+        .find(['/', '?'])
+        .map_or(base.len(), |i| scheme_end + i);
+    let origin = &base[..origin_end];
+
+    if let Some(rest) = href.strip_prefix("//") {
+        return Some(format!(
+            "{}//{}",
+            &base[..scheme_end.saturating_sub(2)],
+            rest
+        ));
+    }
+    if href.starts_with('?') {
+        return Some(format!(
+            "{}{}",
+            base.split('?').next().unwrap_or(base),
+            href
+        ));
+    }
+    let path = if href.starts_with('/') {
+        href.to_string()
+    } else {
+        let base_path = base[origin_end..].split('?').next().unwrap_or("");
+        let dir = base_path.rfind('/').map_or("/", |i| &base_path[..=i]);
+        format!("{}{}", dir, href)
+    };
+    Some(format!("{}{}", origin, remove_dot_segments(&path)))
+}
+
+/// `path` with its `.` and `..` segments resolved, as browsers do.
+fn remove_dot_segments(path: &str) -> String {
+    let (path, query) = match path.find(['?', '#']) {
+        Some(i) => path.split_at(i),
+        None => (path, ""),
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    let parts: Vec<&str> = path.split('/').collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+        match *part {
+            "." if last => segments.push(""),
+            "." => {}
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+                if last {
+                    segments.push("");
+                }
+            }
+            part => segments.push(part),
+        }
+    }
+    format!("{}{}", segments.join("/"), query)
+}
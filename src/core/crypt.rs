@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+
+/// Salt and passphrase check for an encrypted vault, kept in the vault root.
+/// Hidden, so the vault walk never treats it as a note.
+pub const KEY_FILE: &str = ".tui-jot-key.toml";
+
+/// Prefix marking a note file as encrypted. Files without it are read as
+/// plain text, so a vault can be converted a note at a time.
+const MAGIC: &[u8] = b"tui-jot-encrypted-v1\n";
+
+/// Known plaintext sealed with the key, to tell a wrong passphrase apart
+/// from a damaged note.
+const CHECK: &[u8] = b"tui-jot";
+
+const NONCE_LEN: usize = 24;
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    salt: String,
+    check: String,
+}
+
+/// The key notes are sealed with, derived from the vault passphrase.
+#[derive(Clone)]
+pub struct VaultKey {
+    cipher: XChaCha20Poly1305,
+}
+
+impl std::fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("VaultKey(..)")
+    }
+}
+
+impl VaultKey {
+    /// Whether the vault at `root` has a passphrase set.
+    pub fn exists(root: &Path) -> bool {
+        key_path(root).exists()
+    }
+
+    /// Sets up a new passphrase for the vault at `root`.
+    pub fn create(root: &Path, passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let key = Self::derive(passphrase, &salt)?;
+        let file = KeyFile {
+            salt: STANDARD.encode(salt),
+            check: STANDARD.encode(key.seal(CHECK)?),
+        };
+        std::fs::create_dir_all(root)?;
+        std::fs::write(key_path(root), toml::to_string(&file)?)?;
+        Ok(key)
+    }
+
+    /// Derives the vault key from `passphrase`, failing if it is not the one
+    /// the vault was set up with.
+    pub fn unlock(root: &Path, passphrase: &str) -> Result<Self> {
+        let path = key_path(root);
+        let file: KeyFile = toml::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|_| eyre!("{} is damaged", path.display()))?;
+        let salt = STANDARD
+            .decode(&file.salt)
+            .map_err(|_| eyre!("{} is damaged", path.display()))?;
+        let check = STANDARD
+            .decode(&file.check)
+            .map_err(|_| eyre!("{} is damaged", path.display()))?;
+
+        let key = Self::derive(passphrase, &salt)?;
+        match key.open(&check) {
+            Some(text) if text == CHECK => Ok(key),
+            _ => Err(eyre!("Wrong passphrase")),
+        }
+    }
+
+    /// Note text as it should be written to disk.
+    pub fn encrypt(&self, text: &str) -> Result<Vec<u8>> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(self.seal(text.as_bytes())?);
+        Ok(bytes)
+    }
+
+    /// Note text from a file's bytes, which may or may not be encrypted yet.
+    pub fn decrypt(&self, bytes: &[u8]) -> Result<String> {
+        let Some(sealed) = bytes.strip_prefix(MAGIC) else {
+            return Ok(String::from_utf8(bytes.to_vec())?);
+        };
+        let text = self
+            .open(sealed)
+            .ok_or_else(|| eyre!("Could not decrypt note"))?;
+        Ok(String::from_utf8(text)?)
+    }
+
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| eyre!("Could not derive key: {}", e))?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    fn seal(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .cipher
+            .encrypt(&nonce, plain)
+            .map_err(|_| eyre!("Could not encrypt note"))?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(sealed);
+        Ok(bytes)
+    }
+
+    fn open(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        self.cipher.decrypt(XNonce::from_slice(nonce), sealed).ok()
+    }
+}
+
+/// Whether a note file's bytes are encrypted.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn key_path(root: &Path) -> PathBuf {
+    root.join(KEY_FILE)
+}
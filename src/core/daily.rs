@@ -0,0 +1,116 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use super::Vault;
+
+/// The span of time a periodic note covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    /// ISO week, starting on Monday
+    Week,
+    Month,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::Day, Period::Week, Period::Month];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Day => "daily",
+            Period::Week => "weekly",
+            Period::Month => "monthly",
+        }
+    }
+
+    /// The first day of the period containing `date`.
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// The start of the period before or after the one starting on `date`.
+    pub fn step(self, date: NaiveDate, forward: bool) -> Option<NaiveDate> {
+        match (self, forward) {
+            (Period::Day, true) => date.checked_add_days(Days::new(1)),
+            (Period::Day, false) => date.checked_sub_days(Days::new(1)),
+            (Period::Week, true) => date.checked_add_days(Days::new(7)),
+            (Period::Week, false) => date.checked_sub_days(Days::new(7)),
+            (Period::Month, true) => date.checked_add_months(Months::new(1)),
+            (Period::Month, false) => date.checked_sub_months(Months::new(1)),
+        }
+    }
+
+    /// The start of the period a note name spells in `format`. Week and
+    /// month formats don't name a day, so one is supplied for parsing.
+    fn parse(self, stem: &str, format: &str) -> Option<NaiveDate> {
+        let parsed = match self {
+            Period::Day => NaiveDate::parse_from_str(stem, format),
+            Period::Week => {
+                NaiveDate::parse_from_str(&format!("{} 1", stem), &format!("{} %u", format))
+            }
+            Period::Month => {
+                NaiveDate::parse_from_str(&format!("{} 1", stem), &format!("{} %d", format))
+            }
+        };
+        parsed.ok()
+    }
+}
+
+/// The period a note is for, when its file name spells one in `format`.
+pub fn date_of(path: &Path, period: Period, format: &str) -> Option<NaiveDate> {
+    let stem = path.file_stem()?.to_str()?;
+    period.parse(stem, format)
+}
+
+/// The periodic note nearest to `date` in the direction of `forward`,
+/// wherever it lives in the vault. Notes sharing a date resolve to the first
+/// by path.
+pub fn adjacent(
+    vault: &Vault,
+    period: Period,
+    date: NaiveDate,
+    forward: bool,
+    format: &str,
+) -> Option<PathBuf> {
+    let days = vault
+        .notes
+        .keys()
+        .filter_map(|path| Some((date_of(path, period, format)?, path)))
+        .filter(|(day, _)| if forward { *day > date } else { *day < date });
+    let nearest = if forward {
+        days.min_by_key(|&(day, path)| (day, path))
+    } else {
+        days.min_by_key(|&(day, path)| (Reverse(day), path))
+    };
+    nearest.map(|(_, path)| path.clone())
+}
+
+/// The periodic note for exactly `date`, if there is one.
+pub fn find(vault: &Vault, period: Period, date: NaiveDate, format: &str) -> Option<PathBuf> {
+    vault
+        .notes
+        .keys()
+        .filter(|path| date_of(path, period, format) == Some(date))
+        .min()
+        .cloned()
+}
+
+/// Path for a new periodic note for `date` inside `dir`.
+pub fn path_for(dir: &Path, date: NaiveDate, format: &str) -> PathBuf {
+    dir.join(format!("{}.md", date.format(format)))
+}
+
+/// Text of a new periodic note named `name`: the template with `{{date}}`
+/// replaced by the name, or just a heading when there is no template.
+pub fn initial_content(template: Option<&str>, name: &str) -> String {
+    match template {
+        Some(template) => template.replace("{{date}}", name),
+        None => format!("# {}\n\n", name),
+    }
+}
@@ -0,0 +1,119 @@
+/// Beyond this many lines compared against lines, the changed middle of two
+/// texts is shown as removed and re-added instead of being matched up.
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Same(usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// A unified diff of `old` against `new` in the `@@ -a,b +c,d @@` hunk
+/// format git prints, with `context` unchanged lines around each change.
+/// Empty when both have the same lines.
+pub fn unified(old: &str, new: &str, context: usize) -> Vec<String> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = line_ops(&a, &b);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let Some(&first) = changes.first() else {
+        return Vec::new();
+    };
+
+    // Changes close enough to share context go in one hunk
+    let mut hunks = Vec::new();
+    let mut start = first.saturating_sub(context);
+    let mut end = first + 1;
+    for &change in &changes[1..] {
+        if change.saturating_sub(context) > end + context {
+            hunks.push((start, (end + context).min(ops.len())));
+            start = change.saturating_sub(context);
+        }
+        end = change + 1;
+    }
+    hunks.push((start, (end + context).min(ops.len())));
+
+    let mut out = Vec::new();
+    for (start, end) in hunks {
+        let in_old = |op: &Op| !matches!(op, Op::Added(_));
+        let in_new = |op: &Op| !matches!(op, Op::Removed(_));
+        let old_before = ops[..start].iter().filter(|op| in_old(op)).count();
+        let new_before = ops[..start].iter().filter(|op| in_new(op)).count();
+        let old_count = ops[start..end].iter().filter(|op| in_old(op)).count();
+        let new_count = ops[start..end].iter().filter(|op| in_new(op)).count();
+        // Like git, an empty side names the line before the hunk
+        let first_line = |before: usize, count: usize| before + usize::from(count > 0);
+        out.push(format!(
+            "@@ -{},{} +{},{} @@",
+            first_line(old_before, old_count),
+            old_count,
+            first_line(new_before, new_count),
+            new_count
+        ));
+        for op in &ops[start..end] {
+            out.push(match *op {
+                Op::Same(i) => format!(" {}", a[i]),
+                Op::Removed(i) => format!("-{}", a[i]),
+                Op::Added(j) => format!("+{}", b[j]),
+            });
+        }
+    }
+    out
+}
+
+/// Lines kept, removed and added to turn `a` into `b`, from their longest
+/// common subsequence once the shared start and end are set aside.
+fn line_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<Op> = (0..prefix).map(Op::Same).collect();
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_CELLS {
+        ops.extend((0..a_mid.len()).map(|i| Op::Removed(prefix + i)));
+        ops.extend((0..b_mid.len()).map(|j| Op::Added(prefix + j)));
+    } else {
+        // lcs[i][j] is the common subsequence length of a_mid[i..] and b_mid[j..]
+        let width = b_mid.len() + 1;
+        let mut lcs = vec![0u32; (a_mid.len() + 1) * width];
+        for i in (0..a_mid.len()).rev() {
+            for j in (0..b_mid.len()).rev() {
+                lcs[i * width + j] = if a_mid[i] == b_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a_mid.len() || j < b_mid.len() {
+            if i < a_mid.len() && j < b_mid.len() && a_mid[i] == b_mid[j] {
+                ops.push(Op::Same(prefix + i));
+                i += 1;
+                j += 1;
+            } else if j == b_mid.len()
+                || (i < a_mid.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                ops.push(Op::Removed(prefix + i));
+                i += 1;
+            } else {
+                ops.push(Op::Added(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    ops.extend((a.len() - suffix..a.len()).map(Op::Same));
+    ops
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use walkdir::WalkDir;
+
+use super::{Index, Vault, attachments, frontmatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    DuplicateTitle,
+    CaseConflict,
+    MalformedFrontmatter,
+    Unreadable,
+    OrphanedAttachment,
+    BrokenLink,
+}
+
+impl IssueKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            IssueKind::DuplicateTitle => "duplicate title",
+            IssueKind::CaseConflict => "case conflict",
+            IssueKind::MalformedFrontmatter => "malformed frontmatter",
+            IssueKind::Unreadable => "unreadable file",
+            IssueKind::OrphanedAttachment => "orphaned attachment",
+            IssueKind::BrokenLink => "broken link",
+        }
+    }
+}
+
+/// An automatic repair that is safe to apply without user judgement.
+#[derive(Debug, Clone)]
+pub enum Fix {
+    /// Rewrite `[[from]]` links in a note to point at an existing file stem.
+    RelinkByTitle { from: String, to: String },
+    /// Replace tab indentation in the frontmatter block with spaces.
+    UntabFrontmatter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub path: PathBuf,
+    pub detail: String,
+    pub fix: Option<Fix>,
+}
+
+/// Scans the vault on disk and in memory for integrity problems.
+pub fn check(vault: &Vault) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let index = Index::build(vault);
+
+    check_duplicate_titles(vault, &mut issues);
+    check_files(vault, &mut issues);
+    check_orphans(vault, &index, &mut issues);
+    check_frontmatter(vault, &mut issues);
+    check_broken_links(vault, &mut issues);
+
+    issues.sort_by(|a, b| a.path.cmp(&b.path));
+    issues
+}
+
+fn check_duplicate_titles(vault: &Vault, issues: &mut Vec<Issue>) {
+    let mut by_title: HashMap<String, Vec<&PathBuf>> = HashMap::new();
+    for (path, note) in &vault.notes {
+        by_title
+            .entry(note.title.to_lowercase())
+            .or_default()
+            .push(path);
+    }
+
+    for paths in by_title.values().filter(|p| p.len() > 1) {
+        for path in paths {
+            let others: Vec<String> = paths
+                .iter()
+                .filter(|p| p != &path)
+                .map(|p| p.display().to_string())
+                .collect();
+            issues.push(Issue {
+                kind: IssueKind::DuplicateTitle,
+                path: (*path).clone(),
+                detail: format!("same title as {}", others.join(", ")),
+                fix: None,
+            });
+        }
+    }
+}
+
+/// Walks the vault directory for case conflicts and unreadable notes.
+fn check_files(vault: &Vault, issues: &mut Vec<Issue>) {
+    let mut by_lowercase: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    for entry in WalkDir::new(&vault.root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !vault.skips(e))
+    {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e
+                    .path()
+                    .map(|p| relative(&vault.root, p))
+                    .unwrap_or_default();
+                issues.push(Issue {
+                    kind: IssueKind::Unreadable,
+                    path,
+                    detail: e.to_string(),
+                    fix: None,
+                });
+                continue;
+            }
+        };
+
+        let relative = relative(&vault.root, entry.path());
+        by_lowercase
+            .entry(relative.to_string_lossy().to_lowercase())
+            .or_default()
+            .push(relative.clone());
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        if entry.path().extension().is_some_and(|e| e == "md")
+            && let Err(e) = vault.read_file(entry.path())
+        {
+            issues.push(Issue {
+                kind: IssueKind::Unreadable,
+                path: relative,
+                detail: e.to_string(),
+                fix: None,
+            });
+        }
+    }
+
+    for paths in by_lowercase.values().filter(|p| p.len() > 1) {
+        for path in paths {
+            issues.push(Issue {
+                kind: IssueKind::CaseConflict,
+                path: path.clone(),
+                detail: "differs from another entry only by case".to_string(),
+                fix: None,
+            });
+        }
+    }
+}
+
+fn check_frontmatter(vault: &Vault, issues: &mut Vec<Issue>) {
+    for (path, note) in &vault.notes {
+        let problem = match frontmatter::split(&note.content()) {
+            None => continue,
+            Some(Err(e)) => e,
+            Some(Ok((yaml, _))) => match frontmatter::validate(yaml) {
+                Ok(()) => continue,
+                Err(e) => e,
+            },
+        };
+
+        let fix = problem
+            .contains("tabs are not allowed")
+            .then_some(Fix::UntabFrontmatter);
+        issues.push(Issue {
+            kind: IssueKind::MalformedFrontmatter,
+            path: path.clone(),
+            detail: problem,
+            fix,
+        });
+    }
+}
+
+fn check_broken_links(vault: &Vault, issues: &mut Vec<Issue>) {
+    for (path, note) in &vault.notes {
+        let mut seen: Vec<&str> = Vec::new();
+        for link in &note.links {
+            if vault.link_exists(&link.target) || seen.contains(&link.target.as_str()) {
+                continue;
+            }
+            seen.push(&link.target);
+
+            // A link written against a note's title rather than its filename can be
+            // repaired, as long as exactly one note has that title
+            let mut by_title = vault
+                .notes
+                .values()
+                .filter(|n| n.title.eq_ignore_ascii_case(&link.target));
+            let fix = match (by_title.next(), by_title.next()) {
+                (Some(target), None) => target.path.file_stem().map(|stem| Fix::RelinkByTitle {
+                    from: link.target.clone(),
+                    to: stem.to_string_lossy().to_string(),
+                }),
+                _ => None,
+            };
+
+            issues.push(Issue {
+                kind: IssueKind::BrokenLink,
+                path: path.clone(),
+                detail: format!("[[{}]] has no matching note", link.target),
+                fix,
+            });
+        }
+    }
+}
+
+fn check_orphans(vault: &Vault, index: &Index, issues: &mut Vec<Issue>) {
+    for path in attachments::find_orphans(vault, index) {
+        issues.push(Issue {
+            kind: IssueKind::OrphanedAttachment,
+            path,
+            detail: "not referenced by any note (see `tui-jot gc`)".to_string(),
+            fix: None,
+        });
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+/// Applies a fix to the note at `path`.
+pub fn apply_fix(vault: &Vault, path: &Path, fix: &Fix) -> Result<()> {
+    let content = vault.read_file(&vault.root.join(path))?;
+
+    let updated = match fix {
+        Fix::RelinkByTitle { from, to } => content
+            .replace(&format!("[[{}]]", from), &format!("[[{}|{}]]", to, from))
+            .replace(&format!("[[{}|", from), &format!("[[{}|", to)),
+        Fix::UntabFrontmatter => {
+            let Some(Ok((yaml, body))) = frontmatter::split(&content) else {
+                return Ok(());
+            };
+            let yaml: String = yaml
+                .split_inclusive('\n')
+                .map(|line| {
+                    // Keys are flat, so only list items keep an indent
+                    let stripped = line.trim_start_matches('\t');
+                    if stripped.len() < line.len() && stripped.starts_with('-') {
+                        format!("  {}", stripped)
+                    } else {
+                        stripped.to_string()
+                    }
+                })
+                .collect();
+            format!("---\n{}---\n{}", yaml, body)
+        }
+    };
+
+    vault.write_note(path, &updated)
+}
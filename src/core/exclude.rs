@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use walkdir::WalkDir;
+
+/// Ignore files honored when `vault.respect_gitignore` is on, in increasing
+/// precedence.
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Vault-relative glob patterns (`vault.exclude`) for files and folders the
+/// vault scan skips. A pattern ending in `/` names a folder and everything
+/// in it.
+#[derive(Debug, Clone)]
+pub struct Exclude {
+    set: GlobSet,
+    ignore_files: Vec<IgnoreFile>,
+}
+
+/// A `.gitignore` or `.ignore` file and the vault folder it applies to.
+#[derive(Debug, Clone)]
+struct IgnoreFile {
+    dir: PathBuf,
+    rank: usize,
+    matcher: Gitignore,
+}
+
+impl Default for Exclude {
+    fn default() -> Self {
+        Self {
+            set: GlobSet::empty(),
+            ignore_files: Vec::new(),
+        }
+    }
+}
+
+impl Exclude {
+    /// Builds the matcher, leaving out patterns that don't parse and
+    /// describing the first of them.
+    pub fn new(patterns: &[String]) -> (Self, Option<String>) {
+        let mut builder = GlobSetBuilder::new();
+        let mut error = None;
+
+        for pattern in patterns {
+            let pattern = pattern.trim().trim_start_matches("./");
+            let globs = match pattern.strip_suffix('/') {
+                Some(dir) => vec![dir.to_string(), format!("{}/**", dir)],
+                None => vec![pattern.to_string()],
+            };
+            for glob in globs {
+                match Glob::new(&glob) {
+                    Ok(glob) => {
+                        builder.add(glob);
+                    }
+                    Err(e) => {
+                        error.get_or_insert_with(|| {
+                            format!(
+                                "Ignoring vault.exclude pattern \"{}\": {}",
+                                pattern,
+                                e.kind()
+                            )
+                        });
+                    }
+                }
+            }
+        }
+
+        match builder.build() {
+            Ok(set) => (
+                Self {
+                    set,
+                    ignore_files: Vec::new(),
+                },
+                error,
+            ),
+            Err(e) => (
+                Self::default(),
+                Some(format!("Ignoring vault.exclude: {}", e)),
+            ),
+        }
+    }
+
+    /// Also skips what the `.gitignore` and `.ignore` files in the vault at
+    /// `root` list, with git's precedence: a file in a deeper folder wins,
+    /// and `.ignore` wins over `.gitignore`.
+    pub fn with_ignore_files(mut self, root: &Path) -> Self {
+        // Ignore files sort first in each folder, so the folders they rule
+        // out are never walked
+        let mut walk = WalkDir::new(root)
+            .sort_by_key(|e| !IGNORE_FILES.iter().any(|name| e.file_name() == *name))
+            .into_iter();
+
+        while let Some(Ok(entry)) = walk.next() {
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let name = entry.file_name().to_string_lossy();
+
+            if entry.file_type().is_dir() {
+                if entry.depth() > 0 && (name.starts_with('.') || self.is_excluded(relative, true))
+                {
+                    walk.skip_current_dir();
+                }
+                continue;
+            }
+
+            if let Some(rank) = IGNORE_FILES.iter().position(|file| name == *file) {
+                let (matcher, _) = Gitignore::new(entry.path());
+                self.ignore_files.push(IgnoreFile {
+                    dir: relative.parent().unwrap_or(Path::new("")).to_path_buf(),
+                    rank,
+                    matcher,
+                });
+            }
+        }
+
+        // Checked in order, so the file with the final say comes last
+        self.ignore_files
+            .sort_by_key(|file| (file.dir.components().count(), file.rank));
+        self
+    }
+
+    /// Whether a vault-relative file is excluded, by its own path or by one
+    /// of the folders it is in.
+    pub fn covers(&self, relative: &Path) -> bool {
+        relative
+            .ancestors()
+            .filter(|path| !path.as_os_str().is_empty())
+            .any(|path| self.is_excluded(path, path != relative))
+    }
+
+    pub fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.set.is_match(relative) {
+            return true;
+        }
+
+        let mut excluded = false;
+        for file in &self.ignore_files {
+            if let Ok(rest) = relative.strip_prefix(&file.dir) {
+                match file.matcher.matched(rest, is_dir) {
+                    Match::Ignore(_) => excluded = true,
+                    Match::Whitelist(_) => excluded = false,
+                    Match::None => {}
+                }
+            }
+        }
+        excluded
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use color_eyre::{Result, eyre::eyre};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, html};
+use serde::{Deserialize, Serialize};
+
+use super::note::{code_span_len, is_attachment_name, strip_comments};
+use super::{Note, Vault, opml, transclude};
+
+/// How wiki-link targets are previewed in an exported document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkPreview {
+    /// Links to notes exported alongside are plain anchors to their `.html` files.
+    #[default]
+    None,
+    /// A "Linked notes" section with an excerpt of every target is appended.
+    Appendix,
+    /// Each link carries an excerpt of its target as a hover tooltip.
+    Tooltip,
+}
+
+const EXCERPT_CHARS: usize = 240;
+
+/// Output formats offered by the export menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Rendered in-process, no external tools needed.
+    Html,
+    Pdf,
+    Docx,
+    /// The heading and list outline only, also built in.
+    Opml,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Html,
+        ExportFormat::Pdf,
+        ExportFormat::Docx,
+        ExportFormat::Opml,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "HTML",
+            ExportFormat::Pdf => "PDF",
+            ExportFormat::Docx => "Word (DOCX)",
+            ExportFormat::Opml => "OPML outline",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Docx => "docx",
+            ExportFormat::Opml => "opml",
+        }
+    }
+
+    /// Whether the format goes through the configured external converter.
+    pub fn uses_converter(self) -> bool {
+        matches!(self, ExportFormat::Pdf | ExportFormat::Docx)
+    }
+}
+
+/// A note flattened to plain markdown, ready to be piped into a converter.
+/// Owns all its data so conversions can run off the UI thread.
+#[derive(Debug, Clone)]
+pub struct ConvertJob {
+    pub title: String,
+    pub markdown: String,
+    pub workdir: PathBuf, // Directory of the note, so relative images resolve
+    pub output: PathBuf,
+}
+
+/// Renders a note to a standalone HTML document. Links only become anchors
+/// to notes in `names`, the set written alongside it.
+pub fn note_to_html(
+    vault: &Vault,
+    note: &Note,
+    names: &HashMap<&Path, String>,
+    preview: LinkPreview,
+    transclude: bool,
+) -> String {
+    let mut linked: Vec<&Note> = Vec::new();
+    let markdown = replace_wiki_links(&source(vault, note, transclude), |target, display, _| {
+        let Some(target_note) = vault.resolve_link(target) else {
+            return format!(
+                "<span class=\"broken-link\">{}</span>",
+                escape_html(display)
+            );
+        };
+
+        let name = names.get(target_note.path.as_path());
+        match (preview, name) {
+            (LinkPreview::None, None) => {
+                format!("<span class=\"wiki-link\">{}</span>", escape_html(display))
+            }
+            (LinkPreview::Tooltip, None) => format!(
+                "<span class=\"wiki-link\" title=\"{}\">{}</span>",
+                escape_html(&excerpt(target_note)),
+                escape_html(display)
+            ),
+            (LinkPreview::None, Some(name)) => format!(
+                "<a href=\"{}.html\">{}</a>",
+                escape_html(name),
+                escape_html(display)
+            ),
+            (LinkPreview::Tooltip, Some(name)) => format!(
+                "<a href=\"{}.html\" title=\"{}\">{}</a>",
+                escape_html(name),
+                escape_html(&excerpt(target_note)),
+                escape_html(display)
+            ),
+            (LinkPreview::Appendix, _) => {
+                let index = match linked.iter().position(|n| n.path == target_note.path) {
+                    Some(index) => index,
+                    None => {
+                        linked.push(target_note);
+                        linked.len() - 1
+                    }
+                };
+                format!(
+                    "<a href=\"#linked-{}\">{}</a>",
+                    index + 1,
+                    escape_html(display)
+                )
+            }
+        }
+    });
+
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new_ext(&markdown, Options::all()));
+
+    if !linked.is_empty() {
+        body.push_str("<hr>\n<section class=\"linked-notes\">\n<h2>Linked notes</h2>\n");
+        for (index, target) in linked.into_iter().enumerate() {
+            body.push_str(&format!(
+                "<div id=\"linked-{}\">\n<h3>{}</h3>\n<p>{}</p>\n</div>\n",
+                index + 1,
+                escape_html(&target.title),
+                escape_html(&excerpt(target))
+            ));
+        }
+        body.push_str("</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&note.title),
+        body
+    )
+}
+
+/// Markdown with its markup stripped, for pasting where the symbols would
+/// show literally: links become their display text, list items keep a plain
+/// bullet or number and blocks are separated by blank lines.
+pub fn to_plain_text(markdown: &str) -> String {
+    // An embed flattens to its name like any other link
+    let markdown = replace_wiki_links(&strip_comments(markdown), |_, display, _| {
+        display.to_string()
+    });
+    let mut out = String::new();
+    // The next number of each open list, or None for bullets
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut in_metadata = false;
+
+    let end_block = |out: &mut String, nested: bool| {
+        while !out.is_empty() && !out.ends_with(if nested { "\n" } else { "\n\n" }) {
+            out.push('\n');
+        }
+    };
+
+    for event in Parser::new_ext(&markdown, Options::all()) {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => in_metadata = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_metadata = false,
+            _ if in_metadata => {}
+            Event::Start(Tag::List(start)) => {
+                if !lists.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                end_block(&mut out, !lists.is_empty());
+            }
+            Event::Start(Tag::Item) => {
+                out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{}. ", n));
+                        *n += 1;
+                    }
+                    _ => out.push_str("- "),
+                }
+            }
+            Event::End(TagEnd::Item) => end_block(&mut out, true),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::Table,
+            ) => end_block(&mut out, !lists.is_empty()),
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                if out.ends_with('\t') {
+                    out.pop();
+                }
+                out.push('\n');
+            }
+            Event::Text(text)
+            | Event::Code(text)
+            | Event::InlineMath(text)
+            | Event::DisplayMath(text) => out.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => out.push('\n'),
+            Event::TaskListMarker(done) => out.push_str(if done { "[x] " } else { "[ ] " }),
+            Event::Rule => {
+                out.push_str("---");
+                end_block(&mut out, false);
+            }
+            Event::FootnoteReference(label) => out.push_str(&format!("[{}]", label)),
+            _ => {}
+        }
+    }
+
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out
+}
+
+/// File names, without extension, for exporting `notes` together: the slug
+/// of each title, or of the note's path when another note in the vault has
+/// a title with the same slug, so no two notes write the same file.
+pub fn file_names<'a>(vault: &Vault, notes: &[&'a Note]) -> HashMap<&'a Path, String> {
+    let mut slugs: HashMap<String, usize> = HashMap::new();
+    for note in vault.notes.values() {
+        *slugs.entry(slugify(&note.title)).or_default() += 1;
+    }
+
+    let mut taken = HashSet::new();
+    let mut names = HashMap::new();
+    for note in notes {
+        let slug = slugify(&note.title);
+        let base = if slug.is_empty() || slugs.get(&slug).is_some_and(|&count| count > 1) {
+            slugify(&note.path.with_extension("").to_string_lossy())
+        } else {
+            slug
+        };
+        // Paths can still slug alike, as `a b.md` and `a-b.md` do
+        let mut name = base.clone();
+        for n in 2.. {
+            if taken.insert(name.clone()) {
+                break;
+            }
+            name = format!("{}-{}", base, n);
+        }
+        names.insert(note.path.as_path(), name);
+    }
+    names
+}
+
+/// Writes the HTML export of `note` into `dir` and returns the file written.
+/// `names` comes from [`file_names`] for every note in the same export.
+pub fn export_html(
+    vault: &Vault,
+    note: &Note,
+    names: &HashMap<&Path, String>,
+    dir: &Path,
+    preview: LinkPreview,
+    transclude: bool,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let out = dir.join(format!("{}.html", file_name(names, note)));
+    std::fs::write(&out, note_to_html(vault, note, names, preview, transclude))?;
+    Ok(out)
+}
+
+/// Writes the OPML outline of `note` into `dir` and returns the file written.
+pub fn export_opml(
+    vault: &Vault,
+    note: &Note,
+    names: &HashMap<&Path, String>,
+    dir: &Path,
+    transclude: bool,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let out = dir.join(format!("{}.opml", file_name(names, note)));
+    std::fs::write(
+        &out,
+        opml::from_markdown(&note.title, &source(vault, note, transclude)),
+    )?;
+    Ok(out)
+}
+
+/// Prepares `note` for conversion to `format`, writing into `dir`.
+pub fn prepare_job(
+    vault: &Vault,
+    note: &Note,
+    names: &HashMap<&Path, String>,
+    dir: &Path,
+    format: ExportFormat,
+    transclude: bool,
+) -> ConvertJob {
+    let markdown = replace_wiki_links(
+        &source(vault, note, transclude),
+        |target, display, embed| {
+            if is_attachment_name(target) {
+                // `![[image.png]]` becomes `![image.png](image.png)`
+                format!("{}[{}]({})", if embed { "!" } else { "" }, display, target)
+            } else {
+                display.to_string()
+            }
+        },
+    );
+
+    let workdir = vault
+        .root
+        .join(&note.path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| vault.root.clone());
+
+    ConvertJob {
+        title: note.title.clone(),
+        markdown,
+        workdir,
+        output: dir.join(format!("{}.{}", file_name(names, note), format.extension())),
+    }
+}
+
+/// Pipes `job.markdown` into `command` on stdin. `{output}` in `args` is
+/// replaced with the output path; the converter's stderr is surfaced on failure.
+pub fn run_converter(job: &ConvertJob, command: &str, args: &[String]) -> Result<PathBuf> {
+    if let Some(parent) = job.output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let output = job.output.to_string_lossy();
+    let args: Vec<String> = args
+        .iter()
+        .map(|a| a.replace("{output}", &output))
+        .collect();
+
+    let mut child = Command::new(command)
+        .args(&args)
+        .current_dir(&job.workdir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => eyre!("converter `{}` not found", command),
+            _ => eyre!("failed to start `{}`: {}", command, e),
+        })?;
+
+    // Fed from its own thread: a converter that fills its stderr pipe before
+    // reading all of stdin would otherwise block with us writing to it
+    let writer = child.stdin.take().map(|mut stdin| {
+        let markdown = job.markdown.clone();
+        std::thread::spawn(move || stdin.write_all(markdown.as_bytes()))
+    });
+
+    let result = child.wait_with_output()?;
+    let written = match writer {
+        Some(writer) => writer
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("stdin writer panicked"))),
+        None => Ok(()),
+    };
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("no error output");
+        return Err(eyre!(
+            "{} exited with {}: {}",
+            command,
+            result.status,
+            reason.trim()
+        ));
+    }
+    written?;
+
+    Ok(job.output.clone())
+}
+
+/// The markdown to export: the note with comments removed and, optionally,
+/// its `![[Note]]` embeds expanded in place.
+fn source(vault: &Vault, note: &Note, transclude: bool) -> String {
+    if transclude {
+        strip_comments(&transclude::expand(vault, note))
+    } else {
+        strip_comments(&note.content())
+    }
+}
+
+/// Replaces every `[[target|display]]` in `content` with the output of
+/// `render`, which is told whether the link was a `![[embed]]`; the `!` is
+/// consumed with the link. Code blocks and inline code are left alone.
+fn replace_wiki_links(content: &str, mut render: impl FnMut(&str, &str, bool) -> String) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            out.push_str(line);
+        } else if in_fence {
+            out.push_str(line);
+        } else {
+            replace_links_in_line(line, &mut out, &mut render);
+        }
+    }
+
+    out
+}
+
+fn replace_links_in_line(
+    line: &str,
+    out: &mut String,
+    render: &mut impl FnMut(&str, &str, bool) -> String,
+) {
+    let mut rest = line;
+
+    while let Some(pos) = rest.find(['`', '[']) {
+        let from = &rest[pos..];
+        if from.starts_with('`') {
+            // Copy an inline code span through its closing backticks
+            let end = code_span_len(from);
+            out.push_str(&rest[..pos + end]);
+            rest = &rest[pos + end..];
+            continue;
+        }
+        if !from.starts_with("[[") {
+            out.push_str(&rest[..=pos]);
+            rest = &rest[pos + 1..];
+            continue;
+        }
+        let Some(len) = from[2..].find("]]") else {
+            break;
+        };
+
+        let embed = rest[..pos].ends_with('!');
+        out.push_str(&rest[..if embed { pos - 1 } else { pos }]);
+
+        let inner = &from[2..2 + len];
+        let (target, display) = match inner.split_once('|') {
+            Some((target, display)) => (target.trim(), display.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+        out.push_str(&render(target, display, embed));
+
+        rest = &from[2 + len + 2..];
+    }
+
+    out.push_str(rest);
+}
+
+/// The opening prose of a note, skipping its title heading.
+fn excerpt(note: &Note) -> String {
+    let text: String = note
+        .content()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if text.chars().count() > EXCERPT_CHARS {
+        let cut: String = text.chars().take(EXCERPT_CHARS).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        text
+    }
+}
+
+fn file_name(names: &HashMap<&Path, String>, note: &Note) -> String {
+    names
+        .get(note.path.as_path())
+        .cloned()
+        .unwrap_or_else(|| slugify(&note.title))
+}
+
+fn slugify(title: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,193 @@
+use std::time::SystemTime;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+
+/// Splits a leading `---` frontmatter block from the note body.
+/// Returns `None` when the note has no frontmatter, and an error when the
+/// opening delimiter is never closed.
+pub fn split(content: &str) -> Option<Result<(&str, &str), String>> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some(Ok((yaml, body)));
+        }
+        offset += line.len();
+    }
+
+    Some(Err("frontmatter is missing its closing ---".to_string()))
+}
+
+/// Checks that a frontmatter block uses the flat `key: value` / `- item`
+/// subset of YAML that tui-jot understands. Returns the first problem found.
+pub fn validate(yaml: &str) -> Result<(), String> {
+    let mut in_list = false;
+
+    for (i, line) in yaml.lines().enumerate() {
+        let line_no = i + 2; // +1 for 1-based, +1 for the opening ---
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('\t') {
+            return Err(format!(
+                "line {}: tabs are not allowed for indentation",
+                line_no
+            ));
+        }
+        if trimmed.starts_with("- ") || trimmed == "-" {
+            if !in_list {
+                return Err(format!("line {}: list item without a key", line_no));
+            }
+            continue;
+        }
+        if line.starts_with(' ') {
+            // Indented continuation of a multi-line value
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            return Err(format!("line {}: expected `key: value`", line_no));
+        };
+        if key.trim().is_empty() {
+            return Err(format!("line {}: empty key", line_no));
+        }
+        let value = value.trim();
+        if value.starts_with('[') && !value.ends_with(']') {
+            return Err(format!("line {}: unterminated inline list", line_no));
+        }
+        in_list = value.is_empty();
+    }
+
+    Ok(())
+}
+
+/// Reads a list-valued field from a frontmatter block. Accepts an inline
+/// list (`key: [a, b]`), an indented `- item` list, or a single scalar.
+pub fn list_field(yaml: &str, key: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut in_field = false;
+
+    for line in yaml.lines() {
+        let trimmed = line.trim();
+        if in_field {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                values.push(unquote(item));
+                continue;
+            }
+            if trimmed.is_empty() || line.starts_with(' ') {
+                continue;
+            }
+            break;
+        }
+
+        let Some((k, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        if !k.trim().eq_ignore_ascii_case(key) || line.starts_with(' ') {
+            continue;
+        }
+
+        let value = value.trim();
+        if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            values.extend(inner.split(',').map(unquote));
+            break;
+        } else if value.is_empty() {
+            in_field = true;
+        } else {
+            values.push(unquote(value));
+            break;
+        }
+    }
+
+    values.retain(|v| !v.is_empty());
+    values
+}
+
+/// Every top-level field of a frontmatter block with its values, keys
+/// lowercased, in the order they appear.
+pub fn fields(yaml: &str) -> Vec<(String, Vec<String>)> {
+    let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+    for line in yaml.lines() {
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        let Some((key, _)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        if !key.is_empty() && !fields.iter().any(|(k, _)| *k == key) {
+            let values = list_field(yaml, &key);
+            fields.push((key, values));
+        }
+    }
+    fields
+}
+
+/// Whether a boolean field is set to `true` (or `yes`).
+pub fn flag(yaml: &str, key: &str) -> bool {
+    list_field(yaml, key)
+        .first()
+        .is_some_and(|v| v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("yes"))
+}
+
+/// `content` with `key: value` added to its frontmatter, which is created
+/// when the note has none.
+pub fn with_field(content: &str, key: &str, value: &str) -> String {
+    match split(content) {
+        // The block's lines each keep their newline
+        Some(Ok((yaml, body))) => format!("---\n{}{}: {}\n---\n{}", yaml, key, value, body),
+        _ => format!("---\n{}: {}\n---\n{}", key, value, content),
+    }
+}
+
+/// `value` as a double-quoted YAML string, so a `#`, `: ` or quote in it
+/// can't end the value early or break the block.
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads a date field such as `created: 2025-07-01` or
+/// `created: 2025-07-01T14:03`, taken as local time.
+pub fn timestamp(yaml: &str, key: &str) -> Option<SystemTime> {
+    let value = list_field(yaml, key).into_iter().next()?;
+    let value = value.replacen(' ', "T", 1);
+    let time = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    Some(time.and_local_timezone(Local).earliest()?.into())
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+        .to_string()
+}
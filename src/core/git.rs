@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use color_eyre::{Result, eyre::eyre};
+
+/// A single commit touching a note, as reported by `git log`.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub hash: String,
+    pub short_hash: String,
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+    /// The note's path in this commit, which differs from today's when it
+    /// was renamed since
+    pub path: PathBuf,
+}
+
+/// Runs `git` inside the vault root and returns stdout on success.
+fn run_git(root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(eyre!("git {}: {}", args.join(" "), stderr.trim()))
+    }
+}
+
+/// Returns the commits that touched `relative_path`, newest first.
+/// Follows renames so history survives a note being moved.
+pub fn file_history(root: &Path, relative_path: &Path) -> Result<Vec<Revision>> {
+    let path = relative_path.to_string_lossy();
+    let stdout = run_git(
+        root,
+        &[
+            "log",
+            "--follow",
+            "--name-only",
+            "--relative",
+            "--format=%x1e%H%x1f%h%x1f%ad%x1f%an%x1f%s",
+            "--date=short",
+            "--",
+            &path,
+        ],
+    )?;
+
+    // Each commit is its header line followed by the file's name in it
+    let revisions = stdout
+        .split('\x1e')
+        .filter_map(|entry| {
+            let mut lines = entry.lines();
+            let mut fields = lines.next()?.split('\x1f');
+            Some(Revision {
+                hash: fields.next()?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+                path: lines
+                    .find(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| relative_path.to_path_buf()),
+            })
+        })
+        .collect();
+
+    Ok(revisions)
+}
+
+/// Returns a unified diff of the note between `revision` and the working
+/// copy at `relative_path`, pairing the two up when it was renamed since.
+pub fn diff_against_working(
+    root: &Path,
+    relative_path: &Path,
+    revision: &Revision,
+) -> Result<String> {
+    let path = relative_path.to_string_lossy();
+    let old_path = revision.path.to_string_lossy();
+    // Only the two names are compared, so any likeness at all is the rename
+    let mut args = vec![
+        "diff",
+        "--no-color",
+        "--find-renames=1%",
+        &revision.hash,
+        "--",
+    ];
+    args.push(&old_path);
+    if revision.path != relative_path {
+        args.push(&path);
+    }
+    run_git(root, &args)
+}
+
+/// Result of a completed sync cycle.
+#[derive(Debug)]
+pub enum SyncOutcome {
+    /// Everything committed, pulled and pushed. `pulled` is true when HEAD moved.
+    Synced { pulled: bool },
+    /// The pull left unmerged paths that need manual resolution.
+    Conflicts(Vec<PathBuf>),
+}
+
+/// Commits any local changes, then pulls from and pushes to `remote`.
+pub fn sync(root: &Path, remote: &str, message: &str) -> Result<SyncOutcome> {
+    // Never stage conflict markers left over from an earlier pull
+    let unmerged = unmerged_paths(root)?;
+    if !unmerged.is_empty() {
+        return Ok(SyncOutcome::Conflicts(unmerged));
+    }
+
+    // Only the vault's own files, when it is a folder inside a larger repo
+    run_git(root, &["add", "--all", "--", "."])?;
+    let status = run_git(root, &["status", "--porcelain", "--", "."])?;
+    if !status.trim().is_empty() {
+        run_git(root, &["commit", "--quiet", "-m", message, "--", "."])?;
+    }
+
+    // Named explicitly, so a branch without an upstream syncs too
+    let branch = run_git(root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    let branch = branch.trim();
+    if branch == "HEAD" {
+        return Err(eyre!("the vault is not on a branch"));
+    }
+
+    let before = run_git(root, &["rev-parse", "HEAD"])?;
+    // A branch not pushed yet has nothing to pull
+    let published = run_git(
+        root,
+        &["ls-remote", "--exit-code", "--heads", remote, branch],
+    )
+    .is_ok();
+    if published
+        && let Err(e) = run_git(root, &["pull", "--no-rebase", "--no-edit", remote, branch])
+    {
+        let files = unmerged_paths(root)?;
+        if files.is_empty() {
+            return Err(e);
+        }
+        return Ok(SyncOutcome::Conflicts(files));
+    }
+    let after = run_git(root, &["rev-parse", "HEAD"])?;
+
+    run_git(root, &["push", "--quiet", "--set-upstream", remote, branch])?;
+
+    Ok(SyncOutcome::Synced {
+        pulled: before != after,
+    })
+}
+
+fn unmerged_paths(root: &Path) -> Result<Vec<PathBuf>> {
+    let stdout = run_git(root, &["diff", "--name-only", "--diff-filter=U"])?;
+    Ok(stdout.lines().map(PathBuf::from).collect())
+}
+
+/// Abandons an in-progress merge left behind by a conflicting pull.
+pub fn abort_merge(root: &Path) -> Result<()> {
+    run_git(root, &["merge", "--abort"]).map(|_| ())
+}
@@ -4,7 +4,6 @@ use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct GraphNode {
-    pub path: PathBuf,
     pub title: String,
     pub connections: usize,
 }
@@ -37,7 +36,6 @@ impl Graph {
             nodes.insert(
                 path.clone(),
                 GraphNode {
-                    path: path.clone(),
                     title: note.title.clone(),
                     connections: 0,
                 },
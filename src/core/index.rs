@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use super::Vault;
+use super::{Vault, text};
 
 /// Pre-computed index of tags and links across all notes in the vault.
 pub struct Index {
@@ -9,14 +9,59 @@ pub struct Index {
     pub tags: HashMap<String, HashSet<PathBuf>>,
     /// link target (lowercase, no .md) → set of note paths that link to it
     pub forward_links: HashMap<String, HashSet<PathBuf>>,
+    /// attachment reference (lowercase, as written and resolved against the
+    /// note's directory) → set of note paths that embed it
+    pub embeds: HashMap<String, HashSet<PathBuf>>,
+    /// note path → its frontmatter aliases (lowercase), which links can use
+    /// in place of the file name
+    pub aliases: HashMap<PathBuf, Vec<String>>,
+    /// frontmatter key (lowercase) → value (folded) → note paths with it
+    pub properties: HashMap<String, HashMap<String, HashSet<PathBuf>>>,
+    /// note path → (done, total) for notes containing task list items
+    pub tasks: HashMap<PathBuf, (usize, usize)>,
+    /// directory → number of notes anywhere beneath it
+    pub note_counts: HashMap<PathBuf, usize>,
 }
 
 impl Index {
     pub fn build(vault: &Vault) -> Self {
         let mut tags: HashMap<String, HashSet<PathBuf>> = HashMap::new();
         let mut forward_links: HashMap<String, HashSet<PathBuf>> = HashMap::new();
+        let mut embeds: HashMap<String, HashSet<PathBuf>> = HashMap::new();
+        let mut aliases: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        let mut properties: HashMap<String, HashMap<String, HashSet<PathBuf>>> = HashMap::new();
+        let mut tasks: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+        let mut note_counts: HashMap<PathBuf, usize> = HashMap::new();
 
         for (path, note) in &vault.notes {
+            // Count the note in every folder above it, up to the root
+            for dir in path.ancestors().skip(1) {
+                if dir.as_os_str().is_empty() {
+                    break;
+                }
+                *note_counts.entry(dir.to_path_buf()).or_default() += 1;
+            }
+
+            // Index task progress
+            let (done, total) = note.tasks;
+            if total > 0 {
+                tasks.insert(path.clone(), (done, total));
+            }
+
+            if !note.aliases.is_empty() {
+                let names = note.aliases.iter().map(|a| a.to_lowercase()).collect();
+                aliases.insert(path.clone(), names);
+            }
+
+            for (key, value) in &note.properties {
+                properties
+                    .entry(key.clone())
+                    .or_default()
+                    .entry(text::fold(value))
+                    .or_default()
+                    .insert(path.clone());
+            }
+
             // Index tags
             for tag in &note.tags {
                 tags.entry(tag.clone()).or_default().insert(path.clone());
@@ -36,19 +81,56 @@ impl Index {
                     .or_default()
                     .insert(path.clone());
             }
+
+            // Index attachment references both verbatim and relative to the note
+            for embed in &note.embeds {
+                let resolved = path
+                    .parent()
+                    .map(|dir| normalize(&dir.join(embed)))
+                    .unwrap_or_else(|| PathBuf::from(embed));
+                for key in [
+                    embed.to_lowercase(),
+                    resolved.to_string_lossy().to_lowercase(),
+                ] {
+                    embeds.entry(key).or_default().insert(path.clone());
+                }
+            }
         }
 
         Self {
             tags,
             forward_links,
+            embeds,
+            aliases,
+            properties,
+            tasks,
+            note_counts,
         }
     }
 
+    /// Checked and total task list items in a note, if it has any.
+    pub fn task_progress(&self, path: &Path) -> Option<(usize, usize)> {
+        self.tasks.get(path).copied()
+    }
+
+    /// How many notes a directory holds, including those in its subfolders.
+    pub fn note_count(&self, dir: &Path) -> usize {
+        self.note_counts.get(dir).copied().unwrap_or(0)
+    }
+
     /// Returns all note paths that have the given tag.
     pub fn notes_with_tag(&self, tag: &str) -> Option<&HashSet<PathBuf>> {
         self.tags.get(&tag.to_lowercase())
     }
 
+    /// Notes whose frontmatter sets `key` to `value`, or has `value` in its
+    /// `key` list, ignoring case and diacritics.
+    pub fn notes_with_property(&self, key: &str, value: &str) -> Option<&HashSet<PathBuf>> {
+        self.properties
+            .get(&key.to_lowercase())?
+            .get(&text::fold(value))
+    }
+
     /// Returns all note paths that link to the given note path.
     pub fn get_backlinks(&self, note_path: &Path) -> Vec<PathBuf> {
         // Normalize: strip .md, lowercase
@@ -70,15 +152,19 @@ impl Index {
             }
         }
 
-        // Also check by filename only (for links like [[note-name]] without path)
-        if let Some(file_name) = target.file_name() {
-            let name_str = file_name.to_string_lossy().to_lowercase();
-            if name_str != target_str {
-                if let Some(sources) = self.forward_links.get(&*name_str) {
-                    for source in sources {
-                        if source != note_path && !backlinks.contains(source) {
-                            backlinks.push(source.clone());
-                        }
+        // Also check by filename only (for links like [[note-name]] without
+        // path) and by the note's aliases
+        let file_name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase());
+        let aliases = self.aliases.get(note_path).into_iter().flatten().cloned();
+        for name_str in file_name.into_iter().chain(aliases) {
+            if name_str != target_str
+                && let Some(sources) = self.forward_links.get(&name_str)
+            {
+                for source in sources {
+                    if source != note_path && !backlinks.contains(source) {
+                        backlinks.push(source.clone());
                     }
                 }
             }
@@ -91,7 +177,34 @@ impl Index {
     /// Returns a sorted list of all unique tags.
     pub fn all_tags(&self) -> Vec<&str> {
         let mut tags: Vec<&str> = self.tags.keys().map(|s| s.as_str()).collect();
-        tags.sort();
+        tags.sort_by(|a, b| text::collate(a, b));
         tags
     }
+
+    /// Whether any note embeds or links to the attachment at `path`
+    /// (vault-relative), either by full path or by bare file name.
+    pub fn is_attachment_referenced(&self, path: &Path) -> bool {
+        let full = path.to_string_lossy().replace('\\', "/").to_lowercase();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        self.embeds.contains_key(&full) || self.embeds.contains_key(&name)
+    }
+}
+
+/// Collapses `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
 }
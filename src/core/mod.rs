@@ -1,12 +1,35 @@
+pub mod attachments;
+pub mod bibtex;
+mod cache;
+pub mod clip;
+pub mod crypt;
+pub mod daily;
+pub mod diff;
+pub mod doctor;
+mod exclude;
+pub mod export;
+pub mod frontmatter;
+pub mod git;
 mod graph;
 mod index;
-mod note;
+pub mod note;
+pub mod opml;
+pub mod query;
+mod relink;
+pub mod snapshot;
+pub mod spell;
+mod store;
+pub mod table;
+pub mod text;
+pub mod transclude;
+pub mod undo_log;
 mod vault;
 
+pub use exclude::Exclude;
 pub use graph::{Graph, NodePosition};
 pub use index::Index;
 pub use note::Note;
-pub use vault::{TreeEntry, Vault};
+pub use vault::{TreeEntry, TreeSort, Vault, io_error};
 
 /// Fuzzy match: checks if all characters of `query` appear in `text` in order.
 pub fn fuzzy_match(query: &str, text: &str) -> bool {
@@ -1,19 +1,36 @@
 use std::collections::HashSet;
 use std::ops::Range;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::SystemTime;
 
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::cache::CachedNote;
+use super::frontmatter;
+use super::store::NoteStore;
+use super::{fuzzy_match, text};
+
 #[derive(Debug, Clone)]
 pub struct Note {
     pub path: PathBuf,
     pub title: String,
-    pub content: String,
     pub tags: HashSet<String>,
     pub links: Vec<Link>,
+    pub embeds: Vec<String>,  // Local attachment paths referenced by the note
+    pub aliases: Vec<String>, // Alternative names from the `aliases:` frontmatter field
+    pub locked: bool,         // `readonly: true` or `locked: true` in the frontmatter
+    /// Frontmatter fields as (lowercase key, value) pairs, one per list item
+    pub properties: Vec<(String, String)>,
+    pub tasks: (usize, usize), // Checked and total task list items
     pub modified: SystemTime,
+    /// The frontmatter's `created:` date, or else the file's creation time
+    pub created: SystemTime,
+    store: Arc<NoteStore>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Link {
     pub target: String,
     pub display: Option<String>,
@@ -21,28 +38,119 @@ pub struct Link {
 }
 
 impl Note {
-    pub fn from_file(path: PathBuf, content: String, modified: SystemTime) -> Self {
-        let title = Self::extract_title(&path, &content);
-        let tags = Self::extract_tags(&content);
-        let links = Self::extract_links(&content);
+    /// Parses the metadata of a note read from disk. The body itself is left
+    /// to `store`, which reads it back when `content` is called. `created` is
+    /// the file's creation time, used unless the frontmatter gives one.
+    pub fn from_file(
+        path: PathBuf,
+        content: &str,
+        modified: SystemTime,
+        created: SystemTime,
+        store: Arc<NoteStore>,
+    ) -> Self {
+        // Commented-out text must not contribute tags, links or a title
+        let visible = mask_comments(content);
+        let title = Self::extract_title(&path, &visible);
+        let tags = Self::extract_tags(&visible);
+        let links = Self::extract_links(&visible);
+        let embeds = Self::extract_embeds(&visible);
+        let (aliases, locked, created, properties) = match frontmatter::split(content) {
+            Some(Ok((yaml, _))) => (
+                frontmatter::list_field(yaml, "aliases"),
+                frontmatter::flag(yaml, "readonly") || frontmatter::flag(yaml, "locked"),
+                frontmatter::timestamp(yaml, "created").unwrap_or(created),
+                frontmatter::fields(yaml)
+                    .into_iter()
+                    .flat_map(|(key, values)| values.into_iter().map(move |v| (key.clone(), v)))
+                    .collect(),
+            ),
+            _ => (Vec::new(), false, created, Vec::new()),
+        };
+        let tasks = task_counts(content);
 
         Self {
             path,
             title,
-            content,
             tags,
             links,
+            embeds,
+            aliases,
+            locked,
+            properties,
+            tasks,
             modified,
+            created,
+            store,
+        }
+    }
+
+    /// A note whose metadata was cached when the vault was last opened.
+    pub fn from_cached(path: PathBuf, cached: CachedNote, store: Arc<NoteStore>) -> Self {
+        Self {
+            path,
+            title: cached.title,
+            tags: cached.tags,
+            links: cached.links,
+            embeds: cached.embeds,
+            aliases: cached.aliases,
+            locked: cached.locked,
+            properties: cached.properties,
+            tasks: cached.tasks,
+            modified: cached.modified,
+            created: cached.created,
+            store,
         }
     }
 
-    fn extract_title(path: &PathBuf, content: &str) -> String {
+    pub fn to_cached(&self) -> CachedNote {
+        CachedNote {
+            modified: self.modified,
+            created: self.created,
+            title: self.title.clone(),
+            tags: self.tags.clone(),
+            links: self.links.clone(),
+            embeds: self.embeds.clone(),
+            aliases: self.aliases.clone(),
+            locked: self.locked,
+            properties: self.properties.clone(),
+            tasks: self.tasks,
+        }
+    }
+
+    /// The name a folded `query` fuzzy matches: the title, or else the first
+    /// matching alias, flagged as such.
+    pub fn match_name(&self, query: &str) -> Option<(&str, bool)> {
+        if query.is_empty() || fuzzy_match(query, &text::fold(&self.title)) {
+            return Some((&self.title, false));
+        }
+        self.aliases
+            .iter()
+            .find(|alias| fuzzy_match(query, &text::fold(alias)))
+            .map(|alias| (alias.as_str(), true))
+    }
+
+    /// The full text of the note, or `None` when it can no longer be read.
+    /// Anything that writes the text back must use this rather than
+    /// `content`, which would turn an unreadable note into an empty one.
+    pub fn read(&self) -> Option<Arc<str>> {
+        self.store.get(&self.path)
+    }
+
+    /// The full text of the note for display, empty when it can't be read.
+    pub fn content(&self) -> Arc<str> {
+        self.read().unwrap_or_default()
+    }
+
+    /// Like `content`, without disturbing the cache of recently used notes;
+    /// for searches and other passes over every note.
+    pub fn scan(&self) -> Arc<str> {
+        self.store.peek(&self.path).unwrap_or_default()
+    }
+
+    fn extract_title(path: &Path, content: &str) -> String {
         // Try to find first H1 heading
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.starts_with("# ") {
-                return trimmed[2..].trim().to_string();
-            }
+        if let Some(heading) = first_heading(content) {
+            return heading.to_string();
         }
 
         // Fall back to filename without extension
@@ -123,9 +231,11 @@ impl Note {
 
                 if i + 1 < bytes.len() && bytes[i] == b']' && bytes[i + 1] == b']' {
                     let end = i + 2;
-                    if !target.is_empty() {
+                    // `[[Note#Heading]]` and `[[Note^block]]` still point at Note
+                    let target = target.split(['#', '^']).next().unwrap_or("").trim();
+                    if !target.is_empty() && !is_attachment_name(target) {
                         links.push(Link {
-                            target: target.trim().to_string(),
+                            target: target.to_string(),
                             display: display.map(|d| d.trim().to_string()),
                             span: start..end,
                         });
@@ -139,4 +249,413 @@ impl Note {
 
         links
     }
+
+    /// Collects attachment references: `![[file.png]]` embeds plus the
+    /// destinations of markdown links and images, and the `src` of HTML
+    /// images, that point inside the vault.
+    fn extract_embeds(content: &str) -> Vec<String> {
+        let mut embeds = Vec::new();
+        let mut rest = content;
+
+        while let Some(start) = rest.find("[[") {
+            let Some(len) = rest[start + 2..].find("]]") else {
+                break;
+            };
+            let inner = &rest[start + 2..start + 2 + len];
+            let target = inner.split('|').next().unwrap_or(inner).trim();
+            // Broader than is_attachment_name: `gc` must not trash a linked
+            // `data.json` just because links don't treat it as a file
+            if has_file_extension(target) {
+                embeds.push(target.to_string());
+            }
+            rest = &rest[start + 2 + len + 2..];
+        }
+
+        let mut rest = content;
+        while let Some(start) = rest.find("](") {
+            let after = &rest[start + 2..];
+            let (dest, len) = link_destination(after);
+            push_local(&mut embeds, dest);
+            rest = &after[len..];
+        }
+
+        // Raw HTML images, which markdown allows inline
+        let lower = content.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(pos) = lower[from..].find("<img") {
+            let tag_start = from + pos;
+            let tag_end = lower[tag_start..]
+                .find('>')
+                .map_or(lower.len(), |end| tag_start + end);
+            if let Some(src) = lower[tag_start..tag_end].find("src=") {
+                let value = &content[tag_start + src + 4..tag_end];
+                let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''));
+                let dest = match quote {
+                    Some(q) => value[1..].split(q).next().unwrap_or_default(),
+                    None => value
+                        .split(char::is_whitespace)
+                        .next()
+                        .unwrap_or_default()
+                        .trim_end_matches('/'),
+                };
+                push_local(&mut embeds, dest);
+            }
+            from = tag_end;
+        }
+
+        embeds
+    }
+}
+
+/// Counts whitespace-separated words, ignoring frontmatter and comments.
+pub fn word_count(content: &str) -> usize {
+    let body = match frontmatter::split(content) {
+        Some(Ok((_, body))) => body,
+        _ => content,
+    };
+    strip_comments(body)
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Counts task list items as `(done, total)`, skipping code blocks and comments.
+pub fn task_counts(content: &str) -> (usize, usize) {
+    let mut in_code = false;
+    let (mut done, mut total) = (0, 0);
+
+    for line in strip_comments(content).lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(checked) = task_state(line) {
+            total += 1;
+            if checked {
+                done += 1;
+            }
+        }
+    }
+
+    (done, total)
+}
+
+/// For a list item written as a task (`- [ ] todo`, `1. [x] done`), whether
+/// it is checked. Returns `None` for any other line.
+pub fn task_state(line: &str) -> Option<bool> {
+    let item = line.trim_start();
+    let rest = match item.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let rest = item.trim_start_matches(|c: char| c.is_ascii_digit());
+            if rest.len() == item.len() {
+                return None;
+            }
+            rest.strip_prefix(['.', ')'])?
+        }
+    };
+
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let checked = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    rest[3..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace)
+        .then_some(checked)
+}
+
+/// An unchecked or checked task carrying a due date.
+#[derive(Debug, Clone)]
+pub struct DueTask {
+    /// Line of the task in the note, counted from 0
+    pub line: usize,
+    /// The task's text without its list marker, checkbox or due annotation
+    pub text: String,
+    pub due: NaiveDate,
+    pub done: bool,
+}
+
+/// The tasks in `content` annotated with a due date, in note order. Tasks
+/// in code blocks and comments are skipped.
+pub fn due_tasks(content: &str) -> Vec<DueTask> {
+    let mut in_code = false;
+    let mut tasks = Vec::new();
+
+    for (line_idx, line) in strip_comments(content).lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let Some(done) = task_state(line) else {
+            continue;
+        };
+        let Some((due, span)) = due_date(line) else {
+            continue;
+        };
+        let text = format!("{}{}", &line[..span.start], &line[span.end..]);
+        let text = text.trim_start();
+        let text = text
+            .find(']')
+            .map_or(text, |close| &text[close + 1..])
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        tasks.push(DueTask {
+            line: line_idx,
+            text,
+            due,
+            done,
+        });
+    }
+
+    tasks
+}
+
+/// The due date of a line, written `@due(2025-07-01)` or `📅 2025-07-01`,
+/// with the byte range of the annotation.
+pub fn due_date(line: &str) -> Option<(NaiveDate, Range<usize>)> {
+    for (prefix, suffix) in [("@due(", ")"), ("📅 ", "")] {
+        for (start, _) in line.match_indices(prefix) {
+            let date_start = start + prefix.len();
+            let Some(date) = line.get(date_start..date_start + 10) else {
+                continue;
+            };
+            let Ok(due) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            let end = date_start + 10;
+            if line[end..].starts_with(suffix) {
+                return Some((due, start..end + suffix.len()));
+            }
+        }
+    }
+    None
+}
+
+/// File types a link can point at besides notes. Anything else after a dot,
+/// as in `[[Node.js]]` or `[[v1.2]]`, is part of a note's name.
+const ATTACHMENT_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "avif", "heic", "tif", "tiff", "ico", "pdf",
+    "mp3", "wav", "ogg", "m4a", "flac", "opus", "mp4", "mov", "mkv", "webm", "avi", "zip", "csv",
+    "txt", "docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "canvas",
+];
+
+/// Whether a link target names a non-note file such as `diagram.png`.
+pub fn is_attachment_name(target: &str) -> bool {
+    std::path::Path::new(target)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            ATTACHMENT_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Whether `target` ends in a non-note extension of any kind.
+/// The destination of a markdown link starting just after its `](`, and
+/// how many bytes of `text` it spans. Parentheses inside an unwrapped
+/// destination must balance, as in `file(1).png`.
+fn link_destination(text: &str) -> (&str, usize) {
+    let text_start = text.len() - text.trim_start().len();
+    let trimmed = &text[text_start..];
+    if let Some(wrapped) = trimmed.strip_prefix('<') {
+        return match wrapped.find(['>', '\n']) {
+            Some(end) => (&wrapped[..end], text_start + 1 + end),
+            None => ("", text_start),
+        };
+    }
+
+    let mut depth = 0;
+    for (i, c) in trimmed.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return (&trimmed[..i], text_start + i),
+            ')' => depth -= 1,
+            c if c.is_whitespace() => return (&trimmed[..i], text_start + i),
+            _ => {}
+        }
+    }
+    ("", text_start)
+}
+
+/// Records `dest` when it points at a file inside the vault, with any
+/// `%20`-style escapes decoded.
+fn push_local(embeds: &mut Vec<String>, dest: &str) {
+    let is_local = !dest.is_empty()
+        && !dest.contains("://")
+        && !dest.starts_with('#')
+        && !dest.starts_with("mailto:")
+        && !dest.starts_with("data:");
+    if is_local {
+        let path = dest.split(['#', '?']).next().unwrap_or(dest);
+        embeds.push(percent_decode(path.trim_start_matches("./")));
+    }
+}
+
+/// Decodes `%XX` escapes; malformed ones are kept as written.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) if bytes[i] == b'%' => {
+                out.push(byte);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn has_file_extension(target: &str) -> bool {
+    std::path::Path::new(target)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            !ext.eq_ignore_ascii_case("md") && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// The text of the first `# ` heading in `content`.
+pub fn first_heading(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map(str::trim)
+}
+
+/// `content` with its first `# ` heading changed to `title`, or `None` when
+/// it has no such heading.
+pub fn set_first_heading(content: &str, title: &str) -> Option<String> {
+    let index = content
+        .lines()
+        .position(|line| line.trim().starts_with("# "))?;
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    lines[index] = format!("# {}", title);
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// Blanks out `%%comment%%` spans (which may cover several lines) outside
+/// code. Every character is replaced by a space and newlines are kept, so
+/// columns and line numbers still line up with the original content.
+pub fn mask_comments(content: &str) -> String {
+    hide_comments(content, true)
+}
+
+/// Removes `%%comment%%` spans outright, for output where offsets don't matter.
+pub fn strip_comments(content: &str) -> String {
+    hide_comments(content, false)
+}
+
+fn hide_comments(content: &str, keep_width: bool) -> String {
+    if !content.contains("%%") {
+        return content.to_string();
+    }
+
+    let blank = |out: &mut String, text: &str| {
+        if keep_width {
+            out.extend(std::iter::repeat_n(' ', text.chars().count()));
+        }
+    };
+
+    let mut out = String::with_capacity(content.len());
+    let mut in_comment = false;
+    let mut in_code = false;
+
+    for line in content.split_inclusive('\n') {
+        if !in_comment && line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while !rest.is_empty() {
+            if in_comment {
+                match rest.find("%%") {
+                    Some(pos) => {
+                        blank(&mut out, &rest[..pos + 2]);
+                        in_comment = false;
+                        rest = &rest[pos + 2..];
+                    }
+                    None => {
+                        let text = rest.trim_end_matches('\n');
+                        blank(&mut out, text);
+                        out.push_str(&rest[text.len()..]);
+                        rest = "";
+                    }
+                }
+                continue;
+            }
+
+            // `%%` inside inline code is literal
+            let code = rest.find('`');
+            match rest.find("%%") {
+                Some(pos) if code.is_none_or(|code| pos < code) => {
+                    out.push_str(&rest[..pos]);
+                    blank(&mut out, "%%");
+                    in_comment = true;
+                    rest = &rest[pos + 2..];
+                }
+                _ => match code {
+                    Some(code) => {
+                        let end = code + code_span_len(&rest[code..]);
+                        out.push_str(&rest[..end]);
+                        rest = &rest[end..];
+                    }
+                    None => {
+                        out.push_str(rest);
+                        rest = "";
+                    }
+                },
+            }
+        }
+    }
+
+    out
+}
+
+/// Length of the inline code span `text` starts with, from its opening
+/// backticks through the matching closing run; just the opening run when
+/// it is never closed.
+pub fn code_span_len(text: &str) -> usize {
+    let ticks = text.len() - text.trim_start_matches('`').len();
+    let rest = &text[ticks..];
+    let mut from = 0;
+    while let Some(pos) = rest[from..].find('`') {
+        let start = from + pos;
+        let run = rest[start..].len() - rest[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return ticks + start + run;
+        }
+        from = start + run;
+    }
+    ticks
 }
@@ -0,0 +1,256 @@
+use color_eyre::{Result, eyre::eyre};
+
+use super::export;
+
+/// One entry of an outline, `depth` levels below the top.
+struct Entry {
+    depth: usize,
+    text: String,
+    done: bool,
+}
+
+/// The heading and list structure of a note as an OPML 2.0 document.
+/// Headings nest by level, list items under the heading before them by
+/// indentation; prose, code and tables are left out. Checked tasks are
+/// marked `_complete`, as outliners such as Workflowy do.
+pub fn from_markdown(title: &str, markdown: &str) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!(
+        "  <head>\n    <title>{}</title>\n  </head>\n  <body>\n",
+        escape(title)
+    ));
+
+    let entries = outline(markdown);
+    for (i, entry) in entries.iter().enumerate() {
+        let indent = "  ".repeat(entry.depth + 2);
+        let complete = if entry.done {
+            " _complete=\"true\""
+        } else {
+            ""
+        };
+        let has_children = entries
+            .get(i + 1)
+            .is_some_and(|next| next.depth > entry.depth);
+        out.push_str(&format!(
+            "{}<outline text=\"{}\"{}{}>\n",
+            indent,
+            escape(&entry.text),
+            complete,
+            if has_children { "" } else { " /" }
+        ));
+        // Close every entry this one was the last child of
+        let next_depth = entries.get(i + 1).map_or(0, |next| next.depth);
+        for depth in (next_depth..entry.depth).rev() {
+            out.push_str(&format!("{}</outline>\n", "  ".repeat(depth + 2)));
+        }
+    }
+
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// An OPML document as a note: its title, or `fallback_title` when it has
+/// none, as the heading and its outline as a nested list, with `_note` text
+/// indented under the item it belongs to. Returns the title and markdown.
+pub fn to_markdown(opml: &str, fallback_title: &str) -> Result<(String, String)> {
+    if !opml.contains("<opml") {
+        return Err(eyre!("not an OPML document"));
+    }
+    let title = opml
+        .split_once("<title>")
+        .and_then(|(_, rest)| rest.split_once("</title>"))
+        .map(|(title, _)| unescape(title.trim()))
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| fallback_title.to_string());
+
+    let mut out = format!("# {}\n\n", title);
+
+    let mut depth: usize = 0;
+    let mut entries = 0;
+    let mut rest = opml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 3)
+        } else {
+            rest.find('>').map(|i| i + 1)
+        };
+        let Some(end) = end else {
+            break;
+        };
+        let tag = &rest[..end];
+        rest = &rest[end..];
+
+        if tag.starts_with("</outline") {
+            depth = depth.saturating_sub(1);
+        } else if tag.starts_with("<outline") {
+            let indent = "  ".repeat(depth);
+            let text = attribute(tag, "text").unwrap_or_default();
+            let marker = if attribute(tag, "_complete").as_deref() == Some("true") {
+                "- [x] "
+            } else {
+                "- "
+            };
+            out.push_str(&format!("{}{}{}\n", indent, marker, text));
+            if let Some(note) = attribute(tag, "_note") {
+                for line in note.lines() {
+                    out.push_str(&format!("{}  {}\n", indent, line));
+                }
+            }
+            entries += 1;
+            if !tag.ends_with("/>") {
+                depth += 1;
+            }
+        }
+    }
+
+    if entries == 0 {
+        return Err(eyre!("the outline has no entries"));
+    }
+    Ok((title, out))
+}
+
+/// Headings and list items in document order, with how deep each sits.
+fn outline(markdown: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    // Nesting keys of the open entries: heading levels, then list indents
+    // offset past every heading level
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let (key, text) = if let Some(level) = heading_level(line) {
+            (level, line[level..].trim())
+        } else if let Some(text) = list_item(trimmed) {
+            let indent: usize = line[..line.len() - trimmed.len()]
+                .chars()
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+            (7 + indent, text)
+        } else {
+            continue;
+        };
+
+        while open.last().is_some_and(|&last| last >= key) {
+            open.pop();
+        }
+        let (done, text) = match text
+            .strip_prefix("[x] ")
+            .or_else(|| text.strip_prefix("[X] "))
+        {
+            Some(task) => (true, task),
+            None => (false, text.strip_prefix("[ ] ").unwrap_or(text)),
+        };
+        entries.push(Entry {
+            depth: open.len(),
+            text: export::to_plain_text(text),
+            done,
+        });
+        open.push(key);
+    }
+
+    entries
+}
+
+/// The level of an ATX heading line, `# ` through `###### `.
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    ((1..=6).contains(&level) && line[level..].starts_with(' ')).then_some(level)
+}
+
+/// The text of a bullet or numbered list item.
+fn list_item(trimmed: &str) -> Option<&str> {
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet))
+    {
+        return Some(text);
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    trimmed[digits..]
+        .strip_prefix(". ")
+        .or_else(|| trimmed[digits..].strip_prefix(") "))
+}
+
+/// The unescaped value of attribute `name` in an element's opening tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().last();
+        let after = rest[at + name.len()..].trim_start();
+        rest = &rest[at + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let value = &value[1..];
+        let end = value.find(quote)?;
+        return Some(unescape(&value[..end]));
+    }
+    None
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
+
+/// Decodes the predefined XML entities and numeric character references.
+pub(super) fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use super::{Index, text};
+
+/// A search as typed: free text to find in notes, narrowed by filters
+/// joined with AND. `tag:#inbox`, `path:projects/` and `task:open` (or
+/// `task:done`) look at tags, folders and checkboxes; any other
+/// `key:value` at the frontmatter (`status:draft`, `type:"book club"`).
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub text: String,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Frontmatter key (lowercase) and value
+    Property(String, String),
+    /// Tag without its `#`, lowercase
+    Tag(String),
+    /// Start of the vault-relative path, lowercase
+    Path(String),
+    /// Notes with unchecked tasks when true, with all tasks checked when false
+    Task(bool),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut words = Vec::new();
+        for token in tokens(input) {
+            if token == "AND" {
+                continue;
+            }
+            match filter(&token) {
+                Some(filter) => query.filters.push(filter),
+                None => words.push(token),
+            }
+        }
+        // Without filters the text is searched exactly as typed
+        query.text = if query.has_filters() {
+            words.join(" ")
+        } else {
+            input.to_string()
+        };
+        query
+    }
+
+    pub fn has_filters(&self) -> bool {
+        !self.filters.is_empty()
+    }
+
+    /// Whether the note at `path` passes every filter.
+    pub fn matches(&self, path: &Path, index: &Index) -> bool {
+        self.filters.iter().all(|filter| match filter {
+            Filter::Property(key, value) => index
+                .notes_with_property(key, value)
+                .is_some_and(|paths| paths.contains(path)),
+            Filter::Tag(tag) => index
+                .notes_with_tag(tag)
+                .is_some_and(|paths| paths.contains(path)),
+            Filter::Path(prefix) => path
+                .to_string_lossy()
+                .replace('\\', "/")
+                .to_lowercase()
+                .starts_with(prefix.as_str()),
+            Filter::Task(open) => index
+                .task_progress(path)
+                .is_some_and(|(done, total)| (done < total) == *open),
+        })
+    }
+
+    /// Whether `content` holds the query's text, ignoring case and
+    /// diacritics. Always true when there is no text.
+    pub fn matches_text(&self, content: &str) -> bool {
+        self.text.is_empty() || text::fold(content).contains(&text::fold(&self.text))
+    }
+}
+
+/// Splits on whitespace, keeping `"quoted text"` in one token.
+fn tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// A `key:value` token as a filter. The key is a word, so times (`10:30`)
+/// and addresses (`https://...`) stay plain text.
+fn filter(token: &str) -> Option<Filter> {
+    let (key, value) = token.split_once(':')?;
+    let is_key = key.chars().next()?.is_alphabetic()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    let value = value.trim_matches('"');
+    if !is_key || value.is_empty() || value.starts_with('/') {
+        return None;
+    }
+    let key = key.to_lowercase();
+    Some(match key.as_str() {
+        "tag" => Filter::Tag(value.trim_start_matches('#').to_lowercase()),
+        "path" => Filter::Path(value.to_lowercase()),
+        "task" if value.eq_ignore_ascii_case("open") => Filter::Task(true),
+        "task" if value.eq_ignore_ascii_case("done") => Filter::Task(false),
+        _ => Filter::Property(key, value.to_string()),
+    })
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How a wiki-link spells a note's path: forward slashes and no `.md`.
+pub fn link_path(path: &Path) -> String {
+    path.with_extension("").to_string_lossy().replace('\\', "/")
+}
+
+/// [`link_path`] lowercased, for comparing against link targets.
+pub fn link_key(path: &Path) -> String {
+    link_path(path).to_lowercase()
+}
+
+/// Rewrites the wiki-links in `content` whose target is a key of `moved`, the
+/// [`link_key`] of a note's old path or its old name, to the new spelling.
+/// Headings, block references and display text are kept. Returns `None` if
+/// nothing changed.
+pub fn rewrite_links(content: &str, moved: &HashMap<String, String>) -> Option<String> {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    let mut changed = false;
+
+    while let Some(start) = rest.find("[[") {
+        let inner_start = start + 2;
+        let Some(len) = rest[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &rest[inner_start..inner_start + len];
+        let target_len = inner.find(['|', '#', '^']).unwrap_or(inner.len());
+        let target = inner[..target_len].trim();
+        let has_ext = target.to_lowercase().ends_with(".md");
+        let key = target[..target.len() - if has_ext { 3 } else { 0 }].to_lowercase();
+
+        out.push_str(&rest[..inner_start]);
+        match moved.get(&key) {
+            Some(new) => {
+                out.push_str(new);
+                if has_ext {
+                    out.push_str(".md");
+                }
+                out.push_str(&inner[target_len..]);
+                changed = true;
+            }
+            None => out.push_str(inner),
+        }
+        rest = &rest[inner_start + len..];
+    }
+
+    out.push_str(rest);
+    changed.then_some(out)
+}
@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use color_eyre::Result;
+use walkdir::WalkDir;
+
+const EXTENSION: &str = ".tar.zst";
+/// Marks a snapshot holding only the files changed since the one before it
+const INCREMENTAL: &str = "-changed";
+/// Archive entry listing every file the vault held when the snapshot was
+/// taken. Hidden, so it can't clash with a file from the vault
+const MANIFEST: &str = ".tui-jot-files";
+/// Archive entry in a `-changed` snapshot listing the files deleted since
+/// the snapshot before it
+const DELETED: &str = ".tui-jot-deleted";
+
+/// Archives the vault into `dir` as `<vault>-<timestamp>.tar.zst`. Hidden
+/// entries such as `.git` and `.trash` are left out. With `changed_only`,
+/// only files modified since the newest existing snapshot are included, in
+/// a `-changed` archive that needs the full snapshot before it to restore
+/// and lists the files deleted since; a full one is taken instead when
+/// there is none yet, or when `keep` archives already depend on the last
+/// one, so `prune` has chains to drop. Returns the archive path and file
+/// count, or `None` when there was nothing to archive.
+pub fn create(
+    root: &Path,
+    dir: &Path,
+    changed_only: bool,
+    keep: usize,
+) -> Result<Option<(PathBuf, usize)>> {
+    let prefix = prefix(root);
+    let snapshots = list(dir, &prefix);
+    let chain = snapshots.iter().rev().position(|p| !is_incremental(p));
+    let incremental = changed_only && chain.is_some_and(|len| keep == 0 || len + 1 < keep);
+    let previous = snapshots.last().filter(|_| incremental);
+    let since = previous
+        .and_then(|p| p.metadata().ok())
+        .and_then(|m| m.modified().ok());
+
+    let all = vault_files(root, dir);
+    let files: Vec<&PathBuf> = all
+        .iter()
+        .filter(|relative| since.is_none_or(|since| modified_after(&root.join(relative), since)))
+        .collect();
+    let present: HashSet<&PathBuf> = all.iter().collect();
+    let deleted: Vec<PathBuf> = match previous {
+        Some(previous) => read_manifest(previous)
+            .into_iter()
+            .filter(|path| !present.contains(path))
+            .collect(),
+        None => Vec::new(),
+    };
+    if files.is_empty() && deleted.is_empty() {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let kind = if incremental { INCREMENTAL } else { "" };
+    // Two snapshots within a second get a counter rather than clobbering
+    let mut path = dir.join(format!("{}-{}{}{}", prefix, stamp, kind, EXTENSION));
+    for n in 2.. {
+        if !path.exists() {
+            break;
+        }
+        path = dir.join(format!("{}-{}-{}{}{}", prefix, stamp, n, kind, EXTENSION));
+    }
+
+    let encoder = zstd::Encoder::new(File::create(&path)?, 0)?;
+    let mut archive = tar::Builder::new(encoder);
+    // The manifest goes first, so the next snapshot reads it cheaply
+    append_list(&mut archive, MANIFEST, &all)?;
+    if !deleted.is_empty() {
+        append_list(&mut archive, DELETED, &deleted)?;
+    }
+    for relative in &files {
+        archive.append_path_with_name(root.join(relative), relative)?;
+    }
+    archive.into_inner()?.finish()?;
+
+    Ok(Some((path, files.len())))
+}
+
+/// Deletes the oldest snapshots of the vault at `root` while at least `keep`
+/// newer ones remain. A full snapshot and the `-changed` ones after it go
+/// together, since those can't be restored without it, so up to about twice
+/// `keep` may be left with `changed_only`. A `keep` of zero keeps every
+/// snapshot. Returns how many were removed.
+pub fn prune(root: &Path, dir: &Path, keep: usize) -> Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+
+    let snapshots = list(dir, &prefix(root));
+    let mut chains: Vec<&[PathBuf]> = Vec::new();
+    let mut start = 0;
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        if i > start && !is_incremental(snapshot) {
+            chains.push(&snapshots[start..i]);
+            start = i;
+        }
+    }
+
+    let mut removed = 0;
+    for chain in chains {
+        if snapshots.len() - removed - chain.len() < keep {
+            break;
+        }
+        for old in chain {
+            std::fs::remove_file(old)?;
+        }
+        removed += chain.len();
+    }
+    Ok(removed)
+}
+
+fn is_incremental(snapshot: &Path) -> bool {
+    snapshot
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_suffix(EXTENSION))
+        .is_some_and(|n| n.ends_with(INCREMENTAL))
+}
+
+/// Existing snapshots for a vault, oldest first.
+fn list(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut snapshots: Vec<(SnapshotKey, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter_map(|p| {
+            let name = p.file_name()?.to_str()?;
+            Some((snapshot_key(name, prefix)?, p))
+        })
+        .collect();
+    snapshots.sort();
+    snapshots.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The timestamp and same-second counter of a snapshot name.
+type SnapshotKey = (String, u32);
+
+/// Parses `<prefix>-<YYYYmmdd-HHMMSS>[-<n>][-changed].tar.zst`. Anything
+/// else, such as the snapshots of a vault whose name merely starts with
+/// `prefix`, is not one of ours.
+fn snapshot_key(name: &str, prefix: &str) -> Option<SnapshotKey> {
+    let rest = name.strip_prefix(prefix)?.strip_prefix('-')?;
+    let rest = rest.strip_suffix(EXTENSION)?;
+    let rest = rest.strip_suffix(INCREMENTAL).unwrap_or(rest);
+
+    let (stamp, counter) = match rest.get(15..) {
+        Some("") => (rest, 1),
+        Some(counter) => (&rest[..15], counter.strip_prefix('-')?.parse().ok()?),
+        None => return None,
+    };
+    let is_stamp = stamp
+        .char_indices()
+        .all(|(i, c)| if i == 8 { c == '-' } else { c.is_ascii_digit() });
+    is_stamp.then(|| (stamp.to_string(), counter))
+}
+
+/// Vault-relative paths of the files to archive.
+fn vault_files(root: &Path, dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.') && e.path() != dir)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+fn modified_after(path: &Path, since: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| modified > since)
+}
+
+/// Adds `paths`, one per line, to `archive` as the file `name`.
+fn append_list(
+    archive: &mut tar::Builder<impl Write>,
+    name: &str,
+    paths: &[impl AsRef<Path>],
+) -> Result<()> {
+    let text: String = paths
+        .iter()
+        .map(|p| format!("{}\n", p.as_ref().to_string_lossy()))
+        .collect();
+    let mut header = tar::Header::new_gnu();
+    header.set_size(text.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    );
+    header.set_cksum();
+    archive.append_data(&mut header, name, text.as_bytes())?;
+    Ok(())
+}
+
+/// The file list a snapshot was taken with, empty for archives written
+/// before snapshots carried one.
+fn read_manifest(snapshot: &Path) -> Vec<PathBuf> {
+    let Ok(decoder) = File::open(snapshot).and_then(zstd::Decoder::new) else {
+        return Vec::new();
+    };
+    let mut archive = tar::Archive::new(decoder);
+    let Some(Ok(mut entry)) = archive.entries().ok().and_then(|mut e| e.next()) else {
+        return Vec::new();
+    };
+    if entry.path().ok().is_none_or(|p| p != Path::new(MANIFEST)) {
+        return Vec::new();
+    }
+    let mut text = String::new();
+    if entry.read_to_string(&mut text).is_err() {
+        return Vec::new();
+    }
+    text.lines().map(PathBuf::from).collect()
+}
+
+/// Snapshots are named after the vault directory so several vaults can share
+/// one snapshot directory.
+fn prefix(root: &Path) -> String {
+    root.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "vault".to_string())
+}
@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{Result, eyre::eyre};
+use spellbook::Dictionary;
+
+/// Words the user marked as correct, one per line in the vault root.
+pub const IGNORE_FILE: &str = ".spellignore";
+
+/// Most suggestions offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// A Hunspell dictionary plus the vault's ignore list.
+pub struct SpellChecker {
+    dictionary: Dictionary,
+    ignored: HashSet<String>,
+    ignore_path: PathBuf,
+    // Notes are re-rendered every frame; remember verdicts per word
+    cache: RefCell<HashMap<String, bool>>,
+}
+
+impl SpellChecker {
+    /// Loads `<language>.aff` and `<language>.dic` from the first directory
+    /// that has both, and the ignore list from the vault root.
+    pub fn load(language: &str, dirs: &[PathBuf], vault_root: &Path) -> Result<Self> {
+        let (aff, dic) = dirs
+            .iter()
+            .map(|dir| {
+                (
+                    dir.join(format!("{}.aff", language)),
+                    dir.join(format!("{}.dic", language)),
+                )
+            })
+            .find(|(aff, dic)| aff.is_file() && dic.is_file())
+            .ok_or_else(|| eyre!("No {} dictionary found", language))?;
+
+        let dictionary = Dictionary::new(
+            &std::fs::read_to_string(&aff)?,
+            &std::fs::read_to_string(&dic)?,
+        )
+        .map_err(|e| eyre!("Invalid dictionary {}: {}", dic.display(), e))?;
+
+        let ignore_path = vault_root.join(IGNORE_FILE);
+        let ignored = std::fs::read_to_string(&ignore_path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .map(|line| line.trim().to_lowercase())
+                    .filter(|word| !word.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            dictionary,
+            ignored,
+            ignore_path,
+            cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        if self.ignored.contains(&word.to_lowercase()) {
+            return true;
+        }
+        if let Some(&known) = self.cache.borrow().get(word) {
+            return known;
+        }
+        let correct = self.dictionary.check(word);
+        self.cache.borrow_mut().insert(word.to_string(), correct);
+        correct
+    }
+
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Adds a word to the vault's ignore list.
+    pub fn ignore(&mut self, word: &str) -> Result<()> {
+        let word = word.to_lowercase();
+        if self.ignored.insert(word.clone()) {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.ignore_path)?;
+            writeln!(file, "{}", word)?;
+        }
+        Ok(())
+    }
+
+    /// Char ranges of the misspelled words in a line of markdown.
+    pub fn misspelled(&self, line: &str) -> Vec<Range<usize>> {
+        words(line)
+            .into_iter()
+            .filter(|range| {
+                let word: String = line.chars().skip(range.start).take(range.len()).collect();
+                !self.is_correct(&word)
+            })
+            .collect()
+    }
+}
+
+/// Char ranges of the prose words in a line: letters with inner apostrophes,
+/// leaving out wiki-links, inline code, URLs, tags and words containing digits.
+pub fn words(line: &str) -> Vec<Range<usize>> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut words = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Skip over spans that aren't prose
+        let skip_to = if c == '[' && chars.get(i + 1) == Some(&'[') {
+            find_seq(&chars, i + 2, &[']', ']']).map(|end| end + 2)
+        } else if c == '`' {
+            chars[i + 1..]
+                .iter()
+                .position(|&ch| ch == '`')
+                .map(|p| i + p + 2)
+        } else if (c == '#' && (i == 0 || chars[i - 1].is_whitespace()))
+            || chars[i..].starts_with(&['h', 't', 't', 'p'])
+        {
+            // Tags and URLs
+            Some(skip_token(&chars, i))
+        } else if c == ']' && chars.get(i + 1) == Some(&'(') {
+            // Markdown link target
+            chars[i..]
+                .iter()
+                .position(|&ch| ch == ')')
+                .map(|p| i + p + 1)
+        } else {
+            None
+        };
+        if let Some(end) = skip_to {
+            i = end.max(i + 1);
+            continue;
+        }
+
+        if !c.is_alphanumeric() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len()
+            && (chars[i].is_alphanumeric()
+                || (is_apostrophe(chars[i])
+                    && chars.get(i + 1).is_some_and(|ch| ch.is_alphabetic())))
+        {
+            i += 1;
+        }
+        let has_digit = chars[start..i].iter().any(|ch| ch.is_numeric());
+        let in_identifier = chars.get(i).is_some_and(|&ch| ch == '_' || ch == '@')
+            || (start > 0 && matches!(chars[start - 1], '_' | '@' | '/' | '\\'));
+        if !has_digit && !in_identifier && i - start > 1 {
+            words.push(start..i);
+        }
+    }
+
+    words
+}
+
+/// The prose word touching char column `col`, if any.
+pub fn word_at(line: &str, col: usize) -> Option<Range<usize>> {
+    words(line)
+        .into_iter()
+        .find(|range| range.start <= col && col <= range.end)
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+fn skip_token(chars: &[char], from: usize) -> usize {
+    chars[from..]
+        .iter()
+        .position(|ch| ch.is_whitespace())
+        .map_or(chars.len(), |p| from + p)
+}
+
+fn find_seq(chars: &[char], from: usize, seq: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&i| chars[i..].starts_with(seq))
+}
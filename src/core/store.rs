@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
+use super::crypt::VaultKey;
+
+/// How many note bodies stay in memory at once.
+const CACHED_NOTES: usize = 256;
+
+/// How much note text passes over the whole vault keep in memory, so a
+/// search doesn't read and decrypt every note again on each keystroke.
+const SCANNED_BYTES: usize = 64 * 1024 * 1024;
+
+/// Reads note bodies from disk when they are needed, keeping the most
+/// recently used ones in memory. Only the metadata parsed from each note
+/// is held for the whole vault.
+pub struct NoteStore {
+    root: PathBuf,
+    key: Option<VaultKey>,
+    cache: Mutex<LruCache<PathBuf, Arc<str>>>,
+    scanned: Mutex<Scanned>,
+}
+
+/// Note bodies read by `peek`, up to `SCANNED_BYTES` of them. Nothing is
+/// evicted: in a vault too large to hold, each pass would otherwise evict
+/// every note before it was needed again, so the first notes read stay.
+#[derive(Default)]
+struct Scanned {
+    notes: HashMap<PathBuf, Arc<str>>,
+    bytes: usize,
+}
+
+impl Scanned {
+    /// Keeps `content` for `relative` if it fits, dropping any older text.
+    fn insert(&mut self, relative: &Path, content: Arc<str>) {
+        if let Some(old) = self.notes.remove(relative) {
+            self.bytes -= old.len();
+        }
+        if self.bytes + content.len() <= SCANNED_BYTES {
+            self.bytes += content.len();
+            self.notes.insert(relative.to_path_buf(), content);
+        }
+    }
+}
+
+impl std::fmt::Debug for NoteStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NoteStore")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NoteStore {
+    pub fn new(root: PathBuf, key: Option<VaultKey>) -> Self {
+        Self {
+            root,
+            key,
+            cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(CACHED_NOTES).expect("cache size is non-zero"),
+            )),
+            scanned: Mutex::default(),
+        }
+    }
+
+    /// The text of the note at `relative`, read from disk if it isn't cached,
+    /// or `None` when it can no longer be read or decrypted.
+    pub fn get(&self, relative: &Path) -> Option<Arc<str>> {
+        if let Some(content) = self.lock().get(relative) {
+            return Some(content.clone());
+        }
+
+        let content: Arc<str> = self.read(relative)?.into();
+        self.put(relative, content.clone());
+        Some(content)
+    }
+
+    /// Like `get`, but a cached note isn't marked as recently used and one
+    /// read from disk is kept apart from the cache, so a pass over the whole
+    /// vault leaves the cache as it was.
+    pub fn peek(&self, relative: &Path) -> Option<Arc<str>> {
+        if let Some(content) = self.lock().peek(relative) {
+            return Some(content.clone());
+        }
+        if let Some(content) = self.lock_scanned().notes.get(relative) {
+            return Some(content.clone());
+        }
+
+        let content: Arc<str> = self.read(relative)?.into();
+        self.lock_scanned().insert(relative, content.clone());
+        Some(content)
+    }
+
+    /// Caches text just read or written, so the next `get` needn't read it.
+    pub fn put(&self, relative: &Path, content: Arc<str>) {
+        let mut scanned = self.lock_scanned();
+        if scanned.notes.contains_key(relative) {
+            scanned.insert(relative, content.clone());
+        }
+        drop(scanned);
+        self.lock().put(relative.to_path_buf(), content);
+    }
+
+    fn read(&self, relative: &Path) -> Option<String> {
+        let full_path = self.root.join(relative);
+        match &self.key {
+            Some(key) => key.decrypt(&std::fs::read(full_path).ok()?).ok(),
+            None => std::fs::read_to_string(full_path).ok(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<PathBuf, Arc<str>>> {
+        // A panic mid-insert leaves nothing worse than a stale entry
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_scanned(&self) -> std::sync::MutexGuard<'_, Scanned> {
+        self.scanned.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,301 @@
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+/// How a column's cells line up, from its delimiter row (`:--`, `--:`, `:-:`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    None,
+    Left,
+    Right,
+    Center,
+}
+
+/// A markdown pipe table. Rows are numbered as lines of the table: the
+/// header is row 0, the delimiter row 1 and the body starts at row 2.
+#[derive(Debug, Clone)]
+pub struct Table {
+    indent: String,
+    header: Vec<String>,
+    aligns: Vec<Align>,
+    body: Vec<Vec<String>>,
+}
+
+/// The lines of the table `line` is in: a run of lines with pipes whose
+/// second line is a delimiter row.
+pub fn bounds(lines: &[&str], line: usize) -> Option<Range<usize>> {
+    let is_row = |i: usize| lines.get(i).is_some_and(|l| !split(l).is_empty());
+    if !is_row(line) {
+        return None;
+    }
+    let mut start = line;
+    while start > 0 && is_row(start - 1) {
+        start -= 1;
+    }
+    let mut end = line + 1;
+    while is_row(end) {
+        end += 1;
+    }
+    // Text with pipes just above a table is a paragraph, not a header
+    (start..end.saturating_sub(1))
+        .find(|&i| is_delimiter(lines[i + 1]))
+        .filter(|&header| header <= line)
+        .map(|header| header..end)
+}
+
+/// The cell of `line` that column `col` (in characters) falls in.
+pub fn cell_at(line: &str, col: usize) -> usize {
+    let before: String = line.chars().take(col).collect();
+    let pipes = pipe_offsets(&before).len();
+    if line.trim_start().starts_with('|') {
+        pipes.saturating_sub(1)
+    } else {
+        pipes
+    }
+}
+
+impl Table {
+    pub fn parse(lines: &[&str]) -> Self {
+        let indent: String = lines
+            .first()
+            .map(|l| l.chars().take_while(|c| c.is_whitespace()).collect())
+            .unwrap_or_default();
+        let header = lines.first().map(|l| split(l)).unwrap_or_default();
+        let aligns: Vec<Align> = lines
+            .get(1)
+            .map(|l| split(l).iter().map(|cell| align(cell)).collect())
+            .unwrap_or_default();
+        let body: Vec<Vec<String>> = lines.iter().skip(2).map(|l| split(l)).collect();
+
+        let columns = body
+            .iter()
+            .map(Vec::len)
+            .chain([header.len(), aligns.len()])
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let mut table = Self {
+            indent,
+            header,
+            aligns,
+            body,
+        };
+        table.header.resize(columns, String::new());
+        table.aligns.resize(columns, Align::None);
+        for row in &mut table.body {
+            row.resize(columns, String::new());
+        }
+        table
+    }
+
+    pub fn rows(&self) -> usize {
+        self.body.len() + 2
+    }
+
+    pub fn columns(&self) -> usize {
+        self.header.len()
+    }
+
+    /// Adds an empty body row before row `row`.
+    pub fn insert_row(&mut self, row: usize) {
+        let at = row.saturating_sub(2).min(self.body.len());
+        self.body.insert(at, vec![String::new(); self.columns()]);
+    }
+
+    /// Removes body row `row`; the header and delimiter rows stay.
+    pub fn delete_row(&mut self, row: usize) -> bool {
+        if row < 2 || row - 2 >= self.body.len() {
+            return false;
+        }
+        self.body.remove(row - 2);
+        true
+    }
+
+    /// Adds an empty column before column `col`.
+    pub fn insert_column(&mut self, col: usize) {
+        let at = col.min(self.columns());
+        self.header.insert(at, String::new());
+        self.aligns.insert(at, Align::None);
+        for row in &mut self.body {
+            row.insert(at, String::new());
+        }
+    }
+
+    /// Removes column `col`, unless it is the only one.
+    pub fn delete_column(&mut self, col: usize) -> bool {
+        if self.columns() < 2 || col >= self.columns() {
+            return false;
+        }
+        self.header.remove(col);
+        self.aligns.remove(col);
+        for row in &mut self.body {
+            row.remove(col);
+        }
+        true
+    }
+
+    /// The table's lines with every column padded to its widest cell.
+    pub fn render(&self) -> Vec<String> {
+        let widths = self.widths();
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .zip(&self.aligns)
+                .map(|((cell, &width), &align)| pad(cell, width, align))
+                .collect();
+            format!("{}| {} |", self.indent, cells.join(" | "))
+        };
+        let delimiter: Vec<String> = widths
+            .iter()
+            .zip(&self.aligns)
+            .map(|(&width, align)| match align {
+                Align::None => "-".repeat(width),
+                Align::Left => format!(":{}", "-".repeat(width - 1)),
+                Align::Right => format!("{}:", "-".repeat(width - 1)),
+                Align::Center => format!(":{}:", "-".repeat(width - 2)),
+            })
+            .collect();
+
+        let mut lines = vec![
+            row(&self.header),
+            format!("{}| {} |", self.indent, delimiter.join(" | ")),
+        ];
+        lines.extend(self.body.iter().map(|cells| row(cells)));
+        lines
+    }
+
+    /// The column, in characters, where the text of a cell ends in the
+    /// rendered table.
+    pub fn cell_col(&self, row: usize, col: usize) -> usize {
+        let widths = self.widths();
+        let col = col.min(self.columns() - 1);
+        let start = self.indent.chars().count()
+            + 2
+            + widths[..col].iter().map(|width| width + 3).sum::<usize>();
+        let cell = match row {
+            0 => &self.header[col],
+            1 => return start,
+            _ => match self.body.get(row - 2) {
+                Some(cells) => &cells[col],
+                None => return start,
+            },
+        };
+        if cell.is_empty() {
+            return start;
+        }
+        let padded = pad(cell, widths[col], self.aligns[col]);
+        start + padded.trim_end().chars().count()
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        (0..self.columns())
+            .map(|col| {
+                let cells =
+                    std::iter::once(&self.header[col]).chain(self.body.iter().map(|row| &row[col]));
+                // A delimiter needs room for its colons and a dash
+                cells.map(|cell| cell.width()).max().unwrap_or(0).max(3)
+            })
+            .collect()
+    }
+}
+
+/// The trimmed cells of a table line, or none if it has no pipes.
+fn split(line: &str) -> Vec<String> {
+    let pipes = pipe_offsets(line);
+    if pipes.is_empty() {
+        return Vec::new();
+    }
+    let trimmed = line.trim();
+    let mut bounds = vec![0];
+    bounds.extend(pipes.iter().map(|&p| p + 1));
+    let mut cells: Vec<String> = bounds
+        .iter()
+        .zip(pipes.iter().chain([&line.len()]))
+        .map(|(&from, &to)| line[from..to].trim().to_string())
+        .collect();
+    // Outer pipes are optional; they leave an empty cell at either end
+    if trimmed.starts_with('|') {
+        cells.remove(0);
+    }
+    if trimmed.ends_with('|') && !trimmed.ends_with("\\|") && trimmed.len() > 1 {
+        cells.pop();
+    }
+    cells
+}
+
+/// Byte offsets of the pipes in `line` that separate cells, skipping `\|`.
+fn pipe_offsets(line: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if c == '|' && !escaped {
+            offsets.push(i);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    offsets
+}
+
+fn is_delimiter(line: &str) -> bool {
+    let cells = split(line);
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+        })
+}
+
+fn align(cell: &str) -> Align {
+    match (cell.starts_with(':'), cell.len() > 1 && cell.ends_with(':')) {
+        (true, true) => Align::Center,
+        (true, false) => Align::Left,
+        (false, true) => Align::Right,
+        (false, false) => Align::None,
+    }
+}
+
+fn pad(cell: &str, width: usize, align: Align) -> String {
+    let space = width.saturating_sub(cell.width());
+    let (left, right) = match align {
+        Align::Right => (space, 0),
+        Align::Center => (space / 2, space - space / 2),
+        Align::None | Align::Left => (0, space),
+    };
+    format!("{}{}{}", " ".repeat(left), cell, " ".repeat(right))
+}
+
+/// The records of a CSV (or, with `delimiter` '\t', TSV) file. Quoted
+/// fields may hold delimiters, doubled quotes and line breaks.
+pub fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c if c == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
@@ -0,0 +1,34 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+use feruca::Collator;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+thread_local! {
+    static COLLATOR: RefCell<Collator> = RefCell::new(Collator::default());
+}
+
+/// Folds text for matching: lowercased, with diacritics removed, so that
+/// "resume" matches "Résumé" and "uber" matches "Über".
+pub fn fold(s: &str) -> String {
+    s.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Compares two strings by the Unicode Collation Algorithm, so accented and
+/// non-Latin names sort where a reader expects instead of by code point.
+pub fn collate(a: &str, b: &str) -> Ordering {
+    COLLATOR.with(|c| c.borrow_mut().collate(a, b))
+}
+
+/// A file name for a note titled `title`: the characters note names allow,
+/// with runs of spaces collapsed.
+pub fn file_name_for(title: &str) -> String {
+    let kept: String = title
+        .chars()
+        .filter(|&c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ')
+        .collect();
+    kept.split_whitespace().collect::<Vec<_>>().join(" ")
+}
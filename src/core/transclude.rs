@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use super::note::{is_attachment_name, strip_comments};
+use super::{Note, Vault, frontmatter};
+
+/// Expands `![[Note]]` embeds that sit on a line of their own into the
+/// embedded note's body, recursively. Each output line is paired with the
+/// line of `note` it came from, so hits inside embedded text can still be
+/// located in the host note. An embed that would recurse into a note already
+/// being expanded is left as written.
+pub fn expand_lines(vault: &Vault, note: &Note) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut stack = vec![note.path.clone()];
+
+    for (line_idx, line) in note.scan().lines().enumerate() {
+        let mut expanded = Vec::new();
+        expand_line(vault, line, &mut stack, &mut expanded);
+        out.extend(expanded.into_iter().map(|l| (line_idx, l)));
+    }
+
+    out
+}
+
+/// The full text of `note` with embeds expanded.
+pub fn expand(vault: &Vault, note: &Note) -> String {
+    let lines: Vec<String> = expand_lines(vault, note)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect();
+    lines.join("\n")
+}
+
+fn expand_line(vault: &Vault, line: &str, stack: &mut Vec<PathBuf>, out: &mut Vec<String>) {
+    let Some(target) = embed_target(line) else {
+        out.push(line.to_string());
+        return;
+    };
+    let Some(embedded) = vault.resolve_link(target) else {
+        out.push(line.to_string());
+        return;
+    };
+    if stack.contains(&embedded.path) {
+        out.push(line.to_string());
+        return;
+    }
+
+    let content = embedded.scan();
+    let body = match frontmatter::split(&content) {
+        Some(Ok((_, body))) => body,
+        _ => &content,
+    };
+
+    stack.push(embedded.path.clone());
+    for inner in strip_comments(body).lines() {
+        expand_line(vault, inner, stack, out);
+    }
+    stack.pop();
+}
+
+/// The note named by a line consisting only of `![[target]]` or `![[target|alias]]`.
+fn embed_target(line: &str) -> Option<&str> {
+    let inner = line.trim().strip_prefix("![[")?.strip_suffix("]]")?;
+    let target = inner.split('|').next().unwrap_or(inner).trim();
+    (!target.is_empty() && !target.contains("]]") && !is_attachment_name(target)).then_some(target)
+}
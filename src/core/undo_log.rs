@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+/// One step through a note's history: replacing chars `start..end` of one
+/// version with `text` gives the next, with the cursor at `line`/`col`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Patch {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Patch {
+    /// The patch that turns `from` into `to`, trimming the common prefix and suffix.
+    pub fn between(from: &str, to: &str, line: usize, col: usize) -> Self {
+        let from: Vec<char> = from.chars().collect();
+        let to: Vec<char> = to.chars().collect();
+
+        let prefix = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+        let suffix = from[prefix..]
+            .iter()
+            .rev()
+            .zip(to[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Self {
+            start: prefix,
+            end: from.len() - suffix,
+            text: to[prefix..to.len() - suffix].iter().collect(),
+            line,
+            col,
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let start = self.start.min(chars.len());
+        let end = self.end.clamp(start, chars.len());
+
+        let mut result: String = chars[..start].iter().collect();
+        result.push_str(&self.text);
+        result.extend(&chars[end..]);
+        result
+    }
+}
+
+/// A note's undo and redo stacks, stored as patches walking outward from the
+/// text that was saved, nearest step first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoLog {
+    /// Checksum of the note text the patches start from
+    pub checksum: String,
+    #[serde(default)]
+    pub undo: Vec<Patch>,
+    #[serde(default)]
+    pub redo: Vec<Patch>,
+}
+
+impl UndoLog {
+    /// Reads the log for `note`, ignoring it if the note changed since it was
+    /// written (edited outside the app, or synced from elsewhere).
+    pub fn load(dir: &Path, note: &Path, content: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(log_path(dir, note)).ok()?;
+        let log: Self = toml::from_str(&contents).ok()?;
+        (log.checksum == checksum(content)).then_some(log)
+    }
+
+    /// Writes the log for `note`, or removes it once there is nothing to undo.
+    pub fn save(&self, dir: &Path, note: &Path) -> Result<()> {
+        let path = log_path(dir, note);
+        if self.undo.is_empty() && self.redo.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Where undo logs for the vault at `root` live: one directory per vault under
+/// the data directory, so they never end up in the vault or its git history.
+pub fn vault_dir(root: &Path) -> Option<PathBuf> {
+    ProjectDirs::from("com", "tui-jot", "tui-jot").map(|dirs| {
+        dirs.data_dir()
+            .join("undo")
+            .join(checksum(&root.to_string_lossy()))
+    })
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+pub fn checksum(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn log_path(dir: &Path, note: &Path) -> PathBuf {
+    dir.join(format!("{}.toml", checksum(&note.to_string_lossy())))
+}
@@ -1,16 +1,35 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use color_eyre::Result;
-use walkdir::WalkDir;
+use color_eyre::{Report, Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+use walkdir::{DirEntry, WalkDir};
 
-use super::Note;
+use super::bibtex::Bibliography;
+use super::cache::MetadataCache;
+use super::crypt::VaultKey;
+use super::store::NoteStore;
+use super::{Exclude, Note, relink, text};
 
 #[derive(Debug)]
 pub struct Vault {
     pub root: PathBuf,
     pub notes: HashMap<PathBuf, Note>,
     pub tree: Vec<TreeEntry>,
+    show_attachments: bool,
+    /// Notes under this folder are left out of the tree unless shown
+    archive_dir: PathBuf,
+    show_archived: bool,
+    sort: TreeSort,
+    exclude: Exclude,
+    store: Arc<NoteStore>,
+    /// Set for an encrypted vault: notes are decrypted as they are read and
+    /// sealed again on every write
+    key: Option<VaultKey>,
+    /// What `[@key]` citations in the notes refer to
+    bibliography: Bibliography,
 }
 
 #[derive(Debug, Clone)]
@@ -20,10 +39,81 @@ pub struct TreeEntry {
     pub is_dir: bool,
     pub depth: usize,
     pub expanded: bool,
+    /// A smart folder or a note listed in one, not a place on disk
+    pub is_virtual: bool,
+}
+
+/// Order of the entries within each directory of the tree. Directories
+/// always come before the notes beside them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeSort {
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+    /// Most recently created first
+    Created,
+    /// Largest first; directories stay in name order
+    Size,
+}
+
+impl TreeSort {
+    pub fn next(self) -> Self {
+        match self {
+            TreeSort::Name => TreeSort::Modified,
+            TreeSort::Modified => TreeSort::Created,
+            TreeSort::Created => TreeSort::Size,
+            TreeSort::Size => TreeSort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TreeSort::Name => "name",
+            TreeSort::Modified => "modified",
+            TreeSort::Created => "created",
+            TreeSort::Size => "size",
+        }
+    }
+
+    /// What entries are compared by, largest first. Name order has no key.
+    fn key(self, entry: &DirEntry) -> u128 {
+        let metadata = match self {
+            TreeSort::Name => return 0,
+            TreeSort::Size if entry.file_type().is_dir() => return 0,
+            _ => entry.metadata(),
+        };
+        let Ok(metadata) = metadata else {
+            return 0;
+        };
+        match self {
+            TreeSort::Modified => metadata.modified().map_or(0, since_epoch),
+            TreeSort::Created => since_epoch(file_times(&metadata).1),
+            TreeSort::Name | TreeSort::Size => metadata.len() as u128,
+        }
+    }
+}
+
+impl TreeEntry {
+    /// A file in the tree that is not a markdown note.
+    pub fn is_attachment(&self) -> bool {
+        !self.is_dir && self.path.extension().is_none_or(|e| e != "md")
+    }
 }
 
 impl Vault {
-    pub fn open(path: &Path) -> Result<Self> {
+    pub fn open(path: &Path, key: Option<VaultKey>, exclude: Exclude) -> Result<Self> {
+        Self::open_with_progress(path, key, exclude, |_, _| {})
+    }
+
+    /// Like `open`, calling `progress(loaded, total)` as each note is read.
+    pub fn open_with_progress(
+        path: &Path,
+        key: Option<VaultKey>,
+        exclude: Exclude,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Self> {
         let root = path.to_path_buf();
 
         // Ensure vault directory exists
@@ -32,52 +122,157 @@ impl Vault {
         }
 
         let mut notes = HashMap::new();
+        let store = Arc::new(NoteStore::new(root.clone(), key.clone()));
         let tree = Vec::new();
 
-        // Load all markdown files
-        for entry in WalkDir::new(&root)
+        // Find all markdown files first, so progress has a total, skipping
+        // hidden directories such as `.git`, `.obsidian` and `.trash`, and
+        // anything excluded
+        let files: Vec<walkdir::DirEntry> = WalkDir::new(&root)
             .follow_links(true)
             .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !skipped(&root, &exclude, e))
             .filter_map(|e| e.ok())
-        {
+            .filter(|e| e.path().is_file() && e.path().extension().is_some_and(|e| e == "md"))
+            .collect();
+
+        // The cache would hold titles, tags and links in the clear, so
+        // encrypted vaults parse every note each time
+        let mut cache = match key {
+            Some(_) => MetadataCache::default(),
+            None => MetadataCache::load(&root),
+        };
+        let mut parsed = 0;
+
+        let total = files.len();
+        for (index, entry) in files.iter().enumerate() {
+            progress(index + 1, total);
             let path = entry.path();
-            if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
-                let relative = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
-                let content = std::fs::read_to_string(path).unwrap_or_default();
-                let modified = entry
-                    .metadata()
-                    .map(|m| m.modified().ok())
-                    .ok()
-                    .flatten()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-
-                let note = Note::from_file(relative.clone(), content, modified);
+            let relative = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+            let (modified, created) = entry
+                .metadata()
+                .map(|m| file_times(&m))
+                .unwrap_or((SystemTime::UNIX_EPOCH, SystemTime::UNIX_EPOCH));
+
+            if let Some(cached) = cache.take(&relative, modified) {
+                let note = Note::from_cached(relative.clone(), cached, store.clone());
                 notes.insert(relative, note);
+                continue;
             }
+
+            let content = match &key {
+                Some(key) => match std::fs::read(path) {
+                    // A note that will not decrypt is left out rather
+                    // than opened empty, where saving would destroy it
+                    Ok(bytes) => match key.decrypt(&bytes) {
+                        Ok(content) => content,
+                        Err(_) => continue,
+                    },
+                    Err(_) => continue,
+                },
+                // Likewise a note that can't be read at all
+                None => match std::fs::read_to_string(path) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                },
+            };
+
+            let note =
+                Note::from_file(relative.clone(), &content, modified, created, store.clone());
+            store.put(&relative, content.into());
+            notes.insert(relative, note);
+            parsed += 1;
         }
 
-        let mut vault = Self { root, notes, tree };
+        // Rewrite the cache when a note changed, or when some are gone
+        if key.is_none() && (parsed > 0 || !cache.is_empty()) {
+            let _ = MetadataCache::save(&root, &notes);
+        }
+
+        let mut vault = Self {
+            root,
+            notes,
+            tree,
+            show_attachments: false,
+            archive_dir: PathBuf::new(),
+            show_archived: false,
+            sort: TreeSort::default(),
+            exclude,
+            key,
+            store,
+            bibliography: Bibliography::default(),
+        };
         vault.rebuild_tree();
 
         Ok(vault)
     }
 
+    /// A vault with nothing loaded yet, standing in while `open` runs on
+    /// another thread.
+    pub fn placeholder(path: &Path, key: Option<VaultKey>) -> Self {
+        let root = path.to_path_buf();
+        Self {
+            store: Arc::new(NoteStore::new(root.clone(), key.clone())),
+            root,
+            notes: HashMap::new(),
+            tree: Vec::new(),
+            show_attachments: false,
+            archive_dir: PathBuf::new(),
+            show_archived: false,
+            sort: TreeSort::default(),
+            exclude: Exclude::default(),
+            key,
+            bibliography: Bibliography::default(),
+        }
+    }
+
     pub fn rebuild_tree(&mut self) {
         let mut entries: Vec<TreeEntry> = Vec::new();
+        // Folders the user collapsed stay collapsed when the tree is rebuilt
+        let collapsed: HashSet<PathBuf> = self
+            .tree
+            .iter()
+            .filter(|e| e.is_dir && !e.expanded)
+            .map(|e| e.path.clone())
+            .collect();
+
+        // Each entry is stat'ed once rather than on every comparison
+        let sort = self.sort;
+        let mut keys: HashMap<PathBuf, u128> = HashMap::new();
+        // A note's frontmatter may date its creation earlier than its file
+        if sort == TreeSort::Created {
+            keys.extend(
+                self.notes
+                    .values()
+                    .map(|note| (self.root.join(&note.path), since_epoch(note.created))),
+            );
+        }
+        let mut key = move |entry: &DirEntry| {
+            *keys
+                .entry(entry.path().to_path_buf())
+                .or_insert_with(|| sort.key(entry))
+        };
 
         for entry in WalkDir::new(&self.root)
             .min_depth(1)
-            .sort_by(|a, b| {
-                // Directories first, then alphabetical
+            .sort_by(move |a, b| {
+                // Directories first, then by the sort key, then alphabetical
+                // by locale-aware collation
                 let a_is_dir = a.file_type().is_dir();
                 let b_is_dir = b.file_type().is_dir();
                 match (a_is_dir, b_is_dir) {
                     (true, false) => std::cmp::Ordering::Less,
                     (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.file_name().cmp(b.file_name()),
+                    _ => key(b).cmp(&key(a)).then_with(|| {
+                        text::collate(
+                            &a.file_name().to_string_lossy(),
+                            &b.file_name().to_string_lossy(),
+                        )
+                    }),
                 }
             })
             .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !self.skips(e))
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
@@ -85,33 +280,108 @@ impl Vault {
             let is_dir = entry.file_type().is_dir();
             let depth = entry.depth() - 1;
 
-            // Skip non-markdown files
-            if !is_dir && path.extension().map(|e| e != "md").unwrap_or(true) {
+            if !self.show_archived && self.is_archived(&relative) {
                 continue;
             }
 
-            // Skip hidden files/directories
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.starts_with('.') {
+            // Skip non-markdown files unless attachments are shown
+            if !is_dir && !self.show_attachments && path.extension().is_none_or(|e| e != "md") {
                 continue;
             }
 
+            let name = entry.file_name().to_string_lossy().to_string();
+
             entries.push(TreeEntry {
+                expanded: !collapsed.contains(&relative), // Start expanded
                 path: relative,
                 name,
                 is_dir,
                 depth,
-                expanded: true, // Start expanded
+                is_virtual: false,
             });
         }
 
         self.tree = entries;
     }
 
+    /// Lists attachments alongside notes in the tree.
+    pub fn set_show_attachments(&mut self, show: bool) {
+        if self.show_attachments != show {
+            self.show_attachments = show;
+            self.rebuild_tree();
+        }
+    }
+
+    /// Reorders the entries within each directory of the tree.
+    pub fn set_sort(&mut self, sort: TreeSort) {
+        if self.sort != sort {
+            self.sort = sort;
+            self.rebuild_tree();
+        }
+    }
+
+    pub fn sort(&self) -> TreeSort {
+        self.sort
+    }
+
+    /// Sets the folder archived notes live in, relative to the root.
+    pub fn set_archive_dir(&mut self, dir: &Path) {
+        if self.archive_dir != dir {
+            self.archive_dir = dir.to_path_buf();
+            self.rebuild_tree();
+        }
+    }
+
+    pub fn archive_dir(&self) -> &Path {
+        &self.archive_dir
+    }
+
+    pub fn is_archived(&self, path: &Path) -> bool {
+        !self.archive_dir.as_os_str().is_empty() && path.starts_with(&self.archive_dir)
+    }
+
+    /// Lists archived notes in the tree. They are always searchable.
+    pub fn set_show_archived(&mut self, show: bool) {
+        if self.show_archived != show {
+            self.show_archived = show;
+            self.rebuild_tree();
+        }
+    }
+
+    pub fn shows_archived(&self) -> bool {
+        self.show_archived
+    }
+
+    /// Reads the BibTeX file at `path`, relative to the root unless
+    /// absolute. An empty path leaves the vault without a bibliography.
+    pub fn load_bibliography(&mut self, path: &Path) -> Result<()> {
+        self.bibliography = if path.as_os_str().is_empty() {
+            Bibliography::default()
+        } else {
+            Bibliography::load(&self.root.join(path))?
+        };
+        Ok(())
+    }
+
+    pub fn bibliography(&self) -> &Bibliography {
+        &self.bibliography
+    }
+
     pub fn get_note(&self, path: &Path) -> Option<&Note> {
         self.notes.get(path)
     }
 
+    /// The note standing for directory `dir`: its `index.md`, or a note
+    /// inside named after the directory (`projects/projects.md`).
+    pub fn folder_note(&self, dir: &Path) -> Option<&Note> {
+        let name = dir.file_name()?;
+        self.notes.get(&dir.join("index.md")).or_else(|| {
+            // `with_extension` would cut a dotted name like `v1.2` short
+            let note = dir.join(format!("{}.md", name.to_string_lossy()));
+            self.notes.get(&note)
+        })
+    }
+
     pub fn visible_entries(&self) -> Vec<&TreeEntry> {
         let mut visible = Vec::new();
         let mut collapsed_dirs: Vec<&Path> = Vec::new();
@@ -133,6 +403,19 @@ impl Vault {
         visible
     }
 
+    /// Expands every directory above `path` so its entry is visible in the
+    /// tree, showing the archive if that is where it lives.
+    pub fn reveal(&mut self, path: &Path) {
+        if self.is_archived(path) {
+            self.set_show_archived(true);
+        }
+        for entry in self.tree.iter_mut() {
+            if entry.is_dir && path.starts_with(&entry.path) {
+                entry.expanded = true;
+            }
+        }
+    }
+
     pub fn toggle_dir(&mut self, path: &Path) {
         if let Some(entry) = self.tree.iter_mut().find(|e| e.path == path && e.is_dir) {
             entry.expanded = !entry.expanded;
@@ -141,80 +424,185 @@ impl Vault {
 
     pub fn reload_note(&mut self, relative_path: &Path) {
         let full_path = self.root.join(relative_path);
-        if full_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&full_path) {
-                let modified = std::fs::metadata(&full_path)
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-                let note = Note::from_file(relative_path.to_path_buf(), content, modified);
-                self.notes.insert(relative_path.to_path_buf(), note);
-            }
+        if full_path.exists()
+            && let Ok(content) = self.read_file(&full_path)
+        {
+            let (modified, created) = std::fs::metadata(&full_path)
+                .map(|m| file_times(&m))
+                .unwrap_or((SystemTime::UNIX_EPOCH, SystemTime::UNIX_EPOCH));
+            let note = Note::from_file(
+                relative_path.to_path_buf(),
+                &content,
+                modified,
+                created,
+                self.store.clone(),
+            );
+            self.store.put(relative_path, content.into());
+            self.notes.insert(relative_path.to_path_buf(), note);
         }
     }
 
-    pub fn get_backlinks(&self, note_path: &Path) -> Vec<&Note> {
-        let mut backlinks = Vec::new();
+    /// When the file at `relative_path` was last written, as it is on disk
+    /// now rather than when the vault loaded it.
+    pub fn modified_on_disk(&self, relative_path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(self.root.join(relative_path))
+            .and_then(|m| m.modified())
+            .ok()
+    }
 
-        // Normalize the target path - remove .md extension for comparison
-        let target_name = if note_path.extension().map(|e| e == "md").unwrap_or(false) {
-            note_path.with_extension("")
-        } else {
-            note_path.to_path_buf()
-        };
+    /// Whether a walk of the vault should pass over `entry`: hidden files and
+    /// folders, and those matching `vault.exclude`.
+    pub fn skips(&self, entry: &walkdir::DirEntry) -> bool {
+        skipped(&self.root, &self.exclude, entry)
+    }
 
-        // Search through all notes for links to this note
-        for (source_path, note) in &self.notes {
-            // Skip note itself
-            if source_path == note_path {
-                continue;
-            }
+    pub fn key(&self) -> Option<&VaultKey> {
+        self.key.as_ref()
+    }
 
-            for link in &note.links {
-                let link_path = if link.target.ends_with(".md") {
-                    PathBuf::from(link.target.strip_suffix(".md").unwrap())
-                } else {
-                    PathBuf::from(&link.target)
-                };
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
 
-                if Self::paths_match(&target_name, &link_path) {
-                    backlinks.push(note);
-                    break;
-                }
+    /// Reads a note file, decrypting it in an encrypted vault.
+    pub fn read_file(&self, full_path: &Path) -> Result<String> {
+        match &self.key {
+            Some(key) => {
+                key.decrypt(&std::fs::read(full_path).map_err(|e| io_error(full_path, e))?)
             }
+            None => std::fs::read_to_string(full_path).map_err(|e| io_error(full_path, e)),
+        }
+    }
+
+    /// Moves a note or directory to `to` (both relative to the root), then
+    /// points path-style wiki-links at the new location. Links by bare name
+    /// still resolve after a move, so they only change when a note is renamed.
+    /// Returns how many notes had links rewritten. The vault should be
+    /// reloaded afterwards.
+    pub fn move_entry(&self, from: &Path, to: &Path) -> Result<usize> {
+        let full_target = self.root.join(to);
+        if full_target.exists() {
+            return Err(eyre!("{} already exists", to.display()));
         }
 
-        backlinks.sort_by(|a, b| a.title.cmp(&b.title));
+        // Where each note ends up, for notes moved along with a directory too
+        let new_path = |path: &Path| match path.strip_prefix(from) {
+            Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+            Ok(rest) => to.join(rest),
+            Err(_) => path.to_path_buf(),
+        };
+        let mut moved: HashMap<String, String> = self
+            .notes
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .map(|path| (relink::link_key(path), relink::link_path(&new_path(path))))
+            .filter(|(key, _)| key.contains('/'))
+            .collect();
+        if let (Some(old), Some(new)) = (from.file_stem(), to.file_stem())
+            && old != new
+            && self.notes.contains_key(from)
+            && self
+                .resolve_link(&old.to_string_lossy())
+                .is_some_and(|note| note.path == from)
+        {
+            moved.insert(
+                old.to_string_lossy().to_lowercase(),
+                new.to_string_lossy().to_string(),
+            );
+        }
+
+        // Read everything before the move, while notes are where the store expects
+        let rewrites: Vec<(PathBuf, String)> = self
+            .notes
+            .values()
+            .filter_map(|note| {
+                let content = relink::rewrite_links(&note.content(), &moved)?;
+                Some((new_path(&note.path), content))
+            })
+            .collect();
+
+        if let Some(parent) = full_target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        std::fs::rename(self.root.join(from), &full_target).map_err(|e| io_error(from, e))?;
 
-        backlinks
+        for (path, content) in &rewrites {
+            self.write_note(path, content)?;
+        }
+        Ok(rewrites.len())
     }
 
-    pub fn link_exists(&self, target: &str) -> bool {
-        let target_name = if target.ends_with(".md") {
-            target.strip_suffix(".md").unwrap_or(target)
-        } else {
-            target
+    /// Writes a note's text to disk, encrypted if the vault is.
+    pub fn write_note(&self, relative_path: &Path, content: &str) -> Result<()> {
+        let full_path = self.root.join(relative_path);
+        let written = match &self.key {
+            Some(key) => std::fs::write(full_path, key.encrypt(content)?),
+            None => std::fs::write(full_path, content),
         };
+        written.map_err(|e| io_error(relative_path, e))
+    }
+
+    pub fn link_exists(&self, target: &str) -> bool {
+        self.resolve_link(target).is_some()
+    }
+
+    /// Finds the note a wiki-link target points to: a case-insensitive file
+    /// stem match, then a frontmatter alias, then a folder's `index.md`.
+    pub fn resolve_link(&self, target: &str) -> Option<&Note> {
+        let target = target.split(['#', '^']).next().unwrap_or(target).trim();
+        let target_name = target.strip_suffix(".md").unwrap_or(target);
 
-        // Check all notes for a match (Case-insensitive)
-        self.notes.keys().any(|path| {
-            path.file_stem()
+        let by_stem = self.notes.values().find(|note| {
+            note.path
+                .file_stem()
                 .and_then(|s| s.to_str())
                 .map(|name| name.eq_ignore_ascii_case(target_name))
                 .unwrap_or(false)
-        })
+        });
+
+        by_stem
+            .or_else(|| {
+                self.notes.values().find(|note| {
+                    note.aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(target_name))
+                })
+            })
+            .or_else(|| {
+                self.notes.values().find(|note| {
+                    note.path.file_stem().is_some_and(|s| s == "index")
+                        && note
+                            .path
+                            .parent()
+                            .and_then(|dir| dir.file_name())
+                            .and_then(|s| s.to_str())
+                            .is_some_and(|dir| dir.eq_ignore_ascii_case(target_name))
+                })
+            })
     }
+}
 
-    fn paths_match(target: &Path, link: &Path) -> bool {
-        if link.components().count() == 1 {
-            if let Some(target_name) = target.file_name() {
-                return target_name
-                    .to_string_lossy()
-                    .eq_ignore_ascii_case(&link.to_string_lossy());
-            }
-        }
+/// An I/O error that says which file it happened on.
+pub fn io_error(path: &Path, e: std::io::Error) -> Report {
+    eyre!("{}: {}", path.display(), e)
+}
 
-        target
-            .to_string_lossy()
-            .eq_ignore_ascii_case(&link.to_string_lossy())
-    }
+/// A file's modification and creation times. Not every filesystem records
+/// a creation time, so it falls back to the modification time.
+fn file_times(metadata: &std::fs::Metadata) -> (SystemTime, SystemTime) {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    (modified, metadata.created().unwrap_or(modified))
+}
+
+fn since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+}
+
+fn skipped(root: &Path, exclude: &Exclude, entry: &walkdir::DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+        || exclude.is_excluded(
+            entry.path().strip_prefix(root).unwrap_or(entry.path()),
+            entry.file_type().is_dir(),
+        )
 }
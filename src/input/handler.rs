@@ -1,16 +1,31 @@
 use std::io::Stdout;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use chrono::NaiveDate;
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::{Terminal, backend::CrosstermBackend};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Position, Rect},
+};
 
-use crate::app::{App, CreateNoteState, DeleteConfirmState};
-use crate::core::Index;
+use crate::app::{
+    App, CreateNoteState, DeleteConfirmState, DiskChangeState, MoveState, PendingSave, RenameState,
+    SyncStatus, TitleSync, TitleSyncState, UnsavedState,
+};
+use crate::config::{Config, EditorConfig};
+use crate::core::daily::Period;
+use crate::core::{Vault, bibtex, daily, diff, git, io_error, note, spell, text};
 use crate::ui::graph_view::GraphMode;
+use crate::ui::theme::Theme;
 use crate::ui::{
-    EditorMode, FindInNoteState, FinderState, Focus, GraphViewState, SearchState, TagFilterState,
+    AgendaState, CopyMenuState, DiffView, EditorMode, ExportMenuState, FindInNoteState,
+    FinderState, Focus, GraphViewState, HelpState, HistoryState, LinkMenuState, LinksTab,
+    ScratchState, SearchState, SpellChoice, SpellState, TagFilterState, ThemePickerState,
+    TimelineState, ToastLevel, ViewerState, WorkspacePickerState,
 };
+use crate::ui::{breadcrumbs, viewer};
 
 fn copy_to_clipboard(text: &str) {
     if let Ok(mut clipboard) = arboard::Clipboard::new() {
@@ -24,92 +39,200 @@ fn paste_from_clipboard() -> Option<String> {
         .and_then(|mut cb| cb.get_text().ok())
 }
 
+/// Rows the preview scrolls per mouse wheel step.
+const WHEEL_LINES: u16 = 3;
+
+/// Rows the help overlay scrolls per Ctrl+d / Ctrl+u or page key.
+const HELP_PAGE: i32 = 10;
+
+/// Rows the diff of a note changed on disk scrolls per page key.
+const DIFF_PAGE: u16 = 10;
+
 pub struct InputHandler;
 
 impl InputHandler {
     fn follow_link(app: &mut App, target: &str) {
-        // Normalize target - strip .md extension for comparison
-        let target_name = if target.ends_with(".md") {
-            target.strip_suffix(".md").unwrap_or(target)
-        } else {
-            target
-        };
+        let found_path = app.vault.resolve_link(target).map(|note| note.path.clone());
 
-        // Find the note by case-insensitive name match (handles subdirectories too)
-        let found_path = app
-            .vault
-            .notes
-            .keys()
-            .find(|path| {
-                path.file_stem()
-                    .and_then(|s| s.to_str())
-                    .map(|name| name.eq_ignore_ascii_case(target_name))
-                    .unwrap_or(false)
-            })
-            .cloned();
-
-        if let Some(target_path) = found_path {
-            if let Some(index) = app
+        if let Some(target_path) = found_path
+            && let Some(index) = app
                 .vault
                 .visible_entries()
                 .iter()
                 .position(|e| e.path == target_path)
-            {
-                app.browser_state.select(index);
-                if let Some(note) = app.vault.get_note(&target_path) {
-                    app.viewer_state.update_links(note);
-                }
-                app.viewer_scroll = 0;
+        {
+            app.browser_state.select(index);
+            if let Some(note) = app.vault.get_note(&target_path) {
+                app.viewer_state.update_links(note);
             }
+            app.viewer_scroll = 0;
+        }
+    }
+
+    /// Whether the vault was opened with `--read-only` or `vault.read_only`,
+    /// saying so in the status bar when it was.
+    fn refuse_if_read_only(app: &mut App) -> bool {
+        if app.config.vault.read_only {
+            app.status_message = Some("The vault is open read-only".to_string());
+        }
+        app.config.vault.read_only
+    }
+
+    /// Whether the open note's frontmatter, or a read-only vault, locks it
+    /// against edits, saying so in the status bar when it does.
+    fn refuse_if_locked(app: &mut App) -> bool {
+        if Self::refuse_if_read_only(app) {
+            return true;
         }
+        let locked = app
+            .viewer_state
+            .current_note_path
+            .as_ref()
+            .and_then(|path| app.vault.get_note(path))
+            .is_some_and(|note| note.locked);
+        if locked {
+            app.status_message = Some("This note is read-only".to_string());
+        } else if app.viewer_state.unreadable {
+            app.status_message =
+                Some("This note couldn't be read, so it can't be edited".to_string());
+            return true;
+        }
+        locked
     }
 
+    /// Writes an edit made in read mode straight to disk. A failed write
+    /// leaves the buffer as it is, marked unsaved, rather than reloading the
+    /// old text over it.
     fn save_and_reload(app: &mut App) {
         if let Some(path) = app.viewer_state.current_note_path.clone() {
+            if Self::changed_on_disk(app, &path, PendingSave::InPlace) {
+                app.viewer_state.dirty = true;
+                return;
+            }
             let content = app.viewer_state.content.to_string();
-            let full_path = app.vault.root.join(&path);
-            let _ = std::fs::write(&full_path, &content);
+            if let Err(e) = app.vault.write_note(&path, &content) {
+                app.viewer_state.dirty = true;
+                app.toasts
+                    .push(ToastLevel::Error, format!("Save failed: {}", e));
+                return;
+            }
             app.vault.reload_note(&path);
-            app.index = Index::build(&app.vault);
+            app.rebuild_index();
             if let Some(note) = app.vault.get_note(&path) {
-                app.viewer_state.update_links(note);
+                app.viewer_state.reload(note);
             }
         }
     }
 
+    /// Whether something else wrote the note at `path` since the buffer was
+    /// loaded from it. If so, the prompt to overwrite, reload or diff opens
+    /// in place of the `save`.
+    fn changed_on_disk(app: &mut App, path: &Path, save: PendingSave) -> bool {
+        let on_disk = app.vault.modified_on_disk(path);
+        // A file deleted underneath is simply written again
+        if on_disk.is_none() || on_disk == app.viewer_state.disk_mtime {
+            return false;
+        }
+        app.disk_change_state = Some(DiskChangeState {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            save,
+            diff: None,
+            scroll: 0,
+        });
+        true
+    }
+
     pub fn handle(
         app: &mut App,
         key: KeyEvent,
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     ) -> Result<()> {
-        if app.show_help {
+        // Any key dismisses the previous status message
+        app.status_message = None;
+        // A `g` prefix or count only carries over to the next key in the viewer
+        let pending_g = std::mem::take(&mut app.pending_g);
+        let pending_count = std::mem::take(&mut app.pending_count);
+
+        if app.help_state.is_some() {
+            Self::handle_help(app, key);
+            return Ok(());
+        }
+
+        // Release notes shown after an upgrade
+        if let Some(ref mut state) = app.changelog_state {
             match key.code {
-                KeyCode::Esc => {
-                    app.show_help = false;
-                }
-                KeyCode::Char('k') | KeyCode::Char('K')
-                    if key.modifiers.contains(KeyModifiers::CONTROL)
-                        && key.modifiers.contains(KeyModifiers::SHIFT) =>
-                {
-                    app.show_help = false;
+                KeyCode::Char('j') | KeyCode::Down => state.scroll_down(),
+                KeyCode::Char('k') | KeyCode::Up => state.scroll_up(),
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                    app.changelog_state = None;
                 }
                 _ => {}
             }
             return Ok(());
         }
 
+        // Handle sync conflict dialog
+        if app.sync_conflict_state.is_some() {
+            Self::handle_sync_conflict(app, key);
+            return Ok(());
+        }
+
         // Handle create note dialog
         if app.create_note_state.is_some() {
             Self::handle_create_dialog(app, key)?;
             return Ok(());
         }
 
+        // Handle scratch buffer
+        if app.show_scratch {
+            Self::handle_scratch(app, key);
+            return Ok(());
+        }
+
         // Handle delete confirmation dialog
         if app.delete_confirm_state.is_some() {
             Self::handle_delete_dialog(app, key)?;
             return Ok(());
         }
 
+        // Handle move dialog
+        if app.move_state.is_some() {
+            Self::handle_move_dialog(app, key);
+            return Ok(());
+        }
+
+        // Handle rename dialog
+        if app.rename_state.is_some() {
+            Self::handle_rename_dialog(app, key);
+            return Ok(());
+        }
+
+        if app.unsaved_state.is_some() {
+            return Self::handle_unsaved(app, key, terminal);
+        }
+
+        if app.disk_change_state.is_some() {
+            Self::handle_disk_change(app, key);
+            return Ok(());
+        }
+
+        // Handle title sync prompt
+        if app.title_sync_state.is_some() {
+            Self::handle_title_sync(app, key);
+            return Ok(());
+        }
+
+        // Handle export menu
+        if app.export_menu_state.is_some() {
+            Self::handle_export_menu(app, key);
+            return Ok(());
+        }
+
         // Handle tag filter dialog
         if app.tag_filter_state.is_some() {
             Self::handle_tag_filter(app, key);
@@ -128,6 +251,42 @@ impl InputHandler {
             return Ok(());
         }
 
+        // Handle timeline overlay
+        if app.timeline_state.is_some() {
+            Self::handle_timeline(app, key);
+            return Ok(());
+        }
+
+        if app.agenda_state.is_some() {
+            Self::handle_agenda(app, key);
+            return Ok(());
+        }
+
+        if app.spell_state.is_some() {
+            Self::handle_spell(app, key);
+            return Ok(());
+        }
+
+        if app.theme_picker_state.is_some() {
+            Self::handle_theme_picker(app, key);
+            return Ok(());
+        }
+
+        if app.workspace_picker_state.is_some() {
+            Self::handle_workspace_picker(app, key);
+            return Ok(());
+        }
+
+        if app.copy_menu_state.is_some() {
+            Self::handle_copy_menu(app, key);
+            return Ok(());
+        }
+
+        if app.link_menu_state.is_some() {
+            Self::handle_link_menu(app, key);
+            return Ok(());
+        }
+
         // Handle graph view
         if app.graph_view_state.is_some() {
             Self::handle_graph_view(app, key, terminal)?;
@@ -140,6 +299,24 @@ impl InputHandler {
             return Ok(());
         }
 
+        // Handle note history picker
+        if app.history_state.is_some() {
+            Self::handle_history(app, key);
+            return Ok(());
+        }
+
+        // Keys go to the browser's type-ahead filter while it is open
+        if app.focus == Focus::Browser && app.browser_filter.as_ref().is_some_and(|f| f.typing) {
+            Self::handle_browser_filter(app, key);
+            return Ok(());
+        }
+
+        // A key that could leave unsaved text behind asks what to do with it first
+        if app.viewer_state.dirty && !Self::keeps_buffer(app, &key) {
+            Self::ask_unsaved(app, key);
+            return Ok(());
+        }
+
         // Global keybindings (work in any focus)
         match key.code {
             KeyCode::Char('q')
@@ -149,26 +326,35 @@ impl InputHandler {
                 app.should_quit = true;
                 return Ok(());
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            KeyCode::Char('c')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
                 // In edit mode, Ctrl+C is copy (handled in handle_viewer_edit)
-                if app.viewer_state.mode != EditorMode::Edit {
-                    app.should_quit = true;
-                    return Ok(());
-                }
+                && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                app.should_quit = true;
+                return Ok(());
             }
             KeyCode::Char('k') | KeyCode::Char('K')
                 if key.modifiers.contains(KeyModifiers::CONTROL)
                     && key.modifiers.contains(KeyModifiers::SHIFT) =>
             {
-                app.show_help = true;
+                app.help_state = Some(HelpState::new());
                 return Ok(());
             }
             KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                // Open in external editor
-                app.open_in_editor(terminal)?;
+                // Open in external editor, which would only see ciphertext
+                // in an encrypted vault
+                if Self::refuse_if_read_only(app) {
+                } else if app.vault.is_encrypted() {
+                    app.status_message =
+                        Some("The external editor can't open encrypted notes".to_string());
+                } else {
+                    app.open_in_editor(terminal)?;
+                }
                 return Ok(());
             }
-            KeyCode::Tab => {
+            // In EDIT mode Tab indents; Esc leaves the editor first
+            KeyCode::Tab if app.viewer_state.mode != EditorMode::Edit => {
                 let old_focus = app.focus;
                 app.focus = app.focus.next();
 
@@ -181,29 +367,31 @@ impl InputHandler {
                             .filter(|e| !e.is_dir)
                             .map(|e| e.path.clone())
                     };
-                    if let Some(path) = path {
-                        if let Some(note) = app.vault.get_note(&path) {
-                            app.viewer_state.update_links(note);
-                            app.viewer_scroll = 0;
-                        }
+                    if let Some(path) = path
+                        && let Some(note) = app.vault.get_note(&path)
+                    {
+                        app.viewer_state.update_links(note);
+                        app.viewer_scroll = 0;
                     }
                 }
                 return Ok(());
             }
             KeyCode::Char('/') if app.viewer_state.mode != EditorMode::Edit => {
-                app.search_state = Some(SearchState::new());
+                let saved = app.app_state.saved_searches(&app.vault.root).to_vec();
+                app.search_state = Some(SearchState::new(saved));
                 return Ok(());
             }
             KeyCode::Char('p')
                 if key.modifiers.contains(KeyModifiers::CONTROL)
                     && app.viewer_state.mode != EditorMode::Edit =>
             {
-                app.finder_state = Some(FinderState::new(&app.vault));
+                app.finder_state = Some(FinderState::new(&app.vault, &app.app_state));
                 return Ok(());
             }
             KeyCode::Char('f')
                 if key.modifiers.contains(KeyModifiers::CONTROL)
-                    && app.viewer_state.mode != EditorMode::Edit =>
+                    && app.viewer_state.mode != EditorMode::Edit
+                    && app.focus != Focus::Viewer =>
             {
                 // Open find-in-note
                 let mut state = FindInNoteState::new();
@@ -211,7 +399,10 @@ impl InputHandler {
                 app.find_in_note_state = Some(state);
                 return Ok(());
             }
-            KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            KeyCode::Char('g')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
                 // Open graph view (local graph centered on current note)
                 let center_path = {
                     let entries = app.filtered_visible_entries();
@@ -227,32 +418,1100 @@ impl InputHandler {
                 } else {
                     state.update_global(&app.vault, size.width, size.height);
                 }
-                app.graph_view_state = Some(state);
-                return Ok(());
+                app.graph_view_state = Some(state);
+                return Ok(());
+            }
+            KeyCode::Char('r')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                // Open git history for the selected note. Encrypted notes
+                // change completely on every save, so there is nothing to diff
+                if app.vault.is_encrypted() {
+                    app.status_message =
+                        Some("History is not available in an encrypted vault".to_string());
+                } else if let Some(path) = app.selected_note().map(|n| n.path.clone()) {
+                    app.history_state = Some(HistoryState::new(&app.vault.root, path));
+                }
+                return Ok(());
+            }
+            // PageUp/PageDown always scroll the preview, whichever pane has
+            // focus; with Alt they page through the browser instead
+            KeyCode::PageDown | KeyCode::PageUp if app.viewer_state.mode != EditorMode::Edit => {
+                let down = key.code == KeyCode::PageDown;
+                if key.modifiers.contains(KeyModifiers::ALT) {
+                    Self::page_browser(app, down);
+                } else {
+                    let page = app.viewer_area_height.max(1);
+                    app.viewer_scroll = if down {
+                        app.viewer_scroll.saturating_add(page)
+                    } else {
+                        app.viewer_scroll.saturating_sub(page)
+                    };
+                }
+                return Ok(());
+            }
+            KeyCode::Char('t')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                app.show_scratch = true;
+                return Ok(());
+            }
+            // In the preview, Ctrl+b pages up instead
+            KeyCode::Char('b')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && !(app.focus == Focus::Viewer
+                        && app.viewer_state.mode == EditorMode::Read) =>
+            {
+                // Toggle backlinks panel, revealing it if the layout hides it
+                app.focus = if app.focus == Focus::Backlinks {
+                    Focus::Browser
+                } else {
+                    app.config.ui.show_backlinks = true;
+                    Focus::Backlinks
+                };
+                return Ok(());
+            }
+            KeyCode::Char('b')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                let show = !app.config.ui.show_backlinks;
+                app.config.ui.show_backlinks = show;
+                if !show && app.focus == Focus::Backlinks {
+                    app.focus = Focus::Browser;
+                }
+                app.status_message = Some(if show {
+                    "Showing backlinks".to_string()
+                } else {
+                    "Hiding backlinks".to_string()
+                });
+                return Ok(());
+            }
+            KeyCode::Char('o')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                app.timeline_state = Some(TimelineState::new(&app.vault));
+                return Ok(());
+            }
+            KeyCode::Char('a')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                app.agenda_state = Some(AgendaState::new(&app.vault));
+                return Ok(());
+            }
+            KeyCode::Char('w')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.viewer_state.mode != EditorMode::Edit
+                    && app.split.is_some() =>
+            {
+                app.switch_split();
+                return Ok(());
+            }
+            KeyCode::Char('t')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                app.theme_picker_state = Some(ThemePickerState::new(
+                    &app.config.ui.theme,
+                    app.theme.clone(),
+                ));
+                return Ok(());
+            }
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::ALT) => {
+                app.maximized = !app.maximized;
+                return Ok(());
+            }
+            KeyCode::Char('l')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                let names = Self::workspace_names(app);
+                app.workspace_picker_state = Some(WorkspacePickerState::new(names));
+                return Ok(());
+            }
+            KeyCode::Char('s')
+                if key.modifiers.contains(KeyModifiers::ALT) && app.focus == Focus::Viewer =>
+            {
+                Self::open_spell_suggestions(app);
+                return Ok(());
+            }
+            KeyCode::Char('s')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && app.focus == Focus::Viewer
+                    && (app.viewer_state.mode == EditorMode::Edit || app.viewer_state.dirty) =>
+            {
+                Self::save_buffer(app);
+                return Ok(());
+            }
+            // Alt+Left / Alt+Right make the current line's heading shallower / deeper
+            KeyCode::Left | KeyCode::Right
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.focus == Focus::Viewer
+                    && app.viewer_state.current_note_path.is_some()
+                    && app.active_history_diff().is_none() =>
+            {
+                Self::cycle_heading(app, key.code == KeyCode::Right);
+                return Ok(());
+            }
+            // Alt+d / Alt+w / Alt+m open today's, this week's and this month's note
+            KeyCode::Char(c @ ('d' | 'w' | 'm'))
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                let period = match c {
+                    'd' => Period::Day,
+                    'w' => Period::Week,
+                    _ => Period::Month,
+                };
+                Self::open_periodic(app, period);
+                return Ok(());
+            }
+            // [ / ] walk to the previous / next period from a periodic note
+            KeyCode::Char('[') | KeyCode::Char(']')
+                if key.modifiers.is_empty()
+                    && app.focus != Focus::Backlinks
+                    && app.viewer_state.mode != EditorMode::Edit =>
+            {
+                Self::step_periodic(app, key.code == KeyCode::Char(']'));
+                return Ok(());
+            }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let layout = app.config.ui.layout.next();
+                app.config.ui.layout = layout;
+                app.status_message = Some(format!("Layout: {}", layout.label()));
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        app.pending_g = pending_g && app.focus == Focus::Viewer;
+        if app.focus == Focus::Viewer {
+            app.pending_count = pending_count;
+        }
+
+        // Context-specific keybindings
+        match app.focus {
+            Focus::Browser => Self::handle_browser(app, key),
+            Focus::Viewer => Self::handle_viewer(app, key),
+            Focus::Backlinks => Self::handle_backlinks(app, key),
+        }
+
+        Ok(())
+    }
+
+    /// Opens the daily, weekly or monthly note before or after the selected
+    /// one. With `daily.create_missing` this is always the adjacent period,
+    /// created beside the selected note when it has no note yet; otherwise
+    /// periods without a note are skipped.
+    fn step_periodic(app: &mut App, forward: bool) {
+        let selected = app.selected_note().and_then(|note| {
+            Period::ALL.into_iter().find_map(|period| {
+                let format = app.config.daily.format(period);
+                let date = daily::date_of(&note.path, period, format)?;
+                Some((note.path.clone(), period, date))
+            })
+        });
+        let Some((path, period, date)) = selected else {
+            app.status_message = Some("Not a daily, weekly or monthly note".to_string());
+            return;
+        };
+
+        let target = if app.config.daily.create_missing {
+            let Some(date) = period.step(date, forward) else {
+                return;
+            };
+            let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            Self::periodic_note(app, period, date, &dir)
+        } else {
+            let format = app.config.daily.format(period);
+            let target = daily::adjacent(&app.vault, period, date, forward, format);
+            if target.is_none() {
+                let direction = if forward { "later" } else { "earlier" };
+                app.status_message = Some(format!("No {} {} note", direction, period.label()));
+            }
+            target
+        };
+        if let Some(target) = target {
+            app.select_note(&target);
+        }
+    }
+
+    /// Opens today's, this week's or this month's note, creating it in
+    /// `daily.folder` when there is none.
+    fn open_periodic(app: &mut App, period: Period) {
+        let date = period.start(chrono::Local::now().date_naive());
+        let dir = app.config.daily.folder.clone();
+        if let Some(target) = Self::periodic_note(app, period, date, &dir) {
+            app.select_note(&target);
+        }
+    }
+
+    /// The `period` note starting on `date`, wherever it is in the vault, or
+    /// a new one in `dir` filled from the period's template.
+    fn periodic_note(
+        app: &mut App,
+        period: Period,
+        date: NaiveDate,
+        dir: &Path,
+    ) -> Option<PathBuf> {
+        let format = app.config.daily.format(period).to_string();
+        if let Some(existing) = daily::find(&app.vault, period, date, &format) {
+            return Some(existing);
+        }
+        if app.config.vault.read_only {
+            app.status_message = Some(format!(
+                "No {} note for {} (the vault is open read-only)",
+                period.label(),
+                date.format(&format)
+            ));
+            return None;
+        }
+
+        let template = match app.config.daily.template(period) {
+            Some(template) => match app.vault.read_file(&app.vault.root.join(template)) {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    app.toasts
+                        .push(ToastLevel::Error, format!("Could not read template: {}", e));
+                    return None;
+                }
+            },
+            None => None,
+        };
+        let name = date.format(&format).to_string();
+        let target = daily::path_for(dir, date, &format);
+        let content = app.new_note_content(daily::initial_content(template.as_deref(), &name));
+        if let Some(parent) = app.vault.root.join(&target).parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            let e = io_error(parent, e);
+            app.toasts
+                .push(ToastLevel::Error, format!("Could not create note: {}", e));
+            return None;
+        }
+        if let Err(e) = app.vault.write_note(&target, &content) {
+            app.toasts
+                .push(ToastLevel::Error, format!("Could not create note: {}", e));
+            return None;
+        }
+        if let Err(e) = app.refresh_vault() {
+            app.toasts.push(
+                ToastLevel::Error,
+                format!("Could not reload the vault: {}", e),
+            );
+            return None;
+        }
+        app.status_message = Some(format!("Created {}", target.display()));
+        Some(target)
+    }
+
+    /// Opens the literature note for the first citation on the read cursor's
+    /// line, creating it in `citations.notes_dir` from the cited entry when
+    /// the vault has none.
+    fn open_literature_note(app: &mut App) {
+        let state = &app.viewer_state;
+        let line = state.read_cursor.line;
+        let cite = (line < state.content.len_lines())
+            .then(|| state.content.line(line).to_string())
+            .and_then(|text| {
+                bibtex::citations(&text)
+                    .into_iter()
+                    .flat_map(|(_, cites)| cites)
+                    .next()
+            });
+        let Some(cite) = cite else {
+            app.status_message = Some("No [@citation] on this line".to_string());
+            return;
+        };
+
+        let name = bibtex::note_name(&cite.key);
+        if let Some(existing) = app.vault.resolve_link(&name).map(|note| note.path.clone()) {
+            app.select_note(&existing);
+            app.focus = Focus::Viewer;
+            return;
+        }
+        let bibliography = app.vault.bibliography();
+        let Some(entry) = bibliography.get(&cite.key) else {
+            app.status_message = Some(if bibliography.is_empty() {
+                "No bibliography loaded (set citations.bibliography)".to_string()
+            } else {
+                format!("@{} is not in the bibliography", cite.key)
+            });
+            return;
+        };
+        if app.config.vault.read_only {
+            app.status_message = Some(format!(
+                "No literature note for @{} (the vault is open read-only)",
+                cite.key
+            ));
+            return;
+        }
+
+        let content = app.new_note_content(bibtex::literature_note(entry));
+        let target = app.config.citations.notes_dir.join(format!("{}.md", name));
+        if let Some(parent) = app.vault.root.join(&target).parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            let e = io_error(parent, e);
+            app.toasts
+                .push(ToastLevel::Error, format!("Could not create note: {}", e));
+            return;
+        }
+        if let Err(e) = app.vault.write_note(&target, &content) {
+            app.toasts
+                .push(ToastLevel::Error, format!("Could not create note: {}", e));
+            return;
+        }
+        if let Err(e) = app.refresh_vault() {
+            app.toasts.push(
+                ToastLevel::Error,
+                format!("Could not reload the vault: {}", e),
+            );
+            return;
+        }
+        app.select_note(&target);
+        app.focus = Focus::Viewer;
+        app.status_message = Some(format!("Created {}", target.display()));
+    }
+
+    /// Moves the browser selection by one screenful and shows the note it lands on.
+    fn page_browser(app: &mut App, down: bool) {
+        let count = app.filtered_visible_entries().len();
+        let page = app.browser_area_height.max(1) as usize;
+        let selected = app.browser_state.selected;
+        let target = if down {
+            (selected + page).min(count.saturating_sub(1))
+        } else {
+            selected.saturating_sub(page)
+        };
+        Self::select_browser_entry(app, target);
+    }
+
+    /// Selects a browser entry and shows the note it points at.
+    fn select_browser_entry(app: &mut App, index: usize) {
+        app.browser_state.select(index);
+        app.viewer_scroll = 0;
+        app.backlinks_state.reset();
+        if let Some(path) = app.selected_note().map(|n| n.path.clone())
+            && let Some(note) = app.vault.get_note(&path)
+        {
+            app.viewer_state.update_links(note);
+        }
+    }
+
+    /// Routes mouse input to the pane under the pointer.
+    pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+        let pos = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => Self::handle_click(app, pos),
+            MouseEventKind::ScrollDown => Self::handle_wheel(app, pos, true),
+            MouseEventKind::ScrollUp => Self::handle_wheel(app, pos, false),
+            _ => {}
+        }
+    }
+
+    /// The wheel moves the selection in the browser and backlinks and
+    /// scrolls the preview, without changing focus.
+    fn handle_wheel(app: &mut App, pos: Position, down: bool) {
+        if let Some(ref mut state) = app.changelog_state {
+            if down {
+                state.scroll_down();
+            } else {
+                state.scroll_up();
+            }
+            return;
+        }
+        if app.has_overlay() {
+            return;
+        }
+
+        if app.viewer_area.contains(pos) {
+            app.viewer_scroll = if down {
+                app.viewer_scroll.saturating_add(WHEEL_LINES)
+            } else {
+                app.viewer_scroll.saturating_sub(WHEEL_LINES)
+            };
+            return;
+        }
+
+        // Switching notes mid-edit, or after a failed save, would abandon
+        // unsaved text
+        if app.viewer_state.mode == EditorMode::Edit || app.viewer_state.dirty {
+            return;
+        }
+
+        if app.browser_area.contains(pos) {
+            let count = app.filtered_visible_entries().len();
+            let selected = app.browser_state.selected;
+            let target = if down {
+                (selected + 1).min(count.saturating_sub(1))
+            } else {
+                selected.saturating_sub(1)
+            };
+            if target != selected {
+                Self::select_browser_entry(app, target);
+            }
+        } else if app.backlinks_area.contains(pos) {
+            if down {
+                let count = app.linked_notes().len();
+                app.backlinks_state.move_down(count);
+            } else {
+                app.backlinks_state.move_up();
+            }
+        }
+    }
+
+    /// Clicks focus the pane under the pointer. In the browser and backlinks
+    /// a click selects an entry and a second click opens it; in the preview
+    /// it moves the cursor.
+    fn handle_click(app: &mut App, pos: Position) {
+        if app.has_overlay() {
+            return;
+        }
+
+        // While editing, clicks outside the buffer would abandon unsaved text
+        if app.viewer_state.mode == EditorMode::Edit {
+            if let Some((row, col)) = cell_in(app.viewer_content_area(), pos) {
+                app.viewer_state.cursor = viewer::edit_position_at(app, row, col);
+                app.viewer_state.selection = None;
+                app.viewer_state.autocomplete = None;
+                app.viewer_state.sync_snippet();
+            }
+            return;
+        }
+        // Likewise after a failed save
+        if app.viewer_state.dirty {
+            return;
+        }
+
+        if app.browser_area.contains(pos) {
+            let Some((row, _)) = inner_cell(app.browser_area, pos) else {
+                return;
+            };
+            let index = app.browser_state.index_at(row as usize);
+            if index >= app.filtered_visible_entries().len() {
+                return;
+            }
+            if app.focus == Focus::Browser && index == app.browser_state.selected {
+                Self::handle_browser(app, KeyEvent::from(KeyCode::Enter));
+                return;
+            }
+            app.focus = Focus::Browser;
+            Self::select_browser_entry(app, index);
+        } else if app.breadcrumb_area.contains(pos) {
+            if let Some(dir) = breadcrumbs::folder_at(app, app.breadcrumb_area, pos.x) {
+                app.focus_folder(&dir);
+            }
+        } else if app.backlinks_area.contains(pos) {
+            let Some((row, _)) = inner_cell(app.backlinks_area, pos) else {
+                return;
+            };
+            let index = app.backlinks_state.index_at(row as usize);
+            let count = app.linked_notes().len();
+            if index >= count {
+                app.focus = Focus::Backlinks;
+                return;
+            }
+            if app.focus == Focus::Backlinks && index == app.backlinks_state.selected {
+                Self::handle_backlinks(app, KeyEvent::from(KeyCode::Enter));
+                return;
+            }
+            app.focus = Focus::Backlinks;
+            app.backlinks_state.select(index);
+        } else if app.viewer_area.contains(pos) {
+            app.focus = Focus::Viewer;
+            if app.active_history_diff().is_some() {
+                return;
+            }
+            if let Some((row, col)) = cell_in(app.viewer_content_area(), pos)
+                && let Some(position) = viewer::read_position_at(app, row, col)
+            {
+                app.viewer_state.selection = None;
+                app.viewer_state.read_cursor = app.viewer_state.clamp_position(&position);
+            }
+        }
+    }
+
+    fn handle_timeline(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.timeline_state.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Enter => {
+                let path = state.selected_path().cloned();
+                app.timeline_state = None;
+                if let Some(path) = path {
+                    if app.select_note(&path) {
+                        app.focus = Focus::Viewer;
+                    } else {
+                        app.status_message = Some("Note is hidden by the tag filter".to_string());
+                    }
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.timeline_state = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `key` leaves the open note's unsaved text in place: anything
+    /// typed in the editor but launching the external editor, and motions in
+    /// the preview. Other keys may switch notes, quit or hand the file to
+    /// another program.
+    fn keeps_buffer(app: &App, key: &KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('s') {
+            return true;
+        }
+        if app.viewer_state.mode == EditorMode::Edit {
+            return !(ctrl && key.code == KeyCode::Char('e'));
+        }
+        let plain = !ctrl && !key.modifiers.contains(KeyModifiers::ALT);
+        app.focus == Focus::Viewer
+            && match key.code {
+                KeyCode::Char('d' | 'u' | 'f' | 'b') => ctrl,
+                KeyCode::Char('j' | 'k' | 'g' | 'G' | '{' | '}' | 'i' | '0'..='9') => plain,
+                KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::PageUp
+                | KeyCode::PageDown => true,
+                _ => false,
+            }
+    }
+
+    /// Asks whether to save or discard the unsaved text before `key` is
+    /// carried out.
+    fn ask_unsaved(app: &mut App, key: KeyEvent) {
+        let name = app
+            .viewer_state
+            .current_note_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        app.unsaved_state = Some(UnsavedState { name, key });
+    }
+
+    /// Save, discard or cancel for the prompt raised by a key that would
+    /// drop unsaved text. Saving or discarding goes on to carry out the key.
+    fn handle_unsaved(
+        app: &mut App,
+        key: KeyEvent,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    ) -> Result<()> {
+        let Some(state) = app.unsaved_state.take() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Char('s') | KeyCode::Char('y') => {
+                let saved = if app.viewer_state.mode == EditorMode::Edit {
+                    Self::exit_edit_mode(app)
+                } else {
+                    Self::save_and_reload(app);
+                    !app.viewer_state.dirty
+                };
+                if saved {
+                    return Self::handle(app, state.key, terminal);
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Char('n') => {
+                Self::discard_changes(app);
+                return Self::handle(app, state.key, terminal);
+            }
+            KeyCode::Esc | KeyCode::Char('c') => {}
+            _ => app.unsaved_state = Some(state),
+        }
+        Ok(())
+    }
+
+    /// Overwrite, reload or diff for the prompt raised by a save that found
+    /// the note changed on disk.
+    fn handle_disk_change(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.disk_change_state.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('o') => {
+                let Some(state) = app.disk_change_state.take() else {
+                    return;
+                };
+                // Take the disk's version as the one being replaced
+                app.viewer_state.disk_mtime = app.vault.modified_on_disk(&state.path);
+                match state.save {
+                    PendingSave::Buffer => Self::save_buffer(app),
+                    PendingSave::ExitEdit => {
+                        Self::exit_edit_mode(app);
+                    }
+                    PendingSave::InPlace => Self::save_and_reload(app),
+                }
+            }
+            KeyCode::Char('r') => {
+                let Some(state) = app.disk_change_state.take() else {
+                    return;
+                };
+                app.vault.reload_note(&state.path);
+                app.rebuild_index();
+                Self::discard_changes(app);
+                app.toasts.push(
+                    ToastLevel::Info,
+                    format!("Reloaded {} from disk", state.name),
+                );
+            }
+            KeyCode::Char('d') if state.diff.is_some() => state.diff = None,
+            KeyCode::Char('d') => {
+                let full_path = app.vault.root.join(&state.path);
+                match app.vault.read_file(&full_path) {
+                    Ok(on_disk) => {
+                        let buffer = app.viewer_state.content.to_string();
+                        let mut lines = vec!["--- on disk".to_string(), "+++ editor".to_string()];
+                        lines.extend(diff::unified(&on_disk, &buffer, 3));
+                        if lines.len() == 2 {
+                            lines.push("The editor has the same text as the file.".to_string());
+                        }
+                        state.diff = Some(lines);
+                        state.scroll = 0;
+                    }
+                    Err(e) => app
+                        .toasts
+                        .push(ToastLevel::Error, format!("Could not diff: {}", e)),
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => state.scroll = state.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => state.scroll = state.scroll.saturating_sub(1),
+            KeyCode::PageDown => state.scroll = state.scroll.saturating_add(DIFF_PAGE),
+            KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(DIFF_PAGE),
+            KeyCode::Esc | KeyCode::Char('c') => {
+                app.status_message = Some(format!("Not saved: {} changed on disk", state.name));
+                app.disk_change_state = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Drops unsaved edits, returning the viewer to the note as saved.
+    fn discard_changes(app: &mut App) {
+        if app.viewer_state.mode == EditorMode::Edit {
+            app.viewer_state.exit_edit_mode();
+        }
+        if let Some(path) = app.viewer_state.current_note_path.clone()
+            && let Some(note) = app.vault.get_note(&path)
+        {
+            app.viewer_state.reload(note);
+        }
+        app.viewer_state.dirty = false;
+    }
+
+    fn handle_help(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.help_state.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        if ctrl
+            && key.modifiers.contains(KeyModifiers::SHIFT)
+            && matches!(key.code, KeyCode::Char('k') | KeyCode::Char('K'))
+        {
+            app.help_state = None;
+            return;
+        }
+
+        // Typing narrows the list until Enter keeps the filter or Esc drops it
+        if state.filtering {
+            match key.code {
+                KeyCode::Esc => state.clear_query(),
+                KeyCode::Enter => state.filtering = false,
+                KeyCode::Backspace => {
+                    if state.query.is_empty() {
+                        state.filtering = false;
+                    } else {
+                        state.pop_char();
+                    }
+                }
+                KeyCode::Char(c) if !ctrl => state.push_char(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.scroll_by(1),
+            KeyCode::Char('k') | KeyCode::Up => state.scroll_by(-1),
+            KeyCode::Char('d') if ctrl => state.scroll_by(HELP_PAGE),
+            KeyCode::Char('u') if ctrl => state.scroll_by(-HELP_PAGE),
+            KeyCode::PageDown => state.scroll_by(HELP_PAGE),
+            KeyCode::PageUp => state.scroll_by(-HELP_PAGE),
+            KeyCode::Char('g') | KeyCode::Home => state.scroll_to_top(),
+            KeyCode::Char('G') | KeyCode::End => state.scroll_to_bottom(),
+            KeyCode::Char('/') => state.filtering = true,
+            KeyCode::Esc if !state.query.is_empty() => state.clear_query(),
+            KeyCode::Esc | KeyCode::Char('q') => app.help_state = None,
+            _ => {}
+        }
+    }
+
+    fn handle_agenda(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.agenda_state.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Enter => {
+                let task = state
+                    .selected_task()
+                    .map(|(path, line)| (path.clone(), line));
+                app.agenda_state = None;
+                if let Some((path, line)) = task {
+                    if app.select_note(&path) {
+                        app.focus = Focus::Viewer;
+                        app.viewer_state.move_read_cursor_to_line(line);
+                        Self::ensure_cursor_visible(app);
+                    } else {
+                        app.status_message = Some("Note is hidden by the tag filter".to_string());
+                    }
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.agenda_state = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Cycles the heading level of the cursor line.
+    fn cycle_heading(app: &mut App, deeper: bool) {
+        if Self::refuse_if_locked(app) {
+            return;
+        }
+        let state = &mut app.viewer_state;
+        let line = match state.mode {
+            EditorMode::Edit => state.cursor.line,
+            EditorMode::Read => state.read_cursor.line,
+        };
+        if line >= state.content.len_lines() {
+            return;
+        }
+        let level = state.cycle_heading(line, deeper);
+        app.status_message = Some(match level {
+            0 => "Plain text".to_string(),
+            n => format!("Heading {}", n),
+        });
+
+        // Read mode has no explicit save, so write the change straight away
+        if app.viewer_state.mode == EditorMode::Read {
+            Self::save_and_reload(app);
+        }
+    }
+
+    fn handle_copy_menu(app: &mut App, key: KeyEvent) {
+        let Some(ref mut state) = app.copy_menu_state else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(text) = state.selected_text().map(String::from) {
+                    copy_to_clipboard(&text);
+                    app.status_message = state.copied_message();
+                    app.viewer_state.clipboard = Some(text);
+                }
+                app.copy_menu_state = None;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.copy_menu_state = None,
+            _ => {}
+        }
+    }
+
+    fn handle_link_menu(app: &mut App, key: KeyEvent) {
+        let Some(ref mut state) = app.link_menu_state else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if let Some((target, _, _)) = state.links.get(index) {
+                    let target = target.clone();
+                    app.link_menu_state = None;
+                    Self::follow_link(app, &target);
+                }
+            }
+            KeyCode::Enter => {
+                let target = state.selected_target().map(String::from);
+                app.link_menu_state = None;
+                if let Some(target) = target {
+                    Self::follow_link(app, &target);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.link_menu_state = None,
+            _ => {}
+        }
+    }
+
+    /// Brings the read cursor to the line of the link picked with Ctrl+n/p,
+    /// so Enter follows what is highlighted.
+    fn move_read_cursor_to_link(app: &mut App) {
+        if let Some(line) = app.viewer_state.current_link().map(|link| link.line_index) {
+            app.viewer_state.read_cursor.line = line;
+            app.viewer_state.read_cursor.col = 0;
+            Self::ensure_cursor_visible(app);
+        }
+    }
+
+    /// Follows the link on the read cursor's line, asking which one when
+    /// there are several. Lines without one fall back to the link picked
+    /// with Ctrl+n/p.
+    fn follow_link_on_cursor_line(app: &mut App) {
+        let state = &app.viewer_state;
+        let on_line: Vec<_> = state
+            .visible_links
+            .iter()
+            .filter(|link| link.line_index == state.read_cursor.line)
+            .collect();
+
+        let target = match on_line.as_slice() {
+            [] => state.current_link().map(|link| link.target.clone()),
+            [link] => Some(link.target.clone()),
+            links => {
+                // Start on the cycled-to link when it is one of these
+                let selected = state
+                    .current_link()
+                    .and_then(|current| links.iter().position(|link| std::ptr::eq(*link, current)))
+                    .unwrap_or(0);
+                app.link_menu_state = Some(LinkMenuState::new(links, selected, &app.vault));
+                None
+            }
+        };
+        if let Some(target) = target {
+            Self::follow_link(app, &target);
+        }
+    }
+
+    fn handle_theme_picker(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.theme_picker_state.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Enter => {
+                let Some(state) = app.theme_picker_state.take() else {
+                    return;
+                };
+                let Some(name) = state.selected_name() else {
+                    return;
+                };
+                app.config.ui.theme = name.to_string();
+                app.status_message = Some(match Config::persist("ui.theme", name) {
+                    Ok(()) => format!("Theme: {}", name),
+                    Err(e) => format!("Theme applied but not saved: {}", e),
+                });
+                return;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if let Some(state) = app.theme_picker_state.take() {
+                    app.theme = state.original;
+                }
+                return;
+            }
+            _ => return,
+        }
+
+        // Preview the newly highlighted theme
+        let Some(name) = state.selected_name() else {
+            return;
+        };
+        let mut ui = app.config.ui.clone();
+        ui.theme = name.to_string();
+        let (theme, error) = Theme::from_config(&ui);
+        app.theme = theme;
+        app.status_message = error;
+    }
+
+    fn workspace_names(app: &App) -> Vec<String> {
+        app.app_state
+            .workspaces(&app.vault.root)
+            .iter()
+            .map(|w| w.name.clone())
+            .collect()
+    }
+
+    fn handle_workspace_picker(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.workspace_picker_state.as_mut() else {
+            return;
+        };
+        // Naming the current arrangement to save it
+        if let Some(name) = state.naming.as_mut() {
+            match key.code {
+                KeyCode::Esc => state.naming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    let name = name.trim().to_string();
+                    app.workspace_picker_state = None;
+                    let root = app.vault.root.clone();
+                    let workspace = app.workspace(&name);
+                    app.app_state.save_workspace(&root, workspace);
+                    if let Err(e) = app.app_state.save() {
+                        app.toasts.push(
+                            ToastLevel::Error,
+                            format!("Could not save the workspace: {}", e),
+                        );
+                    } else {
+                        app.status_message = Some(format!("Saved workspace \"{}\"", name));
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Char('s') => state.naming = Some(String::new()),
+            KeyCode::Char('d') => {
+                if let Some(name) = state.selected_name().map(String::from) {
+                    let root = app.vault.root.clone();
+                    app.app_state.delete_workspace(&root, &name);
+                    let _ = app.app_state.save();
+                    let names = Self::workspace_names(app);
+                    if let Some(state) = app.workspace_picker_state.as_mut() {
+                        state.set_names(names);
+                    }
+                    app.status_message = Some(format!("Deleted workspace \"{}\"", name));
+                }
+            }
+            KeyCode::Enter => {
+                let Some(name) = state.selected_name().map(String::from) else {
+                    return;
+                };
+                // Restoring moves the selection, which would lose the edits
+                if app.viewer_state.dirty {
+                    app.status_message =
+                        Some("Save or discard your changes before switching".to_string());
+                    return;
+                }
+                app.workspace_picker_state = None;
+                let workspace = app
+                    .app_state
+                    .workspaces(&app.vault.root)
+                    .iter()
+                    .find(|w| w.name == name)
+                    .cloned();
+                if let Some(workspace) = workspace {
+                    app.apply_workspace(&workspace);
+                    app.status_message = Some(format!("Workspace: {}", name));
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => app.workspace_picker_state = None,
+            _ => {}
+        }
+    }
+
+    /// Offers corrections for the word under the cursor (EDIT) or read cursor.
+    fn open_spell_suggestions(app: &mut App) {
+        let Some(ref spell) = app.spell else {
+            app.status_message = Some("Spell check is off (see [spell] in the config)".to_string());
+            return;
+        };
+        let state = &app.viewer_state;
+        let pos = match state.mode {
+            EditorMode::Edit => &state.cursor,
+            EditorMode::Read => &state.read_cursor,
+        };
+        if pos.line >= state.content.len_lines() {
+            return;
+        }
+        let line = state.content.line(pos.line).to_string();
+        let Some(range) = spell::word_at(&line, pos.col) else {
+            app.status_message = Some("No word under the cursor".to_string());
+            return;
+        };
+
+        let word: String = line.chars().skip(range.start).take(range.len()).collect();
+        if spell.is_correct(&word) {
+            app.status_message = Some(format!("\"{}\" is spelled correctly", word));
+            return;
+        }
+        let suggestions = spell.suggest(&word);
+        app.spell_state = Some(SpellState::new(word, pos.line, range, suggestions));
+    }
+
+    fn handle_spell(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.spell_state.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => state.move_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.move_up(),
+            KeyCode::Enter => {
+                let Some(state) = app.spell_state.take() else {
+                    return;
+                };
+                match state.choice() {
+                    SpellChoice::Replace(_) if Self::refuse_if_locked(app) => {}
+                    SpellChoice::Replace(suggestion) => {
+                        app.viewer_state.replace_in_line(
+                            state.line,
+                            state.range.clone(),
+                            suggestion,
+                        );
+                        // READ mode edits are saved straight away
+                        if app.viewer_state.mode == EditorMode::Read {
+                            Self::save_and_reload(app);
+                        }
+                    }
+                    SpellChoice::Ignore => {
+                        if let Some(ref mut spell) = app.spell {
+                            app.status_message = Some(match spell.ignore(&state.word) {
+                                Ok(()) => format!("Added \"{}\" to the word list", state.word),
+                                Err(e) => format!("Could not update the word list: {}", e),
+                            });
+                        }
+                    }
+                }
             }
-            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                // Toggle backlinks panel
-                app.focus = if app.focus == Focus::Backlinks {
-                    Focus::Browser
-                } else {
-                    Focus::Backlinks
-                };
-                return Ok(());
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.spell_state = None;
             }
             _ => {}
         }
-
-        // Context-specific keybindings
-        match app.focus {
-            Focus::Browser => Self::handle_browser(app, key),
-            Focus::Viewer => Self::handle_viewer(app, key),
-            Focus::Backlinks => Self::handle_backlinks(app, key),
-        }
-
-        Ok(())
     }
 
     fn handle_browser(app: &mut App, key: KeyEvent) {
+        // Create, delete, move, rename and archive all change the vault
+        if matches!(key.code, KeyCode::Char('a' | 'A' | 'd' | 'm' | 'r' | 'X'))
+            && Self::refuse_if_read_only(app)
+        {
+            return;
+        }
+        // Smart folders come from config, not the disk
+        if matches!(key.code, KeyCode::Char('a' | 'd' | 'm' | 'r' | 'X' | 'y'))
+            && app
+                .browser_state
+                .selected_entry(&app.filtered_visible_entries())
+                .is_some_and(|e| e.is_dir && e.is_virtual)
+        {
+            app.status_message = Some("Smart folders are set in [smart_folders]".to_string());
+            return;
+        }
         match key.code {
             KeyCode::Char('j') | KeyCode::Down => {
                 app.browser_state
@@ -266,11 +1525,30 @@ impl InputHandler {
                     let entries = app.filtered_visible_entries();
                     app.browser_state
                         .selected_entry(&entries)
-                        .map(|e| (e.is_dir, e.path.clone()))
+                        .map(|e| (e.is_dir, e.is_virtual, e.is_attachment(), e.path.clone()))
                 };
-                if let Some((is_dir, path)) = entry_info {
-                    if is_dir {
+                // Enter on a folder with a folder note opens the note; l and
+                // Right still fold it
+                let folder_note = entry_info
+                    .as_ref()
+                    .filter(|(is_dir, is_virtual, ..)| *is_dir && !*is_virtual)
+                    .filter(|_| key.code == KeyCode::Enter)
+                    .and_then(|(.., path)| app.vault.folder_note(path))
+                    .map(|note| note.path.clone());
+                if let Some(path) = folder_note {
+                    if app.select_note(&path) {
+                        app.focus = Focus::Viewer;
+                    } else {
+                        app.status_message =
+                            Some("The folder note is hidden by the filter".to_string());
+                    }
+                } else if let Some((is_dir, is_virtual, is_attachment, path)) = entry_info {
+                    if is_dir && is_virtual {
+                        app.toggle_smart_folder(&path);
+                    } else if is_dir {
                         app.vault.toggle_dir(&path);
+                    } else if is_attachment {
+                        app.open_attachment(&path);
                     } else {
                         app.focus = Focus::Viewer;
                         app.viewer_scroll = 0;
@@ -286,10 +1564,12 @@ impl InputHandler {
                     app.browser_state
                         .selected_entry(&entries)
                         .filter(|e| e.is_dir && e.expanded)
-                        .map(|e| e.path.clone())
+                        .map(|e| (e.path.clone(), e.is_virtual))
                 };
-                if let Some(path) = dir_path {
-                    app.vault.toggle_dir(&path);
+                match dir_path {
+                    Some((path, true)) => app.toggle_smart_folder(&path),
+                    Some((path, false)) => app.vault.toggle_dir(&path),
+                    None => {}
                 }
             }
             KeyCode::Char('g') => {
@@ -304,6 +1584,7 @@ impl InputHandler {
                 app.create_note_state = Some(CreateNoteState {
                     filename: String::new(),
                     parent_dir: PathBuf::new(),
+                    body: String::new(),
                 });
             }
             KeyCode::Char('a') => {
@@ -328,6 +1609,111 @@ impl InputHandler {
                 app.create_note_state = Some(CreateNoteState {
                     filename: String::new(),
                     parent_dir,
+                    body: String::new(),
+                });
+            }
+            KeyCode::Char('E') => Self::open_export_menu(app),
+            KeyCode::Char('y') => {
+                let path = {
+                    let entries = app.filtered_visible_entries();
+                    app.browser_state
+                        .selected_entry(&entries)
+                        .map(|e| e.path.clone())
+                };
+                if let Some(path) = path {
+                    app.copy_menu_state = Some(CopyMenuState::new(&app.vault, &path));
+                }
+            }
+            KeyCode::Char('v') => app.open_split(),
+            KeyCode::Char('V') if app.split.is_some() => {
+                app.split = None;
+                app.status_message = Some("Closed split".to_string());
+            }
+            KeyCode::Char('X') => {
+                if let Err(e) = Self::archive_entry(app) {
+                    app.toasts
+                        .push(ToastLevel::Error, format!("Could not move: {}", e));
+                }
+            }
+            KeyCode::Char('Z') => {
+                let show = !app.vault.shows_archived();
+                app.vault.set_show_archived(show);
+                Self::reselect_near(app, app.browser_state.selected);
+                app.status_message = Some(if show {
+                    "Showing archived notes".to_string()
+                } else {
+                    "Hiding archived notes".to_string()
+                });
+            }
+            KeyCode::Char('f') => {
+                app.browser_filter.get_or_insert_default().typing = true;
+            }
+            KeyCode::Char('m') => {
+                let selected = {
+                    let entries = app.filtered_visible_entries();
+                    app.browser_state
+                        .selected_entry(&entries)
+                        .map(|e| (e.path.clone(), e.is_dir))
+                };
+                if let Some((path, is_dir)) = selected {
+                    // Start from the folder it is in now
+                    let destination = match path.parent() {
+                        Some(dir) if !dir.as_os_str().is_empty() => {
+                            format!("{}/", dir.display())
+                        }
+                        _ => String::new(),
+                    };
+                    app.move_state = Some(MoveState {
+                        path,
+                        is_dir,
+                        destination,
+                    });
+                }
+            }
+            KeyCode::Char('r') => {
+                let selected = {
+                    let entries = app.filtered_visible_entries();
+                    app.browser_state
+                        .selected_entry(&entries)
+                        .map(|e| (e.path.clone(), e.is_dir))
+                };
+                if let Some((path, is_dir)) = selected {
+                    let name = if is_dir {
+                        path.file_name()
+                    } else {
+                        path.file_stem()
+                    };
+                    let name = name
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    app.rename_state = Some(RenameState { path, is_dir, name });
+                }
+            }
+            KeyCode::Esc if app.browser_filter.is_some() => {
+                Self::close_browser_filter(app);
+            }
+            KeyCode::Char('s') => {
+                let selected = {
+                    let entries = app.filtered_visible_entries();
+                    app.browser_state
+                        .selected_entry(&entries)
+                        .map(|e| e.path.clone())
+                };
+                let sort = app.vault.sort().next();
+                app.vault.set_sort(sort);
+                app.config.ui.sort = sort;
+
+                // The same entry stays selected wherever it moved to
+                let index = app
+                    .filtered_visible_entries()
+                    .iter()
+                    .position(|e| Some(&e.path) == selected.as_ref());
+                if let Some(index) = index {
+                    app.browser_state.select(index);
+                }
+                app.status_message = Some(match Config::persist("ui.sort", sort.label()) {
+                    Ok(()) => format!("Sorted by {}", sort.label()),
+                    Err(e) => format!("Sorted by {} (not saved: {})", sort.label(), e),
                 });
             }
             KeyCode::Char('t') => {
@@ -372,40 +1758,108 @@ impl InputHandler {
         }
     }
 
-    fn ensure_read_cursor_visible(app: &mut App) {
-        let cursor_line = app.viewer_state.read_cursor.line as u16;
-        let height = app.viewer_area_height;
+    /// Scrolls the preview just far enough to show the cursor of the
+    /// current mode.
+    /// Moves the preview's cursor to the last line and scrolls it into view.
+    pub fn jump_to_end(app: &mut App) {
+        app.viewer_state.move_read_cursor_to_line(usize::MAX);
+        Self::ensure_cursor_visible(app);
+    }
+
+    fn ensure_cursor_visible(app: &mut App) {
+        let height = app.viewer_area_height as isize;
         if height == 0 {
             return;
         }
-        if cursor_line < app.viewer_scroll {
-            app.viewer_scroll = cursor_line;
-        } else if cursor_line >= app.viewer_scroll + height {
-            app.viewer_scroll = cursor_line - height + 1;
+        let row = viewer::cursor_screen_row(app);
+        if row < 0 {
+            Self::scroll_cursor_to(app, 0);
+        } else if row >= height {
+            Self::scroll_cursor_to(app, height - 1);
         }
     }
 
+    /// Scrolls the preview so the cursor lands on screen row `target`.
+    /// Rows above the lines in view are estimated, so this corrects itself
+    /// over a few passes when wrapping differs from the estimate.
+    fn scroll_cursor_to(app: &mut App, target: isize) {
+        for _ in 0..4 {
+            let row = viewer::cursor_screen_row(app);
+            if row == target {
+                break;
+            }
+            let scroll = (app.viewer_scroll as isize + row - target).clamp(0, u16::MAX as isize);
+            if scroll == app.viewer_scroll as isize {
+                break;
+            }
+            app.viewer_scroll = scroll as u16;
+        }
+    }
+
+    /// Switches between READ and EDIT mode with `switch`, keeping the cursor
+    /// on the screen row it was on. A read cursor scrolled out of view first
+    /// moves to the top of the preview, so editing starts where you were
+    /// looking.
+    fn switch_mode(app: &mut App, switch: impl FnOnce(&mut App)) {
+        let height = app.viewer_area_height as isize;
+        let mut row = viewer::cursor_screen_row(app);
+        if app.viewer_state.mode == EditorMode::Read
+            && !(0..height).contains(&row)
+            && let Some(line) = viewer::read_line_at(app, 0)
+        {
+            app.viewer_state.move_read_cursor_to_line(line);
+            row = viewer::cursor_screen_row(app);
+        }
+
+        switch(app);
+        Self::scroll_cursor_to(app, row.clamp(0, (height - 1).max(0)));
+    }
+
     fn handle_viewer_read(app: &mut App, key: KeyEvent) {
+        // A history diff is read-only: only scrolling and closing apply
+        if app.active_history_diff().is_some() {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    app.viewer_scroll = app.viewer_scroll.saturating_add(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    app.viewer_scroll = app.viewer_scroll.saturating_sub(1);
+                }
+                KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.viewer_scroll = app.viewer_scroll.saturating_add(10);
+                }
+                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    app.viewer_scroll = app.viewer_scroll.saturating_sub(10);
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.history_diff = None;
+                    app.viewer_scroll = 0;
+                }
+                _ => {}
+            }
+            return;
+        }
+
         // Handle visual selection mode first
         if app.viewer_state.selection.is_some() {
             match key.code {
                 KeyCode::Char('j') | KeyCode::Down => {
-                    app.viewer_state.move_read_cursor_down();
+                    app.viewer_state.read_cursor = viewer::read_row_step(app, true);
                     app.viewer_state.update_selection_head();
-                    Self::ensure_read_cursor_visible(app);
+                    Self::ensure_cursor_visible(app);
                     return;
                 }
                 KeyCode::Char('k') | KeyCode::Up => {
-                    app.viewer_state.move_read_cursor_up();
+                    app.viewer_state.read_cursor = viewer::read_row_step(app, false);
                     app.viewer_state.update_selection_head();
-                    Self::ensure_read_cursor_visible(app);
+                    Self::ensure_cursor_visible(app);
                     return;
                 }
                 KeyCode::Char('g') => {
                     app.viewer_state.read_cursor.line = 0;
                     app.viewer_state.read_cursor.col = 0;
                     app.viewer_state.update_selection_head();
-                    Self::ensure_read_cursor_visible(app);
+                    Self::ensure_cursor_visible(app);
                     return;
                 }
                 KeyCode::Char('G') => {
@@ -413,7 +1867,7 @@ impl InputHandler {
                         app.viewer_state.content.len_lines().saturating_sub(1);
                     app.viewer_state.read_cursor.col = 0;
                     app.viewer_state.update_selection_head();
-                    Self::ensure_read_cursor_visible(app);
+                    Self::ensure_cursor_visible(app);
                     return;
                 }
                 KeyCode::Char('y') => {
@@ -425,8 +1879,19 @@ impl InputHandler {
                     app.viewer_state.clear_selection();
                     return;
                 }
+                KeyCode::Char('Y') => {
+                    // Copy as markdown or plain text
+                    if let Some(text) = app.viewer_state.selected_text() {
+                        app.copy_menu_state = Some(CopyMenuState::for_text(&text));
+                    }
+                    app.viewer_state.clear_selection();
+                    return;
+                }
                 KeyCode::Char('d') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
                     // Cut selected lines
+                    if Self::refuse_if_locked(app) {
+                        return;
+                    }
                     if let Some(text) = app.viewer_state.delete_selected_text() {
                         copy_to_clipboard(&text);
                         app.viewer_state.clipboard = Some(text);
@@ -442,11 +1907,105 @@ impl InputHandler {
             }
         }
 
+        // A count before a motion repeats it, or picks the line for `gg` / `G`
+        let count = std::mem::take(&mut app.pending_count);
+        if let KeyCode::Char(c @ '0'..='9') = key.code
+            && (c != '0' || count > 0)
+        {
+            app.pending_count = (count * 10 + c as usize - '0' as usize).min(9_999);
+            app.pending_g = false;
+            return;
+        }
+        let repeat = count.max(1);
+        let rows = repeat as u16;
+        let page = app.viewer_area_height.max(1).saturating_mul(rows);
+
+        // `gg` goes to the top, `gt` / `gT` switch tabs, `gc` closes the current
+        // one and `gu` selects the note's folder, or with a count one further up
+        if std::mem::take(&mut app.pending_g) {
+            match key.code {
+                KeyCode::Char('g') => {
+                    app.viewer_state
+                        .move_read_cursor_to_line(count.saturating_sub(1));
+                    if count == 0 {
+                        app.viewer_scroll = 0;
+                    }
+                    return Self::ensure_cursor_visible(app);
+                }
+                KeyCode::Char('t') => return app.cycle_tab(true),
+                KeyCode::Char('T') => return app.cycle_tab(false),
+                KeyCode::Char('c') => return app.close_tab(),
+                KeyCode::Char('u') => {
+                    let dir = app
+                        .selected_note()
+                        .and_then(|note| note.path.ancestors().nth(repeat))
+                        .map(Path::to_path_buf)
+                        .unwrap_or_default();
+                    return app.focus_folder(&dir);
+                }
+                _ => {}
+            }
+        }
+
         match key.code {
-            KeyCode::Char('i') => {
-                if app.selected_note().is_some() {
-                    app.viewer_state.enter_edit_mode();
+            KeyCode::Char('g') => {
+                app.pending_g = true;
+                app.pending_count = count;
+            }
+            KeyCode::Char('G') => {
+                let line = match count {
+                    0 => usize::MAX,
+                    n => n - 1,
+                };
+                app.viewer_state.move_read_cursor_to_line(line);
+                Self::ensure_cursor_visible(app);
+            }
+            KeyCode::Char('}') | KeyCode::Char('{') => {
+                for _ in 0..repeat {
+                    app.viewer_state
+                        .move_read_paragraph(key.code == KeyCode::Char('}'));
                 }
+                Self::ensure_cursor_visible(app);
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.viewer_scroll = app.viewer_scroll.saturating_add(page);
+            }
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.viewer_scroll = app.viewer_scroll.saturating_sub(page);
+            }
+            KeyCode::Char('i') if app.selected_note().is_some() && !Self::refuse_if_locked(app) => {
+                Self::switch_mode(app, |app| app.viewer_state.enter_edit_mode());
+            }
+            KeyCode::Char('E') => Self::open_export_menu(app),
+            KeyCode::Char('@') => Self::open_literature_note(app),
+            KeyCode::Char('F') => {
+                if app.is_following() {
+                    app.following = None;
+                    app.status_message = Some("Stopped following".to_string());
+                } else if let Some(path) = app.viewer_state.current_note_path.clone() {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    app.status_message = Some(format!("Following the end of {}", name));
+                    app.following = Some(path);
+                    Self::jump_to_end(app);
+                }
+            }
+            KeyCode::Char('r') => {
+                // Keep the cursor's line in place across the change of wrapping
+                let row = viewer::cursor_screen_row(app);
+                app.raw_source = !app.raw_source;
+                Self::scroll_cursor_to(app, row);
+            }
+            KeyCode::Char('%') => {
+                let show = !app.config.ui.show_comments;
+                app.config.ui.show_comments = show;
+                app.status_message = Some(
+                    if show {
+                        "Showing %%comments%%"
+                    } else {
+                        "Hiding %%comments%%"
+                    }
+                    .to_string(),
+                );
             }
             KeyCode::Char('v') => {
                 // Start visual selection
@@ -454,6 +2013,9 @@ impl InputHandler {
             }
             KeyCode::Char('p') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
                 // Paste from clipboard at read cursor
+                if Self::refuse_if_locked(app) {
+                    return;
+                }
                 let text = paste_from_clipboard().or_else(|| app.viewer_state.clipboard.clone());
                 if let Some(text) = text {
                     app.viewer_state.paste_text_at_read_cursor(&text);
@@ -467,30 +2029,36 @@ impl InputHandler {
                 app.find_in_note_state = Some(state);
             }
             KeyCode::Char('j') => {
-                app.viewer_scroll = app.viewer_scroll.saturating_add(1);
+                app.viewer_scroll = app.viewer_scroll.saturating_add(rows);
             }
             KeyCode::Char('k') => {
-                app.viewer_scroll = app.viewer_scroll.saturating_sub(1);
+                app.viewer_scroll = app.viewer_scroll.saturating_sub(rows);
             }
             KeyCode::Down => {
-                app.viewer_state.move_read_cursor_down();
-                Self::ensure_read_cursor_visible(app);
+                for _ in 0..repeat {
+                    app.viewer_state.read_cursor = viewer::read_row_step(app, true);
+                }
+                Self::ensure_cursor_visible(app);
             }
             KeyCode::Up => {
-                app.viewer_state.move_read_cursor_up();
-                Self::ensure_read_cursor_visible(app);
+                for _ in 0..repeat {
+                    app.viewer_state.read_cursor = viewer::read_row_step(app, false);
+                }
+                Self::ensure_cursor_visible(app);
             }
             KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.viewer_scroll = app.viewer_scroll.saturating_add(10);
+                app.viewer_scroll = app.viewer_scroll.saturating_add(rows.saturating_mul(10));
             }
             KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.viewer_scroll = app.viewer_scroll.saturating_sub(10);
+                app.viewer_scroll = app.viewer_scroll.saturating_sub(rows.saturating_mul(10));
             }
             KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 app.viewer_state.next_link();
+                Self::move_read_cursor_to_link(app);
             }
             KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 app.viewer_state.prev_link();
+                Self::move_read_cursor_to_link(app);
             }
             KeyCode::Left => {
                 app.viewer_state.move_read_cursor_left();
@@ -510,10 +2078,10 @@ impl InputHandler {
             KeyCode::Char('b') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
                 app.viewer_state.move_read_word_left();
             }
-            KeyCode::Enter => {
-                // Follow the current link
-                if let Some(target) = app.viewer_state.current_link().map(|l| l.target.clone()) {
-                    Self::follow_link(app, &target);
+            KeyCode::Enter => Self::follow_link_on_cursor_line(app),
+            KeyCode::Char('y') => {
+                if let Some(path) = app.viewer_state.current_note_path.clone() {
+                    app.copy_menu_state = Some(CopyMenuState::new(&app.vault, &path));
                 }
             }
             KeyCode::Esc => {
@@ -525,28 +2093,183 @@ impl InputHandler {
     }
 
     fn handle_viewer_edit(app: &mut App, key: KeyEvent) {
+        if key.code == KeyCode::Esc && app.viewer_state.autocomplete.is_none() {
+            if app.config.editor.save_on_exit {
+                Self::switch_mode(app, |app| {
+                    Self::exit_edit_mode(app);
+                });
+            } else {
+                Self::switch_mode(app, Self::leave_edit_mode);
+            }
+            return;
+        }
+
+        // Ctrl+] follows the wiki-link under the cursor. Most terminals send
+        // it as Ctrl+5, the legacy control code for that key.
+        if matches!(key.code, KeyCode::Char(']') | KeyCode::Char('5'))
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            let Some(target) = app.viewer_state.link_at_cursor() else {
+                app.status_message = Some("No link under the cursor".to_string());
+                return;
+            };
+            if app.vault.resolve_link(&target).is_none() {
+                app.status_message = Some(format!("No note matches [[{}]]", target));
+                return;
+            }
+            if Self::exit_edit_mode(app) {
+                Self::follow_link(app, &target);
+            }
+            return;
+        }
+
+        Self::edit_buffer(&mut app.viewer_state, &app.vault, key, &app.config.editor);
+        Self::ensure_cursor_visible(app);
+    }
+
+    /// Leaves edit mode without saving. Changes stay in the buffer, shown in
+    /// the preview and marked unsaved, until Ctrl+s writes them.
+    fn leave_edit_mode(app: &mut App) {
+        app.viewer_state.leave_table();
+        let dirty = app.viewer_state.dirty;
+        app.viewer_state.exit_edit_mode();
+        app.viewer_state.dirty = dirty;
+        if dirty {
+            app.status_message = Some("Unsaved changes (Ctrl+s saves)".to_string());
+        }
+    }
+
+    /// Writes the open note without leaving edit mode.
+    fn save_buffer(app: &mut App) {
+        let Some(path) = app.viewer_state.current_note_path.clone() else {
+            return;
+        };
+        if Self::changed_on_disk(app, &path, PendingSave::Buffer) {
+            return;
+        }
+        let content = app.viewer_state.content.to_string();
+        if let Err(e) = app.vault.write_note(&path, &content) {
+            app.toasts
+                .push(ToastLevel::Error, format!("Save failed: {}", e));
+            return;
+        }
+        app.viewer_state.dirty = false;
+        app.vault.reload_note(&path);
+        app.rebuild_index();
+        app.viewer_state.disk_mtime = app.vault.get_note(&path).map(|note| note.modified);
+        // The editor keeps its cursor; the preview picks up the new links
+        if app.viewer_state.mode == EditorMode::Read
+            && let Some(note) = app.vault.get_note(&path)
+        {
+            app.viewer_state.reload(note);
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        app.toasts.push(ToastLevel::Info, format!("Saved {}", name));
+    }
+
+    /// Leaves edit mode, saving the buffer and rebuilding the index. If the
+    /// save fails the editor stays open with its changes and false is
+    /// returned.
+    fn exit_edit_mode(app: &mut App) -> bool {
+        app.viewer_state.leave_table();
+        let path = app.viewer_state.current_note_path.clone();
+        let old_heading = path
+            .as_ref()
+            .and_then(|path| app.vault.get_note(path))
+            .and_then(|note| note::first_heading(&note.content()).map(String::from));
+        if let Some(path) = &path {
+            if Self::changed_on_disk(app, path, PendingSave::ExitEdit) {
+                return false;
+            }
+            let content = app.viewer_state.content.to_string();
+            if let Err(e) = app.vault.write_note(path, &content) {
+                app.toasts
+                    .push(ToastLevel::Error, format!("Save failed: {}", e));
+                return false;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            app.toasts.push(ToastLevel::Info, format!("Saved {}", name));
+        }
+
+        let content = app.viewer_state.exit_edit_mode();
+        if let Some(path) = path {
+            // Reload the note and rebuild index
+            app.vault.reload_note(&path);
+            app.rebuild_index();
+            if let Some(note) = app.vault.get_note(&path) {
+                app.viewer_state.reload(note);
+            }
+
+            // A retitled note can take its new title as the file name
+            let heading = note::first_heading(&content);
+            if app.config.editor.sync_title
+                && heading != old_heading.as_deref()
+                && let Some(name) = heading.map(text::file_name_for)
+                && !name.is_empty()
+                && path.file_stem().is_some_and(|stem| *stem != *name)
+            {
+                app.title_sync_state = Some(TitleSyncState {
+                    path,
+                    offer: TitleSync::FileName(name),
+                });
+            }
+        }
+        true
+    }
+
+    fn handle_scratch(app: &mut App, key: KeyEvent) {
+        let editor = &mut app.scratch.editor;
+        match key.code {
+            KeyCode::Esc if editor.autocomplete.is_none() => {
+                app.show_scratch = false;
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if !app.scratch.is_empty() && !Self::refuse_if_read_only(app) {
+                    app.create_note_state = Some(CreateNoteState {
+                        filename: String::new(),
+                        parent_dir: PathBuf::new(),
+                        body: app.scratch.editor.content.to_string(),
+                    });
+                }
+            }
+            _ => Self::edit_buffer(editor, &app.vault, key, &app.config.editor),
+        }
+    }
+
+    /// Text-editing keys shared by the note editor and the scratch buffer.
+    fn edit_buffer(
+        editor: &mut ViewerState,
+        vault: &Vault,
+        key: KeyEvent,
+        settings: &EditorConfig,
+    ) {
         // Handle autocomplete navigation first if active
-        if app.viewer_state.autocomplete.is_some() {
+        if editor.autocomplete.is_some() {
             match key.code {
                 KeyCode::Down | KeyCode::Char('n')
                     if key.modifiers.contains(KeyModifiers::CONTROL) =>
                 {
-                    app.viewer_state.autocomplete_next();
+                    editor.autocomplete_next();
                     return;
                 }
                 KeyCode::Up | KeyCode::Char('p')
                     if key.modifiers.contains(KeyModifiers::CONTROL) =>
                 {
-                    app.viewer_state.autocomplete_prev();
+                    editor.autocomplete_prev();
                     return;
                 }
-                KeyCode::Tab | KeyCode::Enter => {
-                    app.viewer_state.autocomplete_accept();
-                    app.viewer_state.update_autocomplete_matches(&app.vault);
+                KeyCode::Tab | KeyCode::Enter
+                    if editor
+                        .autocomplete
+                        .as_ref()
+                        .is_some_and(|ac| !ac.matches.is_empty()) =>
+                {
+                    editor.autocomplete_accept();
+                    editor.update_autocomplete_matches(vault);
                     return;
                 }
                 KeyCode::Esc => {
-                    app.viewer_state.autocomplete = None;
+                    editor.autocomplete = None;
                     return;
                 }
                 _ => {}
@@ -554,220 +2277,468 @@ impl InputHandler {
         }
 
         match key.code {
+            // In a table Tab and Enter move between cells, and Alt+r / Alt+c
+            // add a row or column (Alt+R / Alt+C delete one)
+            KeyCode::Tab | KeyCode::BackTab if editor.in_table() => {
+                editor.table_next_cell(key.code == KeyCode::Tab);
+            }
+            KeyCode::Enter if editor.in_table() && editor.selection.is_none() => {
+                editor.table_next_row();
+            }
+            KeyCode::Char(c @ ('r' | 'R' | 'c' | 'C'))
+                if editor.in_table() && key.modifiers.contains(KeyModifiers::ALT) =>
+            {
+                match c {
+                    'r' => editor.table_insert_row(),
+                    'R' => editor.table_delete_row(),
+                    'c' => editor.table_insert_column(),
+                    _ => editor.table_delete_column(),
+                }
+            }
             // Undo/Redo
             KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if key.modifiers.contains(KeyModifiers::SHIFT) {
-                    app.viewer_state.redo();
+                    editor.redo();
                 } else {
-                    app.viewer_state.undo();
+                    editor.undo();
                 }
             }
             KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.viewer_state.redo();
+                editor.redo();
             }
             // Ctrl+C — copy selection (or do nothing if no selection)
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(text) = app.viewer_state.selected_text() {
+                if let Some(text) = editor.selected_text() {
                     copy_to_clipboard(&text);
-                    app.viewer_state.clipboard = Some(text);
-                    app.viewer_state.clear_selection();
+                    editor.clipboard = Some(text);
+                    editor.clear_selection();
                 }
             }
             // Ctrl+X — cut selection
             KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(text) = app.viewer_state.delete_selected_text() {
+                if let Some(text) = editor.delete_selected_text() {
                     copy_to_clipboard(&text);
-                    app.viewer_state.clipboard = Some(text);
+                    editor.clipboard = Some(text);
                 }
             }
             // Ctrl+V — paste (replacing selection if any)
             KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 // Delete selection first if any
-                if app.viewer_state.selection.is_some() {
-                    app.viewer_state.delete_selected_text();
+                if editor.selection.is_some() {
+                    editor.delete_selected_text();
                 }
-                let text = paste_from_clipboard().or_else(|| app.viewer_state.clipboard.clone());
+                let text = paste_from_clipboard().or_else(|| editor.clipboard.clone());
                 if let Some(text) = text {
-                    app.viewer_state.paste_text(&text);
-                }
-            }
-            KeyCode::Esc => {
-                // Exit edit mode and save
-                let content = app.viewer_state.exit_edit_mode();
-                if let Some(path) = app.viewer_state.current_note_path.clone() {
-                    let full_path = app.vault.root.join(&path);
-                    let _ = std::fs::write(&full_path, &content);
-                    // Reload the note and rebuild index
-                    app.vault.reload_note(&path);
-                    app.index = Index::build(&app.vault);
-                    if let Some(note) = app.vault.get_note(&path) {
-                        app.viewer_state.update_links(note);
-                    }
+                    editor.paste_text(&text);
                 }
             }
             // Shift+Arrow keys for char-level selection
             KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.viewer_state.start_char_selection();
-                app.viewer_state.move_cursor_left();
-                app.viewer_state.update_selection_head();
+                editor.start_char_selection();
+                editor.move_cursor_left();
+                editor.update_selection_head();
             }
             KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.viewer_state.start_char_selection();
-                app.viewer_state.move_cursor_right();
-                app.viewer_state.update_selection_head();
+                editor.start_char_selection();
+                editor.move_cursor_right();
+                editor.update_selection_head();
             }
             KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.viewer_state.start_char_selection();
-                app.viewer_state.move_cursor_up();
-                app.viewer_state.update_selection_head();
+                editor.start_char_selection();
+                editor.move_cursor_up();
+                editor.update_selection_head();
             }
             KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                app.viewer_state.start_char_selection();
-                app.viewer_state.move_cursor_down();
-                app.viewer_state.update_selection_head();
+                editor.start_char_selection();
+                editor.move_cursor_down();
+                editor.update_selection_head();
             }
+            // Ctrl with a letter is a command, not text
+            KeyCode::Char(_)
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && !key.modifiers.contains(KeyModifiers::ALT) => {}
             KeyCode::Char(c) => {
                 // If selection active, replace it
-                if app.viewer_state.selection.is_some() {
-                    app.viewer_state.delete_selected_text();
+                if editor.selection.is_some() {
+                    editor.delete_selected_text();
                 }
-                app.viewer_state.insert_char(c);
-                app.viewer_state.update_autocomplete_matches(&app.vault);
+                if settings.auto_pair {
+                    editor.insert_paired(c);
+                } else {
+                    editor.insert_char(c);
+                }
+                editor.update_autocomplete_matches(vault);
             }
             KeyCode::Enter => {
-                if app.viewer_state.selection.is_some() {
-                    app.viewer_state.delete_selected_text();
+                if editor.selection.is_some() {
+                    editor.delete_selected_text();
                 }
-                app.viewer_state.insert_newline();
+                editor.insert_newline();
             }
+            KeyCode::Tab => editor.tab(&settings.snippets, settings.indent_width),
+            KeyCode::BackTab => editor.outdent(settings.indent_width),
             KeyCode::Backspace => {
-                if app.viewer_state.selection.is_some() {
-                    app.viewer_state.delete_selected_text();
-                } else {
-                    app.viewer_state.delete_char();
+                if editor.selection.is_some() {
+                    editor.delete_selected_text();
+                } else if !(settings.auto_pair && editor.delete_empty_pair()) {
+                    editor.delete_char();
                 }
-                app.viewer_state.update_autocomplete_matches(&app.vault);
+                editor.update_autocomplete_matches(vault);
             }
             KeyCode::Delete => {
-                if app.viewer_state.selection.is_some() {
-                    app.viewer_state.delete_selected_text();
+                if editor.selection.is_some() {
+                    editor.delete_selected_text();
                 } else {
-                    app.viewer_state.delete_forward();
+                    editor.delete_forward();
                 }
-                app.viewer_state.update_autocomplete_matches(&app.vault);
+                editor.update_autocomplete_matches(vault);
             }
             KeyCode::Left => {
-                app.viewer_state.clear_selection();
+                editor.clear_selection();
                 if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.viewer_state.move_word_left();
+                    editor.move_word_left();
                 } else {
-                    app.viewer_state.move_cursor_left();
+                    editor.move_cursor_left();
                 }
             }
             KeyCode::Right => {
-                app.viewer_state.clear_selection();
+                editor.clear_selection();
                 if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    app.viewer_state.move_word_right();
+                    editor.move_word_right();
                 } else {
-                    app.viewer_state.move_cursor_right();
+                    editor.move_cursor_right();
                 }
             }
             KeyCode::Up => {
-                app.viewer_state.clear_selection();
-                app.viewer_state.move_cursor_up();
+                editor.clear_selection();
+                editor.move_cursor_up();
             }
             KeyCode::Down => {
-                app.viewer_state.clear_selection();
-                app.viewer_state.move_cursor_down();
+                editor.clear_selection();
+                editor.move_cursor_down();
             }
             KeyCode::Home => {
-                app.viewer_state.clear_selection();
-                app.viewer_state.move_to_line_start();
+                editor.clear_selection();
+                editor.move_to_line_start();
             }
             KeyCode::End => {
-                app.viewer_state.clear_selection();
-                app.viewer_state.move_to_line_end();
+                editor.clear_selection();
+                editor.move_to_line_end();
+            }
+            _ => {}
+        }
+        editor.sync_table();
+        editor.sync_snippet();
+    }
+
+    fn handle_backlinks(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                let count = app.linked_notes().len();
+                app.backlinks_state.move_down(count);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.backlinks_state.move_up();
+            }
+            KeyCode::Char('o') => app.backlinks_state.toggle_tab(),
+            KeyCode::Char('s') if app.backlinks_state.tab == LinksTab::Backlinks => {
+                app.backlinks_state.cycle_sort();
+                app.status_message = Some(format!(
+                    "Backlinks sorted by {}",
+                    app.backlinks_state.sort.label()
+                ));
+            }
+            KeyCode::Enter => {
+                // Navigate to the selected note
+                let links = app.linked_notes();
+                if let Some(target_path) = app.backlinks_state.selected_path(&links) {
+                    // Find this note in the browser tree
+                    if let Some(index) = app
+                        .vault
+                        .visible_entries()
+                        .iter()
+                        .position(|e| &e.path == target_path)
+                    {
+                        app.browser_state.select(index);
+                        if let Some(note) = app.vault.get_note(target_path) {
+                            app.viewer_state.update_links(note);
+                        }
+                        app.viewer_scroll = 0;
+                        app.backlinks_state.reset();
+                        app.focus = Focus::Viewer;
+                    }
+                } else if let Some((name, None)) = links.get(app.backlinks_state.selected) {
+                    app.status_message = Some(format!("No note matches [[{}]]", name));
+                }
+            }
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc => {
+                app.focus = Focus::Browser;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_create_dialog(app: &mut App, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                app.create_note_state = None;
+            }
+            KeyCode::Enter => {
+                if let Some(state) = app.create_note_state.take()
+                    && !state.filename.is_empty()
+                {
+                    if let Err(e) =
+                        Self::create_note(app, &state.parent_dir, &state.filename, &state.body)
+                    {
+                        // Keep the dialog, and any scratch text, to try again
+                        app.toasts
+                            .push(ToastLevel::Error, format!("Could not create note: {}", e));
+                        app.create_note_state = Some(state);
+                        return Ok(());
+                    }
+                    if !state.body.is_empty() {
+                        // The scratch text now lives in the vault
+                        app.scratch = ScratchState::new();
+                        app.show_scratch = false;
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(ref mut state) = app.create_note_state {
+                    state.filename.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                // Allow valid filename characters including '/' for directories
+                if (c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' || c == '/')
+                    && let Some(ref mut state) = app.create_note_state
+                {
+                    state.filename.push(c);
+                }
             }
             _ => {}
         }
+        Ok(())
     }
 
-    fn handle_backlinks(app: &mut App, key: KeyEvent) {
+    fn handle_move_dialog(app: &mut App, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if let Some(note) = app.selected_note() {
-                    let backlinks = app.index.get_backlinks(&note.path);
-                    app.backlinks_state.move_down(backlinks.len());
-                }
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                app.backlinks_state.move_up();
+            KeyCode::Esc => {
+                app.move_state = None;
             }
             KeyCode::Enter => {
-                // Navigate to selected backlink
-                if let Some(note) = app.selected_note() {
-                    let backlinks = app.index.get_backlinks(&note.path);
-                    if let Some(target_path) = app.backlinks_state.selected_path(&backlinks) {
-                        // Find this note in the browser tree
-                        if let Some(index) = app
-                            .vault
-                            .visible_entries()
-                            .iter()
-                            .position(|e| &e.path == target_path)
-                        {
-                            app.browser_state.select(index);
-                            if let Some(note) = app.vault.get_note(target_path) {
-                                app.viewer_state.update_links(note);
-                            }
-                            app.viewer_scroll = 0;
-                            app.backlinks_state.reset();
-                            app.focus = Focus::Viewer;
-                        }
-                    }
+                if let Some(state) = app.move_state.take() {
+                    Self::move_entry(app, &state.path, &state.destination);
                 }
             }
-            KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc => {
-                app.focus = Focus::Browser;
+            KeyCode::Backspace => {
+                if let Some(ref mut state) = app.move_state {
+                    state.destination.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                // Same characters as note names, plus '/' between folders
+                if (c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' || c == '/')
+                    && let Some(ref mut state) = app.move_state
+                {
+                    state.destination.push(c);
+                }
             }
             _ => {}
         }
     }
 
-    fn handle_create_dialog(app: &mut App, key: KeyEvent) -> Result<()> {
+    /// Moves a note or directory into the folder `destination`, rewriting
+    /// wiki-links that spell out its old path.
+    fn move_entry(app: &mut App, path: &Path, destination: &str) {
+        let Some(name) = path.file_name() else {
+            return;
+        };
+        let folder = PathBuf::from(destination.trim_matches('/'));
+        let target = folder.join(name);
+        if target == path {
+            return;
+        }
+        if target.starts_with(path) {
+            app.status_message = Some("Can't move a directory into itself".to_string());
+            return;
+        }
+
+        let rewritten = match app.vault.move_entry(path, &target) {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                app.toasts
+                    .push(ToastLevel::Error, format!("Could not move: {}", e));
+                return;
+            }
+        };
+        if let Err(e) = app.refresh_vault() {
+            app.toasts.push(
+                ToastLevel::Error,
+                format!("Could not reload the vault: {}", e),
+            );
+            return;
+        }
+        app.select_note(&target);
+
+        let links = match rewritten {
+            0 => String::new(),
+            1 => " (updated links in 1 note)".to_string(),
+            n => format!(" (updated links in {} notes)", n),
+        };
+        app.status_message = Some(format!(
+            "Moved {} to {}{}",
+            path.display(),
+            if folder.as_os_str().is_empty() {
+                "the vault root".to_string()
+            } else {
+                folder.display().to_string()
+            },
+            links
+        ));
+    }
+
+    fn handle_rename_dialog(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Esc => {
-                app.create_note_state = None;
+                app.rename_state = None;
             }
             KeyCode::Enter => {
-                if let Some(state) = app.create_note_state.take() {
-                    if !state.filename.is_empty() {
-                        Self::create_note(app, &state.parent_dir, &state.filename)?;
+                if let Some(state) = app.rename_state.take() {
+                    let name = state.name.trim();
+                    if !name.is_empty() {
+                        Self::rename_entry(app, &state.path, name, state.is_dir, true);
                     }
                 }
             }
             KeyCode::Backspace => {
-                if let Some(ref mut state) = app.create_note_state {
-                    state.filename.pop();
+                if let Some(ref mut state) = app.rename_state {
+                    state.name.pop();
                 }
             }
             KeyCode::Char(c) => {
-                // Allow valid filename characters including '/' for directories
-                if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' || c == '/' {
-                    if let Some(ref mut state) = app.create_note_state {
-                        state.filename.push(c);
+                // Same characters as note names
+                if (c.is_alphanumeric() || c == '-' || c == '_' || c == ' ')
+                    && let Some(ref mut state) = app.rename_state
+                {
+                    state.name.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Renames a note or directory in place, rewriting wiki-links to it.
+    /// With `offer_heading`, a renamed note whose first heading differs may
+    /// take the new name as its title.
+    fn rename_entry(app: &mut App, path: &Path, name: &str, is_dir: bool, offer_heading: bool) {
+        let file_name = if is_dir {
+            name.to_string()
+        } else {
+            format!("{}.md", name)
+        };
+        let target = path.with_file_name(file_name);
+        if target == path {
+            return;
+        }
+
+        let rewritten = match app.vault.move_entry(path, &target) {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                app.toasts
+                    .push(ToastLevel::Error, format!("Could not rename: {}", e));
+                return;
+            }
+        };
+        if let Err(e) = app.refresh_vault() {
+            app.toasts.push(
+                ToastLevel::Error,
+                format!("Could not reload the vault: {}", e),
+            );
+            return;
+        }
+        app.select_note(&target);
+
+        let links = match rewritten {
+            0 => String::new(),
+            1 => " (updated links in 1 note)".to_string(),
+            n => format!(" (updated links in {} notes)", n),
+        };
+        app.status_message = Some(format!(
+            "Renamed {} to {}{}",
+            path.display(),
+            target.display(),
+            links
+        ));
+
+        let heading = app
+            .vault
+            .get_note(&target)
+            .and_then(|note| note::first_heading(&note.content()).map(String::from));
+        if offer_heading
+            && app.config.editor.sync_title
+            && heading.is_some_and(|heading| heading != name)
+        {
+            app.title_sync_state = Some(TitleSyncState {
+                path: target,
+                offer: TitleSync::Heading(name.to_string()),
+            });
+        }
+    }
+
+    fn handle_title_sync(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                let Some(state) = app.title_sync_state.take() else {
+                    return;
+                };
+                match state.offer {
+                    TitleSync::FileName(name) => {
+                        Self::rename_entry(app, &state.path, &name, false, false);
                     }
+                    TitleSync::Heading(title) => Self::retitle_note(app, &state.path, &title),
                 }
             }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.title_sync_state = None;
+            }
             _ => {}
         }
-        Ok(())
+    }
+
+    /// Changes the first heading of the note at `path` to `title`.
+    fn retitle_note(app: &mut App, path: &Path, title: &str) {
+        let Some(content) = app
+            .vault
+            .get_note(path)
+            .and_then(|note| note::set_first_heading(&note.content(), title))
+        else {
+            return;
+        };
+        if let Err(e) = app.vault.write_note(path, &content) {
+            app.toasts.push(
+                ToastLevel::Error,
+                format!("Could not update the heading: {}", e),
+            );
+            return;
+        }
+        app.vault.reload_note(path);
+        app.rebuild_index();
+        if app.viewer_state.current_note_path.as_deref() == Some(path)
+            && let Some(note) = app.vault.get_note(path)
+        {
+            app.viewer_state.reload(note);
+        }
     }
 
     fn handle_delete_dialog(app: &mut App, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
-                if let Some(state) = app.delete_confirm_state.take() {
-                    Self::delete_entry(app, &state.path, state.is_dir)?;
+                if let Some(state) = app.delete_confirm_state.take()
+                    && let Err(e) = Self::delete_entry(app, &state.path, state.is_dir)
+                {
+                    app.toasts
+                        .push(ToastLevel::Error, format!("Could not delete: {}", e));
                 }
             }
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
@@ -778,6 +2749,63 @@ impl InputHandler {
         Ok(())
     }
 
+    fn handle_sync_conflict(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('a') | KeyCode::Char('A') if !Self::refuse_if_read_only(app) => {
+                app.sync_conflict_state = None;
+                app.sync_state.status = match git::abort_merge(&app.vault.root) {
+                    Ok(()) => SyncStatus::Idle,
+                    Err(e) => SyncStatus::Failed(e.to_string()),
+                };
+            }
+            KeyCode::Esc | KeyCode::Enter => {
+                app.sync_conflict_state = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn open_export_menu(app: &mut App) {
+        let visible_count = app
+            .filtered_visible_entries()
+            .iter()
+            .filter(|e| !e.is_dir)
+            .count();
+        app.export_menu_state = Some(ExportMenuState::new(
+            visible_count,
+            app.config.export.converter.clone(),
+        ));
+    }
+
+    fn handle_export_menu(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(ref mut state) = app.export_menu_state {
+                    state.move_down();
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some(ref mut state) = app.export_menu_state {
+                    state.move_up();
+                }
+            }
+            KeyCode::Tab | KeyCode::Char('s') => {
+                if let Some(ref mut state) = app.export_menu_state {
+                    state.toggle_scope();
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(state) = app.export_menu_state.take() {
+                    app.start_export(state.selected_format(), state.scope);
+                }
+            }
+            KeyCode::Esc => {
+                app.export_menu_state = None;
+            }
+            _ => {}
+        }
+    }
+
     fn handle_tag_filter(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Char('j') | KeyCode::Down => {
@@ -804,7 +2832,65 @@ impl InputHandler {
     }
 
     fn handle_search(app: &mut App, key: KeyEvent) {
+        let Some(state) = app.search_state.as_mut() else {
+            return;
+        };
+        // Naming the query to save it
+        if let Some(name) = state.naming.as_mut() {
+            match key.code {
+                KeyCode::Esc => state.naming = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    let name = name.trim().to_string();
+                    state.naming = None;
+                    let root = app.vault.root.clone();
+                    app.app_state.save_search(&root, &name, &state.query);
+                    state.set_saved(app.app_state.saved_searches(&root).to_vec());
+                    app.refresh_smart_folders();
+                    if let Err(e) = app.app_state.save() {
+                        app.toasts.push(
+                            ToastLevel::Error,
+                            format!("Could not save the search: {}", e),
+                        );
+                    } else {
+                        app.status_message = Some(format!("Saved search \"{}\"", name));
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
         match key.code {
+            KeyCode::Char('s')
+                if key.modifiers.contains(KeyModifiers::CONTROL) && !state.query.is_empty() =>
+            {
+                state.naming = Some(String::new());
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(name) = state.selected_saved().map(|s| s.name.clone()) {
+                    let root = app.vault.root.clone();
+                    app.app_state.delete_search(&root, &name);
+                    state.set_saved(app.app_state.saved_searches(&root).to_vec());
+                    app.refresh_smart_folders();
+                    let _ = app.app_state.save();
+                    app.status_message = Some(format!("Deleted search \"{}\"", name));
+                }
+            }
+            // Picking a saved search runs its query
+            KeyCode::Enter if state.is_picking() => {
+                if let Some(query) = state.selected_saved().map(|s| s.query.clone()) {
+                    state.query = query;
+                    state.update_results(
+                        &app.vault,
+                        &app.index,
+                        app.config.vault.expand_transclusions,
+                    );
+                }
+            }
             KeyCode::Esc => {
                 app.search_state = None;
             }
@@ -841,17 +2927,8 @@ impl InputHandler {
 
                 if let Some(path) = target_path {
                     app.search_state = None;
-                    // Navigate to the note
-                    if let Some(index) = app
-                        .filtered_visible_entries()
-                        .iter()
-                        .position(|e| e.path == path)
-                    {
-                        app.browser_state.select(index);
-                        if let Some(note) = app.vault.get_note(&path) {
-                            app.viewer_state.update_links(note);
-                        }
-                        app.viewer_scroll = 0;
+                    // Navigate to the note, which may be archived
+                    if app.select_note(&path) {
                         app.focus = Focus::Viewer;
                     }
                 }
@@ -859,13 +2936,21 @@ impl InputHandler {
             KeyCode::Backspace => {
                 if let Some(ref mut state) = app.search_state {
                     state.query.pop();
-                    state.update_results(&app.vault);
+                    state.update_results(
+                        &app.vault,
+                        &app.index,
+                        app.config.vault.expand_transclusions,
+                    );
                 }
             }
             KeyCode::Char(c) => {
                 if let Some(ref mut state) = app.search_state {
                     state.query.push(c);
-                    state.update_results(&app.vault);
+                    state.update_results(
+                        &app.vault,
+                        &app.index,
+                        app.config.vault.expand_transclusions,
+                    );
                 }
             }
             _ => {}
@@ -887,26 +2972,39 @@ impl InputHandler {
                     state.move_up();
                 }
             }
+            // Alt+Enter (or Ctrl+Enter where the terminal reports it) makes
+            // a note of the query; so does Enter when nothing matches
+            KeyCode::Enter
+                if app.finder_state.as_ref().is_some_and(|state| {
+                    !state.has_exact_match()
+                        && (state.creates_on_enter()
+                            || (!state.query.trim().is_empty()
+                                && key
+                                    .modifiers
+                                    .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)))
+                }) =>
+            {
+                let Some(state) = app.finder_state.take() else {
+                    return;
+                };
+                Self::create_from_finder(app, state.query.trim());
+            }
             KeyCode::Enter => {
-                let target_path = app
+                let target = app
                     .finder_state
                     .as_ref()
-                    .and_then(|s| s.selected_path())
-                    .cloned();
+                    .and_then(|s| s.selected_result())
+                    .map(|result| (result.path.clone(), result.is_dir));
 
-                if let Some(path) = target_path {
+                if let Some((path, is_dir)) = target {
                     app.finder_state = None;
-                    if let Some(index) = app
-                        .filtered_visible_entries()
-                        .iter()
-                        .position(|e| e.path == path)
-                    {
-                        app.browser_state.select(index);
-                        if let Some(note) = app.vault.get_note(&path) {
-                            app.viewer_state.update_links(note);
-                        }
-                        app.viewer_scroll = 0;
-                        app.focus = Focus::Viewer;
+                    // A directory is selected in the browser, a note opened
+                    if app.select_note(&path) {
+                        app.focus = if is_dir {
+                            Focus::Browser
+                        } else {
+                            Focus::Viewer
+                        };
                     }
                 }
             }
@@ -926,6 +3024,45 @@ impl InputHandler {
         }
     }
 
+    /// Creates a note named after a finder query in `vault.inbox_dir` and
+    /// opens it. Slashes in the query make subfolders.
+    fn create_from_finder(app: &mut App, query: &str) {
+        if Self::refuse_if_read_only(app) {
+            return;
+        }
+        let mut parts: Vec<String> = query
+            .split('/')
+            .map(text::file_name_for)
+            .filter(|part| !part.is_empty())
+            .collect();
+        let Some(name) = parts.pop() else {
+            app.status_message = Some("A note name needs letters or digits".to_string());
+            return;
+        };
+        let parent = parts
+            .iter()
+            .fold(app.config.vault.inbox_dir.clone(), |dir, part| {
+                dir.join(part)
+            });
+
+        let path = parent.join(format!("{}.md", name));
+        if app.vault.get_note(&path).is_some() {
+            app.select_note(&path);
+            app.focus = Focus::Viewer;
+            return;
+        }
+        match Self::create_note(app, &parent, &name, "") {
+            Ok(()) => {
+                app.select_note(&path);
+                app.focus = Focus::Viewer;
+                app.status_message = Some(format!("Created {}", path.display()));
+            }
+            Err(e) => app
+                .toasts
+                .push(ToastLevel::Error, format!("Could not create note: {}", e)),
+        }
+    }
+
     fn handle_graph_view(
         app: &mut App,
         key: KeyEvent,
@@ -977,6 +3114,11 @@ impl InputHandler {
                 }
             }
             KeyCode::Enter => {
+                // Opening another note would replace unsaved text
+                if app.viewer_state.mode == EditorMode::Edit || app.viewer_state.dirty {
+                    Self::ask_unsaved(app, key);
+                    return Ok(());
+                }
                 // Navigate to the selected node
                 let target = app
                     .graph_view_state
@@ -1003,6 +3145,65 @@ impl InputHandler {
         Ok(())
     }
 
+    fn handle_browser_filter(app: &mut App, key: KeyEvent) {
+        let Some(ref mut filter) = app.browser_filter else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                Self::close_browser_filter(app);
+                return;
+            }
+            KeyCode::Enter => {
+                // Keep the narrowed tree and go back to navigating it
+                filter.typing = false;
+                if filter.query.is_empty() {
+                    app.browser_filter = None;
+                }
+                return;
+            }
+            KeyCode::Down => {
+                app.browser_state
+                    .move_down(app.filtered_visible_entries().len());
+                return;
+            }
+            KeyCode::Up => {
+                app.browser_state.move_up();
+                return;
+            }
+            KeyCode::Backspace => {
+                filter.query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                filter.query.push(c);
+            }
+            _ => return,
+        }
+
+        // Land on the first matching note rather than a folder above it
+        let first_note = app
+            .filtered_visible_entries()
+            .iter()
+            .position(|e| !e.is_dir)
+            .unwrap_or(0);
+        Self::reselect_near(app, first_note);
+    }
+
+    /// Drops the browser filter, keeping the selected entry selected.
+    fn close_browser_filter(app: &mut App) {
+        let selected = {
+            let entries = app.filtered_visible_entries();
+            app.browser_state
+                .selected_entry(&entries)
+                .map(|e| e.path.clone())
+        };
+        app.browser_filter = None;
+        match selected {
+            Some(path) if app.select_note(&path) => {}
+            _ => Self::reselect_near(app, 0),
+        }
+    }
+
     fn handle_find_in_note(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Esc => {
@@ -1059,13 +3260,50 @@ impl InputHandler {
         }
     }
 
-    fn create_note(app: &mut App, parent_dir: &std::path::Path, filename: &str) -> Result<()> {
+    fn handle_history(app: &mut App, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.history_state = None;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(ref mut state) = app.history_state {
+                    state.move_down();
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some(ref mut state) = app.history_state {
+                    state.move_up();
+                }
+            }
+            KeyCode::Enter => {
+                let selection = app.history_state.as_ref().and_then(|s| {
+                    s.selected_revision()
+                        .map(|rev| (s.note_path.clone(), rev.clone()))
+                });
+
+                if let Some((path, revision)) = selection {
+                    app.history_state = None;
+                    app.history_diff = Some(DiffView::load(&app.vault.root, path, revision));
+                    app.viewer_scroll = 0;
+                    app.focus = Focus::Viewer;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn create_note(
+        app: &mut App,
+        parent_dir: &std::path::Path,
+        filename: &str,
+        body: &str,
+    ) -> Result<()> {
         // If filename ends with '/', create a standalone directory
         if filename.ends_with('/') {
             let dir_name = filename.trim_end_matches('/');
             let relative_path = parent_dir.join(dir_name);
             let full_path = app.vault.root.join(&relative_path);
-            std::fs::create_dir_all(&full_path)?;
+            std::fs::create_dir_all(&full_path).map_err(|e| io_error(&relative_path, e))?;
             app.refresh_vault()?;
 
             // Select the newly created directory
@@ -1086,7 +3324,7 @@ impl InputHandler {
 
         // Create parent directories if they don't exist
         if let Some(parent) = full_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
         }
 
         // Create the file with a basic header
@@ -1097,8 +3335,8 @@ impl InputHandler {
             .unwrap_or(filename)
             .replace(['-', '_'], " ");
 
-        let content = format!("# {}\n\n", title);
-        std::fs::write(&full_path, content)?;
+        let content = app.new_note_content(format!("# {}\n\n{}", title, body));
+        app.vault.write_note(&relative_path, &content)?;
 
         // Refresh vault to pick up the new file
         app.refresh_vault()?;
@@ -1123,18 +3361,67 @@ impl InputHandler {
         let full_path = app.vault.root.join(path);
 
         // Delete the file or directory (including contents)
-        if is_dir {
-            std::fs::remove_dir_all(&full_path)?;
+        let removed = if is_dir {
+            std::fs::remove_dir_all(&full_path)
         } else {
-            std::fs::remove_file(&full_path)?;
-        }
+            std::fs::remove_file(&full_path)
+        };
+        removed.map_err(|e| io_error(path, e))?;
 
         // Get current selection before refresh
         let current_idx = app.browser_state.selected;
 
         // Refresh vault
         app.refresh_vault()?;
+        Self::reselect_near(app, current_idx);
+
+        Ok(())
+    }
+
+    /// Moves the selected note or folder into the archive folder, or back to
+    /// where it came from if it is already archived.
+    fn archive_entry(app: &mut App) -> Result<()> {
+        let path = {
+            let entries = app.filtered_visible_entries();
+            app.browser_state
+                .selected_entry(&entries)
+                .map(|e| e.path.clone())
+        };
+        let Some(path) = path else {
+            return Ok(());
+        };
+
+        let archive = app.vault.archive_dir().to_path_buf();
+        if archive.as_os_str().is_empty() || path == archive {
+            return Ok(());
+        }
+        // Archived notes keep their folders so they can be restored in place
+        let (target, verb) = match path.strip_prefix(&archive) {
+            Ok(original) => (original.to_path_buf(), "Restored"),
+            Err(_) => (archive.join(&path), "Archived"),
+        };
+
+        let full_target = app.vault.root.join(&target);
+        if full_target.exists() {
+            app.status_message = Some(format!("{} already exists", target.display()));
+            return Ok(());
+        }
+        if let Some(parent) = full_target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        std::fs::rename(app.vault.root.join(&path), &full_target)
+            .map_err(|e| io_error(&path, e))?;
+
+        let current_idx = app.browser_state.selected;
+        app.refresh_vault()?;
+        Self::reselect_near(app, current_idx);
+        app.status_message = Some(format!("{} {}", verb, path.display()));
 
+        Ok(())
+    }
+
+    /// Keeps the browser selection in bounds after entries were removed.
+    fn reselect_near(app: &mut App, current_idx: usize) {
         // Adjust selection if needed (stay in bounds)
         let visible_count = app.filtered_visible_entries().len();
         if visible_count > 0 {
@@ -1149,13 +3436,29 @@ impl InputHandler {
                     .filter(|e| !e.is_dir)
                     .map(|e| e.path.clone())
             };
-            if let Some(path) = note_path {
-                if let Some(note) = app.vault.get_note(&path) {
-                    app.viewer_state.update_links(note);
-                }
+            if let Some(path) = note_path
+                && let Some(note) = app.vault.get_note(&path)
+            {
+                app.viewer_state.update_links(note);
             }
         }
-
-        Ok(())
     }
 }
+
+/// The row and column of `pos` inside the borders of a pane, if it is inside.
+fn inner_cell(area: Rect, pos: Position) -> Option<(u16, u16)> {
+    let inner = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    cell_in(inner, pos)
+}
+
+/// Row and column of `pos` relative to `inner`, if it falls inside.
+fn cell_in(inner: Rect, pos: Position) -> Option<(u16, u16)> {
+    inner
+        .contains(pos)
+        .then(|| (pos.y - inner.y, pos.x - inner.x))
+}
@@ -1,4 +1,5 @@
 mod app;
+mod cli;
 mod config;
 mod core;
 mod input;
@@ -11,8 +12,18 @@ use color_eyre::Result;
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let config = config::Config::load()?;
-    let mut app = App::new(config)?;
+    let mut config = config::Config::load()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::run(&config, &args)? {
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--read-only") {
+        config.vault.read_only = true;
+    }
+
+    let key = cli::vault_key(&config)?;
+    let mut app = App::new(config, key)?;
 
     app.run().await
 }
@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::core::{Vault, note};
+use crate::ui::theme::Theme;
+
+enum AgendaRow {
+    Header(String),
+    Task {
+        path: PathBuf,
+        line: usize,
+        text: String,
+        note: String,
+        due: NaiveDate,
+    },
+}
+
+/// Open tasks with a due date across the vault, soonest first and grouped
+/// into overdue, today, tomorrow, the coming week and later.
+pub struct AgendaState {
+    rows: Vec<AgendaRow>,
+    today: NaiveDate,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl AgendaState {
+    pub fn new(vault: &Vault) -> Self {
+        let mut tasks: Vec<_> = vault
+            .notes
+            .values()
+            .flat_map(|n| {
+                note::due_tasks(&n.scan())
+                    .into_iter()
+                    .filter(|task| !task.done)
+                    .map(|task| (n.path.clone(), n.title.clone(), task))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        tasks.sort_by(|(a_path, _, a), (b_path, _, b)| {
+            (a.due, a_path, a.line).cmp(&(b.due, b_path, b.line))
+        });
+
+        let today = Local::now().date_naive();
+        let mut rows = Vec::new();
+        let mut current_group = None;
+
+        for (path, title, task) in tasks {
+            let group = group_label(task.due, today);
+            if current_group != Some(group) {
+                rows.push(AgendaRow::Header(group.to_string()));
+                current_group = Some(group);
+            }
+            rows.push(AgendaRow::Task {
+                path,
+                line: task.line,
+                text: task.text,
+                note: title,
+                due: task.due,
+            });
+        }
+
+        let selected = rows
+            .iter()
+            .position(|row| matches!(row, AgendaRow::Task { .. }))
+            .unwrap_or(0);
+        let mut list_state = ListState::default();
+        list_state.select(Some(selected));
+
+        Self {
+            rows,
+            today,
+            selected,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(next) = (self.selected + 1..self.rows.len()).find(|&i| self.is_task(i)) {
+            self.select(next);
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(prev) = (0..self.selected).rev().find(|&i| self.is_task(i)) {
+            self.select(prev);
+        }
+    }
+
+    /// The note and line of the selected task.
+    pub fn selected_task(&self) -> Option<(&PathBuf, usize)> {
+        match self.rows.get(self.selected)? {
+            AgendaRow::Task { path, line, .. } => Some((path, *line)),
+            AgendaRow::Header(_) => None,
+        }
+    }
+
+    fn is_task(&self, index: usize) -> bool {
+        matches!(self.rows.get(index), Some(AgendaRow::Task { .. }))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.list_state.select(Some(index));
+    }
+}
+
+fn group_label(due: NaiveDate, today: NaiveDate) -> &'static str {
+    match (due - today).num_days() {
+        ..0 => "Overdue",
+        0 => "Today",
+        1 => "Tomorrow",
+        2..=7 => "Next 7 days",
+        _ => "Later",
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &AgendaState, t: &Theme) {
+    let popup_width = 70u16.min(area.width.saturating_sub(4));
+    let popup_height = (area.height * 3 / 4)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Agenda ")
+        .title_bottom(Line::from(Span::styled(
+            " Enter: open  Esc: close ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    if state.rows.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            "No open tasks with a due date",
+            Style::default().fg(t.empty_hint),
+        )))
+        .block(block);
+        frame.render_widget(empty, popup_area);
+        return;
+    }
+
+    let inner_width = popup_width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = state
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            AgendaRow::Header(label) => ListItem::new(Line::from(Span::styled(
+                label.clone(),
+                Style::default().fg(t.aqua).add_modifier(Modifier::BOLD),
+            ))),
+            AgendaRow::Task {
+                text, note, due, ..
+            } => {
+                let style = if i == state.selected {
+                    t.selection_style()
+                } else {
+                    Style::default().fg(t.due_color(*due, state.today).unwrap_or(t.fg1))
+                };
+                let date = due.format("%b %d").to_string();
+                let note = format!("{} ", note);
+                let text: String = text
+                    .chars()
+                    .take(inner_width.saturating_sub(note.chars().count() + date.len() + 4))
+                    .collect();
+                let name = format!("  {} ", text);
+                let pad = inner_width
+                    .saturating_sub(name.chars().count() + note.chars().count() + date.len() + 1);
+                ListItem::new(Line::from(vec![
+                    Span::styled(name, style),
+                    Span::styled(" ".repeat(pad), style),
+                    Span::styled(
+                        note,
+                        Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+                    ),
+                    Span::styled(format!("{} ", date), Style::default().fg(t.fg4)),
+                ]))
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(block);
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
@@ -5,15 +5,43 @@ use ratatui::{
     layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{List, ListItem, ListState},
 };
 
 use crate::app::App;
 use crate::ui::layout::Focus;
-use crate::ui::theme;
+
+/// Which links the pane lists for the selected note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinksTab {
+    Backlinks,
+    Outgoing,
+}
+
+/// Order of the backlinks tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacklinkSort {
+    Name,
+    /// Most recently modified source first
+    Recent,
+    /// Source with the most links to the note first
+    Count,
+}
+
+impl BacklinkSort {
+    pub fn label(self) -> &'static str {
+        match self {
+            BacklinkSort::Name => "name",
+            BacklinkSort::Recent => "recent",
+            BacklinkSort::Count => "count",
+        }
+    }
+}
 
 pub struct BacklinksState {
     pub selected: usize,
+    pub tab: LinksTab,
+    pub sort: BacklinkSort,
     list_state: ListState,
 }
 
@@ -24,6 +52,8 @@ impl BacklinksState {
 
         Self {
             selected: 0,
+            tab: LinksTab::Backlinks,
+            sort: BacklinkSort::Name,
             list_state,
         }
     }
@@ -47,55 +77,89 @@ impl BacklinksState {
         self.list_state.select(Some(0));
     }
 
-    pub fn selected_path<'a>(&self, backlinks: &'a [PathBuf]) -> Option<&'a PathBuf> {
-        backlinks.get(self.selected)
+    /// Index of the backlink drawn on the given row of the list.
+    pub fn index_at(&self, row: usize) -> usize {
+        self.list_state.offset() + row
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.list_state.select(Some(index));
+    }
+
+    /// Switches between backlinks and outgoing links.
+    pub fn toggle_tab(&mut self) {
+        self.tab = match self.tab {
+            LinksTab::Backlinks => LinksTab::Outgoing,
+            LinksTab::Outgoing => LinksTab::Backlinks,
+        };
+        self.reset();
+    }
+
+    /// Moves to the next backlink order.
+    pub fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            BacklinkSort::Name => BacklinkSort::Recent,
+            BacklinkSort::Recent => BacklinkSort::Count,
+            BacklinkSort::Count => BacklinkSort::Name,
+        };
+        self.reset();
+    }
+
+    /// The note the selected entry points at, unless its link is broken.
+    pub fn selected_path<'a>(&self, links: &'a [(String, Option<PathBuf>)]) -> Option<&'a PathBuf> {
+        links.get(self.selected)?.1.as_ref()
     }
 }
 
-pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
     let t = &app.theme;
     let is_focused = app.focus == Focus::Backlinks;
 
-    let backlink_paths = if let Some(note) = app.selected_note() {
-        app.index.get_backlinks(&note.path)
-    } else {
-        Vec::new()
+    let links = app.linked_notes();
+    let (name, other) = match app.backlinks_state.tab {
+        LinksTab::Backlinks => ("Backlinks", "outgoing"),
+        LinksTab::Outgoing => ("Outgoing", "backlinks"),
     };
 
-    let title = format!(" {}Backlinks ({}) ", theme::ICON_LINK, backlink_paths.len());
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::ALL)
-        .border_type(theme::border_type())
-        .border_style(t.border_style(is_focused));
+    let title = format!(" {}{} ({}) ", t.icons.link, name, links.len());
+    let mut block = t.pane_block(Some(title), app.config.ui.pane("backlinks"), is_focused);
+    if is_focused {
+        let hint = match app.backlinks_state.tab {
+            LinksTab::Backlinks => {
+                format!(" o: {}  s: {} ", other, app.backlinks_state.sort.label())
+            }
+            LinksTab::Outgoing => format!(" o: {} ", other),
+        };
+        block = block.title_bottom(Line::from(Span::styled(
+            hint,
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )));
+    }
 
-    let items: Vec<ListItem> = if backlink_paths.is_empty() {
+    let items: Vec<ListItem> = if links.is_empty() {
         vec![ListItem::new(Line::from(Span::styled(
-            "   No backlinks",
+            format!("   No {}", name.to_lowercase()),
             Style::default().fg(t.empty_hint),
         )))]
     } else {
-        backlink_paths
+        links
             .iter()
             .enumerate()
-            .map(|(i, path)| {
-                let name = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Unknown");
-
+            .map(|(i, (name, path))| {
                 let style = if is_focused && i == app.backlinks_state.selected {
                     t.selection_style()
+                } else if path.is_none() {
+                    Style::default()
+                        .fg(t.link_broken)
+                        .add_modifier(Modifier::CROSSED_OUT)
                 } else {
                     Style::default().fg(t.backlink_fg)
                 };
 
                 ListItem::new(Line::from(vec![
-                    Span::styled(
-                        format!("  {} ", theme::ICON_LINK),
-                        Style::default().fg(t.bg4),
-                    ),
-                    Span::styled(name, style),
+                    Span::styled(format!("  {} ", t.icons.link), Style::default().fg(t.bg4)),
+                    Span::styled(name.as_str(), style),
                 ]))
             })
             .collect()
@@ -109,4 +173,5 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
 
     let mut state = app.backlinks_state.list_state.clone();
     frame.render_stateful_widget(list, area, &mut state);
+    app.backlinks_state.list_state = state;
 }
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::App;
+
+const SEPARATOR: &str = " › ";
+
+/// The folders above the selected note, from the vault itself down, as
+/// labels and vault-relative paths, followed by the note's title.
+fn segments(app: &App) -> Option<(Vec<(String, PathBuf)>, String)> {
+    let note = app.selected_note()?;
+    let vault = app
+        .vault
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "vault".to_string());
+    let mut folders = vec![(vault, PathBuf::new())];
+    let mut dirs: Vec<&Path> = note
+        .path
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect();
+    dirs.reverse();
+    folders.extend(dirs.into_iter().map(|dir| {
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        (name, dir.to_path_buf())
+    }));
+    Some((folders, note.title.clone()))
+}
+
+/// One row above the preview with the folders the note is in; clicking
+/// one, or `gu`, selects it in the browser.
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let t = &app.theme;
+    let Some((folders, title)) = segments(app) else {
+        return;
+    };
+
+    let mut spans = vec![Span::raw(" ")];
+    for (name, _) in &folders {
+        spans.push(Span::styled(name.clone(), Style::default().fg(t.dir_fg)));
+        spans.push(Span::styled(SEPARATOR, Style::default().fg(t.fg4)));
+    }
+    spans.push(Span::styled(
+        title,
+        Style::default().fg(t.fg1).add_modifier(Modifier::BOLD),
+    ));
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// The folder drawn at column `x` of the breadcrumb row, if any.
+pub fn folder_at(app: &App, area: Rect, x: u16) -> Option<PathBuf> {
+    let (folders, _) = segments(app)?;
+    let mut start = area.x as usize + 1;
+    for (name, path) in folders {
+        let end = start + name.width();
+        if (start..end).contains(&(x as usize)) {
+            return Some(path);
+        }
+        start = end + SEPARATOR.width();
+    }
+    None
+}
@@ -1,15 +1,33 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{List, ListItem, ListState},
 };
 
+use std::time::SystemTime;
+
 use crate::app::App;
-use crate::core::{TreeEntry, Vault};
+use crate::core::{self, TreeEntry, TreeSort, Vault, text};
+use crate::ui::image;
 use crate::ui::layout::Focus;
-use crate::ui::theme;
+
+/// Narrows the tree to entries whose names fuzzy-match `query`, keeping the
+/// folders above them. Keys go to the query while `typing` is set.
+#[derive(Debug, Default)]
+pub struct BrowserFilter {
+    pub query: String,
+    pub typing: bool,
+}
+
+impl BrowserFilter {
+    /// Whether an entry called `name` matches, ignoring case and accents.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.strip_suffix(".md").unwrap_or(name);
+        core::fuzzy_match(&text::fold(&self.query), &text::fold(name))
+    }
+}
 
 pub struct BrowserState {
     pub selected: usize,
@@ -56,42 +74,67 @@ impl BrowserState {
         self.list_state.select(Some(index));
     }
 
+    /// Index of the entry drawn on the given row of the list.
+    pub fn index_at(&self, row: usize) -> usize {
+        self.list_state.offset() + row
+    }
+
     pub fn selected_entry<'a>(&self, entries: &'a [&TreeEntry]) -> Option<&'a TreeEntry> {
         entries.get(self.selected).copied()
     }
 }
 
-pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+    // Inner height, used to page through the tree with Alt+PageUp/PageDown
+    app.browser_area_height = area.height.saturating_sub(2);
     let t = &app.theme;
     let is_focused = app.focus == Focus::Browser;
 
+    // Name order is the default, so only other orders are called out
+    let sort = match app.vault.sort() {
+        TreeSort::Name => String::new(),
+        sort => format!("by {} ", sort.label()),
+    };
     let title = if let Some(ref tag) = app.active_tag_filter {
-        format!(" Notes [{}#{}] ", theme::ICON_TAG, tag)
+        format!(" Notes [{}#{}] {}", t.icons.tag, tag, sort)
     } else {
-        " Notes ".to_string()
+        format!(" Notes {}", sort)
     };
 
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::ALL)
-        .border_type(theme::border_type())
-        .border_style(t.border_style(is_focused));
+    let mut block = t.pane_block(Some(title), app.config.ui.pane("browser"), is_focused);
+    if let Some(ref filter) = app.browser_filter {
+        let cursor = if filter.typing { "▏" } else { "" };
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {}{}{} ", t.icons.search, filter.query, cursor),
+            Style::default().fg(t.fg1),
+        )));
+    }
 
+    let width = block.inner(area).width as usize;
     let visible = app.filtered_visible_entries();
+    let now = SystemTime::now();
 
     let items: Vec<ListItem> = visible
         .iter()
         .enumerate()
         .map(|(i, entry)| {
             let indent = "  ".repeat(entry.depth);
-            let icon = if entry.is_dir {
+            let icon = if entry.is_dir && entry.is_virtual {
+                t.icons.smart_folder
+            } else if entry.is_dir {
                 if entry.expanded {
-                    theme::ICON_FOLDER_OPEN
+                    t.icons.folder_open
                 } else {
-                    theme::ICON_FOLDER_CLOSED
+                    t.icons.folder_closed
                 }
+            } else if !entry.is_attachment() {
+                t.icons.file
+            } else if image::is_image(&entry.path) {
+                t.icons.image
+            } else if entry.path.extension().is_some_and(|e| e == "pdf") {
+                t.icons.pdf
             } else {
-                theme::ICON_FILE
+                t.icons.attachment
             };
 
             let name = if entry.is_dir {
@@ -103,17 +146,53 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
 
             let style = if i == app.browser_state.selected {
                 t.selection_style()
+            } else if entry.is_dir && entry.is_virtual {
+                Style::default().fg(t.dir_fg).add_modifier(Modifier::ITALIC)
             } else if entry.is_dir {
                 Style::default().fg(t.dir_fg)
+            } else if entry.is_attachment() {
+                Style::default().fg(t.fg4)
             } else {
                 Style::default().fg(t.file_fg)
             };
 
-            let line = Line::from(vec![
+            let mut spans = vec![
                 Span::raw(indent),
                 Span::styled(icon, style),
                 Span::styled(name, style),
-            ]);
+            ];
+            if let Some(progress) = app.index.task_progress(&entry.path) {
+                spans.push(Span::styled(
+                    format!(" {}/{}", progress.0, progress.1),
+                    t.task_progress_style(progress, i == app.browser_state.selected),
+                ));
+            }
+            let mut line = Line::from(spans);
+
+            // Right-aligned and dimmed, and only where there's room for it
+            let detail = if !app.config.ui.show_tree_details {
+                None
+            } else if entry.is_dir && entry.is_virtual {
+                Some(smart_folder_size(&app.smart_folders, &entry.path).to_string())
+            } else if entry.is_dir {
+                Some(app.index.note_count(&entry.path).to_string())
+            } else {
+                app.vault
+                    .get_note(&entry.path)
+                    .map(|note| age(now, note.modified))
+            };
+            if let Some(detail) = detail {
+                let gap = width.saturating_sub(line.width() + detail.chars().count());
+                if gap > 0 {
+                    let style = if i == app.browser_state.selected {
+                        style
+                    } else {
+                        Style::default().fg(t.fg4)
+                    };
+                    line.push_span(Span::raw(" ".repeat(gap)));
+                    line.push_span(Span::styled(detail, style));
+                }
+            }
 
             ListItem::new(line)
         })
@@ -123,6 +202,36 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         .block(block)
         .highlight_style(t.selection_style());
 
+    // Keep the scroll offset so clicks can be mapped back to entries
     let mut state = app.browser_state.list_state.clone();
     frame.render_stateful_widget(list, area, &mut state);
+    app.browser_state.list_state = state;
+}
+
+/// How many notes the smart folder called `name` lists.
+fn smart_folder_size(entries: &[TreeEntry], name: &std::path::Path) -> usize {
+    entries
+        .iter()
+        .skip_while(|entry| !(entry.is_dir && entry.path == name))
+        .skip(1)
+        .take_while(|entry| !entry.is_dir)
+        .count()
+}
+
+/// How long ago `modified` was, as compactly as the tree needs: `now`, `5m`,
+/// `3h`, `2d`, `3w`, `4mo` or `2y`.
+fn age(now: SystemTime, modified: SystemTime) -> String {
+    let secs = now
+        .duration_since(modified)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    match secs {
+        0..60 => "now".to_string(),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        86_400..604_800 => format!("{}d", secs / 86_400),
+        604_800..2_592_000 => format!("{}w", secs / 604_800),
+        2_592_000..31_536_000 => format!("{}mo", secs / 2_592_000),
+        _ => format!("{}y", secs / 31_536_000),
+    }
 }
@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::ui::theme::Theme;
+
+const CHANGELOG: &str = include_str!("../../CHANGELOG.md");
+
+/// Release notes shown once after an upgrade, together with any config
+/// migrations that ran at startup.
+pub struct ChangelogState {
+    pub version: String,
+    pub releases: Vec<(String, Vec<String>)>, // (version, entries), newest first
+    pub migrations: Vec<String>,
+    pub backup: Option<PathBuf>,
+    pub scroll: u16,
+}
+
+impl ChangelogState {
+    /// Notes for every release after `previous` up to the running version.
+    /// Without a previous version only the running release is listed.
+    pub fn new(previous: Option<&str>, migrations: Vec<String>, backup: Option<PathBuf>) -> Self {
+        let version = env!("CARGO_PKG_VERSION").to_string();
+        let current = parse_version(&version);
+        let since = previous.map(parse_version);
+
+        let releases = releases()
+            .into_iter()
+            .filter(|(v, _)| {
+                let v = parse_version(v);
+                match &since {
+                    Some(since) => v > *since && v <= current,
+                    None => v == current,
+                }
+            })
+            .collect();
+
+        Self {
+            version,
+            releases,
+            migrations,
+            backup,
+            scroll: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.releases.is_empty() && self.migrations.is_empty()
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+/// Sections of the bundled changelog as `(version, bullet entries)`.
+fn releases() -> Vec<(String, Vec<String>)> {
+    let mut releases: Vec<(String, Vec<String>)> = Vec::new();
+
+    for line in CHANGELOG.lines() {
+        if let Some(version) = line.strip_prefix("## ") {
+            releases.push((version.trim().to_string(), Vec::new()));
+        } else if let Some(entry) = line.strip_prefix("- ")
+            && let Some((_, entries)) = releases.last_mut()
+        {
+            entries.push(entry.trim().to_string());
+        }
+    }
+
+    releases
+}
+
+/// Numeric version components, so `0.10.0` sorts after `0.9.1`.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(0))
+        .collect()
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &ChangelogState, t: &Theme) {
+    let popup_width = 72u16.min(area.width.saturating_sub(4));
+    let popup_height = (area.height * 3 / 4)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" What's new in tui-jot {} ", state.version))
+        .title_bottom(Line::from(Span::styled(
+            " j/k: scroll  Esc: dismiss ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let heading = Style::default().fg(t.yellow).add_modifier(Modifier::BOLD);
+    let bullet = Style::default().fg(t.fg4);
+    let text = Style::default().fg(t.fg1);
+    let code = Style::default().fg(t.green);
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if !state.migrations.is_empty() {
+        lines.push(Line::from(Span::styled("Config updated", heading)));
+        for change in &state.migrations {
+            let mut spans = vec![Span::styled("  • ", bullet)];
+            spans.extend(entry_spans(change, text, code));
+            lines.push(Line::from(spans));
+        }
+        if let Some(ref backup) = state.backup {
+            lines.push(Line::from(Span::styled(
+                format!("  Previous config saved to {}", backup.display()),
+                Style::default().fg(t.empty_hint),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+
+    for (version, entries) in &state.releases {
+        lines.push(Line::from(Span::styled(version.clone(), heading)));
+        for entry in entries {
+            let mut spans = vec![Span::styled("  • ", bullet)];
+            spans.extend(entry_spans(entry, text, code));
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((state.scroll, 0));
+    frame.render_widget(paragraph, popup_area);
+}
+
+/// Splits an entry on backticks so `code` spans stand out.
+fn entry_spans(entry: &str, text: Style, code: Style) -> Vec<Span<'static>> {
+    entry
+        .split('`')
+        .enumerate()
+        .filter(|(_, part)| !part.is_empty())
+        .map(|(i, part)| {
+            let style = if i % 2 == 1 { code } else { text };
+            Span::styled(part.to_string(), style)
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+use crate::core::{Vault, export};
+use crate::ui::theme::Theme;
+
+/// Ways to refer to the selected entry, or its text, each ready to go on the
+/// clipboard.
+pub struct CopyMenuState {
+    pub choices: Vec<(&'static str, String)>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl CopyMenuState {
+    /// Choices for the entry at `path`, relative to the vault root. Notes get
+    /// a wiki-link that resolves back to them and their whole text; other
+    /// entries only paths.
+    pub fn new(vault: &Vault, path: &Path) -> Self {
+        let mut choices = Vec::new();
+        if let Some(note) = vault.get_note(path) {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // Links resolve by file name, so a differing title is display text
+            let link = if note.title.eq_ignore_ascii_case(&stem) {
+                format!("[[{}]]", stem)
+            } else {
+                format!("[[{}|{}]]", stem, note.title)
+            };
+            choices.push(("Wiki-link", link));
+        }
+        choices.push(("Path", path.display().to_string()));
+        choices.push(("Full path", vault.root.join(path).display().to_string()));
+        if let Some(note) = vault.get_note(path) {
+            choices.extend(text_choices(&note.content()));
+        }
+        Self::with_choices(choices)
+    }
+
+    /// Choices for a stretch of note text, such as a visual selection.
+    pub fn for_text(text: &str) -> Self {
+        Self::with_choices(text_choices(text).to_vec())
+    }
+
+    fn with_choices(choices: Vec<(&'static str, String)>) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        Self {
+            choices,
+            selected: 0,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.choices.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.choices
+            .get(self.selected)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// What the status bar says was copied: the text itself when it is one
+    /// line, otherwise the kind of copy and its length.
+    pub fn copied_message(&self) -> Option<String> {
+        let (label, text) = self.choices.get(self.selected)?;
+        Some(match text.lines().count() {
+            0 | 1 => format!("Copied {}", text),
+            n => format!("Copied {} ({} lines)", label.to_lowercase(), n),
+        })
+    }
+}
+
+/// Markdown as written and with its markup stripped.
+fn text_choices(markdown: &str) -> [(&'static str, String); 2] {
+    [
+        ("Markdown", markdown.to_string()),
+        ("Plain text", export::to_plain_text(markdown)),
+    ]
+}
+
+/// The first line of `text`, marked as cut short when there is more.
+fn summary(text: &str) -> String {
+    let mut lines = text.lines();
+    let first = lines.next().unwrap_or_default();
+    if lines.next().is_some() {
+        format!("{}…", first)
+    } else {
+        first.to_string()
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &CopyMenuState, t: &Theme) {
+    let longest = state
+        .choices
+        .iter()
+        .map(|(_, text)| summary(text).chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let popup_width = (longest + 16).clamp(36, area.width.saturating_sub(4));
+    let popup_height = (state.choices.len() as u16 + 2).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Copy ")
+        .title_bottom(Line::from(Span::styled(
+            " Enter: copy  Esc: cancel ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let items: Vec<ListItem> = state
+        .choices
+        .iter()
+        .map(|(label, text)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("  {:<11}", label), Style::default().fg(t.fg4)),
+                Span::styled(summary(text), Style::default().fg(t.fg1)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(t.selection_style());
+
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
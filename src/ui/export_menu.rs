@@ -0,0 +1,137 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::core::export::ExportFormat;
+use crate::ui::theme::Theme;
+
+/// Which notes an export applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    CurrentNote,
+    VisibleNotes, // Every note shown in the browser, honouring the tag filter
+}
+
+pub struct ExportMenuState {
+    pub scope: ExportScope,
+    pub visible_count: usize,
+    pub converter: String,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl ExportMenuState {
+    pub fn new(visible_count: usize, converter: String) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        Self {
+            scope: ExportScope::CurrentNote,
+            visible_count,
+            converter,
+            selected: 0,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected < ExportFormat::ALL.len() - 1 {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn toggle_scope(&mut self) {
+        self.scope = match self.scope {
+            ExportScope::CurrentNote => ExportScope::VisibleNotes,
+            ExportScope::VisibleNotes => ExportScope::CurrentNote,
+        };
+    }
+
+    pub fn selected_format(&self) -> ExportFormat {
+        ExportFormat::ALL[self.selected]
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &ExportMenuState, t: &Theme) {
+    let popup_width = 44u16.min(area.width.saturating_sub(4));
+    let popup_height = (ExportFormat::ALL.len() as u16 + 6).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Export ")
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Scope
+            Constraint::Min(0),    // Formats
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+    let scope = match state.scope {
+        ExportScope::CurrentNote => "current note".to_string(),
+        ExportScope::VisibleNotes => format!(
+            "{} visible note{}",
+            state.visible_count,
+            if state.visible_count == 1 { "" } else { "s" }
+        ),
+    };
+    let scope_line = Line::from(vec![
+        Span::styled("Scope: ", Style::default().fg(t.fg4)),
+        Span::styled(scope, Style::default().fg(t.yellow)),
+    ]);
+    frame.render_widget(Paragraph::new(scope_line), chunks[0]);
+
+    let items: Vec<ListItem> = ExportFormat::ALL
+        .iter()
+        .map(|format| {
+            let via = if format.uses_converter() {
+                format!("  via {}", state.converter)
+            } else {
+                "  built-in".to_string()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("  {:<12}", format.label()),
+                    Style::default().fg(t.fg1),
+                ),
+                Span::styled(via, Style::default().fg(t.fg4)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(t.selection_style());
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, chunks[1], &mut list_state);
+
+    let hints = Line::from(Span::styled(
+        "Enter: export  Tab: scope  Esc: cancel",
+        Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+    ));
+    frame.render_widget(Paragraph::new(hints), chunks[2]);
+}
@@ -8,12 +8,11 @@ use ratatui::{
 use ropey::Rope;
 
 use super::theme::Theme;
+use crate::core::text;
 
 #[derive(Debug, Clone)]
 pub struct FindMatch {
     pub line: usize,
-    pub col_start: usize,
-    pub col_end: usize,
 }
 
 pub struct FindInNoteState {
@@ -42,7 +41,7 @@ impl FindInNoteState {
         let query = if self.case_sensitive {
             self.query.clone()
         } else {
-            self.query.to_lowercase()
+            text::fold(&self.query)
         };
 
         for line_idx in 0..content.len_lines() {
@@ -50,19 +49,18 @@ impl FindInNoteState {
             let search_text = if self.case_sensitive {
                 line_text.clone()
             } else {
-                line_text.to_lowercase()
+                text::fold(&line_text)
             };
 
             let mut start = 0;
             while let Some(pos) = search_text[start..].find(&query) {
-                let col_start = start + pos;
-                let col_end = col_start + query.len();
-                self.matches.push(FindMatch {
-                    line: line_idx,
-                    col_start,
-                    col_end,
-                });
-                start = col_start + 1;
+                self.matches.push(FindMatch { line: line_idx });
+                // Step past the whole first character, which may be multibyte
+                let first = search_text[start + pos..]
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+                start += pos + first;
             }
         }
 
@@ -102,11 +100,7 @@ impl FindInNoteState {
         let mut best = 0;
         let mut best_dist = usize::MAX;
         for (i, m) in self.matches.iter().enumerate() {
-            let dist = if m.line >= line {
-                m.line - line
-            } else {
-                line - m.line
-            };
+            let dist = m.line.abs_diff(line);
             if dist < best_dist {
                 best_dist = dist;
                 best = i;
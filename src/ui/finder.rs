@@ -1,69 +1,164 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap},
 };
 
-use crate::core::{self, Vault};
-use crate::ui::theme::{self, Theme};
+use crate::config::AppState;
+use crate::core::{Index, Note, Vault, frontmatter, fuzzy_match, text};
+use crate::ui::source;
+use crate::ui::theme::Theme;
+
+pub struct FinderResult {
+    pub path: PathBuf,
+    /// The note's title, or the directory's name
+    pub title: String,
+    /// The alias the note matched by, if not its title
+    pub alias: Option<String>,
+    pub is_dir: bool,
+}
 
 pub struct FinderState {
     pub query: String,
-    pub results: Vec<(PathBuf, String)>, // (path, title)
+    pub results: Vec<FinderResult>,
     pub selected: usize,
+    /// How often and recently each note was opened; higher ranks first
+    frecency: HashMap<PathBuf, u32>,
     list_state: ListState,
 }
 
 impl FinderState {
-    pub fn new(vault: &Vault) -> Self {
+    pub fn new(vault: &Vault, state: &AppState) -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
 
-        let mut results: Vec<(PathBuf, String)> = vault
+        let now = chrono::Local::now().timestamp();
+        let frecency: HashMap<PathBuf, u32> = vault
+            .notes
+            .keys()
+            .filter_map(|path| {
+                let visit = state
+                    .visits
+                    .get(&*vault.root.join(path).to_string_lossy())?;
+                Some((path.clone(), visit.frecency(now)))
+            })
+            .collect();
+
+        let mut results: Vec<FinderResult> = vault
             .notes
             .iter()
-            .map(|(path, note)| (path.clone(), note.title.clone()))
+            .map(|(path, note)| FinderResult {
+                path: path.clone(),
+                title: note.title.clone(),
+                alias: None,
+                is_dir: false,
+            })
             .collect();
-        results.sort_by(|a, b| a.1.cmp(&b.1));
+        let score = |result: &FinderResult| frecency.get(&result.path).copied().unwrap_or(0);
+        results.sort_by(|a, b| {
+            score(b)
+                .cmp(&score(a))
+                .then_with(|| text::collate(&a.title, &b.title))
+        });
 
         Self {
             query: String::new(),
             results,
             selected: 0,
+            frecency,
             list_state,
         }
     }
 
+    /// Matches notes by title, alias or path, and directories by path.
+    /// Name matches rank above path matches, prefix matches first, and
+    /// within each the most frequently and recently opened notes lead.
     pub fn update_results(&mut self, vault: &Vault) {
         self.results.clear();
         self.selected = 0;
         self.list_state.select(Some(0));
 
-        let query_lower = self.query.to_lowercase();
+        let query = text::fold(&self.query);
+        let path_matches = |path: &Path| {
+            let path = path.with_extension("");
+            fuzzy_match(&query, &text::fold(&path.to_string_lossy()))
+        };
 
+        let mut ranked = Vec::new();
         for (path, note) in &vault.notes {
-            let name_lower = note.title.to_lowercase();
-            if query_lower.is_empty() || core::fuzzy_match(&query_lower, &name_lower) {
-                self.results.push((path.clone(), note.title.clone()));
+            let (alias, rank) = match note.match_name(&query) {
+                Some((name, is_alias)) => {
+                    let prefix = text::fold(name).starts_with(&query);
+                    (
+                        is_alias.then(|| name.to_string()),
+                        if prefix { 0 } else { 1 },
+                    )
+                }
+                None if path_matches(path) => (None, 2),
+                None => continue,
+            };
+            let result = FinderResult {
+                path: path.clone(),
+                title: note.title.clone(),
+                alias,
+                is_dir: false,
+            };
+            ranked.push((rank, result));
+        }
+        if !query.is_empty() {
+            for entry in vault.tree.iter().filter(|e| e.is_dir) {
+                let rank = if text::fold(&entry.name).starts_with(&query) {
+                    0
+                } else if path_matches(&entry.path) {
+                    2
+                } else {
+                    continue;
+                };
+                let result = FinderResult {
+                    path: entry.path.clone(),
+                    title: entry.name.clone(),
+                    alias: None,
+                    is_dir: true,
+                };
+                ranked.push((rank, result));
             }
         }
 
-        // Sort: prefix matches first, then alphabetical
-        self.results.sort_by(|a, b| {
-            let a_starts = a.1.to_lowercase().starts_with(&query_lower);
-            let b_starts = b.1.to_lowercase().starts_with(&query_lower);
-            match (a_starts, b_starts) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.1.cmp(&b.1),
-            }
+        let score = |result: &FinderResult| self.frecency.get(&result.path).copied().unwrap_or(0);
+        ranked.sort_by(|(a_rank, a), (b_rank, b)| {
+            a_rank
+                .cmp(b_rank)
+                .then_with(|| score(b).cmp(&score(a)))
+                .then_with(|| text::collate(&a.title, &b.title))
         });
+        self.results = ranked
+            .into_iter()
+            .take(20)
+            .map(|(_, result)| result)
+            .collect();
+    }
+
+    /// Whether a note is titled or named exactly as the query, ignoring case.
+    pub fn has_exact_match(&self) -> bool {
+        let query = text::fold(self.query.trim());
+        self.results.iter().any(|result| {
+            !result.is_dir
+                && (text::fold(&result.title) == query
+                    || result
+                        .path
+                        .file_stem()
+                        .is_some_and(|stem| text::fold(&stem.to_string_lossy()) == query))
+        })
+    }
 
-        self.results.truncate(20);
+    /// Whether Enter would make a new note rather than open a result.
+    pub fn creates_on_enter(&self) -> bool {
+        !self.query.trim().is_empty() && self.results.is_empty()
     }
 
     pub fn move_down(&mut self) {
@@ -80,14 +175,34 @@ impl FinderState {
         }
     }
 
-    pub fn selected_path(&self) -> Option<&PathBuf> {
-        self.results.get(self.selected).map(|(p, _)| p)
+    pub fn selected_result(&self) -> Option<&FinderResult> {
+        self.results.get(self.selected)
     }
 }
 
-pub fn render(frame: &mut Frame, area: Rect, state: &FinderState, t: &Theme) {
-    let popup_width = 50u16.min(area.width.saturating_sub(4));
-    let popup_height = 16u16.min(area.height.saturating_sub(4));
+/// Width of the result list; wider screens show the highlighted note beside it.
+const LIST_WIDTH: u16 = 50;
+const PREVIEW_MIN_WIDTH: u16 = 30;
+
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    state: &FinderState,
+    vault: &Vault,
+    index: &Index,
+    t: &Theme,
+) {
+    let with_preview = area.width >= LIST_WIDTH + PREVIEW_MIN_WIDTH + 5;
+    let popup_width = if with_preview {
+        (LIST_WIDTH * 2 + 1).min(area.width.saturating_sub(4))
+    } else {
+        LIST_WIDTH.min(area.width.saturating_sub(4))
+    };
+    let popup_height = if with_preview {
+        24u16.min(area.height.saturating_sub(4))
+    } else {
+        16u16.min(area.height.saturating_sub(4))
+    };
 
     let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
     let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
@@ -95,12 +210,23 @@ pub fn render(frame: &mut Frame, area: Rect, state: &FinderState, t: &Theme) {
 
     frame.render_widget(Clear, popup_area);
 
-    let block = Block::default()
-        .title(format!(" {}Find Note ", theme::ICON_SEARCH))
+    let mut block = Block::default()
+        .title(format!(" {}Find Note ", t.icons.search))
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.finder_prompt))
         .style(Style::default().bg(t.bg0));
+    if !state.query.trim().is_empty() && !state.has_exact_match() {
+        let key = if state.creates_on_enter() {
+            "Enter"
+        } else {
+            "Alt+Enter"
+        };
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {}: create \"{}\" ", key, state.query.trim()),
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )));
+    }
 
     let inner = block.inner(popup_area);
     frame.render_widget(block, popup_area);
@@ -131,13 +257,30 @@ pub fn render(frame: &mut Frame, area: Rect, state: &FinderState, t: &Theme) {
     )));
     frame.render_widget(sep, sep_area);
 
-    // Results
+    // Results, with the preview to their right when there is room
+    let list_width = if with_preview {
+        LIST_WIDTH - 2
+    } else {
+        inner.width
+    };
     let results_area = Rect::new(
         inner.x,
         inner.y + 2,
-        inner.width,
+        list_width,
         inner.height.saturating_sub(2),
     );
+    if with_preview {
+        let preview_area = Rect::new(
+            inner.x + list_width,
+            inner.y + 2,
+            inner.width.saturating_sub(list_width),
+            inner.height.saturating_sub(2),
+        );
+        let note = state
+            .selected_result()
+            .and_then(|result| vault.get_note(&result.path));
+        render_preview(frame, preview_area, note, t);
+    }
 
     if state.results.is_empty() {
         let empty = Paragraph::new(Line::from(Span::styled(
@@ -150,24 +293,45 @@ pub fn render(frame: &mut Frame, area: Rect, state: &FinderState, t: &Theme) {
             .results
             .iter()
             .enumerate()
-            .map(|(i, (_path, title))| {
+            .map(|(i, result)| {
                 let style = if i == state.selected {
                     t.selection_style()
                 } else {
                     Style::default().fg(t.fg1)
                 };
+                let dim = Style::default().fg(t.fg4);
 
-                ListItem::new(Line::from(vec![
+                let icon = if result.is_dir {
+                    t.icons.folder_closed
+                } else {
+                    t.icons.file
+                };
+                let mut spans = vec![
                     Span::styled(
-                        format!("  {} ", theme::ICON_FILE),
-                        if i == state.selected {
-                            style
-                        } else {
-                            Style::default().fg(t.fg4)
-                        },
+                        format!("  {} ", icon),
+                        if i == state.selected { style } else { dim },
                     ),
-                    Span::styled(title, style),
-                ]))
+                    Span::styled(&result.title, style),
+                ];
+                if let Some(alias) = &result.alias {
+                    spans.push(Span::styled(
+                        format!("  {}", alias),
+                        dim.add_modifier(Modifier::ITALIC),
+                    ));
+                }
+                if let Some(progress) = index.task_progress(&result.path) {
+                    spans.push(Span::styled(
+                        format!("  {}/{}", progress.0, progress.1),
+                        t.task_progress_style(progress, i == state.selected),
+                    ));
+                }
+                // The folder tells apart notes that share a title
+                if let Some(parent) = result.path.parent()
+                    && !parent.as_os_str().is_empty()
+                {
+                    spans.push(Span::styled(format!("  {}/", parent.display()), dim));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -181,3 +345,29 @@ pub fn render(frame: &mut Frame, area: Rect, state: &FinderState, t: &Theme) {
         frame.render_stateful_widget(list, results_area, &mut list_state);
     }
 }
+
+/// The opening lines of the highlighted note, past any frontmatter, as
+/// markdown source.
+fn render_preview(frame: &mut Frame, area: Rect, note: Option<&Note>, t: &Theme) {
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.bg3))
+        .padding(Padding::horizontal(1));
+    let Some(note) = note else {
+        frame.render_widget(block, area);
+        return;
+    };
+
+    let content = note.content();
+    let body_start = match frontmatter::split(&content) {
+        Some(Ok((yaml, _))) => yaml.lines().count() + 2,
+        _ => 0,
+    };
+    let end = body_start + area.height as usize;
+    let lines = source::highlight(&content, body_start..end, t);
+    let preview = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, area);
+}
@@ -9,8 +9,6 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, canvas::Canvas},
 };
 
-use super::theme;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphMode {
     Local,
@@ -97,7 +95,7 @@ pub fn render(frame: &mut Frame, area: Rect, state: &GraphViewState, t: &crate::
             }
         ))
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.border_overlay));
 
     let inner = block.inner(area);
@@ -0,0 +1,258 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::ui::theme::Theme;
+
+/// Every keybinding by the pane it applies to, as `(keys, action)`. Popups
+/// list their own keys along their bottom border and are left out.
+const KEYBINDINGS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Browser",
+        &[
+            ("j / k", "Move down / up"),
+            ("h / l", "Collapse / expand directory"),
+            ("g / G", "Jump to top / bottom"),
+            ("Enter", "Open note / folder note / toggle directory"),
+            ("a", "Create note or directory here"),
+            ("A", "Create note or directory at the root"),
+            ("d", "Delete note or directory"),
+            ("m", "Move note or directory"),
+            ("r", "Rename note or directory"),
+            ("X", "Archive / restore"),
+            ("Z", "Show archived notes"),
+            ("s", "Sort by name / modified / created / size"),
+            ("f", "Filter by name as you type"),
+            ("t", "Filter by tag"),
+            ("@", "Open / create literature note for citation"),
+            ("E", "Export (HTML, PDF, DOCX, OPML)"),
+            ("y", "Copy wiki-link or path"),
+            ("v / V", "Open note in split / close split"),
+            ("Tab", "Switch to viewer"),
+        ],
+    ),
+    (
+        "Viewer",
+        &[
+            ("j / k", "Move down / up"),
+            ("Ctrl+d / u", "Half page down / up"),
+            ("Ctrl+f / b", "Page down / up"),
+            ("gg / G", "First / last line"),
+            ("{ / }", "Previous / next paragraph"),
+            ("1-9", "Count for the next motion (5j, 3})"),
+            ("Ctrl+n / p", "Next / previous link"),
+            ("Enter", "Follow link on cursor line"),
+            ("y", "Copy wiki-link, path or text"),
+            ("Y", "Copy selection as markdown / plain text"),
+            ("i", "Enter edit mode"),
+            ("r", "Toggle rendered / source view"),
+            ("%", "Show / hide %%comments%%"),
+            ("F", "Follow: keep the end of a growing note in view"),
+            ("E", "Export (HTML, PDF, DOCX, OPML)"),
+            ("gt / gT", "Next / previous tab"),
+            ("gc", "Close tab"),
+            ("gu", "Select note's folder in browser"),
+            ("h / Esc", "Back to browser"),
+            ("Tab", "Switch to browser"),
+        ],
+    ),
+    (
+        "Editor",
+        &[
+            ("Arrows", "Move cursor"),
+            ("Home / End", "Line start / end"),
+            ("Enter", "New line, keeping indentation"),
+            ("Tab", "Expand snippet, next tab stop or indent"),
+            ("Shift+Tab", "Outdent"),
+            ("Ctrl+z / y", "Undo / redo"),
+            ("[[", "Link autocomplete"),
+            ("[@", "Citation autocomplete"),
+            ("Ctrl+]", "Save and follow link"),
+            ("Tab / Shift+Tab", "Table: next / previous cell"),
+            ("Alt+r / R", "Table: add / delete row"),
+            ("Alt+c / C", "Table: add / delete column"),
+            ("Ctrl+s", "Save"),
+            ("Esc", "Leave edit mode (saves)"),
+        ],
+    ),
+    (
+        "Backlinks",
+        &[
+            ("j / k", "Move down / up"),
+            ("Enter", "Open the selected note"),
+            ("o", "Backlinks / outgoing links"),
+            ("s", "Sort backlinks: name / recent / count"),
+            ("h / Esc", "Back to browser"),
+        ],
+    ),
+    (
+        "Global",
+        &[
+            ("/", "Search; Ctrl+s in it saves the query"),
+            ("Ctrl+p", "Find note"),
+            ("Ctrl+e", "Open in external editor"),
+            ("Ctrl+b", "Focus backlinks panel"),
+            ("Alt+b", "Show / hide backlinks panel"),
+            ("Ctrl+o", "Timeline of recent edits"),
+            ("Alt+a", "Agenda of due tasks"),
+            ("Alt+d/w/m", "Today's / week's / month's note"),
+            ("[ / ]", "Previous / next periodic note"),
+            ("Ctrl+l", "Cycle pane layout"),
+            ("Ctrl+w", "Switch split"),
+            ("Alt+z", "Maximize / restore pane"),
+            ("Alt+l", "Save / restore workspace layouts"),
+            ("Alt+s", "Spelling suggestions"),
+            ("Alt+t", "Pick theme"),
+            ("Alt+Left/Right", "Heading level up / down"),
+            ("PgUp / PgDn", "Scroll preview"),
+            ("Alt+PgUp/Dn", "Page through browser"),
+            ("Ctrl+r", "Note git history"),
+            ("Ctrl+t", "Scratch buffer"),
+            ("Ctrl+Shift+K", "Toggle this help"),
+            ("Ctrl+q", "Quit"),
+        ],
+    ),
+];
+
+const KEY_WIDTH: usize = 16;
+
+/// The keybindings overlay, scrolled with j/k and narrowed with `/`.
+pub struct HelpState {
+    pub query: String,
+    /// Typing goes to the query rather than scrolling
+    pub filtering: bool,
+    scroll: u16,
+}
+
+impl HelpState {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            filtering: false,
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_by(&mut self, rows: i32) {
+        self.scroll = (self.scroll as i32 + rows).clamp(0, u16::MAX as i32) as u16;
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = u16::MAX;
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.scroll = 0;
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.scroll = 0;
+    }
+
+    pub fn clear_query(&mut self) {
+        self.query.clear();
+        self.filtering = false;
+        self.scroll = 0;
+    }
+
+    /// Sections with the entries whose keys, action or section name contain
+    /// the query, ignoring case. Empty sections are dropped.
+    fn sections(&self) -> Vec<(&'static str, Vec<(&'static str, &'static str)>)> {
+        let query = self.query.to_lowercase();
+        KEYBINDINGS
+            .iter()
+            .filter_map(|(section, entries)| {
+                let section_matches = section.to_lowercase().contains(&query);
+                let entries: Vec<_> = entries
+                    .iter()
+                    .filter(|(keys, action)| {
+                        section_matches
+                            || keys.to_lowercase().contains(&query)
+                            || action.to_lowercase().contains(&query)
+                    })
+                    .copied()
+                    .collect();
+                (!entries.is_empty()).then_some((*section, entries))
+            })
+            .collect()
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &mut HelpState, t: &Theme) {
+    let popup_width = 64u16.min(area.width.saturating_sub(4));
+    let popup_height = (area.height * 3 / 4)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let hint = if state.filtering {
+        " Enter: keep filter  Esc: clear "
+    } else {
+        " j/k: scroll  /: filter  Esc: close "
+    };
+    let mut block = Block::default()
+        .title(" Keybindings ")
+        .title_bottom(Line::from(Span::styled(
+            hint,
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().fg(t.fg1).bg(t.bg0));
+    if state.filtering || !state.query.is_empty() {
+        block = block.title(Line::from(vec![
+            Span::styled(" /", Style::default().fg(t.yellow)),
+            Span::styled(format!("{} ", state.query), Style::default().fg(t.fg1)),
+        ]));
+    }
+
+    let mut lines = Vec::new();
+    for (i, (section, entries)) in state.sections().into_iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section,
+            Style::default().fg(t.aqua).add_modifier(Modifier::BOLD),
+        )));
+        for (keys, action) in entries {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}", keys, width = KEY_WIDTH),
+                    Style::default().fg(t.yellow),
+                ),
+                Span::styled(action, Style::default().fg(t.fg1)),
+            ]));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No keybindings match",
+            Style::default().fg(t.empty_hint),
+        )));
+    }
+
+    // Keep the last page in view rather than scrolling past the end
+    let visible = popup_height.saturating_sub(2);
+    let max_scroll = (lines.len() as u16).saturating_sub(visible);
+    state.scroll = state.scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((state.scroll, 0));
+    frame.render_widget(paragraph, popup_area);
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::core::git::{self, Revision};
+use crate::ui::theme::Theme;
+
+pub struct HistoryState {
+    pub note_path: PathBuf,
+    pub revisions: Vec<Revision>,
+    pub error: Option<String>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+/// A diff of a note at some revision against its working copy, shown in the viewer pane.
+pub struct DiffView {
+    pub note_path: PathBuf,
+    pub revision: Revision,
+    pub lines: Vec<String>,
+}
+
+impl HistoryState {
+    pub fn new(root: &Path, note_path: PathBuf) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        let (revisions, error) = match git::file_history(root, &note_path) {
+            Ok(revisions) => (revisions, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+
+        Self {
+            note_path,
+            revisions,
+            error,
+            selected: 0,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if !self.revisions.is_empty() && self.selected < self.revisions.len() - 1 {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_revision(&self) -> Option<&Revision> {
+        self.revisions.get(self.selected)
+    }
+}
+
+impl DiffView {
+    pub fn load(root: &Path, note_path: PathBuf, revision: Revision) -> Self {
+        let lines = match git::diff_against_working(root, &note_path, &revision) {
+            Ok(diff) if diff.trim().is_empty() => {
+                vec!["No changes since this revision.".to_string()]
+            }
+            Ok(diff) => diff.lines().map(String::from).collect(),
+            Err(e) => vec![e.to_string()],
+        };
+
+        Self {
+            note_path,
+            revision,
+            lines,
+        }
+    }
+
+    pub fn render(&self, t: &Theme) -> Text<'static> {
+        diff_text(&self.lines, t)
+    }
+}
+
+/// Colorizes a unified diff: additions green, removals red, hunk headers aqua.
+pub fn diff_text(lines: &[String], t: &Theme) -> Text<'static> {
+    let lines: Vec<Line<'static>> = lines
+        .iter()
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                Style::default().fg(t.fg4).add_modifier(Modifier::BOLD)
+            } else if line.starts_with('+') {
+                Style::default().fg(t.green)
+            } else if line.starts_with('-') {
+                Style::default().fg(t.red)
+            } else if line.starts_with("@@") {
+                Style::default().fg(t.aqua)
+            } else if line.starts_with("diff ") || line.starts_with("index ") {
+                Style::default().fg(t.fg4)
+            } else {
+                Style::default().fg(t.fg2)
+            };
+            Line::from(Span::styled(line.clone(), style))
+        })
+        .collect();
+
+    Text::from(lines)
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &HistoryState, t: &Theme) {
+    let popup_width = 70u16.min(area.width.saturating_sub(4));
+    let popup_height = 18u16.min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" History: {} ", state.note_path.display()))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    if let Some(ref error) = state.error {
+        let text = Paragraph::new(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(t.red),
+        )))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    if state.revisions.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            "No commits touch this note",
+            Style::default().fg(t.empty_hint),
+        )));
+        frame.render_widget(empty, inner);
+        return;
+    }
+
+    let items: Vec<ListItem> = state
+        .revisions
+        .iter()
+        .enumerate()
+        .map(|(i, rev)| {
+            let style = if i == state.selected {
+                t.selection_style()
+            } else {
+                Style::default().fg(t.fg1)
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", rev.short_hash),
+                    Style::default().fg(t.yellow),
+                ),
+                Span::styled(format!("{} ", rev.date), Style::default().fg(t.fg4)),
+                Span::styled(rev.subject.clone(), style),
+                Span::styled(format!("  {}", rev.author), Style::default().fg(t.fg4)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(t.selected_bg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, inner, &mut list_state);
+}
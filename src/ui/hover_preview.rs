@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::app::App;
+use crate::core::frontmatter;
+use crate::ui::layout::Focus;
+use crate::ui::tag_filter;
+use crate::ui::theme::Theme;
+
+/// How long a tag or backlink has to stay selected before its preview appears.
+const HOVER_DELAY: Duration = Duration::from_millis(300);
+
+const PREVIEW_WIDTH: u16 = 50;
+const PREVIEW_HEIGHT: u16 = 12;
+
+/// Tracks the note under the selection in the tag filter or backlinks pane,
+/// restarting the delay whenever it changes.
+pub struct HoverPreview {
+    target: Option<PathBuf>,
+    since: Instant,
+}
+
+impl HoverPreview {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            since: Instant::now(),
+        }
+    }
+
+    pub fn update(&mut self, target: Option<PathBuf>) {
+        if target != self.target {
+            self.target = target;
+            self.since = Instant::now();
+        }
+    }
+
+    /// The note to preview, once the selection has settled on it.
+    pub fn ready(&self) -> Option<&PathBuf> {
+        self.target
+            .as_ref()
+            .filter(|_| self.since.elapsed() >= HOVER_DELAY)
+    }
+
+    /// How much longer until a pending preview should appear.
+    pub fn waiting(&self) -> Option<Duration> {
+        self.target.as_ref()?;
+        HOVER_DELAY.checked_sub(self.since.elapsed())
+    }
+}
+
+/// The note a preview would show for the current selection: the most recently
+/// modified note carrying the selected tag, or the selected backlink.
+pub fn target(app: &App) -> Option<PathBuf> {
+    if let Some(ref state) = app.tag_filter_state {
+        let tag = state.selected_tag()?;
+        return app
+            .index
+            .notes_with_tag(tag)?
+            .iter()
+            .filter_map(|path| app.vault.notes.get(path))
+            .max_by_key(|note| note.modified)
+            .map(|note| note.path.clone());
+    }
+
+    if app.focus != Focus::Backlinks || app.has_overlay() {
+        return None;
+    }
+    let links = app.linked_notes();
+    app.backlinks_state.selected_path(&links).cloned()
+}
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let Some(path) = app.hover_preview.ready() else {
+        return;
+    };
+    let Some(note) = app.vault.notes.get(path) else {
+        return;
+    };
+    let t = &app.theme;
+
+    let (anchor, footer) = match app.tag_filter_state {
+        Some(ref state) => {
+            let count = state
+                .selected_tag()
+                .and_then(|tag| app.index.notes_with_tag(tag))
+                .map_or(0, |notes| notes.len());
+            let footer = match count {
+                1 => " only note with this tag ".to_string(),
+                n => format!(" latest of {} notes ", n),
+            };
+            (tag_filter::popup_area(frame.area(), state), Some(footer))
+        }
+        None => (app.backlinks_area, None),
+    };
+    let area = place_beside(anchor, frame.area());
+    if area.width < 10 || area.height < 3 {
+        return;
+    }
+
+    frame.render_widget(Clear, area);
+
+    let mut block = Block::default()
+        .title(format!(" {}{} ", t.icons.file, note.title))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+    if let Some(footer) = footer {
+        block = block.title_bottom(Line::from(Span::styled(
+            footer,
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    let content = note.content();
+    let paragraph = Paragraph::new(preview_lines(&content, area.height as usize, t))
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// The opening lines of the note body, skipping frontmatter and leading blanks.
+fn preview_lines<'a>(content: &'a str, max: usize, t: &Theme) -> Vec<Line<'a>> {
+    let body = match frontmatter::split(content) {
+        Some(Ok((_, body))) => body,
+        _ => content,
+    };
+
+    body.lines()
+        .skip_while(|line| line.trim().is_empty())
+        .take(max)
+        .map(|line| {
+            if line.starts_with('#') {
+                Line::from(Span::styled(
+                    line,
+                    Style::default().fg(t.yellow).add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(Span::styled(line, Style::default().fg(t.fg1)))
+            }
+        })
+        .collect()
+}
+
+/// Puts the preview to the right of `anchor`, or to its left when there is no
+/// room, keeping it inside `screen`.
+fn place_beside(anchor: Rect, screen: Rect) -> Rect {
+    let height = PREVIEW_HEIGHT.min(screen.height.saturating_sub(2));
+    let y = anchor
+        .y
+        .min(screen.bottom().saturating_sub(height + 1))
+        .max(screen.y + 1);
+
+    let room_right = screen.right().saturating_sub(anchor.right());
+    let room_left = anchor.x.saturating_sub(screen.x);
+    let (x, width) = if room_right >= PREVIEW_WIDTH || room_right >= room_left {
+        (anchor.right(), PREVIEW_WIDTH.min(room_right))
+    } else {
+        let width = PREVIEW_WIDTH.min(room_left);
+        (anchor.x - width, width)
+    };
+
+    Rect::new(x, y, width, height)
+}
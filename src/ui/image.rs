@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use color_eyre::Result;
+use ratatui::buffer::{Buffer, Cell};
+
+use crate::core::{Vault, attachments};
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Terminal graphics protocol used to draw images in the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// kitty graphics protocol (kitty, Ghostty, WezTerm). PNG only.
+    Kitty,
+    /// iTerm2 inline images (iTerm2, WezTerm, Konsole). Any format the terminal decodes.
+    Iterm2,
+    /// No graphics, sixel-only terminals included: images show as placeholders.
+    None,
+}
+
+impl GraphicsProtocol {
+    /// Picks a protocol from the `ui.image_preview` setting, probing the
+    /// environment when it is `auto`.
+    pub fn detect(setting: &str) -> Self {
+        match setting {
+            "kitty" => return GraphicsProtocol::Kitty,
+            "iterm2" => return GraphicsProtocol::Iterm2,
+            "off" => return GraphicsProtocol::None,
+            _ => {}
+        }
+
+        let env = |key: &str| std::env::var(key).unwrap_or_default();
+        let term = env("TERM");
+        let program = env("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || program == "ghostty"
+        {
+            GraphicsProtocol::Kitty
+        } else if program == "iTerm.app"
+            || program == "WezTerm"
+            || std::env::var_os("KONSOLE_VERSION").is_some()
+        {
+            GraphicsProtocol::Iterm2
+        } else {
+            GraphicsProtocol::None
+        }
+    }
+
+    fn supports(self, path: &Path) -> bool {
+        match self {
+            GraphicsProtocol::Kitty => path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("png")),
+            GraphicsProtocol::Iterm2 => true,
+            GraphicsProtocol::None => false,
+        }
+    }
+}
+
+/// An image drawn at a fixed cell rectangle on screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImagePlacement {
+    pub path: PathBuf,
+    pub x: u16,
+    pub y: u16,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Graphics state for the preview: which images the last frame wants on
+/// screen and which ones were actually written to the terminal.
+pub struct ImagePreview {
+    pub protocol: GraphicsProtocol,
+    pub max_rows: u16,
+    pub placements: Vec<ImagePlacement>,
+    drawn: Vec<ImagePlacement>,
+    resolved: HashMap<(PathBuf, String), Option<PathBuf>>,
+}
+
+impl ImagePreview {
+    pub fn new(protocol: GraphicsProtocol, max_rows: u16) -> Self {
+        Self {
+            protocol,
+            max_rows,
+            placements: Vec::new(),
+            drawn: Vec::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Whether the placements requested by the last frame differ from what is on screen.
+    pub fn is_stale(&self) -> bool {
+        self.placements != self.drawn
+    }
+
+    /// Whether a frame should reserve space and draw `path`.
+    pub fn can_draw(&self, path: &Path) -> bool {
+        self.protocol.supports(path)
+    }
+
+    /// The cell size to draw an image at, no wider than `max_cols` and no
+    /// taller than `max_rows`. Small images are not scaled up, and the aspect
+    /// ratio is kept assuming cells about 8px wide and 16px tall.
+    pub fn fit(&self, path: &Path, max_cols: u16) -> (u16, u16) {
+        let Ok(size) = imagesize::size(path) else {
+            return (max_cols, self.max_rows);
+        };
+        if size.width == 0 || size.height == 0 {
+            return (max_cols, self.max_rows);
+        }
+
+        let mut cols = (size.width / 8).clamp(1, max_cols as usize);
+        let mut rows = (cols * size.height / size.width / 2).max(1);
+        if rows > self.max_rows as usize {
+            rows = self.max_rows as usize;
+            cols = (rows * 2 * size.width / size.height).clamp(1, max_cols as usize);
+        }
+        (cols as u16, rows as u16)
+    }
+
+    /// Finds an embedded image on disk: relative to the note, then to the
+    /// vault root, then anywhere in the vault by file name.
+    pub fn resolve(&mut self, vault: &Vault, note_path: &Path, target: &str) -> Option<PathBuf> {
+        let dir = note_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let key = (dir.clone(), target.to_string());
+        if let Some(found) = self.resolved.get(&key) {
+            return found.clone();
+        }
+
+        let found = [vault.root.join(&dir).join(target), vault.root.join(target)]
+            .into_iter()
+            .find(|p| p.is_file())
+            .or_else(|| {
+                let name = Path::new(target).file_name()?;
+                attachments::list(vault)
+                    .into_iter()
+                    .find(|p| p.file_name() == Some(name))
+                    .map(|p| vault.root.join(p))
+            });
+        self.resolved.insert(key, found.clone());
+        found
+    }
+
+    /// Forgets cached lookups after the vault changes on disk.
+    pub fn clear_cache(&mut self) {
+        self.resolved.clear();
+    }
+
+    /// The cells of `buffer`, the frame just drawn, under iTerm2 images that
+    /// are going away. Those images live in the cells themselves, so only
+    /// writing the text back over them erases them.
+    pub fn uncovered_cells(&self, buffer: &Buffer) -> Vec<(u16, u16, Cell)> {
+        if self.protocol != GraphicsProtocol::Iterm2 || !self.is_stale() {
+            return Vec::new();
+        }
+        self.drawn
+            .iter()
+            .filter(|old| !self.placements.contains(old))
+            .flat_map(|old| {
+                (old.y..old.y + old.rows)
+                    .flat_map(move |y| (old.x..old.x + old.cols).map(move |x| (x, y)))
+            })
+            .filter_map(|(x, y)| Some((x, y, buffer.cell((x, y))?.clone())))
+            .collect()
+    }
+
+    /// Writes the pending placements to the terminal, replacing what was drawn before.
+    pub fn flush(&mut self, out: &mut impl Write) -> Result<()> {
+        if self.protocol == GraphicsProtocol::Kitty && !self.drawn.is_empty() {
+            // Delete every placement this client made
+            write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+        }
+
+        for image in &self.placements {
+            write!(out, "\x1b[{};{}H", image.y + 1, image.x + 1)?;
+            match self.protocol {
+                GraphicsProtocol::Kitty => {
+                    // Let the terminal read the file itself
+                    let path = STANDARD.encode(image.path.to_string_lossy().as_bytes());
+                    write!(
+                        out,
+                        "\x1b_Ga=T,t=f,f=100,c={},r={},C=1,q=2;{}\x1b\\",
+                        image.cols, image.rows, path
+                    )?;
+                }
+                GraphicsProtocol::Iterm2 => {
+                    let Ok(bytes) = std::fs::read(&image.path) else {
+                        continue;
+                    };
+                    write!(
+                        out,
+                        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                        bytes.len(),
+                        image.cols,
+                        image.rows,
+                        STANDARD.encode(&bytes)
+                    )?;
+                }
+                GraphicsProtocol::None => {}
+            }
+        }
+
+        out.flush()?;
+        self.drawn = self.placements.clone();
+        Ok(())
+    }
+}
+
+/// Parses a line that consists only of an image embed, either
+/// `![alt](path.png)` or `![[path.png]]`. Returns `(alt, target)`.
+pub fn image_embed(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim();
+
+    let (alt, target) = if let Some(inner) = trimmed
+        .strip_prefix("![[")
+        .and_then(|s| s.strip_suffix("]]"))
+    {
+        // `![[img.png|300]]` carries a display width, not alt text
+        let target = inner.split('|').next().unwrap_or(inner).trim();
+        (target.to_string(), target.to_string())
+    } else {
+        let rest = trimmed.strip_prefix("![")?;
+        let (alt, rest) = rest.split_once("](")?;
+        let dest = rest.strip_suffix(')')?;
+        let dest = dest.split_once(" \"").map(|(d, _)| d).unwrap_or(dest);
+        let dest = dest.trim().trim_start_matches('<').trim_end_matches('>');
+        (alt.to_string(), dest.to_string())
+    };
+
+    (is_image(Path::new(&target)) && !target.contains("://")).then_some((alt, target))
+}
+
+/// Whether `path` names an image file the preview knows how to embed.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
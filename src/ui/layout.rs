@@ -1,17 +1,28 @@
+use chrono::{DateTime, Local};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
+use serde::{Deserialize, Serialize};
 
-use crate::app::{App, CreateNoteState, DeleteConfirmState};
+use crate::app::{
+    App, CreateNoteState, DeleteConfirmState, DiskChangeState, MoveState, RenameState,
+    SyncConflictState, SyncStatus, TitleSync, TitleSyncState, UnsavedState,
+};
+use crate::config::PaneLayout;
+use crate::ui::theme::Theme;
 
-use super::theme;
-use super::{backlinks, browser, find_in_note, finder, graph_view, search, tag_filter, viewer};
+use super::{
+    agenda, backlinks, breadcrumbs, browser, changelog, copy_menu, export_menu, find_in_note,
+    finder, graph_view, help, history, hover_preview, link_menu, scratch, search, spell, tabs,
+    tag_filter, theme_picker, timeline, toast, viewer, which_key, workspace_picker,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Focus {
     Browser,
     Viewer,
@@ -29,6 +40,9 @@ impl Focus {
 }
 
 pub fn render(frame: &mut Frame, app: &mut App) {
+    let hovered = hover_preview::target(app);
+    app.hover_preview.update(hovered);
+
     // Fill entire screen with theme background
     let bg = Block::default().style(Style::default().bg(app.theme.bg0).fg(app.theme.fg1));
     frame.render_widget(bg, frame.area());
@@ -46,8 +60,16 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     render_main(frame, chunks[1], app);
     render_status_bar(frame, chunks[2], app);
 
-    if app.show_help {
-        render_help(frame, app);
+    if app.pending_g && app.config.ui.which_key {
+        which_key::render(frame, chunks[1], app.pending_count, &app.theme);
+    }
+
+    if let Some(state) = &mut app.help_state {
+        help::render(frame, frame.area(), state, &app.theme);
+    }
+
+    if app.show_scratch {
+        scratch::render(frame, frame.area(), &mut app.scratch, &app.theme);
     }
 
     if let Some(state) = &app.create_note_state {
@@ -58,36 +80,102 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_delete_dialog(frame, state, app);
     }
 
+    if let Some(state) = &app.move_state {
+        render_move_dialog(frame, state, app);
+    }
+
+    if let Some(state) = &app.rename_state {
+        render_rename_dialog(frame, state, app);
+    }
+
+    if let Some(state) = &app.unsaved_state {
+        render_unsaved_dialog(frame, state, app);
+    }
+
+    if let Some(state) = app.disk_change_state.as_mut() {
+        render_disk_change_dialog(frame, state, &app.theme);
+    }
+
+    if let Some(state) = &app.title_sync_state {
+        render_title_sync_dialog(frame, state, app);
+    }
+
+    if let Some(state) = &app.sync_conflict_state {
+        render_sync_conflict_dialog(frame, state, app);
+    }
+
     let t = &app.theme;
 
     if let Some(state) = &app.tag_filter_state {
         tag_filter::render(frame, frame.area(), state, t);
     }
 
+    hover_preview::render(frame, app);
+
     if let Some(state) = &app.search_state {
         search::render(frame, frame.area(), state, t);
     }
 
     if let Some(state) = &app.finder_state {
-        finder::render(frame, frame.area(), state, t);
+        finder::render(frame, frame.area(), state, &app.vault, &app.index, t);
     }
 
     if let Some(state) = &app.graph_view_state {
         graph_view::render(frame, frame.area(), state, t);
     }
 
+    if let Some(state) = &app.history_state {
+        history::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.export_menu_state {
+        export_menu::render(frame, frame.area(), state, t);
+    }
+
     if let Some(state) = &app.find_in_note_state {
         find_in_note::render_find_bar(frame, frame.area(), state, t);
     }
+
+    if let Some(state) = &app.timeline_state {
+        timeline::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.agenda_state {
+        agenda::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.spell_state {
+        spell::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.theme_picker_state {
+        theme_picker::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.workspace_picker_state {
+        workspace_picker::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.copy_menu_state {
+        copy_menu::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.link_menu_state {
+        link_menu::render(frame, frame.area(), state, t);
+    }
+
+    if let Some(state) = &app.changelog_state {
+        changelog::render(frame, frame.area(), state, t);
+    }
+
+    // Toasts sit above everything, clear of the title and status bars
+    toast::render(frame, chunks[1], &app.toasts, t);
 }
 
 fn render_title_bar(frame: &mut Frame, area: Rect, app: &App) {
     let t = &app.theme;
-    let title = Line::from(vec![
-        Span::styled(
-            format!(" {}", theme::ICON_APP),
-            Style::default().fg(t.title_fg),
-        ),
+    let mut title = Line::from(vec![
+        Span::styled(format!(" {}", t.icons.app), Style::default().fg(t.title_fg)),
         Span::styled(
             "tui-jot ",
             Style::default().fg(t.title_fg).add_modifier(Modifier::BOLD),
@@ -99,149 +187,248 @@ fn render_title_bar(frame: &mut Frame, area: Rect, app: &App) {
         ),
     ]);
 
+    if app.maximized {
+        title.push_span(Span::styled(" │ ", Style::default().fg(t.bg3)));
+        title.push_span(Span::styled("maximized", Style::default().fg(t.yellow)));
+    }
+
+    if let Some((loaded, total)) = app.loading {
+        let label = if total == 0 {
+            "Indexing…".to_string()
+        } else {
+            format!("Indexing… {}/{} notes", loaded, total)
+        };
+        title.push_span(Span::styled(" │ ", Style::default().fg(t.bg3)));
+        title.push_span(Span::styled(label, Style::default().fg(t.yellow)));
+    }
+
+    if app.config.sync.enabled {
+        let (label, color) = match &app.sync_state.status {
+            _ if app.config.vault.read_only => ("sync off", t.fg4),
+            SyncStatus::Idle => ("sync pending", t.fg4),
+            SyncStatus::Running => ("syncing…", t.yellow),
+            SyncStatus::Synced => ("synced", t.green),
+            SyncStatus::Failed(_) => ("sync failed", t.red),
+        };
+        title.push_span(Span::styled(" │ ", Style::default().fg(t.bg3)));
+        title.push_span(Span::styled(label, Style::default().fg(color)));
+    }
+
     let title_bar = Paragraph::new(title).style(Style::default().bg(t.title_bar_bg));
 
     frame.render_widget(title_bar, area);
 }
 
+/// Terminals narrower than this stack the panes under `layout = "auto"`.
+const NARROW_WIDTH: u16 = 80;
+
 fn render_main(frame: &mut Frame, area: Rect, app: &mut App) {
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Length(app.config.ui.tree_width),
-            Constraint::Min(0),
-        ])
-        .split(area);
+    let ui = &app.config.ui;
+    let layout = match ui.layout {
+        PaneLayout::Auto if area.width < NARROW_WIDTH => PaneLayout::Stacked,
+        PaneLayout::Auto => PaneLayout::Left,
+        layout => layout,
+    };
 
-    let left_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(70), Constraint::Min(5)])
-        .split(main_chunks[0]);
+    // Split off the side column (or top row) holding browser and backlinks
+    let (side, viewer_area) = match layout {
+        PaneLayout::Stacked => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(35), Constraint::Min(0)])
+                .split(area);
+            (rows[0], rows[1])
+        }
+        PaneLayout::Right => {
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(ui.tree_width)])
+                .split(area);
+            (cols[1], cols[0])
+        }
+        _ => {
+            let cols = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(ui.tree_width), Constraint::Min(0)])
+                .split(area);
+            (cols[0], cols[1])
+        }
+    };
 
-    browser::render(frame, left_chunks[0], app);
-    render_backlinks(frame, left_chunks[1], app);
-    viewer::render(frame, main_chunks[1], app);
+    let (browser_area, backlinks_area) = if ui.show_backlinks {
+        let (direction, constraints) = if layout == PaneLayout::Stacked {
+            (
+                Direction::Horizontal,
+                [Constraint::Percentage(60), Constraint::Percentage(40)],
+            )
+        } else {
+            let height = ui.backlinks_height.clamp(10, 90);
+            (
+                Direction::Vertical,
+                [Constraint::Percentage(100 - height), Constraint::Min(5)],
+            )
+        };
+        let chunks = Layout::default()
+            .direction(direction)
+            .constraints(constraints)
+            .split(side);
+        (chunks[0], chunks[1])
+    } else {
+        (side, Rect::default())
+    };
+
+    // A maximized pane takes the whole content area; the layout itself is
+    // untouched, so toggling back restores it
+    let (browser_area, backlinks_area, viewer_area) = if app.maximized {
+        match app.focus {
+            Focus::Browser => (area, Rect::default(), Rect::default()),
+            Focus::Backlinks => (Rect::default(), area, Rect::default()),
+            Focus::Viewer => (Rect::default(), Rect::default(), area),
+        }
+    } else {
+        (browser_area, backlinks_area, viewer_area)
+    };
+
+    // Open notes get a tab bar above the preview
+    let (tab_area, viewer_area) = if app.tabs.is_empty() || viewer_area.is_empty() {
+        (Rect::default(), viewer_area)
+    } else {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(viewer_area);
+        (rows[0], rows[1])
+    };
+
+    // The folders above the note get a row of their own
+    let (breadcrumb_area, viewer_area) =
+        if !app.config.ui.breadcrumbs || viewer_area.is_empty() || app.selected_note().is_none() {
+            (Rect::default(), viewer_area)
+        } else {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(viewer_area);
+            (rows[0], rows[1])
+        };
+
+    // A pinned note takes half of the preview area
+    let (viewer_area, split_area) = match app.split {
+        Some(ref split) => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(viewer_area);
+            if split.on_left {
+                (halves[1], halves[0])
+            } else {
+                (halves[0], halves[1])
+            }
+        }
+        None => (viewer_area, Rect::default()),
+    };
+
+    // Remembered for mouse hit-testing
+    app.browser_area = browser_area;
+    app.backlinks_area = backlinks_area;
+    app.viewer_area = viewer_area;
+    app.breadcrumb_area = breadcrumb_area;
+
+    if !browser_area.is_empty() {
+        browser::render(frame, browser_area, app);
+    }
+    if app.config.ui.show_backlinks && !backlinks_area.is_empty() {
+        render_backlinks(frame, backlinks_area, app);
+    }
+    if !tab_area.is_empty() {
+        tabs::render(frame, tab_area, app);
+    }
+    if !breadcrumb_area.is_empty() {
+        breadcrumbs::render(frame, breadcrumb_area, app);
+    }
+    if viewer_area.is_empty() {
+        app.image_preview.placements.clear();
+    } else {
+        viewer::render(frame, viewer_area, app);
+    }
+    if app.split.is_some() && !split_area.is_empty() {
+        viewer::render_split(frame, split_area, app);
+    }
 }
 
-fn render_backlinks(frame: &mut Frame, area: Rect, app: &App) {
+fn render_backlinks(frame: &mut Frame, area: Rect, app: &mut App) {
     backlinks::render(frame, area, app);
 }
 
-fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
+fn render_status_bar(frame: &mut Frame, area: Rect, app: &mut App) {
+    let words = match app.status_message {
+        Some(_) => None,
+        None => app.selected_word_count(),
+    };
     let t = &app.theme;
     let help_text = match app.focus {
+        Focus::Browser if app.config.vault.read_only => {
+            "j/k: navigate  Enter: open  t: tags  /: search  Ctrl+q: quit"
+        }
+        Focus::Viewer if app.config.vault.read_only => {
+            "j/k: scroll  h/Esc: back  /: search  Ctrl+p: find  Ctrl+q: quit"
+        }
         Focus::Browser => {
             "j/k: navigate  Enter: open  a: new  d: delete  t: tags  /: search  Ctrl+q: quit"
         }
         Focus::Viewer => "j/k: scroll  h/Esc: back  i: edit  /: search  Ctrl+p: find  Ctrl+q: quit",
-        Focus::Backlinks => "j/k: navigate  Enter: open  Tab: switch pane  Ctrl+q: quit",
+        Focus::Backlinks => {
+            "j/k: navigate  Enter: open  o: outgoing/backlinks  s: sort  Tab: switch pane  Ctrl+q: quit"
+        }
     };
 
-    let note_info = app
-        .selected_note()
-        .map(|n| {
-            format!(
-                "{} │ {} tags │ {} links",
-                n.path.display(),
-                n.tags.len(),
-                n.links.len()
-            )
-        })
-        .unwrap_or_default();
+    let note_info = if let Some(ref message) = app.status_message {
+        message.clone()
+    } else {
+        app.selected_note()
+            .map(|n| {
+                let created: DateTime<Local> = n.created.into();
+                let unsaved = if app.buffer_unsaved() {
+                    format!("{} ", t.icons.unsaved)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{}{} │ created {} │ {} words │ {} tags │ {} links",
+                    unsaved,
+                    n.path.display(),
+                    created.format("%Y-%m-%d"),
+                    words.unwrap_or_default(),
+                    n.tags.len(),
+                    n.links.len()
+                )
+            })
+            .unwrap_or_default()
+    };
 
-    let status = Line::from(vec![
+    let mut spans = Vec::new();
+    if app.config.vault.read_only {
+        spans.push(Span::styled(
+            if app.config.sync.enabled {
+                "READ-ONLY, sync off "
+            } else {
+                "READ-ONLY "
+            },
+            Style::default().fg(t.yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.extend([
         Span::styled(help_text, Style::default().fg(t.fg4)),
         Span::raw("  "),
         Span::styled(note_info, Style::default().fg(t.aqua)),
     ]);
+    let status = Line::from(spans);
 
     let status_bar = Paragraph::new(status).style(Style::default().bg(t.status_bar_bg));
 
     frame.render_widget(status_bar, area);
 }
 
-fn render_help(frame: &mut Frame, app: &App) {
-    let t = &app.theme;
-    let keybindings = vec![
-        (
-            "Navigation",
-            vec![
-                ("j / k", "Move down / up"),
-                ("Enter", "Open note or follow link"),
-                ("Tab", "Switch pane"),
-                ("h / Esc", "Go back"),
-            ],
-        ),
-        (
-            "Browser",
-            vec![
-                ("a", "Create new note"),
-                ("d", "Delete note"),
-                ("t", "Filter by tag"),
-            ],
-        ),
-        (
-            "Viewer",
-            vec![
-                ("i", "Enter edit mode"),
-                ("Ctrl+n / p", "Next / previous link"),
-                ("Ctrl+d / u", "Page down / up"),
-            ],
-        ),
-        (
-            "Global",
-            vec![
-                ("/", "Full-text search"),
-                ("Ctrl+p", "Find note"),
-                ("Ctrl+e", "Open in external editor"),
-                ("Ctrl+b", "Toggle backlinks panel"),
-                ("Ctrl+Shift+K", "Toggle this help"),
-                ("Ctrl+q", "Quit"),
-            ],
-        ),
-    ];
-
-    // Calculate content size
-    let content_height = keybindings
-        .iter()
-        .map(|(_, items)| items.len() + 2) // +2 for header and blank line
-        .sum::<usize>()
-        + 1;
-    let content_width = 42;
-
-    let area = centered_fixed_rect(content_width, content_height as u16, frame.area());
-    frame.render_widget(Clear, area);
-
-    let block = Block::default()
-        .title(" Keybindings ")
-        .borders(Borders::ALL)
-        .border_type(theme::border_type())
-        .border_style(Style::default().fg(t.border_overlay));
-
-    let mut text = Vec::new();
-    for (i, (section, items)) in keybindings.iter().enumerate() {
-        if i > 0 {
-            text.push(Line::from(""));
-        }
-        text.push(Line::from(Span::styled(
-            *section,
-            Style::default().fg(t.aqua).add_modifier(Modifier::BOLD),
-        )));
-        for (key, action) in items {
-            text.push(Line::from(vec![
-                Span::styled(format!("  {:<14}", key), Style::default().fg(t.yellow)),
-                Span::styled(*action, Style::default().fg(t.fg1)),
-            ]));
-        }
-    }
-
-    let help_paragraph = Paragraph::new(text)
-        .block(block)
-        .style(Style::default().fg(t.fg1).bg(t.bg0));
-
-    frame.render_widget(help_paragraph, area);
-}
-
 fn centered_fixed_rect(width: u16, height: u16, r: Rect) -> Rect {
     let popup_width = width.min(r.width.saturating_sub(4));
     let popup_height = height.min(r.height.saturating_sub(2));
@@ -257,10 +444,15 @@ fn render_create_dialog(frame: &mut Frame, state: &CreateNoteState, app: &App) {
     let area = centered_fixed_rect(50, 6, frame.area());
     frame.render_widget(Clear, area);
 
+    let title = if state.body.is_empty() {
+        " New Note "
+    } else {
+        " Save Scratch as Note "
+    };
     let block = Block::default()
-        .title(" New Note ")
+        .title(title)
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.aqua))
         .style(Style::default().bg(t.bg0));
 
@@ -300,6 +492,255 @@ fn render_create_dialog(frame: &mut Frame, state: &CreateNoteState, app: &App) {
     frame.render_widget(paragraph, inner);
 }
 
+fn render_move_dialog(frame: &mut Frame, state: &MoveState, app: &App) {
+    let t = &app.theme;
+    let area = centered_fixed_rect(50, 6, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = if state.is_dir {
+        " Move Directory "
+    } else {
+        " Move Note "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.aqua))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let name = state.path.display().to_string();
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Moving: ", Style::default().fg(t.fg4)),
+            Span::styled(name, Style::default().fg(t.fg2)),
+        ]),
+        Line::from(vec![
+            Span::styled("To folder: ", Style::default().fg(t.yellow)),
+            Span::styled(&state.destination, Style::default().fg(t.fg1)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(t.cursor_blink)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(vec![Span::styled(
+            "Tip: empty = vault root; new folders are created",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )]),
+    ];
+
+    let paragraph = Paragraph::new(text);
+    frame.render_widget(paragraph, inner);
+}
+
+fn render_rename_dialog(frame: &mut Frame, state: &RenameState, app: &App) {
+    let t = &app.theme;
+    let area = centered_fixed_rect(50, 6, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = if state.is_dir {
+        " Rename Directory "
+    } else {
+        " Rename Note "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.aqua))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let name = state.path.display().to_string();
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Renaming: ", Style::default().fg(t.fg4)),
+            Span::styled(name, Style::default().fg(t.fg2)),
+        ]),
+        Line::from(vec![
+            Span::styled("New name: ", Style::default().fg(t.yellow)),
+            Span::styled(&state.name, Style::default().fg(t.fg1)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(t.cursor_blink)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+            Span::styled(
+                if state.is_dir { "" } else { ".md" },
+                Style::default().fg(t.fg4),
+            ),
+        ]),
+        Line::from(vec![Span::styled(
+            "Tip: links to it are updated",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )]),
+    ];
+
+    let paragraph = Paragraph::new(text);
+    frame.render_widget(paragraph, inner);
+}
+
+fn render_title_sync_dialog(frame: &mut Frame, state: &TitleSyncState, app: &App) {
+    let t = &app.theme;
+    let area = centered_fixed_rect(50, 6, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Sync Title ")
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.aqua))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let (question, value) = match &state.offer {
+        TitleSync::FileName(name) => ("Rename the file to ", format!("{}.md", name)),
+        TitleSync::Heading(title) => ("Change the heading to ", format!("# {}", title)),
+    };
+    let text = vec![
+        Line::from(vec![
+            Span::styled(question, Style::default().fg(t.fg1)),
+            Span::styled(value, Style::default().fg(t.yellow)),
+            Span::styled("?", Style::default().fg(t.fg1)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "y",
+                Style::default().fg(t.green).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" = yes    ", Style::default().fg(t.fg3)),
+            Span::styled(
+                "n/Esc",
+                Style::default().fg(t.red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" = keep as is", Style::default().fg(t.fg3)),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, inner);
+}
+
+fn render_unsaved_dialog(frame: &mut Frame, state: &UnsavedState, app: &App) {
+    let t = &app.theme;
+    let area = centered_fixed_rect(50, 6, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Unsaved Changes ")
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.yellow))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(&state.name, Style::default().fg(t.yellow)),
+            Span::styled(" has unsaved changes.", Style::default().fg(t.fg1)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "s",
+                Style::default().fg(t.green).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" = save    ", Style::default().fg(t.fg3)),
+            Span::styled("d", Style::default().fg(t.red).add_modifier(Modifier::BOLD)),
+            Span::styled(" = discard    ", Style::default().fg(t.fg3)),
+            Span::styled(
+                "Esc",
+                Style::default().fg(t.fg1).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" = cancel", Style::default().fg(t.fg3)),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, inner);
+}
+
+/// The overwrite / reload / diff prompt, grown to show the diff once asked.
+fn render_disk_change_dialog(frame: &mut Frame, state: &mut DiskChangeState, t: &Theme) {
+    let key = |key: &'static str, color| {
+        Span::styled(key, Style::default().fg(color).add_modifier(Modifier::BOLD))
+    };
+    let choices = Line::from(vec![
+        key("o", t.red),
+        Span::styled(" = overwrite  ", Style::default().fg(t.fg3)),
+        key("r", t.green),
+        Span::styled(" = reload  ", Style::default().fg(t.fg3)),
+        key("d", t.aqua),
+        Span::styled(
+            if state.diff.is_some() {
+                " = hide diff  "
+            } else {
+                " = diff  "
+            },
+            Style::default().fg(t.fg3),
+        ),
+        key("Esc", t.fg1),
+        Span::styled(" = cancel", Style::default().fg(t.fg3)),
+    ]);
+    let message = Line::from(vec![
+        Span::styled(state.name.as_str(), Style::default().fg(t.yellow)),
+        Span::styled(
+            " was changed by another program since it was opened.",
+            Style::default().fg(t.fg1),
+        ),
+    ]);
+
+    let area = match state.diff {
+        Some(_) => {
+            let area = frame.area();
+            centered_fixed_rect(area.width.saturating_sub(8).min(100), area.height, area)
+        }
+        None => centered_fixed_rect(60, 6, frame.area()),
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Changed on Disk ")
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.red))
+        .style(Style::default().bg(t.bg0));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(diff) = &state.diff else {
+        let text = vec![message, Line::from(""), choices];
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), inner);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    // Keep the last page in view rather than scrolling past the end
+    let max_scroll = (diff.len() as u16).saturating_sub(chunks[0].height);
+    state.scroll = state.scroll.min(max_scroll);
+    frame.render_widget(
+        Paragraph::new(history::diff_text(diff, t)).scroll((state.scroll, 0)),
+        chunks[0],
+    );
+    frame.render_widget(Paragraph::new(choices), chunks[1]);
+}
+
 fn render_delete_dialog(frame: &mut Frame, state: &DeleteConfirmState, app: &App) {
     let t = &app.theme;
     let has_warning = state.is_dir && state.note_count > 0;
@@ -322,7 +763,7 @@ fn render_delete_dialog(frame: &mut Frame, state: &DeleteConfirmState, app: &App
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.red))
         .style(Style::default().bg(t.bg0));
 
@@ -379,3 +820,55 @@ fn render_delete_dialog(frame: &mut Frame, state: &DeleteConfirmState, app: &App
     let paragraph = Paragraph::new(text);
     frame.render_widget(paragraph, inner);
 }
+
+fn render_sync_conflict_dialog(frame: &mut Frame, state: &SyncConflictState, app: &App) {
+    let t = &app.theme;
+    let shown = state.files.len().min(8);
+    let area = centered_fixed_rect(56, shown as u16 + 6, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Sync Conflict ")
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.red))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut text = vec![Line::from(Span::styled(
+        "Pulling from the remote left unmerged notes:",
+        Style::default().fg(t.fg1),
+    ))];
+
+    for file in state.files.iter().take(shown) {
+        text.push(Line::from(Span::styled(
+            format!("  {}", file.display()),
+            Style::default().fg(t.yellow),
+        )));
+    }
+    if state.files.len() > shown {
+        text.push(Line::from(Span::styled(
+            format!("  …and {} more", state.files.len() - shown),
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )));
+    }
+
+    text.push(Line::from(""));
+    text.push(Line::from(vec![
+        Span::styled(
+            "a",
+            Style::default().fg(t.green).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" = abort merge    ", Style::default().fg(t.fg3)),
+        Span::styled(
+            "Esc",
+            Style::default().fg(t.red).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" = resolve manually", Style::default().fg(t.fg3)),
+    ]));
+
+    let paragraph = Paragraph::new(text);
+    frame.render_widget(paragraph, inner);
+}
@@ -0,0 +1,122 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+use crate::core::Vault;
+use crate::ui::theme::Theme;
+use crate::ui::viewer_state::VisibleLink;
+
+/// Picks which of several links on the read cursor's line to follow.
+pub struct LinkMenuState {
+    /// Target and display text of each link, and whether its note exists
+    pub links: Vec<(String, String, bool)>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl LinkMenuState {
+    /// Starts on `selected`, an index into `links`.
+    pub fn new(links: &[&VisibleLink], selected: usize, vault: &Vault) -> Self {
+        let links = links
+            .iter()
+            .map(|link| {
+                let exists = vault.link_exists(&link.target);
+                (link.target.clone(), link.display.clone(), exists)
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(selected));
+
+        Self {
+            links,
+            selected,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.links.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_target(&self) -> Option<&str> {
+        self.links
+            .get(self.selected)
+            .map(|(target, _, _)| target.as_str())
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &LinkMenuState, t: &Theme) {
+    let longest = state
+        .links
+        .iter()
+        .map(|(target, display, _)| target.chars().count() + display.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let popup_width = (longest + 12).clamp(36, area.width.saturating_sub(4));
+    let popup_height = (state.links.len() as u16 + 2).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Follow link ")
+        .title_bottom(Line::from(Span::styled(
+            " 1-9/Enter: follow  Esc: cancel ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let items: Vec<ListItem> = state
+        .links
+        .iter()
+        .enumerate()
+        .map(|(i, (target, display, exists))| {
+            let style = if *exists {
+                Style::default().fg(t.link_fg)
+            } else {
+                Style::default()
+                    .fg(t.link_broken)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            };
+            let mut spans = vec![
+                Span::styled(format!(" {} ", i + 1), Style::default().fg(t.fg4)),
+                Span::styled(format!("[[{}]]", display), style),
+            ];
+            if display != target {
+                spans.push(Span::styled(
+                    format!("  {}", target),
+                    Style::default().fg(t.fg4),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(t.selection_style());
+
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
@@ -0,0 +1,308 @@
+/// Approximates LaTeX math in Unicode: Greek letters and symbols, super- and
+/// subscripts, fractions as `a/b` and roots as `√x`. Returns `None` when
+/// something has no Unicode form, so the caller can show the source instead.
+pub fn to_unicode(latex: &str) -> Option<String> {
+    let chars: Vec<char> = latex.chars().collect();
+    let mut parser = Parser {
+        chars: &chars,
+        pos: 0,
+    };
+    parser.sequence(false)
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    /// Converts up to the end of the input, or past the `}` closing the
+    /// current group.
+    fn sequence(&mut self, in_group: bool) -> Option<String> {
+        let mut out = String::new();
+        while let Some(&c) = self.chars.get(self.pos) {
+            match c {
+                '}' if in_group => {
+                    self.pos += 1;
+                    return Some(out);
+                }
+                '}' => return None,
+                '^' | '_' => {
+                    self.pos += 1;
+                    let arg = self.argument()?;
+                    // Scripts without a Unicode form keep their marker
+                    match script(&arg, c == '^') {
+                        Some(raised) => out.push_str(&raised),
+                        None if arg.chars().count() == 1 => out.push_str(&format!("{}{}", c, arg)),
+                        None => out.push_str(&format!("{}({})", c, arg)),
+                    }
+                }
+                _ => out.push_str(&self.atom()?),
+            }
+        }
+        (!in_group).then_some(out)
+    }
+
+    /// The next token: a `{group}`, a command or a single character.
+    fn atom(&mut self) -> Option<String> {
+        let c = *self.chars.get(self.pos)?;
+        self.pos += 1;
+        match c {
+            '{' => self.sequence(true),
+            '\\' => self.command(),
+            '\'' => Some("′".to_string()),
+            // Alignment points in multi-line blocks
+            '&' => Some(String::new()),
+            _ => Some(c.to_string()),
+        }
+    }
+
+    /// The argument of a command or script, skipping spaces before it.
+    fn argument(&mut self) -> Option<String> {
+        while self.chars.get(self.pos) == Some(&' ') {
+            self.pos += 1;
+        }
+        self.atom()
+    }
+
+    fn command(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            self.pos += 1;
+        }
+
+        // Control symbols such as \{ or \,
+        if self.pos == start {
+            let c = *self.chars.get(self.pos)?;
+            self.pos += 1;
+            return match c {
+                ',' | ':' | ';' | ' ' | '\\' => Some(" ".to_string()),
+                '!' => Some(String::new()),
+                '|' => Some("‖".to_string()),
+                '{' | '}' | '$' | '%' | '#' | '&' | '_' => Some(c.to_string()),
+                _ => None,
+            };
+        }
+
+        let name: String = self.chars[start..self.pos].iter().collect();
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument()?;
+                let denominator = self.argument()?;
+                Some(format!("{}/{}", operand(&numerator), operand(&denominator)))
+            }
+            "sqrt" => Some(format!("√{}", operand(&self.argument()?))),
+            "text" | "textrm" | "mathrm" | "mathit" | "mathbf" | "mathsf" | "boldsymbol"
+            | "operatorname" => self.argument(),
+            "bar" | "overline" | "hat" | "widehat" | "tilde" | "widetilde" | "vec" | "dot"
+            | "ddot" => {
+                let mark = match name.as_str() {
+                    "bar" | "overline" => '\u{304}',
+                    "hat" | "widehat" => '\u{302}',
+                    "tilde" | "widetilde" => '\u{303}',
+                    "vec" => '\u{20d7}',
+                    "dot" => '\u{307}',
+                    _ => '\u{308}',
+                };
+                // A combining mark over each character
+                Some(self.argument()?.chars().flat_map(|c| [c, mark]).collect())
+            }
+            "mathbb" => self.argument()?.chars().map(double_struck).collect(),
+            "left" | "right" | "big" | "Big" | "bigl" | "bigr" | "Bigl" | "Bigr"
+            | "displaystyle" | "limits" => Some(String::new()),
+            "quad" | "qquad" => Some("  ".to_string()),
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan"
+            | "sinh" | "cosh" | "tanh" | "log" | "ln" | "exp" | "lim" | "max" | "min" | "sup"
+            | "inf" | "det" | "dim" | "ker" | "deg" | "gcd" | "arg" | "mod" => Some(name),
+            _ => symbol(&name).map(str::to_string),
+        }
+    }
+}
+
+/// Brackets a fraction or root operand unless it is a single symbol or number.
+fn operand(text: &str) -> String {
+    let simple = text.chars().count() == 1 || text.chars().all(|c| c.is_alphanumeric());
+    if simple {
+        text.to_string()
+    } else {
+        format!("({})", text)
+    }
+}
+
+/// `text` raised or lowered, if every character has a script form.
+fn script(text: &str, superscript: bool) -> Option<String> {
+    let (from, to) = if superscript {
+        (
+            "0123456789+-=()abcdefghijklmnoprstuvwxyzT′*",
+            "⁰¹²³⁴⁵⁶⁷⁸⁹⁺⁻⁼⁽⁾ᵃᵇᶜᵈᵉᶠᵍʰⁱʲᵏˡᵐⁿᵒᵖʳˢᵗᵘᵛʷˣʸᶻᵀ′*",
+        )
+    } else {
+        (
+            "0123456789+-=()aehijklmnoprstuvx",
+            "₀₁₂₃₄₅₆₇₈₉₊₋₌₍₎ₐₑₕᵢⱼₖₗₘₙₒₚᵣₛₜᵤᵥₓ",
+        )
+    };
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            let i = from.chars().position(|f| f == c)?;
+            to.chars().nth(i)
+        })
+        .collect()
+}
+
+fn double_struck(c: char) -> Option<char> {
+    match c {
+        'N' => Some('ℕ'),
+        'Z' => Some('ℤ'),
+        'Q' => Some('ℚ'),
+        'R' => Some('ℝ'),
+        'C' => Some('ℂ'),
+        'P' => Some('ℙ'),
+        'H' => Some('ℍ'),
+        _ => None,
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // Greek
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        // Big operators
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        // Binary operators
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "·",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        // Relations
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        // Arrows
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" => "⟹",
+        "iff" => "⟺",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        // Delimiters
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "vert" => "|",
+        "Vert" => "‖",
+        // Miscellaneous
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "emptyset" | "varnothing" => "∅",
+        "angle" => "∠",
+        "degree" => "°",
+        "prime" => "′",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "therefore" => "∴",
+        "because" => "∵",
+        _ => return None,
+    })
+}
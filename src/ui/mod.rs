@@ -1,21 +1,57 @@
+mod agenda;
 mod backlinks;
+pub mod breadcrumbs;
 mod browser;
+mod changelog;
+mod copy_menu;
+mod export_menu;
 pub mod find_in_note;
 mod finder;
 pub mod graph_view;
+mod help;
+mod history;
+mod hover_preview;
+pub mod image;
 mod layout;
+mod link_menu;
+mod math;
+mod scratch;
 mod search;
+mod source;
+mod spell;
+mod tabs;
 mod tag_filter;
+mod text_preview;
 pub mod theme;
-mod viewer;
+mod theme_picker;
+mod timeline;
+mod toast;
+pub mod viewer;
 mod viewer_state;
+mod which_key;
+mod workspace_picker;
 
-pub use backlinks::BacklinksState;
-pub use browser::BrowserState;
+pub use agenda::AgendaState;
+pub use backlinks::{BacklinkSort, BacklinksState, LinksTab};
+pub use browser::{BrowserFilter, BrowserState};
+pub use changelog::ChangelogState;
+pub use copy_menu::CopyMenuState;
+pub use export_menu::{ExportMenuState, ExportScope};
 pub use find_in_note::FindInNoteState;
 pub use finder::FinderState;
 pub use graph_view::GraphViewState;
+pub use help::HelpState;
+pub use history::{DiffView, HistoryState};
+pub use hover_preview::HoverPreview;
 pub use layout::{Focus, render};
+pub use link_menu::LinkMenuState;
+pub use scratch::ScratchState;
 pub use search::SearchState;
+pub use spell::{SpellChoice, SpellState};
 pub use tag_filter::TagFilterState;
-pub use viewer_state::{EditorMode, ViewerState};
+pub use text_preview::TextPreview;
+pub use theme_picker::ThemePickerState;
+pub use timeline::TimelineState;
+pub use toast::{ToastLevel, Toasts};
+pub use viewer_state::{EditorMode, Position, ViewerState};
+pub use workspace_picker::WorkspacePickerState;
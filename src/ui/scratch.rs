@@ -0,0 +1,91 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use super::viewer;
+use super::viewer_state::ViewerState;
+use crate::ui::theme::Theme;
+
+/// A session-only note for temporary text. It lives in memory until the app
+/// exits unless it is explicitly saved into the vault.
+pub struct ScratchState {
+    pub editor: ViewerState,
+    scroll: usize,
+}
+
+impl ScratchState {
+    pub fn new() -> Self {
+        let mut editor = ViewerState::new();
+        editor.enter_edit_mode();
+        Self { editor, scroll: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.editor.content.len_chars() == 0
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &mut ScratchState, t: &Theme) {
+    let popup_width = (area.width * 3 / 4)
+        .max(40)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (area.height * 3 / 4)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" {}Scratch ", t.icons.edit))
+        .title_bottom(Line::from(Span::styled(
+            " Esc: hide  Ctrl+s: save as note ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Keep the cursor line in view; long lines are cut rather than wrapped
+    let height = inner.height as usize;
+    let cursor = &state.editor.cursor;
+    if cursor.line < state.scroll {
+        state.scroll = cursor.line;
+    } else if height > 0 && cursor.line >= state.scroll + height {
+        state.scroll = cursor.line + 1 - height;
+    }
+
+    if state.is_empty() {
+        let hint = Paragraph::new(Line::from(Span::styled(
+            "Temporary text, not saved to the vault",
+            Style::default().fg(t.empty_hint),
+        )));
+        frame.render_widget(hint, inner);
+    } else {
+        let lines = state.scroll..state.scroll + height;
+        let text = viewer::render_edit_mode(&state.editor, lines, None, t);
+        let paragraph = Paragraph::new(text);
+        frame.render_widget(paragraph, inner);
+    }
+
+    let cursor_y = inner.y + (cursor.line - state.scroll) as u16;
+    let cursor_x = inner.x + (cursor.col as u16).min(inner.width.saturating_sub(1));
+    frame.set_cursor_position((cursor_x, cursor_y));
+
+    if let Some(ref ac) = state.editor.autocomplete
+        && !ac.matches.is_empty()
+    {
+        viewer::render_autocomplete(frame, popup_area, ac, &state.editor, t);
+    }
+}
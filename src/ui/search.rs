@@ -8,12 +8,18 @@ use ratatui::{
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
 
-use crate::core::Vault;
-use crate::ui::theme::{self, Theme};
+use crate::config::SavedSearch;
+use crate::core::query::{Filter, Query};
+use crate::core::{Index, Vault, text, transclude};
+use crate::ui::theme::Theme;
 
 pub struct SearchState {
     pub query: String,
     pub results: Vec<SearchResult>,
+    /// Saved searches, listed to pick from while the query is empty
+    pub saved: Vec<SavedSearch>,
+    /// Name being typed for the query, when saving it
+    pub naming: Option<String>,
     pub selected: usize,
     list_state: ListState,
 }
@@ -26,32 +32,63 @@ pub struct SearchResult {
 }
 
 impl SearchState {
-    pub fn new() -> Self {
+    pub fn new(saved: Vec<SavedSearch>) -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
 
         Self {
             query: String::new(),
             results: Vec::new(),
+            saved,
+            naming: None,
             selected: 0,
             list_state,
         }
     }
 
-    pub fn update_results(&mut self, vault: &Vault) {
+    /// Searches every note, or those whose frontmatter matches the query's
+    /// `key:value` filters. With `transclude`, text pulled in through
+    /// `![[Note]]` embeds also matches and is reported at the embed's line.
+    pub fn update_results(&mut self, vault: &Vault, index: &Index, transclude: bool) {
         self.results.clear();
         self.selected = 0;
         self.list_state.select(Some(0));
 
-        if self.query.len() < 2 {
+        let parsed = Query::parse(&self.query);
+        if !parsed.has_filters() && self.query.len() < 2 {
             return;
         }
 
-        let query_lower = self.query.to_lowercase();
+        let query = text::fold(&parsed.text);
 
         for note in vault.notes.values() {
-            for (line_num, line) in note.content.lines().enumerate() {
-                if line.to_lowercase().contains(&query_lower) {
+            if !parsed.matches(&note.path, index) {
+                continue;
+            }
+            // Filters alone list each matching note at its first filtered field
+            if query.is_empty() {
+                let content = note.scan();
+                let (line_num, line) = content
+                    .lines()
+                    .enumerate()
+                    .find(|(_, line)| shows_filter(&parsed.filters[0], line))
+                    .unwrap_or((0, ""));
+                self.results.push(SearchResult {
+                    path: note.path.clone(),
+                    title: note.title.clone(),
+                    matched_line: line.trim().to_string(),
+                    line_number: line_num + 1,
+                });
+                continue;
+            }
+
+            let lines = if transclude {
+                transclude::expand_lines(vault, note)
+            } else {
+                note.scan().lines().map(String::from).enumerate().collect()
+            };
+            for (line_num, line) in lines {
+                if text::fold(&line).contains(&query) {
                     self.results.push(SearchResult {
                         path: note.path.clone(),
                         title: note.title.clone(),
@@ -64,17 +101,25 @@ impl SearchState {
 
         // Sort by title then line number
         self.results.sort_by(|a, b| {
-            a.title
-                .cmp(&b.title)
-                .then(a.line_number.cmp(&b.line_number))
+            text::collate(&a.title, &b.title).then(a.line_number.cmp(&b.line_number))
         });
 
         // Limit results
         self.results.truncate(50);
     }
 
+    /// Whether the list shows saved searches rather than results.
+    pub fn is_picking(&self) -> bool {
+        self.query.is_empty() && !self.saved.is_empty()
+    }
+
     pub fn move_down(&mut self) {
-        if !self.results.is_empty() && self.selected < self.results.len() - 1 {
+        let len = if self.is_picking() {
+            self.saved.len()
+        } else {
+            self.results.len()
+        };
+        if self.selected + 1 < len {
             self.selected += 1;
             self.list_state.select(Some(self.selected));
         }
@@ -90,6 +135,19 @@ impl SearchState {
     pub fn selected_result(&self) -> Option<&SearchResult> {
         self.results.get(self.selected)
     }
+
+    /// Replaces the saved searches, keeping the selection in the list.
+    pub fn set_saved(&mut self, saved: Vec<SavedSearch>) {
+        self.saved = saved;
+        if self.is_picking() {
+            self.selected = self.selected.min(self.saved.len() - 1);
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_saved(&self) -> Option<&SavedSearch> {
+        self.saved.get(self.selected).filter(|_| self.is_picking())
+    }
 }
 
 pub fn render(frame: &mut Frame, area: Rect, state: &SearchState, t: &Theme) {
@@ -102,10 +160,23 @@ pub fn render(frame: &mut Frame, area: Rect, state: &SearchState, t: &Theme) {
 
     frame.render_widget(Clear, popup_area);
 
+    let hint = if state.naming.is_some() {
+        " Enter: save  Esc: cancel "
+    } else if state.is_picking() {
+        " Enter: run saved search  Ctrl+d: delete "
+    } else if !state.query.is_empty() {
+        " Ctrl+s: save this search "
+    } else {
+        ""
+    };
     let block = Block::default()
-        .title(format!(" {}Search ", theme::ICON_SEARCH))
+        .title(format!(" {}Search ", t.icons.search))
+        .title_bottom(Line::from(Span::styled(
+            hint,
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.search_prompt))
         .style(Style::default().bg(t.bg0));
 
@@ -118,12 +189,13 @@ pub fn render(frame: &mut Frame, area: Rect, state: &SearchState, t: &Theme) {
 
     // Input field
     let input_area = Rect::new(inner.x, inner.y, inner.width, 1);
+    let (prompt, typed) = match &state.naming {
+        Some(name) => (" Save as: ".to_string(), name),
+        None => (format!(" {} ", t.icons.search), &state.query),
+    };
     let input = Paragraph::new(Line::from(vec![
-        Span::styled(
-            format!(" {} ", theme::ICON_SEARCH),
-            Style::default().fg(t.search_prompt),
-        ),
-        Span::styled(&state.query, Style::default().fg(t.fg1)),
+        Span::styled(prompt, Style::default().fg(t.search_prompt)),
+        Span::styled(typed, Style::default().fg(t.fg1)),
         Span::styled(
             "_",
             Style::default()
@@ -149,9 +221,23 @@ pub fn render(frame: &mut Frame, area: Rect, state: &SearchState, t: &Theme) {
         inner.height.saturating_sub(2),
     );
 
-    if state.results.is_empty() {
+    if state.is_picking() {
+        let items: Vec<ListItem> = state
+            .saved
+            .iter()
+            .map(|search| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(&search.name, Style::default().fg(t.fg1)),
+                    Span::styled(format!("  {}", search.query), Style::default().fg(t.fg4)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(t.selection_style());
+        let mut list_state = state.list_state.clone();
+        frame.render_stateful_widget(list, results_area, &mut list_state);
+    } else if state.results.is_empty() {
         let msg = if state.query.len() < 2 {
-            "Type to search..."
+            "Type to search, or key:value to filter by frontmatter"
         } else {
             "No results"
         };
@@ -209,3 +295,16 @@ pub fn render(frame: &mut Frame, area: Rect, state: &SearchState, t: &Theme) {
         frame.render_stateful_widget(list, results_area, &mut list_state);
     }
 }
+
+/// Whether `line` is where a note shows it passes `filter`: the frontmatter
+/// field, the tag or the first open task.
+fn shows_filter(filter: &Filter, line: &str) -> bool {
+    match filter {
+        Filter::Property(key, _) => line
+            .split_once(':')
+            .is_some_and(|(k, _)| k.trim().eq_ignore_ascii_case(key)),
+        Filter::Tag(tag) => line.to_lowercase().contains(&format!("#{}", tag)),
+        Filter::Task(true) => line.trim_start().starts_with("- [ ]"),
+        Filter::Task(false) | Filter::Path(_) => false,
+    }
+}
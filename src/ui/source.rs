@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::core::frontmatter;
+use crate::ui::theme::Theme;
+
+/// Colors the markup of the lines in `range` of a note's source, leaving the
+/// text itself exactly as written: heading and list markers, emphasis
+/// delimiters, links, tags, code, math and comments.
+pub fn highlight(content: &str, range: Range<usize>, t: &Theme) -> Vec<Line<'static>> {
+    let body_start = match frontmatter::split(content) {
+        Some(Ok((yaml, _))) => yaml.lines().count() + 2,
+        _ => 0,
+    };
+
+    // Whether the first line in view sits inside a code or math block
+    let (mut in_fence, mut in_math) = (false, false);
+    let mut lines = Vec::new();
+    for (line_idx, line) in content.lines().enumerate().take(range.end) {
+        let is_fence = line.trim_start().starts_with("```");
+        let is_math_fence = !in_fence && line.trim() == "$$";
+        let style = if line_idx < body_start || is_fence {
+            Some(Style::default().fg(t.fg4))
+        } else if in_fence {
+            Some(Style::default().fg(t.inline_code))
+        } else if in_math || is_math_fence {
+            Some(Style::default().fg(t.math_fg))
+        } else {
+            None
+        };
+        in_fence ^= is_fence;
+        in_math ^= is_math_fence;
+
+        if line_idx < range.start {
+            continue;
+        }
+        lines.push(match style {
+            Some(style) => Line::from(Span::styled(line.to_string(), style)),
+            None => highlight_line(line, t),
+        });
+    }
+    lines
+}
+
+/// Splits a markdown line into its block marker and the inline text after it.
+fn highlight_line(line: &str, t: &Theme) -> Line<'static> {
+    let marker = Style::default().fg(t.fg4);
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+
+    // Headings: the hashes are the marker, the text takes the heading style
+    let level = rest.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) && rest[level..].starts_with(' ') {
+        return Line::from(vec![
+            Span::raw(indent.to_string()),
+            Span::styled(rest[..level].to_string(), marker),
+            Span::styled(rest[level..].to_string(), t.heading_style(level)),
+        ]);
+    }
+
+    // Horizontal rules and setext underlines
+    if rest.len() >= 3
+        && let Some(c) = rest.chars().next()
+        && matches!(c, '-' | '*' | '_' | '=')
+        && rest.chars().all(|r| r == c || r == ' ')
+    {
+        return Line::from(Span::styled(line.to_string(), marker));
+    }
+
+    let mut spans = vec![Span::raw(indent.to_string())];
+    let mut rest = rest;
+    while let Some(quoted) = rest.strip_prefix('>') {
+        spans.push(Span::styled(">", marker));
+        rest = quoted;
+    }
+    let bullet_len = list_marker_len(rest.trim_start());
+    if bullet_len > 0 {
+        let lead = rest.len() - rest.trim_start().len();
+        spans.push(Span::raw(rest[..lead].to_string()));
+        let (bullet, text) = rest[lead..].split_at(bullet_len);
+        spans.push(Span::styled(
+            bullet.to_string(),
+            Style::default().fg(t.blue),
+        ));
+        rest = text;
+    }
+    spans.extend(highlight_inline(rest, t));
+    Line::from(spans)
+}
+
+/// Byte length of a list marker at the start of `text`, with its trailing
+/// space and any task checkbox, or 0 when there is none.
+fn list_marker_len(text: &str) -> usize {
+    let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    let len = if matches!(text.chars().next(), Some('-' | '*' | '+')) {
+        1
+    } else if digits > 0 && matches!(text[digits..].chars().next(), Some('.' | ')')) {
+        digits + 1
+    } else {
+        return 0;
+    };
+    if !text[len..].starts_with(' ') {
+        return 0;
+    }
+    let len = len + 1;
+    let after = &text[len..];
+    if ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .any(|checkbox| after.starts_with(checkbox))
+    {
+        len + 4
+    } else {
+        len
+    }
+}
+
+fn highlight_inline(text: &str, t: &Theme) -> Vec<Span<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let marker = Style::default().fg(t.fg4);
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let rest = &chars[i..];
+        let span = if rest.starts_with(&['\\']) && rest.len() > 1 {
+            Some((2, marker))
+        } else if rest.starts_with(&['%', '%']) {
+            let end = closing(rest, 2, &['%', '%']).unwrap_or(rest.len());
+            Some((end, marker.add_modifier(Modifier::ITALIC)))
+        } else if rest.starts_with(&['[', '[']) || rest.starts_with(&['!', '[', '[']) {
+            closing(rest, 2, &[']', ']']).map(|end| (end, Style::default().fg(t.link_fg)))
+        } else if rest[0] == '`' {
+            closing(rest, 1, &['`']).map(|end| (end, Style::default().fg(t.inline_code)))
+        } else if rest[0] == '$' {
+            let delim: &[char] = if rest.get(1) == Some(&'$') {
+                &['$', '$']
+            } else {
+                &['$']
+            };
+            closing(rest, delim.len(), delim).map(|end| (end, Style::default().fg(t.math_fg)))
+        } else if rest[0] == '#'
+            && (i == 0 || chars[i - 1].is_whitespace())
+            && rest.get(1).is_some_and(|c| c.is_alphanumeric())
+        {
+            let len = 1 + rest[1..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+                .count();
+            Some((len, Style::default().fg(t.tag_fg)))
+        } else if rest[0] == ']' && rest.get(1) == Some(&'(') {
+            // The destination of a [text](url) link
+            let end = rest
+                .iter()
+                .position(|&c| c == ')')
+                .map_or(rest.len(), |p| p + 1);
+            Some((end, marker))
+        } else if matches!(rest[0], '*' | '_' | '~' | '=' | '[') {
+            // Emphasis, strikethrough and highlight delimiters
+            let len = rest.iter().take_while(|&&c| c == rest[0]).count();
+            let is_delimiter = match rest[0] {
+                '~' | '=' => len >= 2,
+                '_' => {
+                    let before = i.checked_sub(1).map(|p| chars[p]);
+                    let after = rest.get(len);
+                    !before.is_some_and(char::is_alphanumeric)
+                        || !after.is_some_and(|c| c.is_alphanumeric())
+                }
+                _ => true,
+            };
+            is_delimiter.then_some((len, marker))
+        } else {
+            None
+        };
+
+        match span {
+            Some((len, style)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(
+                    chars[i..i + len].iter().collect::<String>(),
+                    style,
+                ));
+                i += len;
+            }
+            None => {
+                plain.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+/// Length of `chars` up to and including the `delim` that closes what opens
+/// it, searching from `from`.
+fn closing(chars: &[char], from: usize, delim: &[char]) -> Option<usize> {
+    (from..chars.len())
+        .find(|&i| chars[i..].starts_with(delim))
+        .map(|i| i + delim.len())
+}
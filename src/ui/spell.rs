@@ -0,0 +1,122 @@
+use std::ops::Range;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+use crate::ui::theme::Theme;
+
+/// Suggestions for a misspelled word, with a final entry to ignore it.
+pub struct SpellState {
+    pub word: String,
+    pub line: usize,
+    pub range: Range<usize>, // char columns of the word within the line
+    pub suggestions: Vec<String>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+/// What picking the selected entry should do.
+pub enum SpellChoice<'a> {
+    Replace(&'a str),
+    Ignore,
+}
+
+impl SpellState {
+    pub fn new(word: String, line: usize, range: Range<usize>, suggestions: Vec<String>) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        Self {
+            word,
+            line,
+            range,
+            suggestions,
+            selected: 0,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        // +1 for the ignore entry at the bottom
+        if self.selected < self.suggestions.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn choice(&self) -> SpellChoice<'_> {
+        match self.suggestions.get(self.selected) {
+            Some(suggestion) => SpellChoice::Replace(suggestion),
+            None => SpellChoice::Ignore,
+        }
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &SpellState, t: &Theme) {
+    let ignore_label = format!("  Add \"{}\" to the vault's word list", state.word);
+    let popup_width = (ignore_label.chars().count() as u16 + 4)
+        .max(40)
+        .min(area.width.saturating_sub(4));
+    let popup_height = (state.suggestions.len() as u16 + 4).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!(" Spelling: {} ", state.word))
+        .title_bottom(Line::from(Span::styled(
+            " Enter: apply  Esc: cancel ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let mut items: Vec<ListItem> = state
+        .suggestions
+        .iter()
+        .map(|suggestion| {
+            ListItem::new(Line::from(Span::styled(
+                format!("  {}", suggestion),
+                Style::default().fg(t.fg1),
+            )))
+        })
+        .collect();
+    if state.suggestions.is_empty() {
+        items.push(ListItem::new(Line::from(Span::styled(
+            "  (no suggestions)",
+            Style::default().fg(t.empty_hint),
+        ))));
+    }
+    items.push(ListItem::new(Line::from(Span::styled(
+        ignore_label,
+        Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+    ))));
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(t.selection_style());
+
+    // The placeholder row shifts the ignore entry down by one
+    let mut list_state = state.list_state.clone();
+    if state.suggestions.is_empty() {
+        list_state.select(Some(1));
+    }
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
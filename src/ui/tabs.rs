@@ -0,0 +1,59 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::app::App;
+
+/// One row of open notes above the preview, the one in view highlighted.
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let t = &app.theme;
+    let active = app.active_tab();
+
+    let labels: Vec<String> = app
+        .tabs
+        .iter()
+        .map(|tab| {
+            let title = app
+                .vault
+                .get_note(&tab.path)
+                .map(|note| note.title.as_str())
+                .unwrap_or("?");
+            format!(" {} ", title)
+        })
+        .collect();
+
+    // Drop tabs from the left until the active one fits
+    let width = area.width as usize;
+    let mut first = 0;
+    if let Some(active) = active {
+        let span_width = |from: usize| -> usize {
+            labels[from..=active]
+                .iter()
+                .map(|label| label.chars().count() + 1)
+                .sum()
+        };
+        while first < active && span_width(first) > width {
+            first += 1;
+        }
+    }
+
+    let mut spans = Vec::new();
+    if first > 0 {
+        spans.push(Span::styled("…", Style::default().fg(t.fg4)));
+    }
+    for (i, label) in labels.iter().enumerate().skip(first) {
+        let style = if Some(i) == active {
+            t.selection_style().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(t.fg4).bg(t.bg1)
+        };
+        spans.push(Span::styled(label.clone(), style));
+        spans.push(Span::raw(" "));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
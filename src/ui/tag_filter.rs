@@ -6,7 +6,7 @@ use ratatui::{
     widgets::{Block, Borders, Clear, List, ListItem, ListState},
 };
 
-use crate::ui::theme::{self, Theme};
+use crate::ui::theme::Theme;
 
 pub struct TagFilterState {
     pub tags: Vec<String>,
@@ -52,20 +52,25 @@ impl TagFilterState {
     }
 }
 
-pub fn render(frame: &mut Frame, area: Rect, state: &TagFilterState, t: &Theme) {
+/// Where the popup is drawn within `area`, also used to place the hover preview.
+pub fn popup_area(area: Rect, state: &TagFilterState) -> Rect {
     let popup_width = 40u16.min(area.width.saturating_sub(4));
     let popup_height = (state.tags.len() as u16 + 4).min(area.height.saturating_sub(4));
 
     let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
     let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-    let popup_area = Rect::new(x, y, popup_width, popup_height);
+    Rect::new(x, y, popup_width, popup_height)
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &TagFilterState, t: &Theme) {
+    let popup_area = popup_area(area, state);
 
     frame.render_widget(Clear, popup_area);
 
     let block = Block::default()
-        .title(format!(" {}Filter by Tag ", theme::ICON_TAG))
+        .title(format!(" {}Filter by Tag ", t.icons.tag))
         .borders(Borders::ALL)
-        .border_type(theme::border_type())
+        .border_set(t.borders)
         .border_style(Style::default().fg(t.tag_filter_border))
         .style(Style::default().bg(t.bg0));
 
@@ -76,7 +81,7 @@ pub fn render(frame: &mut Frame, area: Rect, state: &TagFilterState, t: &Theme)
 
     for tag in &state.tags {
         items.push(ListItem::new(Line::from(vec![
-            Span::styled(format!("  {}", theme::ICON_TAG), Style::default().fg(t.fg4)),
+            Span::styled(format!("  {}", t.icons.tag), Style::default().fg(t.fg4)),
             Span::styled(tag, Style::default().fg(t.tag_fg)),
         ])));
     }
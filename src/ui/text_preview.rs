@@ -0,0 +1,184 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::core::table;
+use crate::ui::theme::Theme;
+
+/// How much of a file the preview reads; a long log shows its start.
+const MAX_BYTES: u64 = 256 * 1024;
+
+/// Widest a CSV column gets before its cells are cut short.
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// The text of the plaintext attachment last previewed, read again when
+/// another file is selected or the file changes.
+#[derive(Default)]
+pub struct TextPreview {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    text: Option<String>,
+    truncated: bool,
+}
+
+impl TextPreview {
+    /// Renders the file at `path` as read-only text, CSV and TSV files as
+    /// aligned tables. None if it isn't text.
+    pub fn render(&mut self, path: &Path, t: &Theme) -> Option<Text<'static>> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if self.path != path || self.modified != modified {
+            self.path = path.to_path_buf();
+            self.modified = modified;
+            let loaded = read_text(path);
+            self.truncated = loaded.as_ref().is_some_and(|(_, truncated)| *truncated);
+            self.text = loaded.map(|(text, _)| text);
+        }
+        let text = self.text.as_deref()?;
+
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let mut lines = match ext.as_str() {
+            "csv" => csv_lines(text, ',', t),
+            "tsv" | "tab" => csv_lines(text, '\t', t),
+            _ => text
+                .lines()
+                .map(|line| {
+                    Line::from(Span::styled(
+                        line.replace('\t', "    "),
+                        Style::default().fg(t.fg1),
+                    ))
+                })
+                .collect(),
+        };
+        if self.truncated {
+            lines.push(Line::from(Span::styled(
+                format!("… only the first {} KB are shown", MAX_BYTES / 1024),
+                Style::default().fg(t.empty_hint),
+            )));
+        }
+        Some(Text::from(lines))
+    }
+}
+
+/// The start of the file at `path` if it reads as UTF-8 text, and whether
+/// there is more of it.
+fn read_text(path: &Path) -> Option<(String, bool)> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut bytes = Vec::new();
+    file.take(MAX_BYTES + 1).read_to_end(&mut bytes).ok()?;
+    let truncated = bytes.len() as u64 > MAX_BYTES;
+    bytes.truncate(MAX_BYTES as usize);
+    // Binary formats give themselves away with NUL bytes early on, and an
+    // empty file could be anything
+    if bytes.is_empty() || bytes.contains(&0) {
+        return None;
+    }
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        // The cut may fall inside a character
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()?
+        }
+        Err(_) => return None,
+    };
+    Some((text, truncated))
+}
+
+/// A CSV file as a table: columns padded to their widest cell, the header
+/// in bold above a rule.
+fn csv_lines(text: &str, delimiter: char, t: &Theme) -> Vec<Line<'static>> {
+    let records: Vec<Vec<String>> = table::parse_csv(text, delimiter)
+        .into_iter()
+        .map(|record| {
+            record
+                .iter()
+                .map(|field| fit(&field.replace(['\n', '\t'], " "), MAX_COLUMN_WIDTH))
+                .collect()
+        })
+        .collect();
+    let columns = records.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            records
+                .iter()
+                .filter_map(|record| record.get(col))
+                .map(|field| field.width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let separator = Span::styled(" │ ", Style::default().fg(t.fg4));
+    let mut lines = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        let style = if i == 0 {
+            Style::default()
+                .fg(t.heading_2)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(t.fg1)
+        };
+        let mut spans = Vec::new();
+        for (col, &width) in widths.iter().enumerate() {
+            if col > 0 {
+                spans.push(separator.clone());
+            }
+            let field = record.get(col).map(String::as_str).unwrap_or_default();
+            let pad = " ".repeat(width.saturating_sub(field.width()));
+            // Numbers line up on the right, like a spreadsheet
+            let cell = if i > 0 && is_number(field) {
+                format!("{}{}", pad, field)
+            } else {
+                format!("{}{}", field, pad)
+            };
+            spans.push(Span::styled(cell, style));
+        }
+        lines.push(Line::from(spans));
+
+        if i == 0 && records.len() > 1 {
+            let rule: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+            lines.push(Line::from(Span::styled(
+                rule.join("─┼─"),
+                Style::default().fg(t.fg4),
+            )));
+        }
+    }
+    lines
+}
+
+/// `field` cut to `width` columns, ending in an ellipsis when shortened.
+fn fit(field: &str, width: usize) -> String {
+    if field.width() <= width {
+        return field.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in field.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w >= width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+fn is_number(field: &str) -> bool {
+    let digits = field
+        .trim_start_matches(['-', '+'])
+        .replace([',', '.', '%'], "");
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
@@ -1,7 +1,13 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use color_eyre::{Result, eyre::eyre};
+use directories::ProjectDirs;
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::widgets::BorderType;
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Borders, Padding};
+
+use crate::config::{BorderStyle, PaneChrome, UiConfig};
 
 // ── Theme Struct ────────────────────────────────────────────────
 
@@ -42,6 +48,8 @@ pub struct Theme {
     pub link_broken: Color,
     pub tag_fg: Color,
     pub inline_code: Color,
+    pub highlight_bg: Color,
+    pub math_fg: Color,
     pub title_fg: Color,
     pub title_bar_bg: Color,
     pub status_bar_bg: Color,
@@ -59,9 +67,29 @@ pub struct Theme {
     pub selection_bg: Color,
     pub find_match_bg: Color,
     pub find_current_bg: Color,
+    pub icons: Icons,
+    pub borders: border::Set,
 }
 
 impl Theme {
+    /// The bordered block of a main pane, with its title and padding as configured.
+    pub fn pane_block<'a>(
+        &self,
+        title: Option<String>,
+        chrome: PaneChrome,
+        focused: bool,
+    ) -> Block<'a> {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(self.borders)
+            .border_style(self.border_style(focused))
+            .padding(Padding::horizontal(chrome.padding));
+        if let Some(title) = title.filter(|_| chrome.title) {
+            block = block.title(title);
+        }
+        block
+    }
+
     pub fn border_style(&self, focused: bool) -> Style {
         if focused {
             Style::default().fg(self.border_focused)
@@ -77,6 +105,81 @@ impl Theme {
             .add_modifier(Modifier::BOLD)
     }
 
+    /// Style for a heading of `level` 1–6. Levels past 3 share `heading_3`'s
+    /// color and step down in weight, ending dimmed at level 6.
+    pub fn heading_style(&self, level: usize) -> Style {
+        match level {
+            1 => Style::default()
+                .fg(self.heading_1)
+                .add_modifier(Modifier::BOLD),
+            2 => Style::default()
+                .fg(self.heading_2)
+                .add_modifier(Modifier::BOLD),
+            3 => Style::default()
+                .fg(self.heading_3)
+                .add_modifier(Modifier::BOLD),
+            4 => Style::default().fg(self.heading_3),
+            5 => Style::default()
+                .fg(self.heading_3)
+                .add_modifier(Modifier::ITALIC),
+            _ => Style::default().fg(self.fg4).add_modifier(Modifier::ITALIC),
+        }
+    }
+
+    /// Underline for words the spell checker rejects.
+    pub fn misspelled_style(&self) -> Style {
+        Style::default()
+            .add_modifier(Modifier::UNDERLINED)
+            .underline_color(self.red)
+    }
+
+    /// Color of an open task due on `due`: red once overdue, yellow on the
+    /// day itself, and the usual text color otherwise.
+    pub fn due_color(&self, due: chrono::NaiveDate, today: chrono::NaiveDate) -> Option<Color> {
+        if due < today {
+            Some(self.red)
+        } else if due == today {
+            Some(self.yellow)
+        } else {
+            None
+        }
+    }
+
+    /// Style for a `done/total` task count: green once every task is done.
+    pub fn task_progress_style(&self, (done, total): (usize, usize), selected: bool) -> Style {
+        if selected {
+            self.selection_style()
+        } else if done == total {
+            Style::default().fg(self.green)
+        } else {
+            Style::default().fg(self.fg4)
+        }
+    }
+
+    /// Built-in themes followed by the user's theme files, by selectable name.
+    pub fn available() -> Vec<String> {
+        let mut custom: Vec<String> = themes_dir()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_string();
+                (!BUILTIN_THEMES.contains(&name.as_str())).then_some(name)
+            })
+            .collect();
+        custom.sort();
+
+        BUILTIN_THEMES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(custom)
+            .collect()
+    }
+
     pub fn from_name(name: &str) -> Option<Theme> {
         match name {
             "gruvbox-dark" => Some(gruvbox_dark()),
@@ -97,71 +200,218 @@ impl Theme {
         }
     }
 
-    pub fn from_config(ui: &crate::config::UiConfig) -> Theme {
-        let mut theme = Theme::from_name(&ui.theme).unwrap_or_else(gruvbox_dark);
+    /// The configured theme: a built-in, or a file of that name in
+    /// [`themes_dir`]. Falls back to gruvbox-dark, returning why alongside.
+    pub fn from_config(ui: &UiConfig) -> (Theme, Option<String>) {
+        let (mut theme, error) = match Theme::from_name(&ui.theme) {
+            Some(theme) => (theme, None),
+            None => match themes_dir().map(|dir| dir.join(format!("{}.toml", ui.theme))) {
+                Some(path) if path.is_file() => match Theme::from_file(&path) {
+                    Ok(theme) => (theme, None),
+                    Err(e) => (gruvbox_dark(), Some(format!("Theme not loaded: {}", e))),
+                },
+                _ => (
+                    gruvbox_dark(),
+                    Some(format!("Unknown theme '{}'", ui.theme)),
+                ),
+            },
+        };
         theme.apply_overrides(&ui.theme_overrides);
-        theme
+        if !ui.nerd_font {
+            theme.icons = Icons::ASCII;
+        }
+        theme.borders = match ui.border {
+            BorderStyle::Rounded => border::ROUNDED,
+            BorderStyle::Plain => border::PLAIN,
+            BorderStyle::Double => border::DOUBLE,
+            BorderStyle::Thick => border::THICK,
+            // Blank borders keep pane titles and spacing in place
+            BorderStyle::None => border::EMPTY,
+        };
+        (theme, error)
     }
 
     pub fn apply_overrides(&mut self, overrides: &HashMap<String, String>) {
         for (key, value) in overrides {
             if let Some(color) = parse_hex_color(value) {
-                match key.as_str() {
-                    "bg0" => self.bg0 = color,
-                    "bg1" => self.bg1 = color,
-                    "bg2" => self.bg2 = color,
-                    "bg3" => self.bg3 = color,
-                    "bg4" => self.bg4 = color,
-                    "fg0" => self.fg0 = color,
-                    "fg1" => self.fg1 = color,
-                    "fg2" => self.fg2 = color,
-                    "fg3" => self.fg3 = color,
-                    "fg4" => self.fg4 = color,
-                    "red" => self.red = color,
-                    "green" => self.green = color,
-                    "yellow" => self.yellow = color,
-                    "blue" => self.blue = color,
-                    "purple" => self.purple = color,
-                    "aqua" => self.aqua = color,
-                    "orange" => self.orange = color,
-                    "border_focused" => self.border_focused = color,
-                    "border_unfocused" => self.border_unfocused = color,
-                    "border_overlay" => self.border_overlay = color,
-                    "selected_fg" => self.selected_fg = color,
-                    "selected_bg" => self.selected_bg = color,
-                    "heading_1" => self.heading_1 = color,
-                    "heading_2" => self.heading_2 = color,
-                    "heading_3" => self.heading_3 = color,
-                    "link_fg" => self.link_fg = color,
-                    "link_selected_fg" => self.link_selected_fg = color,
-                    "link_selected_bg" => self.link_selected_bg = color,
-                    "link_broken" => self.link_broken = color,
-                    "tag_fg" => self.tag_fg = color,
-                    "inline_code" => self.inline_code = color,
-                    "title_fg" => self.title_fg = color,
-                    "title_bar_bg" => self.title_bar_bg = color,
-                    "status_bar_bg" => self.status_bar_bg = color,
-                    "cursor_blink" => self.cursor_blink = color,
-                    "empty_hint" => self.empty_hint = color,
-                    "dir_fg" => self.dir_fg = color,
-                    "file_fg" => self.file_fg = color,
-                    "backlink_fg" => self.backlink_fg = color,
-                    "tag_filter_border" => self.tag_filter_border = color,
-                    "search_prompt" => self.search_prompt = color,
-                    "finder_prompt" => self.finder_prompt = color,
-                    "autocomplete_bg" => self.autocomplete_bg = color,
-                    "autocomplete_sel_bg" => self.autocomplete_sel_bg = color,
-                    "cursor_line_bg" => self.cursor_line_bg = color,
-                    "selection_bg" => self.selection_bg = color,
-                    "find_match_bg" => self.find_match_bg = color,
-                    "find_current_bg" => self.find_current_bg = color,
-                    _ => {}
+                self.set(key, color);
+            }
+        }
+    }
+
+    /// Reads a theme file: a `field = "#rrggbb"` entry for every color field,
+    /// or a `base` built-in theme plus the fields that differ from it.
+    pub fn from_file(path: &Path) -> Result<Theme> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let contents = std::fs::read_to_string(path)?;
+        let mut entries: HashMap<String, String> =
+            toml::from_str(&contents).map_err(|e| eyre!("{}: {}", name, e.message()))?;
+
+        let mut theme = match entries.remove("base") {
+            Some(base) => Theme::from_name(&base)
+                .ok_or_else(|| eyre!("{}: unknown base theme '{}'", name, base))?,
+            None => {
+                let missing: Vec<&str> = COLOR_FIELDS
+                    .iter()
+                    .copied()
+                    .filter(|field| !entries.contains_key(*field))
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(eyre!(
+                        "{}: missing {} (or set `base` to a built-in theme)",
+                        name,
+                        missing.join(", ")
+                    ));
                 }
+                gruvbox_dark()
+            }
+        };
+
+        for (key, value) in &entries {
+            let color = parse_hex_color(value)
+                .ok_or_else(|| eyre!("{}: {} = \"{}\" is not a #rrggbb color", name, key, value))?;
+            if !theme.set(key, color) {
+                return Err(eyre!("{}: unknown field '{}'", name, key));
             }
         }
+        Ok(theme)
+    }
+
+    /// Sets the color field called `key`, returning false if there is none.
+    fn set(&mut self, key: &str, color: Color) -> bool {
+        match key {
+            "bg0" => self.bg0 = color,
+            "bg1" => self.bg1 = color,
+            "bg2" => self.bg2 = color,
+            "bg3" => self.bg3 = color,
+            "bg4" => self.bg4 = color,
+            "fg0" => self.fg0 = color,
+            "fg1" => self.fg1 = color,
+            "fg2" => self.fg2 = color,
+            "fg3" => self.fg3 = color,
+            "fg4" => self.fg4 = color,
+            "red" => self.red = color,
+            "green" => self.green = color,
+            "yellow" => self.yellow = color,
+            "blue" => self.blue = color,
+            "purple" => self.purple = color,
+            "aqua" => self.aqua = color,
+            "orange" => self.orange = color,
+            "border_focused" => self.border_focused = color,
+            "border_unfocused" => self.border_unfocused = color,
+            "border_overlay" => self.border_overlay = color,
+            "selected_fg" => self.selected_fg = color,
+            "selected_bg" => self.selected_bg = color,
+            "heading_1" => self.heading_1 = color,
+            "heading_2" => self.heading_2 = color,
+            "heading_3" => self.heading_3 = color,
+            "link_fg" => self.link_fg = color,
+            "link_selected_fg" => self.link_selected_fg = color,
+            "link_selected_bg" => self.link_selected_bg = color,
+            "link_broken" => self.link_broken = color,
+            "tag_fg" => self.tag_fg = color,
+            "inline_code" => self.inline_code = color,
+            "highlight_bg" => self.highlight_bg = color,
+            "math_fg" => self.math_fg = color,
+            "title_fg" => self.title_fg = color,
+            "title_bar_bg" => self.title_bar_bg = color,
+            "status_bar_bg" => self.status_bar_bg = color,
+            "cursor_blink" => self.cursor_blink = color,
+            "empty_hint" => self.empty_hint = color,
+            "dir_fg" => self.dir_fg = color,
+            "file_fg" => self.file_fg = color,
+            "backlink_fg" => self.backlink_fg = color,
+            "tag_filter_border" => self.tag_filter_border = color,
+            "search_prompt" => self.search_prompt = color,
+            "finder_prompt" => self.finder_prompt = color,
+            "autocomplete_bg" => self.autocomplete_bg = color,
+            "autocomplete_sel_bg" => self.autocomplete_sel_bg = color,
+            "cursor_line_bg" => self.cursor_line_bg = color,
+            "selection_bg" => self.selection_bg = color,
+            "find_match_bg" => self.find_match_bg = color,
+            "find_current_bg" => self.find_current_bg = color,
+            _ => return false,
+        }
+        true
     }
 }
 
+pub const BUILTIN_THEMES: &[&str] = &[
+    "gruvbox-dark",
+    "gruvbox-light",
+    "catppuccin-mocha",
+    "catppuccin-latte",
+    "tokyo-night",
+    "tokyo-night-day",
+    "nord",
+    "dracula",
+    "tidal-dark",
+    "tidal-light",
+    "ember-dark",
+    "ember-light",
+    "sunset-dark",
+    "sunset-light",
+];
+
+/// Names of the color fields, as written in theme files and `theme_overrides`.
+pub const COLOR_FIELDS: &[&str] = &[
+    "bg0",
+    "bg1",
+    "bg2",
+    "bg3",
+    "bg4",
+    "fg0",
+    "fg1",
+    "fg2",
+    "fg3",
+    "fg4",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "purple",
+    "aqua",
+    "orange",
+    "border_focused",
+    "border_unfocused",
+    "border_overlay",
+    "selected_fg",
+    "selected_bg",
+    "heading_1",
+    "heading_2",
+    "heading_3",
+    "link_fg",
+    "link_selected_fg",
+    "link_selected_bg",
+    "link_broken",
+    "tag_fg",
+    "inline_code",
+    "highlight_bg",
+    "math_fg",
+    "title_fg",
+    "title_bar_bg",
+    "status_bar_bg",
+    "cursor_blink",
+    "empty_hint",
+    "dir_fg",
+    "file_fg",
+    "backlink_fg",
+    "tag_filter_border",
+    "search_prompt",
+    "finder_prompt",
+    "autocomplete_bg",
+    "autocomplete_sel_bg",
+    "cursor_line_bg",
+    "selection_bg",
+    "find_match_bg",
+    "find_current_bg",
+];
+
+/// Directory holding user theme files, each selectable by its file stem.
+pub fn themes_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "tui-jot", "tui-jot").map(|dirs| dirs.config_dir().join("themes"))
+}
+
 fn parse_hex_color(s: &str) -> Option<Color> {
     let s = s.strip_prefix('#').unwrap_or(s);
     if s.len() != 6 {
@@ -173,21 +423,81 @@ fn parse_hex_color(s: &str) -> Option<Color> {
     Some(Color::Rgb(r, g, b))
 }
 
-// ── Nerd Font Icons ───────────────────────────────────────────────
+// ── Icons ─────────────────────────────────────────────────────────
 
-pub const ICON_APP: &str = "󰠮 ";
-pub const ICON_FILE: &str = "󰈙 ";
-pub const ICON_FOLDER_OPEN: &str = " ";
-pub const ICON_FOLDER_CLOSED: &str = " ";
-pub const ICON_SEARCH: &str = " ";
-pub const ICON_TAG: &str = " ";
-pub const ICON_LINK: &str = "󰌹 ";
-pub const ICON_EDIT: &str = " ";
+/// Glyphs drawn before titles and tree entries. Each includes its trailing space.
+#[derive(Debug, Clone)]
+pub struct Icons {
+    pub app: &'static str,
+    pub file: &'static str,
+    pub folder_open: &'static str,
+    pub folder_closed: &'static str,
+    /// A smart folder, open or closed
+    pub smart_folder: &'static str,
+    pub search: &'static str,
+    pub tag: &'static str,
+    pub link: &'static str,
+    pub edit: &'static str,
+    pub scroll: &'static str,
+    pub image: &'static str,
+    pub pdf: &'static str,
+    pub attachment: &'static str,
+    pub lock: &'static str,
+    /// Marks a note with unsaved changes
+    pub unsaved: &'static str,
+    /// Marks a preview following the end of its note
+    pub follow: &'static str,
+    /// Marks the editor's table mode
+    pub table: &'static str,
+    /// List bullets by nesting depth, repeating past the last.
+    pub bullets: [&'static str; 3],
+}
 
-// ── Style Helpers (non-theme) ───────────────────────────────────
+impl Icons {
+    /// Needs a patched Nerd Font.
+    pub const NERD_FONT: Icons = Icons {
+        app: "󰠮 ",
+        file: "󰈙 ",
+        folder_open: " ",
+        folder_closed: " ",
+        smart_folder: "󰥨 ",
+        search: " ",
+        tag: " ",
+        link: "󰌹 ",
+        edit: " ",
+        scroll: "⇅ ",
+        image: "󰋩 ",
+        pdf: "󰈦 ",
+        attachment: "󰁦 ",
+        lock: " ",
+        unsaved: "●",
+        follow: "⇣ ",
+        table: "󰓫 ",
+        bullets: ["•", "◦", "▪"],
+    };
 
-pub fn border_type() -> BorderType {
-    BorderType::Rounded
+    /// Plain ASCII for terminals without a Nerd Font. Icons whose meaning
+    /// the surrounding text already carries are left out.
+    pub const ASCII: Icons = Icons {
+        app: "",
+        file: "- ",
+        folder_open: "v ",
+        folder_closed: "> ",
+        smart_folder: "~ ",
+        search: "",
+        tag: "",
+        link: "",
+        edit: "* ",
+        scroll: "",
+        image: "[img] ",
+        pdf: "[pdf] ",
+        attachment: "[+] ",
+        lock: "",
+        unsaved: "[+]",
+        follow: "",
+        table: "",
+        bullets: ["-", "*", "+"],
+    };
 }
 
 // ── Built-in Presets ────────────────────────────────────────────
@@ -243,6 +553,8 @@ pub fn gruvbox_dark() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -260,6 +572,8 @@ pub fn gruvbox_dark() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -314,6 +628,8 @@ pub fn gruvbox_light() -> Theme {
         link_broken: red,
         tag_fg: purple,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -331,6 +647,8 @@ pub fn gruvbox_light() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -385,6 +703,8 @@ pub fn catppuccin_mocha() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -402,6 +722,8 @@ pub fn catppuccin_mocha() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -456,6 +778,8 @@ pub fn catppuccin_latte() -> Theme {
         link_broken: red,
         tag_fg: purple,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -473,6 +797,8 @@ pub fn catppuccin_latte() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -527,6 +853,8 @@ pub fn tokyo_night() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -544,6 +872,8 @@ pub fn tokyo_night() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -598,6 +928,8 @@ pub fn tokyo_night_day() -> Theme {
         link_broken: red,
         tag_fg: purple,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -615,6 +947,8 @@ pub fn tokyo_night_day() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -669,6 +1003,8 @@ pub fn nord() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -686,6 +1022,8 @@ pub fn nord() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -740,6 +1078,8 @@ pub fn dracula() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -757,6 +1097,8 @@ pub fn dracula() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -814,6 +1156,8 @@ pub fn tidal_dark() -> Theme {
         link_broken: red,
         tag_fg: orange,
         inline_code: red,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -831,6 +1175,8 @@ pub fn tidal_dark() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: aqua,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -886,6 +1232,8 @@ pub fn tidal_light() -> Theme {
         link_broken: red,
         tag_fg: orange,
         inline_code: purple,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -903,6 +1251,8 @@ pub fn tidal_light() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -960,6 +1310,8 @@ pub fn ember_dark() -> Theme {
         link_broken: Color::Rgb(160, 70, 30), // reddish brown
         tag_fg: orange,
         inline_code: Color::Rgb(212, 168, 120), // lighter warm
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: aqua,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -977,6 +1329,8 @@ pub fn ember_dark() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: orange,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -1032,6 +1386,8 @@ pub fn ember_light() -> Theme {
         link_broken: red,
         tag_fg: orange,
         inline_code: purple,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -1049,6 +1405,8 @@ pub fn ember_light() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -1106,6 +1464,8 @@ pub fn sunset_dark() -> Theme {
         link_broken: red,
         tag_fg: yellow,
         inline_code: orange,
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg1,
         status_bar_bg: bg1,
@@ -1123,6 +1483,8 @@ pub fn sunset_dark() -> Theme {
         selection_bg: bg2,
         find_match_bg: bg3,
         find_current_bg: yellow,
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
 
@@ -1178,6 +1540,8 @@ pub fn sunset_light() -> Theme {
         link_broken: red,
         tag_fg: orange,
         inline_code: Color::Rgb(100, 50, 10), // muted burnt orange
+        highlight_bg: yellow,
+        math_fg: purple,
         title_fg: blue,
         title_bar_bg: bg2,
         status_bar_bg: bg2,
@@ -1195,5 +1559,7 @@ pub fn sunset_light() -> Theme {
         selection_bg: bg3,
         find_match_bg: bg4,
         find_current_bg: Color::Rgb(255, 192, 154), // #ffc09a
+        icons: Icons::NERD_FONT,
+        borders: border::ROUNDED,
     }
 }
@@ -0,0 +1,100 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+use crate::ui::theme::{BUILTIN_THEMES, Theme};
+
+/// Themes to choose from. The highlighted one is applied as a preview;
+/// `original` is put back if the picker is cancelled.
+pub struct ThemePickerState {
+    pub names: Vec<String>,
+    pub selected: usize,
+    pub original: Theme,
+    list_state: ListState,
+}
+
+impl ThemePickerState {
+    pub fn new(current: &str, original: Theme) -> Self {
+        let names = Theme::available();
+        let selected = names.iter().position(|name| name == current).unwrap_or(0);
+        let mut list_state = ListState::default();
+        list_state.select(Some(selected));
+
+        Self {
+            names,
+            selected,
+            original,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.names.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_name(&self) -> Option<&str> {
+        self.names.get(self.selected).map(String::as_str)
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &ThemePickerState, t: &Theme) {
+    let popup_width = 36.min(area.width.saturating_sub(4));
+    let popup_height = (state.names.len() as u16 + 2).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Theme ")
+        .title_bottom(Line::from(Span::styled(
+            " Enter: keep  Esc: revert ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    let items: Vec<ListItem> = state
+        .names
+        .iter()
+        .map(|name| {
+            let mut spans = vec![Span::styled(
+                format!("  {}", name),
+                Style::default().fg(t.fg1),
+            )];
+            // Theme files are marked so they aren't mistaken for built-ins
+            if !BUILTIN_THEMES.contains(&name.as_str()) {
+                spans.push(Span::styled(
+                    "  (custom)",
+                    Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(t.selection_style());
+
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
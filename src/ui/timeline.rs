@@ -0,0 +1,181 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::core::Vault;
+use crate::ui::theme::Theme;
+
+enum TimelineRow {
+    Header(String),
+    Note {
+        path: PathBuf,
+        title: String,
+        time: String,
+    },
+}
+
+/// Notes ordered by modification time and grouped by day, newest first.
+pub struct TimelineState {
+    rows: Vec<TimelineRow>,
+    pub selected: usize,
+    list_state: ListState,
+}
+
+impl TimelineState {
+    pub fn new(vault: &Vault) -> Self {
+        let mut notes: Vec<_> = vault.notes.values().collect();
+        notes.sort_by_key(|note| std::cmp::Reverse(note.modified));
+
+        let today = Local::now().date_naive();
+        let mut rows = Vec::new();
+        let mut current_group = None;
+
+        for note in notes {
+            let modified: DateTime<Local> = note.modified.into();
+            let group = group_label(modified.date_naive(), today);
+            if current_group.as_ref() != Some(&group) {
+                rows.push(TimelineRow::Header(group.clone()));
+                current_group = Some(group);
+            }
+
+            let time = if modified.date_naive() >= today - chrono::Days::new(6) {
+                modified.format("%a %H:%M").to_string()
+            } else {
+                modified.format("%b %d").to_string()
+            };
+            rows.push(TimelineRow::Note {
+                path: note.path.clone(),
+                title: note.title.clone(),
+                time,
+            });
+        }
+
+        let selected = rows
+            .iter()
+            .position(|row| matches!(row, TimelineRow::Note { .. }))
+            .unwrap_or(0);
+        let mut list_state = ListState::default();
+        list_state.select(Some(selected));
+
+        Self {
+            rows,
+            selected,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if let Some(next) = (self.selected + 1..self.rows.len()).find(|&i| self.is_note(i)) {
+            self.select(next);
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if let Some(prev) = (0..self.selected).rev().find(|&i| self.is_note(i)) {
+            self.select(prev);
+        }
+    }
+
+    pub fn selected_path(&self) -> Option<&PathBuf> {
+        match self.rows.get(self.selected)? {
+            TimelineRow::Note { path, .. } => Some(path),
+            TimelineRow::Header(_) => None,
+        }
+    }
+
+    fn is_note(&self, index: usize) -> bool {
+        matches!(self.rows.get(index), Some(TimelineRow::Note { .. }))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.list_state.select(Some(index));
+    }
+}
+
+/// "Today", "Yesterday", the weekday within the past week, then month names.
+fn group_label(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (today - date).num_days();
+    match days {
+        ..=0 => "Today".to_string(),
+        1 => "Yesterday".to_string(),
+        2..=6 => date.format("%A").to_string(),
+        7..=13 => "Last week".to_string(),
+        _ if (date.year(), date.month()) == (today.year(), today.month()) => {
+            "Earlier this month".to_string()
+        }
+        _ => date.format("%B %Y").to_string(),
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &TimelineState, t: &Theme) {
+    let popup_width = 60u16.min(area.width.saturating_sub(4));
+    let popup_height = (area.height * 3 / 4)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Timeline ")
+        .title_bottom(Line::from(Span::styled(
+            " Enter: open  Esc: close ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )))
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    if state.rows.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            "No notes yet",
+            Style::default().fg(t.empty_hint),
+        )))
+        .block(block);
+        frame.render_widget(empty, popup_area);
+        return;
+    }
+
+    let inner_width = popup_width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = state
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            TimelineRow::Header(label) => ListItem::new(Line::from(Span::styled(
+                label.clone(),
+                Style::default().fg(t.aqua).add_modifier(Modifier::BOLD),
+            ))),
+            TimelineRow::Note { title, time, .. } => {
+                let style = if i == state.selected {
+                    t.selection_style()
+                } else {
+                    Style::default().fg(t.fg1)
+                };
+                let name = format!("  {}{}", t.icons.file, title);
+                let pad = inner_width.saturating_sub(name.chars().count() + time.len() + 1);
+                ListItem::new(Line::from(vec![
+                    Span::styled(name, style),
+                    Span::styled(" ".repeat(pad), style),
+                    Span::styled(format!("{} ", time), Style::default().fg(t.fg4)),
+                ]))
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(block);
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap},
+};
+
+use crate::ui::theme::Theme;
+
+/// At most this many toasts are kept; older ones make way for new ones.
+const MAX_TOASTS: usize = 4;
+const TOAST_WIDTH: u16 = 44;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl ToastLevel {
+    /// Errors stay up longest, since they usually need acting on.
+    fn duration(self) -> Duration {
+        match self {
+            ToastLevel::Info => Duration::from_secs(3),
+            ToastLevel::Warn => Duration::from_secs(5),
+            ToastLevel::Error => Duration::from_secs(8),
+        }
+    }
+
+    fn color(self, t: &Theme) -> Color {
+        match self {
+            ToastLevel::Info => t.aqua,
+            ToastLevel::Warn => t.yellow,
+            ToastLevel::Error => t.red,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ToastLevel::Info => "Info",
+            ToastLevel::Warn => "Warning",
+            ToastLevel::Error => "Error",
+        }
+    }
+}
+
+struct Toast {
+    level: ToastLevel,
+    message: String,
+    expires: Instant,
+}
+
+/// Results of saves, exports and other work that finishes on its own,
+/// shown in the bottom-right corner until they time out. Unlike the status
+/// message they survive key presses.
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows `message`, replacing an identical toast still on screen so a
+    /// repeated result restarts its timer instead of stacking up.
+    pub fn push(&mut self, level: ToastLevel, message: impl Into<String>) {
+        let message = message.into();
+        self.queue
+            .retain(|toast| toast.level != level || toast.message != message);
+        if self.queue.len() == MAX_TOASTS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Toast {
+            level,
+            message,
+            expires: Instant::now() + level.duration(),
+        });
+    }
+
+    /// Drops expired toasts, returning whether any went.
+    pub fn prune(&mut self) -> bool {
+        let now = Instant::now();
+        let before = self.queue.len();
+        self.queue.retain(|toast| toast.expires > now);
+        self.queue.len() != before
+    }
+
+    /// How long until the next toast expires, if any are showing.
+    pub fn next_expiry(&self) -> Option<Duration> {
+        self.queue
+            .iter()
+            .map(|toast| toast.expires.saturating_duration_since(Instant::now()))
+            .min()
+    }
+}
+
+/// Stacks the toasts up from just inside the bottom-right corner of `area`,
+/// newest at the bottom.
+pub fn render(frame: &mut Frame, area: Rect, toasts: &Toasts, t: &Theme) {
+    let max_width = TOAST_WIDTH.min(area.width.saturating_sub(4));
+    let mut bottom = (area.y + area.height).saturating_sub(1);
+
+    for toast in toasts.queue.iter().rev() {
+        // Short messages get a snug box, long ones wrap
+        let width = (toast.message.chars().count() as u16 + 4)
+            .max(toast.level.label().len() as u16 + 4)
+            .min(max_width);
+        let inner_width = width.saturating_sub(4).max(1) as usize;
+        let rows = toast.message.chars().count().div_ceil(inner_width).max(1) as u16;
+        let height = rows + 2;
+        if bottom < area.y + height {
+            break;
+        }
+        bottom -= height;
+        let toast_area = Rect::new(
+            area.x + area.width.saturating_sub(width + 2),
+            bottom,
+            width,
+            height,
+        );
+
+        let color = toast.level.color(t);
+        let block = Block::default()
+            .title(Span::styled(
+                format!(" {} ", toast.level.label()),
+                Style::default().fg(color),
+            ))
+            .borders(Borders::ALL)
+            .border_set(t.borders)
+            .border_style(Style::default().fg(color))
+            .style(Style::default().bg(t.bg0));
+        let message = Paragraph::new(Line::from(Span::styled(
+            toast.message.as_str(),
+            Style::default().fg(t.fg1),
+        )))
+        .block(block.padding(Padding::horizontal(1)))
+        .wrap(Wrap { trim: true });
+
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(message, toast_area);
+    }
+}
@@ -1,57 +1,150 @@
 use ratatui::{
     Frame,
-    layout::Rect,
-    style::{Modifier, Style},
+    buffer::{Buffer, Cell},
+    layout::{Margin, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Widget, Wrap,
+    },
 };
+use unicode_width::UnicodeWidthStr;
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
 
 use super::find_in_note::FindInNoteState;
-use super::viewer_state::{AutocompleteState, EditorMode, ViewerState};
+use super::image::{self, ImagePlacement};
+use super::math;
+use super::source;
+use super::viewer_state::{AutocompleteState, CompletionKind, EditorMode, Position, ViewerState};
 use crate::app::App;
+use crate::config::PaneChrome;
 use crate::core::Note;
+use crate::core::bibtex;
+use crate::core::frontmatter;
+use crate::core::note::{self, mask_comments};
+use crate::core::spell::{self, SpellChecker};
 use crate::ui::layout::Focus;
-use crate::ui::theme::{self, Theme};
+use crate::ui::theme::Theme;
 
 pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+    let chrome = app.config.ui.pane("viewer");
+    let inner = content_area(area, chrome.padding);
     // Store viewer area height for scroll-follow in input handler
-    // Inner height = area height minus 2 for borders
-    app.viewer_area_height = area.height.saturating_sub(2);
-    let t = &app.theme;
+    app.viewer_area_height = inner.height;
+    // Up/Down step through wrapped lines a screen row at a time
+    app.viewer_state.wrap_width = inner.width as usize;
     let is_focused = app.focus == Focus::Viewer;
 
-    let mode_indicator = match app.viewer_state.mode {
-        EditorMode::Read => " Preview ".to_string(),
-        EditorMode::Edit => {
-            if app.viewer_state.dirty {
-                format!(" {}EDIT [modified] ", theme::ICON_EDIT)
-            } else {
-                format!(" {}EDIT ", theme::ICON_EDIT)
-            }
+    // A history diff replaces the preview until dismissed
+    let showing_diff =
+        app.viewer_state.mode == EditorMode::Read && app.active_history_diff().is_some();
+    let images = if app.viewer_state.mode == EditorMode::Read && !showing_diff {
+        embedded_images(app, inner.width)
+    } else {
+        Vec::new()
+    };
+
+    let t = &app.theme;
+    let diff = app
+        .active_history_diff()
+        .filter(|_| app.viewer_state.mode == EditorMode::Read);
+
+    let view = if app.raw_source { "Source" } else { "Preview" };
+    let unsaved = if app.buffer_unsaved() {
+        format!(" {}", t.icons.unsaved)
+    } else {
+        String::new()
+    };
+    let mode_indicator = match (app.viewer_state.mode, diff) {
+        (EditorMode::Read, Some(d)) => format!(
+            " History: {} ({}) vs working copy ",
+            d.revision.short_hash, d.revision.date
+        ),
+        // Hint that PageUp/PageDown scroll this pane from another one
+        (EditorMode::Read, None) if !is_focused => {
+            format!(" {}{} {}", view, unsaved, t.icons.scroll)
         }
+        (EditorMode::Read, None) => format!(" {}{} ", view, unsaved),
+        (EditorMode::Edit, _) => format!(" {}EDIT{} ", t.icons.edit, unsaved),
     };
 
-    let block = Block::default()
-        .title(mode_indicator)
-        .borders(Borders::ALL)
-        .border_type(theme::border_type())
-        .border_style(t.border_style(is_focused));
+    let mode_indicator = if app.viewer_state.in_table() {
+        format!("{}{}table ", mode_indicator, t.icons.table)
+    } else {
+        mode_indicator
+    };
+
+    let mode_indicator = if diff.is_none() && app.is_following() {
+        format!("{}{}follow ", mode_indicator, t.icons.follow)
+    } else {
+        mode_indicator
+    };
 
-    let content = if let Some(note) = app.selected_note() {
+    let locked = diff.is_none() && app.selected_note().is_some_and(|note| note.locked);
+    let mode_indicator = if locked {
+        format!("{}{}read-only ", mode_indicator, t.icons.lock)
+    } else {
+        mode_indicator
+    };
+
+    // Edit, history, lock and follow indicators show even when pane titles are off
+    let chrome = PaneChrome {
+        title: chrome.title
+            || diff.is_some()
+            || locked
+            || app.is_following()
+            || app.viewer_state.mode == EditorMode::Edit,
+        ..chrome
+    };
+    let block = t.pane_block(Some(mode_indicator), chrome, is_focused);
+
+    // Notes only build the lines in view; anything else scrolls as a whole
+    let mut window = Window::whole(app.viewer_scroll);
+    let mut total_rows = None;
+    let content = if let Some(diff) = diff {
+        diff.render(t)
+    } else if app.selected_note().is_some() {
         match app.viewer_state.mode {
             EditorMode::Read => {
-                let read_cursor_line = app.viewer_state.read_cursor.line;
-                render_markdown(
-                    note,
-                    &app.viewer_state,
-                    &app.vault,
-                    t,
-                    read_cursor_line,
+                let text = read_text(app).unwrap_or_default();
+                window = Window::read(&text, &images, app.viewer_scroll, inner);
+                total_rows = Some(read_heights(&text, &images, inner.width as usize).sum());
+                render_read(
+                    app,
+                    &text,
+                    window.lines(),
+                    inner.width as usize,
+                    &images,
                     app.find_in_note_state.as_ref(),
+                    app.spell.as_ref(),
                 )
             }
-            EditorMode::Edit => render_edit_mode(&app.viewer_state, t),
+            EditorMode::Edit => {
+                window = Window::edit(&app.viewer_state, app.viewer_scroll, inner);
+                total_rows = Some(edit_heights(&app.viewer_state, inner.width as usize).sum());
+                render_edit_mode(&app.viewer_state, window.lines(), app.spell.as_ref(), t)
+            }
         }
+    } else if let Some(entry) = app.selected_attachment() {
+        // Text files show read-only; anything else just names the viewer
+        let path = app.vault.root.join(&entry.path);
+        let name = entry.name.clone();
+        app.text_preview.render(&path, t).unwrap_or_else(|| {
+            Text::from(vec![
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("  {}{}", t.icons.attachment, name),
+                    Style::default().fg(t.fg1),
+                )),
+                Line::from(Span::styled(
+                    format!("  Press Enter to open with {}", app.config.editor.viewer),
+                    Style::default().fg(t.empty_hint),
+                )),
+            ])
+        })
     } else {
         Text::from(vec![
             Line::from(""),
@@ -62,25 +155,82 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
         ])
     };
 
-    let paragraph = Paragraph::new(content)
-        .block(block)
-        .wrap(Wrap { trim: false })
-        .scroll((app.viewer_scroll, 0));
+    let placements = place_images(&content, &window, &images, inner);
+    app.image_preview.placements = if app.has_overlay() || app.hover_preview.ready().is_some() {
+        Vec::new()
+    } else {
+        placements
+    };
+
+    let mut paragraph = Paragraph::new(content).wrap(Wrap { trim: false });
+    let total_rows = total_rows.unwrap_or_else(|| paragraph.line_count(inner.width));
+    let max_scroll = total_rows.saturating_sub(inner.height as usize);
+    let scroll = (app.viewer_scroll as usize).min(max_scroll);
+    let block = match scroll_position(scroll, max_scroll) {
+        Some(position) => block.title_top(
+            Line::from(Span::styled(
+                format!(" {} ", position),
+                Style::default().fg(t.fg4),
+            ))
+            .right_aligned(),
+        ),
+        None => block,
+    };
+    let block = if app.disk_changed {
+        let name = app
+            .viewer_state
+            .current_note_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .unwrap_or_default()
+            .to_string_lossy();
+        block.title_bottom(Span::styled(
+            format!(
+                " {} changed on disk; saving will ask to overwrite or reload ",
+                name
+            ),
+            Style::default().fg(t.red).add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        block
+    };
+    paragraph = paragraph.block(block).scroll((window.offset, 0));
 
     frame.render_widget(paragraph, area);
 
+    // A thumb over the right border shows where the view is in a long note
+    if max_scroll > 0 {
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some(t.borders.vertical_right))
+            .track_style(t.border_style(is_focused))
+            .thumb_symbol("┃")
+            .thumb_style(Style::default().fg(t.fg4));
+        let mut state = ScrollbarState::new(max_scroll)
+            .position(scroll)
+            .viewport_content_length(inner.height as usize);
+        frame.render_stateful_widget(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut state,
+        );
+    }
+
     // Render autocomplete popup if active
-    if app.viewer_state.mode == EditorMode::Edit {
-        if let Some(ref ac) = app.viewer_state.autocomplete {
-            if !ac.matches.is_empty() {
-                render_autocomplete(frame, area, ac, &app.viewer_state, t);
-            }
-        }
+    if app.viewer_state.mode == EditorMode::Edit
+        && let Some(ref ac) = app.viewer_state.autocomplete
+        && !ac.matches.is_empty()
+    {
+        render_autocomplete(frame, inner, ac, &app.viewer_state, t);
     }
 
     // Set cursor position in EDIT mode, accounting for soft wrapping
     if is_focused && app.viewer_state.mode == EditorMode::Edit {
-        let inner_width = area.width.saturating_sub(2) as usize; // minus borders
+        let inner_width = inner.width as usize;
         if inner_width > 0 {
             let scroll = app.viewer_scroll as usize;
 
@@ -107,66 +257,170 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
             // Subtract visual scroll offset
             let visible_y = visual_y.saturating_sub(scroll);
 
-            let x = area.x + 1 + visual_x as u16;
-            let y = area.y + 1 + visible_y as u16;
+            let x = inner.x + visual_x as u16;
+            let y = inner.y + visible_y as u16;
 
-            if y >= area.y + 1 && y < area.y + area.height - 1 {
+            if y < inner.bottom() {
                 frame.set_cursor_position((x, y));
             }
         }
     }
 }
 
-fn render_edit_mode(viewer_state: &ViewerState, t: &Theme) -> Text<'static> {
+/// How far down a view that scrolls up to `max_scroll` rows is, as `Top`,
+/// `Bot` or a percentage, or `None` when everything fits.
+fn scroll_position(scroll: usize, max_scroll: usize) -> Option<String> {
+    match scroll {
+        _ if max_scroll == 0 => None,
+        0 => Some("Top".to_string()),
+        s if s >= max_scroll => Some("Bot".to_string()),
+        s => Some(format!("{}%", s * 100 / max_scroll)),
+    }
+}
+
+/// Where the text goes inside a viewer pane drawn over `area`: within the
+/// border and `padding` columns in from each side.
+pub fn content_area(area: Rect, padding: u16) -> Rect {
+    let inset = (1 + padding).min(area.width / 2);
+    Rect::new(
+        area.x + inset,
+        area.y + 1.min(area.height),
+        area.width - inset * 2,
+        area.height.saturating_sub(2),
+    )
+}
+
+/// Draws the note pinned in the split beside the preview.
+pub fn render_split(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(ref split) = app.split else {
+        return;
+    };
+    let t = &app.theme;
+    let note = app.vault.get_note(&split.path);
+
+    let title = match note {
+        Some(note) => format!(" {}{} ", t.icons.file, note.title),
+        None => " Split ".to_string(),
+    };
+    let block = t
+        .pane_block(Some(title), app.config.ui.pane("viewer"), false)
+        .title_bottom(Line::from(Span::styled(
+            " Ctrl+w: switch ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )));
+
+    let inner = content_area(area, app.config.ui.pane("viewer").padding);
+    let mut window = Window::whole(split.scroll);
+    let content = match note {
+        Some(note) => {
+            let text = if app.config.ui.show_comments {
+                note.content().to_string()
+            } else {
+                mask_comments(&note.content())
+            };
+            window = Window::read(&text, &[], split.scroll, inner);
+            render_markdown(
+                note,
+                &text,
+                window.lines(),
+                inner.width as usize,
+                &split.viewer_state,
+                &app.vault,
+                t,
+                None,
+                &[],
+                app.spell.as_ref(),
+            )
+        }
+        None => Text::from(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "  This note no longer exists",
+                Style::default().fg(t.empty_hint),
+            )),
+        ]),
+    };
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((window.offset, 0));
+    frame.render_widget(paragraph, area);
+}
+
+/// Builds the buffer lines in `range` for EDIT mode.
+pub(super) fn render_edit_mode(
+    viewer_state: &ViewerState,
+    range: Range<usize>,
+    spell: Option<&SpellChecker>,
+    t: &Theme,
+) -> Text<'static> {
     let mut lines: Vec<Line<'static>> = Vec::new();
     let has_selection = viewer_state.selection.is_some();
+    let end = range.end.min(viewer_state.content.len_lines());
 
-    for line_idx in 0..viewer_state.content.len_lines() {
+    // Whether the first line in view sits inside a code block
+    let mut in_fence = spell.is_some()
+        && (0..range.start.min(end))
+            .filter(|&i| is_fence_line(viewer_state.content.line(i).chars()))
+            .count()
+            % 2
+            == 1;
+
+    for line_idx in range.start.min(end)..end {
         let line_text = viewer_state.content.line(line_idx).to_string();
 
-        if has_selection {
-            // Render with per-character selection highlighting
-            let chars: Vec<char> = line_text.chars().collect();
-            let mut spans: Vec<Span<'static>> = Vec::new();
-            let mut current = String::new();
-            let mut in_selection = false;
-
-            for (col, &ch) in chars.iter().enumerate() {
-                let selected = viewer_state.is_char_selected(line_idx, col);
-                if selected != in_selection {
-                    // Flush current span
-                    if !current.is_empty() {
-                        let style = if in_selection {
-                            Style::default().bg(t.selection_bg)
-                        } else {
-                            Style::default()
-                        };
-                        spans.push(Span::styled(current.clone(), style));
-                        current.clear();
-                    }
-                    in_selection = selected;
-                }
-                current.push(ch);
+        // Code blocks aren't spell checked
+        let is_fence = line_text.trim_start().starts_with("```");
+        let misspelled = match spell {
+            Some(spell) if !in_fence && !is_fence => spell.misspelled(&line_text),
+            _ => Vec::new(),
+        };
+        in_fence ^= is_fence;
+
+        if !has_selection && misspelled.is_empty() {
+            lines.push(Line::from(line_text));
+            continue;
+        }
+
+        // Render with per-character selection and misspelling styles
+        let char_style = |col: usize| {
+            let mut style = Style::default();
+            if viewer_state.is_char_selected(line_idx, col) {
+                style = style.bg(t.selection_bg);
             }
-            // Flush remaining
-            if !current.is_empty() {
-                let style = if in_selection {
-                    Style::default().bg(t.selection_bg)
-                } else {
-                    Style::default()
-                };
-                spans.push(Span::styled(current, style));
+            if misspelled.iter().any(|range| range.contains(&col)) {
+                style = style.patch(t.misspelled_style());
             }
-            lines.push(Line::from(spans));
-        } else {
-            lines.push(Line::from(line_text));
+            style
+        };
+
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut current = String::new();
+        let mut current_style = Style::default();
+
+        for (col, ch) in line_text.chars().enumerate() {
+            let style = char_style(col);
+            if style != current_style {
+                // Flush current span
+                if !current.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut current), current_style));
+                }
+                current_style = style;
+            }
+            current.push(ch);
         }
+        // Flush remaining
+        if !current.is_empty() {
+            spans.push(Span::styled(current, current_style));
+        }
+        lines.push(Line::from(spans));
     }
 
     Text::from(lines)
 }
 
-fn render_autocomplete(
+pub(super) fn render_autocomplete(
     frame: &mut Frame,
     area: Rect,
     ac: &AutocompleteState,
@@ -187,12 +441,14 @@ fn render_autocomplete(
     let cursor_x = ac.trigger_pos.col + 2; // After [[
 
     let popup_height = (ac.matches.len() + 2).min(12) as u16;
-    let popup_width = 30;
+    let (popup_width, title) = match ac.kind {
+        CompletionKind::Note => (30, "Notes"),
+        CompletionKind::Citation => (44, "Citations"),
+    };
 
     // Position popup near cursor, but keep it within bounds
-    let popup_x = (area.x + 1 + cursor_x as u16).min(area.width.saturating_sub(popup_width + 2));
-    let popup_y =
-        (area.y + 1 + cursor_y as u16 + 1).min(area.height.saturating_sub(popup_height + 1));
+    let popup_x = (area.x + cursor_x as u16).min(area.right().saturating_sub(popup_width));
+    let popup_y = (area.y + cursor_y as u16 + 1).min(area.bottom().saturating_sub(popup_height));
 
     let popup_area = Rect {
         x: popup_x,
@@ -205,8 +461,8 @@ fn render_autocomplete(
         .matches
         .iter()
         .enumerate()
-        .map(|(i, (_, name)): (usize, &(std::path::PathBuf, String))| {
-            let style = if i == ac.selected {
+        .map(|(i, completion)| {
+            let mut style = if i == ac.selected {
                 Style::default()
                     .fg(t.selected_fg)
                     .bg(t.autocomplete_sel_bg)
@@ -214,9 +470,15 @@ fn render_autocomplete(
             } else {
                 Style::default().fg(t.fg1)
             };
+            // Aliases are set apart from titles
+            if completion.is_alias {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
 
-            let display = if name.len() > popup_width as usize - 4 {
-                format!("{}...", &name[..popup_width as usize - 7])
+            let name = &completion.label;
+            let max = popup_width as usize - 4;
+            let display = if name.chars().count() > max {
+                format!("{}...", name.chars().take(max - 3).collect::<String>())
             } else {
                 name.clone()
             };
@@ -228,52 +490,816 @@ fn render_autocomplete(
     let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
-            .border_type(theme::border_type())
+            .border_set(t.borders)
             .border_style(Style::default().fg(t.border_overlay))
-            .title(format!(" Notes ({}) ", ac.matches.len()))
+            .title(format!(" {} ({}) ", title, ac.matches.len()))
             .style(Style::default().bg(t.autocomplete_bg)),
     );
 
     frame.render_widget(list, popup_area);
 }
 
+/// An image embed on its own line of the previewed note.
+struct EmbeddedImage {
+    line_idx: usize,
+    alt: String,
+    path: Option<PathBuf>,
+    size: Option<(u16, u16)>, // (cols, rows) when the terminal can draw it
+}
+
+/// Finds the image embeds in the selected note and sizes the drawable ones.
+fn embedded_images(app: &mut App, max_cols: u16) -> Vec<EmbeddedImage> {
+    let Some(note) = app.selected_note().filter(|_| !app.raw_source) else {
+        return Vec::new();
+    };
+    let note_path = note.path.clone();
+    let embeds: Vec<(usize, String, String)> = note
+        .content()
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| image::image_embed(line).map(|(alt, target)| (i, alt, target)))
+        .collect();
+
+    embeds
+        .into_iter()
+        .map(|(line_idx, alt, target)| {
+            let path = app.image_preview.resolve(&app.vault, &note_path, &target);
+            let size = path
+                .as_deref()
+                .filter(|p| app.image_preview.can_draw(p))
+                .map(|p| app.image_preview.fit(p, max_cols));
+            EmbeddedImage {
+                line_idx,
+                alt,
+                path,
+                size,
+            }
+        })
+        .collect()
+}
+
+/// The note line drawn `row` rows below the top of the preview in READ
+/// mode, accounting for soft wrapping and the rows reserved for images.
+pub fn read_line_at(app: &mut App, row: u16) -> Option<usize> {
+    read_row_at(app, row).map(|(line, _)| line)
+}
+
+/// The note position under a cell of the preview in READ mode: the cell is
+/// traced back through the line's word wrapping and the markup the preview
+/// hides to the character it was drawn from.
+pub fn read_position_at(app: &mut App, row: u16, col: u16) -> Option<Position> {
+    let (line, wrapped_row) = read_row_at(app, row)?;
+    let col = source_col_at(&read_layout(app, line), wrapped_row, col);
+    Some(Position { line, col })
+}
+
+/// The READ cursor moved one screen row down (or up), by the rows the
+/// preview draws: lines word-wrapped with their markup hidden. The cursor
+/// keeps its screen column as far as the row allows.
+pub fn read_row_step(app: &mut App, down: bool) -> Position {
+    let cursor = app.viewer_state.read_cursor.clone();
+    let layout = read_layout(app, cursor.line);
+    let (row, x) = cell_of(&layout, cursor.col);
+    let rows = layout.last().map_or(1, |c| c.row + 1);
+
+    let target = if down {
+        if row + 1 < rows {
+            Some((cursor.line, layout, row + 1))
+        } else if cursor.line + 1 < app.viewer_state.content.len_lines() {
+            Some((cursor.line + 1, read_layout(app, cursor.line + 1), 0))
+        } else {
+            None
+        }
+    } else if row > 0 {
+        Some((cursor.line, layout, row - 1))
+    } else if cursor.line > 0 {
+        let above = read_layout(app, cursor.line - 1);
+        let last = above.last().map_or(0, |c| c.row);
+        Some((cursor.line - 1, above, last))
+    } else {
+        None
+    };
+
+    match target {
+        Some((line, layout, row)) => app.viewer_state.clamp_position(&Position {
+            line,
+            col: source_col_at(&layout, row, x),
+        }),
+        None => cursor,
+    }
+}
+
+/// The source column drawn at `x` on wrapped row `row` of a laid-out line.
+fn source_col_at(layout: &[LaidOutChar], row: usize, x: u16) -> usize {
+    let on_row: Vec<&LaidOutChar> = layout.iter().filter(|c| c.row == row).collect();
+    match on_row.iter().rev().find(|c| c.x <= x) {
+        Some(c) if x < c.x + c.width => c.source_col,
+        // Right of the row's text
+        Some(c) => c.source_col + 1,
+        // Left of it, or a row with nothing drawn
+        None => on_row.first().map_or(0, |c| c.source_col),
+    }
+}
+
+/// The wrapped row and screen column source column `col` is drawn at: the
+/// cell of the last character drawn from at or before it, or just past the
+/// line's text when `col` is beyond everything drawn.
+fn cell_of(layout: &[LaidOutChar], col: usize) -> (usize, u16) {
+    let Some(last) = layout.last() else {
+        return (0, 0);
+    };
+    if col > last.source_col {
+        return (last.row, last.x + last.width);
+    }
+    match layout.iter().rev().find(|c| c.source_col <= col) {
+        Some(c) => (c.row, c.x),
+        None => (0, layout[0].x),
+    }
+}
+
+/// The note line drawn `row` rows below the top of the preview in READ
+/// mode, and which of its wrapped rows that is.
+fn read_row_at(app: &mut App, row: u16) -> Option<(usize, usize)> {
+    let width = app.viewer_content_area().width;
+    let images = embedded_images(app, width);
+    let inner = app.viewer_content_area();
+    let text = read_text(app)?;
+    let window = Window::read(&text, &images, app.viewer_scroll, inner);
+    let content = render_read(
+        app,
+        &text,
+        window.lines(),
+        width as usize,
+        &images,
+        None,
+        None,
+    );
+
+    // The note line each rendered line belongs to
+    let mut owners = Vec::with_capacity(content.lines.len());
+    for line_idx in window.lines() {
+        owners.push(line_idx);
+        if let Some((_, rows)) = images
+            .iter()
+            .find(|image| image.line_idx == line_idx)
+            .and_then(|image| image.size)
+        {
+            owners.extend((0..rows).map(|_| line_idx));
+        }
+    }
+
+    let target = window.offset as usize + row as usize;
+    let mut top = 0;
+    let mut line_top = 0;
+    let mut previous = None;
+    for (line, owner) in content.lines.into_iter().zip(owners) {
+        // An image's reserved rows count as rows of its line
+        if previous != Some(owner) {
+            line_top = top;
+            previous = Some(owner);
+        }
+        top += Paragraph::new(line)
+            .wrap(Wrap { trim: false })
+            .line_count(width);
+        if target < top {
+            return Some((owner, target - line_top));
+        }
+    }
+    None
+}
+
+/// A character of a line as READ mode draws it.
+struct LaidOutChar {
+    /// Which of the line's wrapped rows it is on
+    row: usize,
+    x: u16,
+    width: u16,
+    /// The column in the note text it was drawn from
+    source_col: usize,
+}
+
+/// Where READ mode draws each character of note line `line_idx`, in
+/// reading order. The line is wrapped exactly as the preview wraps it.
+fn read_layout(app: &mut App, line_idx: usize) -> Vec<LaidOutChar> {
+    // Drawn into cells that start out holding this, to tell text from padding
+    const UNWRITTEN: &str = "\u{E000}";
+
+    let width = app.viewer_content_area().width.max(1);
+    let images = embedded_images(app, width);
+    let Some(text) = read_text(app) else {
+        return Vec::new();
+    };
+    let Some(source) = text.lines().nth(line_idx) else {
+        return Vec::new();
+    };
+    let rendered = render_read(
+        app,
+        &text,
+        line_idx..line_idx + 1,
+        width as usize,
+        &images,
+        None,
+        None,
+    );
+    let Some(line) = rendered.lines.into_iter().next() else {
+        return Vec::new();
+    };
+
+    let paragraph = Paragraph::new(line).wrap(Wrap { trim: false });
+    let rows = paragraph.line_count(width).max(1) as u16;
+    let area = Rect::new(0, 0, width, rows);
+    let mut buffer = Buffer::filled(area, Cell::new(UNWRITTEN));
+    paragraph.render(area, &mut buffer);
+
+    let mut cells = Vec::new();
+    let mut drawn = Vec::new();
+    for y in 0..rows {
+        for x in 0..width {
+            let symbol = buffer[(x, y)].symbol();
+            if symbol == UNWRITTEN {
+                continue;
+            }
+            if let Some(c) = symbol.chars().next() {
+                cells.push((y as usize, x, symbol.width().max(1) as u16));
+                drawn.push(c);
+            }
+        }
+    }
+
+    let source: Vec<char> = source.chars().collect();
+    cells
+        .into_iter()
+        .zip(align_to_source(&source, &drawn))
+        .map(|((row, x, width), source_col)| LaidOutChar {
+            row,
+            x,
+            width,
+            source_col,
+        })
+        .collect()
+}
+
+/// The column of `source` each character of `drawn` comes from, matching
+/// the two up along their longest common subsequence. Characters only in
+/// `drawn`, such as list bullets, take the column of the next source
+/// character; markup only in `source` is skipped.
+fn align_to_source(source: &[char], drawn: &[char]) -> Vec<usize> {
+    // Past this the table gets big, so columns are simply paired up
+    const MAX_CELLS: usize = 1 << 20;
+
+    let (n, m) = (source.len(), drawn.len());
+    if (n + 1) * (m + 1) > MAX_CELLS {
+        return (0..m).map(|j| j.min(n)).collect();
+    }
+
+    // common[i * (m + 1) + j]: longest common subsequence of source[i..] and drawn[j..]
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    let mut common = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[at(i, j)] = if source[i] == drawn[j] {
+                common[at(i + 1, j + 1)] + 1
+            } else {
+                common[at(i + 1, j)].max(common[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut cols = Vec::with_capacity(m);
+    let (mut i, mut j) = (0, 0);
+    while j < m {
+        if i < n && source[i] == drawn[j] && common[at(i, j)] == common[at(i + 1, j + 1)] + 1 {
+            cols.push(i);
+            i += 1;
+            j += 1;
+        } else if i < n && common[at(i + 1, j)] >= common[at(i, j + 1)] {
+            i += 1;
+        } else {
+            cols.push(i);
+            j += 1;
+        }
+    }
+    cols
+}
+
+/// The buffer position under a cell of the preview in EDIT mode, using the
+/// same character-count wrapping as the cursor.
+pub fn edit_position_at(app: &App, row: u16, col: u16) -> Position {
+    let editor = &app.viewer_state;
+    let width = app.viewer_content_area().width.max(1) as usize;
+    let target = app.viewer_scroll as usize + row as usize;
+
+    let mut top = 0;
+    for line_idx in 0..editor.content.len_lines() {
+        let line = editor.content.line(line_idx);
+        let len = match line.len_chars() {
+            n if n > 0 && line.char(n - 1) == '\n' => n - 1,
+            n => n,
+        };
+        let rows = visual_lines_for_width(len, width);
+        if target < top + rows {
+            let col = (target - top) * width + col as usize;
+            return Position {
+                line: line_idx,
+                col: col.min(len),
+            };
+        }
+        top += rows;
+    }
+
+    let last = editor.content.len_lines().saturating_sub(1);
+    editor.clamp_position(&Position {
+        line: last,
+        col: usize::MAX,
+    })
+}
+
+/// Screen rectangles for images whose reserved rows are fully in view.
+fn place_images(
+    content: &Text,
+    window: &Window,
+    images: &[EmbeddedImage],
+    inner: Rect,
+) -> Vec<ImagePlacement> {
+    let inner_width = inner.width;
+    let inner_height = inner.height as usize;
+    let mut placements = Vec::new();
+    let mut reserved = 0;
+
+    for image in images {
+        let (Some(path), Some((cols, rows))) = (&image.path, image.size) else {
+            continue;
+        };
+        // Images scrolled past aren't part of the rendered window
+        if image.line_idx < window.first {
+            continue;
+        }
+        // Reserved rows follow the placeholder line
+        let first_row = image.line_idx - window.first + reserved + 1;
+        reserved += rows as usize;
+
+        let before = Paragraph::new(Text::from(
+            content.lines[..first_row.min(content.lines.len())].to_vec(),
+        ))
+        .wrap(Wrap { trim: false })
+        .line_count(inner_width);
+        let Some(top) = before.checked_sub(window.offset as usize) else {
+            continue;
+        };
+        if top + rows as usize > inner_height {
+            continue;
+        }
+
+        placements.push(ImagePlacement {
+            path: path.clone(),
+            x: inner.x,
+            y: inner.y + top as u16,
+            cols,
+            rows,
+        });
+    }
+
+    placements
+}
+
+/// Rows each line of a note takes in READ mode, counting the rows reserved
+/// for images below their placeholder.
+fn read_heights<'a>(
+    text: &'a str,
+    images: &'a [EmbeddedImage],
+    width: usize,
+) -> impl Iterator<Item = usize> + 'a {
+    text.lines().enumerate().map(move |(line_idx, line)| {
+        match images.iter().find(|image| image.line_idx == line_idx) {
+            Some(image) => 1 + image.size.map_or(0, |(_, rows)| rows as usize),
+            None => visual_lines_for_width(line.chars().count(), width),
+        }
+    })
+}
+
+/// Rows each line of the buffer takes in EDIT mode.
+fn edit_heights(editor: &ViewerState, width: usize) -> impl Iterator<Item = usize> + '_ {
+    (0..editor.content.len_lines()).map(move |line_idx| {
+        let line = editor.content.line(line_idx);
+        let len = match line.len_chars() {
+            n if n > 0 && line.char(n - 1) == '\n' => n - 1,
+            n => n,
+        };
+        visual_lines_for_width(len, width)
+    })
+}
+
+/// The screen row of the cursor of the current mode, counted from the top
+/// of the preview and negative when it is scrolled past. READ mode measures
+/// the word-wrapped lines in view, images included, and estimates the rest;
+/// EDIT mode wraps by character count like the cursor itself.
+pub fn cursor_screen_row(app: &mut App) -> isize {
+    let inner = app.viewer_content_area();
+    let width = inner.width.max(1);
+    let scroll = app.viewer_scroll as isize;
+    let editor = &app.viewer_state;
+    if editor.mode == EditorMode::Edit {
+        let above: usize = edit_heights(editor, width as usize)
+            .take(editor.cursor.line)
+            .sum();
+        return (above + editor.cursor.col / width as usize) as isize - scroll;
+    }
+
+    let images = embedded_images(app, width);
+    let Some(text) = read_text(app) else {
+        return 0;
+    };
+    let cursor = app.viewer_state.read_cursor.clone();
+    let window = Window::read(&text, &images, app.viewer_scroll, inner);
+    // Outside the lines in view an estimate will do, and is cheap
+    if !window.lines().contains(&cursor.line) {
+        let above: usize = read_heights(&text, &images, width as usize)
+            .take(cursor.line)
+            .sum();
+        return (above + cursor.col / width as usize) as isize - scroll;
+    }
+    let wrap_row = cell_of(&read_layout(app, cursor.line), cursor.col).0 as isize;
+
+    let above = render_read(
+        app,
+        &text,
+        window.first..cursor.line,
+        width as usize,
+        &images,
+        None,
+        None,
+    );
+    let rows = Paragraph::new(above)
+        .wrap(Wrap { trim: false })
+        .line_count(width);
+    rows as isize - window.offset as isize + wrap_row
+}
+
+/// The note lines worth building for a pane scrolled `scroll` rows down.
+/// Lines above the pane are only measured, by character count, so a long note
+/// costs little more to draw than a short one.
+struct Window {
+    first: usize,
+    end: usize,
+    /// Rows of the first line that are still scrolled past
+    offset: u16,
+}
+
+impl Window {
+    /// Every line, for text that isn't virtualized.
+    fn whole(scroll: u16) -> Self {
+        Self {
+            first: 0,
+            end: usize::MAX,
+            offset: scroll,
+        }
+    }
+
+    /// The window over a note in READ mode, counting the rows reserved for
+    /// images below their placeholder.
+    fn read(text: &str, images: &[EmbeddedImage], scroll: u16, inner: Rect) -> Self {
+        let heights = read_heights(text, images, inner.width as usize);
+        Self::over(heights, scroll, inner.height)
+    }
+
+    /// The window over the buffer in EDIT mode.
+    fn edit(editor: &ViewerState, scroll: u16, inner: Rect) -> Self {
+        Self::over(
+            edit_heights(editor, inner.width as usize),
+            scroll,
+            inner.height,
+        )
+    }
+
+    fn over(heights: impl Iterator<Item = usize>, scroll: u16, height: u16) -> Self {
+        let scroll = scroll as usize;
+        let mut first = None;
+        let mut offset = 0;
+        let mut top = 0;
+        let mut count = 0;
+
+        for (line_idx, rows) in heights.enumerate() {
+            count = line_idx + 1;
+            if first.is_none() && top + rows > scroll {
+                first = Some(line_idx);
+                offset = scroll - top;
+            }
+            top += rows;
+            if top >= scroll + height as usize {
+                break;
+            }
+        }
+
+        // Word wrapping can take fewer rows than estimated, so build a
+        // screenful more; every line takes at least one row.
+        let first = first.unwrap_or(count);
+        Self {
+            first,
+            end: count + height as usize,
+            offset: offset as u16,
+        }
+    }
+
+    fn lines(&self) -> Range<usize> {
+        self.first..self.end
+    }
+}
+
+/// The text of the selected note that READ mode shows: the raw source, or
+/// with `%%comments%%` masked out for the rendered preview unless
+/// `ui.show_comments` is on.
+fn read_text(app: &App) -> Option<String> {
+    let note = app.selected_note()?;
+    // Edits kept unsaved show in place of the text on disk
+    let content = if app.buffer_unsaved() {
+        app.viewer_state.content.to_string()
+    } else {
+        note.content().to_string()
+    };
+    Some(if app.raw_source || app.config.ui.show_comments {
+        content
+    } else {
+        mask_comments(&content)
+    })
+}
+
+/// Builds the lines in `range` of the selected note's `text` for READ mode:
+/// rendered markdown, or the source with its markup colored.
+fn render_read(
+    app: &App,
+    text: &str,
+    range: Range<usize>,
+    width: usize,
+    images: &[EmbeddedImage],
+    find_state: Option<&FindInNoteState>,
+    spell: Option<&SpellChecker>,
+) -> Text<'static> {
+    let Some(note) = app.selected_note() else {
+        return Text::default();
+    };
+    if app.raw_source {
+        return render_source(text, range, &app.viewer_state, find_state, &app.theme);
+    }
+    render_markdown(
+        note,
+        text,
+        range,
+        width,
+        &app.viewer_state,
+        &app.vault,
+        &app.theme,
+        find_state,
+        images,
+        spell,
+    )
+}
+
+/// Builds the lines in `range` of `content`, the note's text with
+/// `%%comments%%` masked out (they keep their lines so the cursor stays aligned).
+#[allow(clippy::too_many_arguments)]
 fn render_markdown(
     note: &Note,
+    content: &str,
+    range: Range<usize>,
+    width: usize,
     viewer_state: &ViewerState,
     vault: &crate::core::Vault,
     t: &Theme,
-    read_cursor_line: usize,
     find_state: Option<&FindInNoteState>,
+    images: &[EmbeddedImage],
+    spell: Option<&SpellChecker>,
 ) -> Text<'static> {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
-    for (line_idx, line) in note.content.lines().enumerate() {
-        let mut rendered = render_line(line, note, viewer_state, line_idx, vault, t);
-
-        // Priority: find_current > find_match > selection > cursor_line
-        let is_current_find = find_state
-            .map(|fs| fs.is_current_match_line(line_idx))
-            .unwrap_or(false);
-        let has_find_match = find_state
-            .map(|fs| fs.has_match_on_line(line_idx))
-            .unwrap_or(false);
-        let is_selected = viewer_state.is_line_selected(line_idx);
-
-        if is_current_find {
-            rendered = rendered.style(Style::default().bg(t.find_current_bg));
-        } else if has_find_match {
-            rendered = rendered.style(Style::default().bg(t.find_match_bg));
-        } else if is_selected {
-            rendered = rendered.style(Style::default().bg(t.selection_bg));
-        } else if line_idx == read_cursor_line {
-            rendered = rendered.style(Style::default().bg(t.cursor_line_bg));
+    // Whether the first line in view sits inside a code or math block
+    let (mut in_fence, mut in_math) = (false, false);
+    for line in content.lines().take(range.start) {
+        if is_fence_line(line.chars()) {
+            in_fence = !in_fence;
+        } else if !in_fence && line.trim() == "$$" {
+            in_math = !in_math;
+        }
+    }
+    // Frontmatter's closing `---` isn't a setext underline
+    let body_start = match frontmatter::split(content) {
+        Some(Ok((yaml, _))) => yaml.lines().count() + 2,
+        _ => 0,
+    };
+    let today = chrono::Local::now().date_naive();
+
+    // Setext headings need the lines either side of each line in view
+    let mut lines_in_view = content
+        .lines()
+        .enumerate()
+        .skip(range.start.saturating_sub(1))
+        .peekable();
+    let mut prev = None;
+    if range.start > 0 {
+        prev = lines_in_view.next().map(|(_, line)| line);
+    }
+    while let Some((line_idx, line)) = lines_in_view.next() {
+        if line_idx >= range.end {
+            break;
+        }
+        let next = lines_in_view.peek().map(|&(_, line)| line);
+        let is_math_fence = !in_fence && line.trim() == "$$";
+        let math = in_math || is_math_fence;
+        // Code blocks, math and frontmatter show as written
+        let literal = in_fence || math || line_idx < body_start;
+        let setext = if literal {
+            None
+        } else if is_setext_text(line) {
+            next.and_then(setext_underline)
+        } else if line_idx > body_start && prev.is_some_and(is_setext_text) {
+            setext_underline(line)
+        } else {
+            None
+        };
+        prev = Some(line);
+
+        let image = images.iter().find(|image| image.line_idx == line_idx);
+        let mut rendered = match (image, setext) {
+            (Some(image), _) => render_image_placeholder(image, t),
+            (None, _) if is_math_fence => {
+                Line::from(Span::styled(line.to_string(), Style::default().fg(t.fg4)))
+            }
+            (None, _) if math => render_math(line, line, t),
+            (None, Some(level)) => {
+                Line::from(Span::styled(line.to_string(), t.heading_style(level)))
+            }
+            (None, None) if !literal && is_rule(line) => {
+                Line::from(Span::styled("─".repeat(width), Style::default().fg(t.bg3)))
+            }
+            (None, None) => match list_bullet(line).filter(|_| !literal) {
+                Some((indent, rest)) => {
+                    render_list_item(indent, rest, note, viewer_state, line_idx, vault, t)
+                }
+                None => render_line(line, note, viewer_state, line_idx, vault, t),
+            },
+        };
+
+        // Code and math blocks aren't spell checked
+        let is_fence = line.trim_start().starts_with("```");
+        if let Some(spell) = spell
+            && !in_fence
+            && !is_fence
+            && !math
+            && image.is_none()
+        {
+            rendered = underline_misspelled(rendered, line, spell, t);
+        }
+        if !literal
+            && note::task_state(line) == Some(false)
+            && let Some((due, span)) = note::due_date(line)
+            && let Some(color) = t.due_color(due, today)
+        {
+            rendered = color_annotation(rendered, &line[span], color);
+        }
+        in_fence ^= is_fence;
+        in_math ^= is_math_fence;
+
+        if let Some(background) = line_background(line_idx, viewer_state, find_state, t) {
+            rendered = rendered.style(background);
         }
         lines.push(rendered);
+
+        // Blank rows for the terminal to draw the image into
+        if let Some((_, rows)) = image.and_then(|image| image.size) {
+            lines.extend((0..rows).map(|_| Line::from("")));
+        }
     }
 
     Text::from(lines)
 }
 
+/// Builds the lines in `range` of a note's raw source, its markup colored.
+fn render_source(
+    content: &str,
+    range: Range<usize>,
+    viewer_state: &ViewerState,
+    find_state: Option<&FindInNoteState>,
+    t: &Theme,
+) -> Text<'static> {
+    let lines = source::highlight(content, range.clone(), t)
+        .into_iter()
+        .zip(range)
+        .map(
+            |(line, line_idx)| match line_background(line_idx, viewer_state, find_state, t) {
+                Some(background) => line.style(background),
+                None => line,
+            },
+        );
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+/// The background of a line in READ mode.
+/// Priority: find_current > find_match > selection > cursor_line
+fn line_background(
+    line_idx: usize,
+    viewer_state: &ViewerState,
+    find_state: Option<&FindInNoteState>,
+    t: &Theme,
+) -> Option<Style> {
+    let background = if find_state.is_some_and(|fs| fs.is_current_match_line(line_idx)) {
+        t.find_current_bg
+    } else if find_state.is_some_and(|fs| fs.has_match_on_line(line_idx)) {
+        t.find_match_bg
+    } else if viewer_state.is_line_selected(line_idx) {
+        t.selection_bg
+    } else if line_idx == viewer_state.read_cursor.line {
+        t.cursor_line_bg
+    } else {
+        return None;
+    };
+    Some(Style::default().bg(background))
+}
+
+/// Underlines the words of the source `line` that the checker rejects
+/// wherever they appear in the rendered spans.
+fn underline_misspelled(
+    rendered: Line<'static>,
+    line: &str,
+    spell: &SpellChecker,
+    t: &Theme,
+) -> Line<'static> {
+    let misspelled: HashSet<String> = spell
+        .misspelled(line)
+        .into_iter()
+        .map(|range| line.chars().skip(range.start).take(range.len()).collect())
+        .collect();
+    if misspelled.is_empty() {
+        return rendered;
+    }
+
+    let mut spans = Vec::new();
+    for span in rendered.spans {
+        let text: Vec<char> = span.content.chars().collect();
+        let mut last = 0;
+        for range in spell::words(&span.content) {
+            let word: String = text[range.clone()].iter().collect();
+            if !misspelled.contains(&word) {
+                continue;
+            }
+            if range.start > last {
+                spans.push(Span::styled(
+                    text[last..range.start].iter().collect::<String>(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(word, span.style.patch(t.misspelled_style())));
+            last = range.end;
+        }
+        if last == 0 {
+            spans.push(span);
+        } else if last < text.len() {
+            spans.push(Span::styled(
+                text[last..].iter().collect::<String>(),
+                span.style,
+            ));
+        }
+    }
+
+    Line { spans, ..rendered }
+}
+
+/// Colors the first occurrence of `annotation` in the rendered spans.
+fn color_annotation(rendered: Line<'static>, annotation: &str, color: Color) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut found = false;
+    for span in rendered.spans {
+        let Some(start) = span.content.find(annotation).filter(|_| !found) else {
+            spans.push(span);
+            continue;
+        };
+        found = true;
+        let end = start + annotation.len();
+        let style = span.style.fg(color).add_modifier(Modifier::BOLD);
+        if start > 0 {
+            spans.push(Span::styled(span.content[..start].to_string(), span.style));
+        }
+        spans.push(Span::styled(annotation.to_string(), style));
+        if end < span.content.len() {
+            spans.push(Span::styled(span.content[end..].to_string(), span.style));
+        }
+    }
+
+    Line { spans, ..rendered }
+}
+
+fn render_image_placeholder(image: &EmbeddedImage, t: &Theme) -> Line<'static> {
+    let mut spans = vec![Span::styled(
+        format!("{}{}", t.icons.image, image.alt),
+        Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+    )];
+    if image.path.is_none() {
+        spans.push(Span::styled(
+            " (not found)",
+            Style::default().fg(t.link_broken),
+        ));
+    }
+    Line::from(spans)
+}
+
 fn render_line(
     line: &str,
     note: &Note,
@@ -285,29 +1311,9 @@ fn render_line(
     let trimmed = line.trim();
 
     // Headings
-    if trimmed.starts_with("# ") {
-        return Line::from(Span::styled(
-            line.to_string(),
-            Style::default()
-                .fg(t.heading_1)
-                .add_modifier(Modifier::BOLD),
-        ));
-    }
-    if trimmed.starts_with("## ") {
-        return Line::from(Span::styled(
-            line.to_string(),
-            Style::default()
-                .fg(t.heading_2)
-                .add_modifier(Modifier::BOLD),
-        ));
-    }
-    if trimmed.starts_with("### ") {
-        return Line::from(Span::styled(
-            line.to_string(),
-            Style::default()
-                .fg(t.heading_3)
-                .add_modifier(Modifier::BOLD),
-        ));
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+        return Line::from(Span::styled(line.to_string(), t.heading_style(level)));
     }
 
     // Code blocks (simple detection)
@@ -319,6 +1325,90 @@ fn render_line(
     render_inline(line, note, viewer_state, line_idx, vault, t)
 }
 
+/// The level of a setext heading underlined by `line`: a run of `=` for
+/// level 1 or of `-` for level 2.
+fn setext_underline(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    let level = match trimmed.chars().next()? {
+        '=' => 1,
+        '-' => 2,
+        _ => return None,
+    };
+    let marker = trimmed.as_bytes()[0];
+    trimmed.bytes().all(|b| b == marker).then_some(level)
+}
+
+/// Whether `line` is plain text that an underline below would make a heading,
+/// rather than a blank line, list item or another block.
+fn is_setext_text(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && setext_underline(trimmed).is_none()
+        && !trimmed.starts_with(['#', '>', '|'])
+        && !trimmed.starts_with("```")
+        && !trimmed.starts_with("- ")
+        && !trimmed.starts_with("* ")
+        && !trimmed.starts_with("+ ")
+}
+
+/// Math in its Unicode approximation, or as its `source` when `latex` uses
+/// something that has none.
+fn render_math(latex: &str, source: &str, t: &Theme) -> Line<'static> {
+    let style = Style::default().fg(t.math_fg);
+    match math::to_unicode(latex) {
+        Some(text) => Line::from(Span::styled(text, style)),
+        None => Line::from(Span::styled(
+            source.to_string(),
+            style.add_modifier(Modifier::ITALIC),
+        )),
+    }
+}
+
+/// Whether `line` is a thematic break: three or more `-`, `*` or `_`,
+/// optionally spaced out.
+fn is_rule(line: &str) -> bool {
+    let mut marks = line.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = marks.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+        return false;
+    };
+    marks.clone().all(|c| c == first) && marks.count() >= 2
+}
+
+/// Splits a bulleted list item into its indentation and the text after the
+/// `-`, `*` or `+` marker.
+fn list_bullet(line: &str) -> Option<(&str, &str)> {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    let rest = text.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    Some((indent, rest))
+}
+
+/// Draws a list item with a typographic bullet whose shape and color follow
+/// its nesting depth, taking every two columns of indentation as a level.
+fn render_list_item(
+    indent: &str,
+    rest: &str,
+    note: &Note,
+    viewer_state: &ViewerState,
+    line_idx: usize,
+    vault: &crate::core::Vault,
+    t: &Theme,
+) -> Line<'static> {
+    let columns: usize = indent.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum();
+    let depth = columns / 2 % t.icons.bullets.len();
+    let color = [t.blue, t.aqua, t.purple][depth];
+
+    let mut spans = vec![
+        Span::raw(indent.to_string()),
+        Span::styled(
+            format!("{} ", t.icons.bullets[depth]),
+            Style::default().fg(color),
+        ),
+    ];
+    spans.extend(render_inline(rest, note, viewer_state, line_idx, vault, t).spans);
+    Line::from(spans)
+}
+
 fn render_inline(
     line: &str,
     _note: &Note,
@@ -334,6 +1424,59 @@ fn render_inline(
     let mut link_count_on_line = 0;
 
     while i < chars.len() {
+        // Backslash escapes: \* \[ \# etc. render the punctuation literally
+        if chars[i] == '\\' && i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() {
+            current.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+
+        // HTML entities: &amp; &mdash; &#8212; &#x2014;
+        if chars[i] == '&'
+            && let Some((decoded, len)) = decode_entity(&chars[i..])
+        {
+            current.push(decoded);
+            i += len;
+            continue;
+        }
+
+        // Citations [@key] and [@a, p. 4; @b] show as (Author, year); keys
+        // missing from the bibliography stay as written, marked broken
+        if chars[i] == '['
+            && chars.get(i + 1) == Some(&'@')
+            && let Some(end) = chars[i..].iter().position(|&c| c == ']').map(|n| i + n)
+            && let Some(cites) =
+                bibtex::parse_citation(&chars[i + 1..end].iter().collect::<String>())
+        {
+            if !current.is_empty() {
+                spans.push(Span::raw(current.clone()));
+                current.clear();
+            }
+            let style = Style::default().fg(t.aqua);
+            spans.push(Span::styled("(", style));
+            for (n, cite) in cites.iter().enumerate() {
+                if n > 0 {
+                    spans.push(Span::styled("; ", style));
+                }
+                let (mut text, cite_style) = match vault.bibliography().get(&cite.key) {
+                    Some(entry) => (entry.label(), style),
+                    None => (
+                        format!("@{}", cite.key),
+                        Style::default()
+                            .fg(t.link_broken)
+                            .add_modifier(Modifier::CROSSED_OUT),
+                    ),
+                };
+                if !cite.locator.is_empty() {
+                    text.push_str(&format!(", {}", cite.locator));
+                }
+                spans.push(Span::styled(text, cite_style));
+            }
+            spans.push(Span::styled(")", style));
+            i = end + 1;
+            continue;
+        }
+
         // Check for wiki-link [[...]]
         if i + 1 < chars.len() && chars[i] == '[' && chars[i + 1] == '[' {
             // Flush current text
@@ -476,6 +1619,68 @@ fn render_inline(
             continue;
         }
 
+        // Check for math $...$ or $$...$$; a closing $ before a digit is a price
+        if chars[i] == '$' {
+            let delim: &[char] = if chars.get(i + 1) == Some(&'$') {
+                &['$', '$']
+            } else {
+                &['$']
+            };
+            if let Some(end) = find_closing(&chars, i + delim.len(), delim)
+                && (delim.len() == 2 || !chars.get(end + 1).is_some_and(|c| c.is_ascii_digit()))
+            {
+                if !current.is_empty() {
+                    spans.push(Span::raw(current.clone()));
+                    current.clear();
+                }
+                let latex: String = chars[i + delim.len()..end].iter().collect();
+                let source: String = chars[i..end + delim.len()].iter().collect();
+                spans.extend(render_math(&latex, &source, t).spans);
+                i = end + delim.len();
+                continue;
+            }
+        }
+
+        // Check for strikethrough ~~...~~ and highlight ==...==
+        let paired = match chars[i] {
+            '~' => Some(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            '=' => Some(Style::default().fg(t.bg0).bg(t.highlight_bg)),
+            _ => None,
+        };
+        if let Some(style) = paired
+            && chars.get(i + 1) == Some(&chars[i])
+            && let Some(end) = find_closing(&chars, i + 2, &[chars[i], chars[i]])
+        {
+            if !current.is_empty() {
+                spans.push(Span::raw(current.clone()));
+                current.clear();
+            }
+            spans.push(Span::styled(
+                chars[i + 2..end].iter().collect::<String>(),
+                style,
+            ));
+            i = end + 2;
+            continue;
+        }
+
+        // Check for italic *...* or _..._; underscores inside words are literal
+        if (chars[i] == '*' || chars[i] == '_')
+            && (chars[i] == '*' || i == 0 || !chars[i - 1].is_alphanumeric())
+            && let Some(end) = find_closing(&chars, i + 1, &[chars[i]])
+            && (chars[i] == '*' || !chars.get(end + 1).is_some_and(|c| c.is_alphanumeric()))
+        {
+            if !current.is_empty() {
+                spans.push(Span::raw(current.clone()));
+                current.clear();
+            }
+            spans.push(Span::styled(
+                chars[i + 1..end].iter().collect::<String>(),
+                Style::default().add_modifier(Modifier::ITALIC),
+            ));
+            i = end + 1;
+            continue;
+        }
+
         // Check for inline code `...`
         if chars[i] == '`' {
             if !current.is_empty() {
@@ -516,11 +1721,97 @@ fn render_inline(
     Line::from(spans)
 }
 
+/// Finds the closing `delim` of an emphasis span whose text starts at `from`.
+/// Like Markdown, the text may not start or end with whitespace, so a lone
+/// `*` or `~~` between words stays literal.
+fn find_closing(chars: &[char], from: usize, delim: &[char]) -> Option<usize> {
+    if chars.get(from).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+    (from + 1..=chars.len().checked_sub(delim.len())?)
+        .find(|&end| chars[end..].starts_with(delim) && !chars[end - 1].is_whitespace())
+}
+
+/// Decodes an HTML entity at the start of `chars`, returning the character
+/// and how many chars the entity spans.
+fn decode_entity(chars: &[char]) -> Option<(char, usize)> {
+    // Longest entity we recognise is "&thetasym;"; cap the scan accordingly
+    let end = chars.iter().take(12).position(|&c| c == ';')?;
+    let name: String = chars[1..end].iter().collect();
+
+    let decoded = if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        char::from_u32(code)?
+    } else {
+        match name.as_str() {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            "mdash" => '—',
+            "ndash" => '–',
+            "hellip" => '…',
+            "lsquo" => '‘',
+            "rsquo" => '’',
+            "ldquo" => '“',
+            "rdquo" => '”',
+            "laquo" => '«',
+            "raquo" => '»',
+            "bull" => '•',
+            "middot" => '·',
+            "copy" => '©',
+            "reg" => '®',
+            "trade" => '™',
+            "deg" => '°',
+            "plusmn" => '±',
+            "times" => '×',
+            "divide" => '÷',
+            "ne" => '≠',
+            "le" => '≤',
+            "ge" => '≥',
+            "infin" => '∞',
+            "larr" => '←',
+            "rarr" => '→',
+            "uarr" => '↑',
+            "darr" => '↓',
+            "harr" => '↔',
+            "lArr" => '⇐',
+            "rArr" => '⇒',
+            "hArr" => '⇔',
+            "sect" => '§',
+            "para" => '¶',
+            "euro" => '€',
+            "pound" => '£',
+            "yen" => '¥',
+            "cent" => '¢',
+            "frac12" => '½',
+            "frac14" => '¼',
+            "frac34" => '¾',
+            "check" => '✓',
+            _ => return None,
+        }
+    };
+
+    Some((decoded, end + 1))
+}
+
+/// Whether a line opens or closes a code block.
+fn is_fence_line(mut line: impl Iterator<Item = char>) -> bool {
+    line.by_ref()
+        .find(|c| !c.is_whitespace())
+        .is_some_and(|c| c == '`' && line.take(2).eq("``".chars()))
+}
+
 /// How many visual rows a line of `char_len` characters occupies in a column of `width`.
 fn visual_lines_for_width(char_len: usize, width: usize) -> usize {
     if char_len == 0 || width == 0 {
         1
     } else {
-        (char_len + width - 1) / width
+        char_len.div_ceil(width)
     }
 }
@@ -1,7 +1,12 @@
 use ropey::Rope;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
-use crate::core::{self, Note};
+use crate::core::table::{self, Table};
+use crate::core::undo_log::{self, Patch, UndoLog};
+use crate::core::{Note, text};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EditorMode {
@@ -15,11 +20,30 @@ pub struct Position {
     pub col: usize,
 }
 
+/// What an autocomplete popup offers: notes after `[[`, or bibliography
+/// entries after `[@`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Note,
+    Citation,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    /// What the popup lists
+    pub label: String,
+    /// What accepting it writes in place of the trigger and query
+    pub text: String,
+    /// Listed under a note's alias rather than its title
+    pub is_alias: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct AutocompleteState {
+    pub kind: CompletionKind,
     pub trigger_pos: Position,
     pub query: String,
-    pub matches: Vec<(PathBuf, String)>, // (path, display_name)
+    pub matches: Vec<Completion>,
     pub selected: usize,
 }
 
@@ -72,9 +96,21 @@ pub struct ViewerState {
     pub cursor: Position,
     pub read_cursor: Position,
     pub scroll_offset: usize,
+    pub wrap_width: usize, // columns the view soft-wraps at, 0 when it doesn't wrap
     pub dirty: bool,
+    /// The note's text couldn't be read, so the buffer is empty and must
+    /// never be saved over it
+    pub unreadable: bool,
     pub current_note_path: Option<PathBuf>,
+    /// Modification time of the note file the buffer was loaded from. A save
+    /// finding a different one on disk would overwrite someone else's change
+    pub disk_mtime: Option<SystemTime>,
     pub autocomplete: Option<AutocompleteState>,
+    snippet_stops: Vec<usize>, // char indices of the expanded snippet's remaining tab stops
+    snippet_span: Range<usize>, // char range the expanded snippet covers
+    snippet_len: usize,        // buffer length at the last sync_snippet
+    snippet_cursor: usize,     // cursor char index at the last sync_snippet
+    table: Option<usize>,      // first line of the table the cursor is in, in table mode
 
     // Selection
     pub selection: Option<Selection>,
@@ -84,6 +120,8 @@ pub struct ViewerState {
     undo_stack: Vec<EditorSnapshot>,
     redo_stack: Vec<EditorSnapshot>,
     max_undo_history: usize,
+    typing: bool, // typed characters since the last snapshot undo as one step
+    pub undo_dir: Option<PathBuf>, // where the history outlives the session, if anywhere
 }
 
 #[derive(Debug, Clone)]
@@ -103,55 +141,93 @@ impl ViewerState {
             cursor: Position { line: 0, col: 0 },
             read_cursor: Position { line: 0, col: 0 },
             scroll_offset: 0,
+            wrap_width: 0,
             dirty: false,
+            unreadable: false,
             current_note_path: None,
+            disk_mtime: None,
             autocomplete: None,
+            snippet_stops: Vec::new(),
+            snippet_span: 0..0,
+            snippet_len: 0,
+            snippet_cursor: 0,
+            table: None,
             selection: None,
             clipboard: None,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             max_undo_history: 100,
+            typing: false,
+            undo_dir: None,
         }
     }
 
     pub fn update_links(&mut self, note: &Note) {
+        let same_note = self.current_note_path.as_ref() == Some(&note.path);
         self.visible_links.clear();
         self.selected_link = 0;
         self.selection = None;
         self.current_note_path = Some(note.path.clone());
 
         // Update content rope
-        self.content = Rope::from_str(&note.content);
+        let content = note.read();
+        self.unreadable = content.is_none();
+        let content = content.unwrap_or_default();
+        self.content = Rope::from_str(&content);
+        self.dirty = false;
+        self.disk_mtime = Some(note.modified);
 
         // Reset cursors when loading new note
         self.cursor = Position { line: 0, col: 0 };
         self.read_cursor = Position { line: 0, col: 0 };
 
-        // Clear undo/redo history when loading a new note
-        self.undo_stack.clear();
-        self.redo_stack.clear();
-
-        // Build list of visible links with their line Position
-        let mut line_index = 0;
-        for _line in note.content.lines() {
-            for link in &note.links {
-                let line_start = note.content[..note.content.len().min(link.span.start)]
-                    .lines()
-                    .count()
-                    .saturating_sub(1);
+        // Swap in the history of a newly opened note
+        if !same_note {
+            self.load_history();
+        }
 
-                if line_start == line_index {
-                    self.visible_links.push(VisibleLink {
-                        target: link.target.clone(),
-                        display: link.display.clone().unwrap_or_else(|| link.target.clone()),
-                        line_index,
-                    });
-                }
-            }
-            line_index += 1;
+        // Build list of visible links with their line Position, counting
+        // newlines as we go since the links are in order of appearance
+        let (mut line_index, mut counted) = (0, 0);
+        for link in &note.links {
+            let start = content.len().min(link.span.start);
+            line_index += content.as_bytes()[counted.min(start)..start]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            counted = counted.max(start);
+            self.visible_links.push(VisibleLink {
+                target: link.target.clone(),
+                display: link.display.clone().unwrap_or_else(|| link.target.clone()),
+                line_index,
+            });
         }
     }
 
+    /// Reloads the current note after it was written, keeping the read cursor
+    /// and selection in place rather than jumping back to the top.
+    pub fn reload(&mut self, note: &Note) {
+        let cursor = self.read_cursor.clone();
+        let selection = self.selection.take();
+
+        self.update_links(note);
+
+        self.read_cursor = self.clamp_position(&cursor);
+        self.selection = selection.map(|sel| Selection {
+            anchor: self.clamp_position(&sel.anchor),
+            head: self.clamp_position(&sel.head),
+            mode: sel.mode,
+        });
+        self.save_history();
+    }
+
+    /// Pulls a position back inside the current content.
+    pub fn clamp_position(&self, pos: &Position) -> Position {
+        let line = pos.line.min(self.content.len_lines().saturating_sub(1));
+        let col = pos.col.min(Self::line_content_len(self.content.line(line)));
+        Position { line, col }
+    }
+
     pub fn next_link(&mut self) {
         if !self.visible_links.is_empty() {
             self.selected_link = (self.selected_link + 1) % self.visible_links.len();
@@ -173,21 +249,126 @@ impl ViewerState {
     }
 
     // EDIT mode operations
+    /// Switches to Edit mode at the read cursor. A line-level Visual selection
+    /// becomes a CharSelect spanning the same lines, with the cursor at its head.
+    /// A note whose text couldn't be read stays in Read mode.
     pub fn enter_edit_mode(&mut self) {
+        if self.unreadable {
+            return;
+        }
         self.mode = EditorMode::Edit;
-        self.cursor = self.read_cursor.clone();
-        self.selection = None;
-        self.save_undo_snapshot();
+        self.cursor = self.clamp_position(&self.read_cursor);
+        if let Some(sel) = self.selection.take() {
+            let sel = match sel.mode {
+                SelectionMode::Visual => {
+                    let forward = sel.head.line >= sel.anchor.line;
+                    let line_end = |line: usize| Position {
+                        line,
+                        col: Self::line_content_len(self.content.line(line)),
+                    };
+                    let line_start = |line: usize| Position { line, col: 0 };
+                    let (anchor, head) = if forward {
+                        (line_start(sel.anchor.line), line_end(sel.head.line))
+                    } else {
+                        (line_end(sel.anchor.line), line_start(sel.head.line))
+                    };
+                    Selection {
+                        anchor,
+                        head,
+                        mode: SelectionMode::CharSelect,
+                    }
+                }
+                SelectionMode::CharSelect => sel,
+            };
+            self.cursor = sel.head.clone();
+            self.selection = Some(sel);
+        }
+        self.sync_table();
     }
 
+    /// Switches back to Read mode, leaving the read cursor where the edit cursor
+    /// was. A CharSelect selection carries over as a Visual selection.
     pub fn exit_edit_mode(&mut self) -> String {
         self.mode = EditorMode::Read;
         self.dirty = false;
         self.autocomplete = None;
-        self.selection = None;
+        self.read_cursor = self.cursor.clone();
+        if let Some(ref mut sel) = self.selection {
+            sel.mode = SelectionMode::Visual;
+        }
+        self.typing = false;
+        self.snippet_stops.clear();
+        self.table = None;
+        self.save_history();
+        self.content.to_string()
+    }
+
+    /// Replaces the undo and redo stacks with the ones saved for the current
+    /// note, or empty ones if it has none or changed since.
+    fn load_history(&mut self) {
         self.undo_stack.clear();
         self.redo_stack.clear();
-        self.content.to_string()
+        self.typing = false;
+
+        let (Some(dir), Some(path)) = (&self.undo_dir, &self.current_note_path) else {
+            return;
+        };
+        let content = self.content.to_string();
+        let Some(log) = UndoLog::load(dir, path, &content) else {
+            return;
+        };
+
+        let rebuild = |patches: &[Patch]| -> Vec<EditorSnapshot> {
+            let mut text = content.clone();
+            let mut snapshots: Vec<EditorSnapshot> = patches
+                .iter()
+                .map(|patch| {
+                    text = patch.apply(&text);
+                    EditorSnapshot {
+                        content: Rope::from_str(&text),
+                        cursor: Position {
+                            line: patch.line,
+                            col: patch.col,
+                        },
+                    }
+                })
+                .collect();
+            // Stacks keep the nearest step last
+            snapshots.reverse();
+            snapshots
+        };
+        self.undo_stack = rebuild(&log.undo);
+        self.redo_stack = rebuild(&log.redo);
+    }
+
+    /// Writes the undo and redo stacks for the current note, which has just
+    /// been saved with the buffer's content.
+    fn save_history(&self) {
+        let (Some(dir), Some(path)) = (&self.undo_dir, &self.current_note_path) else {
+            return;
+        };
+        let content = self.content.to_string();
+
+        let patches = |stack: &[EditorSnapshot]| -> Vec<Patch> {
+            let mut from = content.clone();
+            stack
+                .iter()
+                .rev()
+                .map(|snapshot| {
+                    let to = snapshot.content.to_string();
+                    let patch =
+                        Patch::between(&from, &to, snapshot.cursor.line, snapshot.cursor.col);
+                    from = to;
+                    patch
+                })
+                .collect()
+        };
+        let log = UndoLog {
+            checksum: undo_log::checksum(&content),
+            undo: patches(&self.undo_stack),
+            redo: patches(&self.redo_stack),
+        };
+        let _ = log.save(dir, path);
     }
 
     fn save_undo_snapshot(&mut self) {
@@ -203,6 +384,15 @@ impl ViewerState {
         }
 
         self.redo_stack.clear();
+        self.typing = false;
+    }
+
+    /// Snapshots before the first of a run of typed characters.
+    fn begin_typing(&mut self) {
+        if !self.typing {
+            self.save_undo_snapshot();
+            self.typing = true;
+        }
     }
 
     pub fn undo(&mut self) -> bool {
@@ -218,6 +408,9 @@ impl ViewerState {
             self.content = snapshot.content;
             self.cursor = snapshot.cursor;
             self.dirty = true;
+            self.typing = false;
+            self.snippet_stops.clear();
+            self.table = None;
             true
         } else {
             false
@@ -237,6 +430,9 @@ impl ViewerState {
             self.content = snapshot.content;
             self.cursor = snapshot.cursor;
             self.dirty = true;
+            self.typing = false;
+            self.snippet_stops.clear();
+            self.table = None;
             true
         } else {
             false
@@ -244,6 +440,7 @@ impl ViewerState {
     }
 
     pub fn insert_char(&mut self, c: char) {
+        self.begin_typing();
         let char_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
         self.content.insert_char(char_idx, c);
         self.cursor.col += 1;
@@ -253,17 +450,141 @@ impl ViewerState {
         self.check_autocomplete_trigger();
     }
 
+    /// Types `c`, pairing brackets and backticks: an opener also inserts its
+    /// closer after the cursor, and typing a closer steps over one already there.
+    pub fn insert_paired(&mut self, c: char) {
+        let char_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
+        let next = (char_idx < self.content.len_chars()).then(|| self.content.char(char_idx));
+        let prev = (char_idx > 0).then(|| self.content.char(char_idx - 1));
+
+        if matches!(c, ']' | ')' | '`') && next == Some(c) {
+            self.cursor.col += 1;
+            self.check_autocomplete_trigger();
+            return;
+        }
+
+        // Only pair at word boundaries, and leave ``` fences alone
+        let Some(close) = closing_pair(c) else {
+            return self.insert_char(c);
+        };
+        if next.is_some_and(|ch| ch.is_alphanumeric())
+            || (c == '`' && prev.is_some_and(|ch| ch.is_alphanumeric() || ch == '`'))
+        {
+            return self.insert_char(c);
+        }
+
+        self.begin_typing();
+        self.content.insert(char_idx, &format!("{}{}", c, close));
+        self.cursor.col += 1;
+        self.dirty = true;
+        self.check_autocomplete_trigger();
+    }
+
+    /// Backspace between an opener and its closer removes both. Returns
+    /// false, changing nothing, when the cursor isn't inside an empty pair.
+    pub fn delete_empty_pair(&mut self) -> bool {
+        let char_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
+        if self.cursor.col == 0 || char_idx >= self.content.len_chars() {
+            return false;
+        }
+        let prev = self.content.char(char_idx - 1);
+        let next = self.content.char(char_idx);
+        if closing_pair(prev) != Some(next) {
+            return false;
+        }
+
+        self.save_undo_snapshot();
+        self.content.remove(char_idx - 1..char_idx + 1);
+        self.cursor.col -= 1;
+        self.dirty = true;
+        self.check_autocomplete_trigger();
+        true
+    }
+
+    /// Splits the line at the cursor, carrying its indentation over to the new line.
     pub fn insert_newline(&mut self) {
         self.save_undo_snapshot();
 
+        let indent: String = self
+            .content
+            .line(self.cursor.line)
+            .chars()
+            .take(self.cursor.col)
+            .take_while(|&c| c == ' ' || c == '\t')
+            .collect();
+
         let char_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
-        self.content.insert_char(char_idx, '\n');
+        self.content.insert(char_idx, &format!("\n{}", indent));
         self.cursor.line += 1;
-        self.cursor.col = 0;
+        self.cursor.col = indent.chars().count();
         self.dirty = true;
         self.autocomplete = None;
     }
 
+    /// Indents the cursor line, or every line the selection touches, by `width` spaces.
+    pub fn indent(&mut self, width: usize) {
+        self.save_undo_snapshot();
+        let (first, last) = self.edited_lines();
+        let indent = " ".repeat(width);
+
+        for line in first..=last {
+            // Blank lines inside a selection stay empty
+            if first != last && Self::line_content_len(self.content.line(line)) == 0 {
+                continue;
+            }
+            let char_idx = self.content.line_to_char(line);
+            self.content.insert(char_idx, &indent);
+            self.shift_columns(line, width as isize);
+        }
+        self.dirty = true;
+    }
+
+    /// Removes up to `width` leading spaces, or one tab, from the cursor line
+    /// or every line the selection touches.
+    pub fn outdent(&mut self, width: usize) {
+        self.save_undo_snapshot();
+        let (first, last) = self.edited_lines();
+
+        for line in first..=last {
+            let text = self.content.line(line);
+            let remove = if text.chars().next() == Some('\t') {
+                1
+            } else {
+                text.chars().take(width).take_while(|&c| c == ' ').count()
+            };
+            if remove == 0 {
+                continue;
+            }
+            let char_idx = self.content.line_to_char(line);
+            self.content.remove(char_idx..char_idx + remove);
+            self.shift_columns(line, -(remove as isize));
+            self.dirty = true;
+        }
+    }
+
+    /// First and last line affected by a line-wise edit.
+    fn edited_lines(&self) -> (usize, usize) {
+        self.selection
+            .as_ref()
+            .map(|sel| sel.line_range())
+            .unwrap_or((self.cursor.line, self.cursor.line))
+    }
+
+    /// Moves the cursor and selection ends on `line` after text was added or
+    /// removed at its start.
+    fn shift_columns(&mut self, line: usize, delta: isize) {
+        let shift = |pos: &mut Position| {
+            if pos.line == line {
+                pos.col = pos.col.saturating_add_signed(delta);
+            }
+        };
+        shift(&mut self.cursor);
+        if let Some(ref mut sel) = self.selection {
+            shift(&mut sel.anchor);
+            shift(&mut sel.head);
+        }
+    }
+
     pub fn delete_char(&mut self) {
         if self.cursor.col > 0 {
             self.save_undo_snapshot();
@@ -326,16 +647,70 @@ impl ViewerState {
     }
 
     pub fn move_cursor_up(&mut self) {
-        if self.cursor.line > 0 {
-            self.cursor.line -= 1;
-            self.cursor.col = self.cursor.col.min(self.current_line_len());
-        }
+        self.cursor = self.visual_row_up(&self.cursor);
     }
 
     pub fn move_cursor_down(&mut self) {
-        if self.cursor.line < self.content.len_lines().saturating_sub(1) {
-            self.cursor.line += 1;
-            self.cursor.col = self.cursor.col.min(self.current_line_len());
+        self.cursor = self.visual_row_down(&self.cursor);
+    }
+
+    /// The position one screen row above `pos`. Wrapped lines are stepped
+    /// through a row at a time, keeping the column within the row.
+    fn visual_row_up(&self, pos: &Position) -> Position {
+        let width = self.wrap_width.max(1);
+        let (row, x) = if self.wrap_width == 0 {
+            (0, pos.col)
+        } else {
+            (pos.col / width, pos.col % width)
+        };
+
+        if row > 0 {
+            return Position {
+                line: pos.line,
+                col: (row - 1) * width + x,
+            };
+        }
+        if pos.line == 0 {
+            return pos.clone();
+        }
+
+        let line = pos.line - 1;
+        let len = Self::line_content_len(self.content.line(line));
+        let last_row = if self.wrap_width == 0 { 0 } else { len / width };
+        Position {
+            line,
+            col: (last_row * width + x).min(len),
+        }
+    }
+
+    /// The position one screen row below `pos`.
+    fn visual_row_down(&self, pos: &Position) -> Position {
+        if pos.line >= self.content.len_lines() {
+            return pos.clone();
+        }
+        let width = self.wrap_width.max(1);
+        let len = Self::line_content_len(self.content.line(pos.line));
+        let (row, x) = if self.wrap_width == 0 {
+            (0, pos.col)
+        } else {
+            (pos.col / width, pos.col % width)
+        };
+
+        if self.wrap_width > 0 && row < len / width {
+            return Position {
+                line: pos.line,
+                col: ((row + 1) * width + x).min(len),
+            };
+        }
+        if pos.line + 1 >= self.content.len_lines() {
+            return pos.clone();
+        }
+
+        let line = pos.line + 1;
+        let next_len = Self::line_content_len(self.content.line(line));
+        Position {
+            line,
+            col: x.min(next_len),
         }
     }
 
@@ -429,18 +804,35 @@ impl ViewerState {
         }
     }
 
-    pub fn move_read_cursor_up(&mut self) {
-        if self.read_cursor.line > 0 {
-            self.read_cursor.line -= 1;
-            self.read_cursor.col = self.read_cursor.col.min(self.read_line_len());
+    /// Moves the read cursor to the blank line after (or before) the
+    /// paragraph it is in, or to the end (or start) of the note.
+    pub fn move_read_paragraph(&mut self, forward: bool) {
+        let last = self.content.len_lines().saturating_sub(1);
+        let is_blank = |line: usize| self.content.line(line).chars().all(|c| c.is_whitespace());
+        // Leave the blank run the cursor is in, then cross the paragraph
+        let mut line = self.read_cursor.line;
+        if forward {
+            while line < last && is_blank(line) {
+                line += 1;
+            }
+            while line < last && !is_blank(line) {
+                line += 1;
+            }
+        } else {
+            while line > 0 && is_blank(line) {
+                line -= 1;
+            }
+            while line > 0 && !is_blank(line) {
+                line -= 1;
+            }
         }
+        self.read_cursor = Position { line, col: 0 };
     }
 
-    pub fn move_read_cursor_down(&mut self) {
-        if self.read_cursor.line < self.content.len_lines().saturating_sub(1) {
-            self.read_cursor.line += 1;
-            self.read_cursor.col = self.read_cursor.col.min(self.read_line_len());
-        }
+    /// Moves the read cursor to the start of `line`, clamped to the note.
+    pub fn move_read_cursor_to_line(&mut self, line: usize) {
+        let line = line.min(self.content.len_lines().saturating_sub(1));
+        self.read_cursor = Position { line, col: 0 };
     }
 
     pub fn move_read_word_left(&mut self) {
@@ -569,33 +961,33 @@ impl ViewerState {
     }
 
     pub fn is_line_selected(&self, line: usize) -> bool {
-        if let Some(ref sel) = self.selection {
-            if sel.mode == SelectionMode::Visual {
-                let (start, end) = sel.line_range();
-                return line >= start && line <= end;
-            }
+        if let Some(ref sel) = self.selection
+            && sel.mode == SelectionMode::Visual
+        {
+            let (start, end) = sel.line_range();
+            return line >= start && line <= end;
         }
         false
     }
 
     pub fn is_char_selected(&self, line: usize, col: usize) -> bool {
-        if let Some(ref sel) = self.selection {
-            if sel.mode == SelectionMode::CharSelect {
-                let (start, end) = sel.ordered();
-                if line < start.line || line > end.line {
-                    return false;
-                }
-                if start.line == end.line {
-                    return col >= start.col && col < end.col;
-                }
-                if line == start.line {
-                    return col >= start.col;
-                }
-                if line == end.line {
-                    return col < end.col;
-                }
-                return true;
+        if let Some(ref sel) = self.selection
+            && sel.mode == SelectionMode::CharSelect
+        {
+            let (start, end) = sel.ordered();
+            if line < start.line || line > end.line {
+                return false;
+            }
+            if start.line == end.line {
+                return col >= start.col && col < end.col;
             }
+            if line == start.line {
+                return col >= start.col;
+            }
+            if line == end.line {
+                return col < end.col;
+            }
+            return true;
         }
         false
     }
@@ -679,6 +1071,352 @@ impl ViewerState {
         self.dirty = true;
     }
 
+    /// Replaces columns `cols` of `line` with `text`, leaving both cursors
+    /// after the inserted text when they were on that line.
+    pub fn replace_in_line(&mut self, line: usize, cols: std::ops::Range<usize>, text: &str) {
+        self.save_undo_snapshot();
+        let start = self.line_col_to_char_idx(line, cols.start);
+        let end = self.line_col_to_char_idx(line, cols.end);
+        self.content.remove(start..end);
+        self.content.insert(start, text);
+
+        let col = cols.start + text.chars().count();
+        for cursor in [&mut self.cursor, &mut self.read_cursor] {
+            if cursor.line == line {
+                cursor.col = col;
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Tab in the editor: expands a snippet trigger before the cursor, else
+    /// moves to the next tab stop of the last snippet, else indents.
+    pub fn tab(&mut self, snippets: &HashMap<String, String>, indent_width: usize) {
+        if !self.expand_snippet(snippets) && !self.next_snippet_stop() {
+            self.indent(indent_width);
+        }
+    }
+
+    /// Replaces the snippet trigger just before the cursor with its body,
+    /// moving to the first tab stop. Returns false if no trigger matched.
+    fn expand_snippet(&mut self, snippets: &HashMap<String, String>) -> bool {
+        if self.selection.is_some() || self.cursor.line >= self.content.len_lines() {
+            return false;
+        }
+        let line: Vec<char> = self.content.line(self.cursor.line).chars().collect();
+        let col = self.cursor.col.min(line.len());
+        let start = line[..col]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .map_or(0, |i| i + 1);
+        let trigger: String = line[start..col].iter().collect();
+        let Some(body) = snippets.get(&trigger) else {
+            return false;
+        };
+
+        // Later lines of the body line up with the trigger's line
+        let indent: String = line
+            .iter()
+            .take_while(|&&c| c == ' ' || c == '\t')
+            .collect();
+        let body = body.replace('\n', &format!("\n{}", indent));
+        let (text, mut stops) = parse_snippet(&body);
+
+        self.save_undo_snapshot();
+        let trigger_idx = self.line_col_to_char_idx(self.cursor.line, start);
+        let cursor_idx = self.line_col_to_char_idx(self.cursor.line, col);
+        self.content.remove(trigger_idx..cursor_idx);
+        self.content.insert(trigger_idx, &text);
+        self.dirty = true;
+        self.autocomplete = None;
+
+        for stop in &mut stops {
+            *stop += trigger_idx;
+        }
+        if stops.is_empty() {
+            stops.push(trigger_idx + text.chars().count());
+        }
+        self.snippet_stops = stops;
+        self.snippet_span = trigger_idx..trigger_idx + text.chars().count();
+        self.snippet_len = self.content.len_chars();
+        self.next_snippet_stop();
+        true
+    }
+
+    /// Moves to the next tab stop of the last expanded snippet, if any are left.
+    fn next_snippet_stop(&mut self) -> bool {
+        if self.snippet_stops.is_empty() {
+            return false;
+        }
+        let stop = self.snippet_stops.remove(0);
+
+        let idx = stop.min(self.content.len_chars());
+        self.cursor.line = self.content.char_to_line(idx);
+        self.cursor.col = idx - self.content.line_to_char(self.cursor.line);
+        self.snippet_len = self.content.len_chars();
+        self.snippet_cursor = idx;
+        self.selection = None;
+        self.typing = false;
+        true
+    }
+
+    /// Keeps the tab stops of the last expanded snippet in step with edits
+    /// made at the cursor, and drops them once the cursor leaves the snippet.
+    /// Called after every edit-mode key and click.
+    pub fn sync_snippet(&mut self) {
+        if self.snippet_stops.is_empty() {
+            return;
+        }
+        // Only the stops after the edit move, so `$2` may come before `$1`
+        let len = self.content.len_chars();
+        let grown = len as isize - self.snippet_len as isize;
+        if grown != 0 {
+            let at = self.snippet_cursor;
+            for stop in &mut self.snippet_stops {
+                if *stop >= at {
+                    *stop = stop.saturating_add_signed(grown).max(at);
+                }
+            }
+            self.snippet_span.end = self.snippet_span.end.saturating_add_signed(grown).max(at);
+        }
+
+        let cursor = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
+        if self.mode != EditorMode::Edit
+            || cursor < self.snippet_span.start
+            || cursor > self.snippet_span.end
+        {
+            self.snippet_stops.clear();
+            return;
+        }
+        self.snippet_len = len;
+        self.snippet_cursor = cursor;
+    }
+
+    // ── Table mode ─────────────────────────────────────────────────
+
+    /// Whether the edit cursor is in a markdown table, where Tab moves
+    /// between cells.
+    pub fn in_table(&self) -> bool {
+        self.table.is_some()
+    }
+
+    /// Enters table mode when the cursor has moved into a table, and
+    /// realigns the table it was in when it has moved out.
+    pub fn sync_table(&mut self) {
+        let on_pipe = self.mode == EditorMode::Edit
+            && self.cursor.line < self.content.len_lines()
+            && self
+                .content
+                .line(self.cursor.line)
+                .chars()
+                .any(|c| c == '|');
+        if self.table.is_none() && !on_pipe {
+            return;
+        }
+        let lines = self.lines();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let current = on_pipe
+            .then(|| table::bounds(&lines, self.cursor.line))
+            .flatten();
+        if let Some(start) = self.table
+            && current.as_ref().is_none_or(|range| range.start != start)
+            && let Some(range) = table::bounds(&lines, start)
+        {
+            let aligned = Table::parse(&lines[range.clone()]).render();
+            self.replace_lines(range, &aligned);
+        }
+        self.table = current.map(|range| range.start);
+    }
+
+    /// Leaves table mode, realigning the table the cursor is in.
+    pub fn leave_table(&mut self) {
+        if self.table.is_some() {
+            self.edit_table(|_, _| {});
+            self.table = None;
+        }
+    }
+
+    /// Moves to the next (or previous) cell, past the last one onto a new row.
+    pub fn table_next_cell(&mut self, forward: bool) {
+        self.edit_table(|table, (row, col)| {
+            let last = table.columns() - 1;
+            if forward {
+                if *col < last {
+                    *col += 1;
+                } else {
+                    *col = 0;
+                    *row = if *row == 0 { 2 } else { *row + 1 };
+                    if *row >= table.rows() {
+                        table.insert_row(*row);
+                    }
+                }
+            } else if *col > 0 {
+                *col -= 1;
+            } else if *row > 0 {
+                *col = last;
+                *row = if *row == 2 { 0 } else { *row - 1 };
+            }
+        });
+    }
+
+    /// Moves down a row in the same column, adding a row at the bottom.
+    pub fn table_next_row(&mut self) {
+        self.edit_table(|table, (row, _)| {
+            *row = if *row == 0 { 2 } else { *row + 1 };
+            if *row >= table.rows() {
+                table.insert_row(*row);
+            }
+        });
+    }
+
+    /// Adds an empty row below the cursor's.
+    pub fn table_insert_row(&mut self) {
+        self.edit_table(|table, (row, _)| {
+            *row = (*row + 1).max(2);
+            table.insert_row(*row);
+        });
+    }
+
+    /// Deletes the cursor's row, unless it is the header.
+    pub fn table_delete_row(&mut self) {
+        self.edit_table(|table, (row, _)| {
+            if table.delete_row(*row) {
+                *row = (*row).min(table.rows() - 1);
+            }
+        });
+    }
+
+    /// Adds an empty column after the cursor's.
+    pub fn table_insert_column(&mut self) {
+        self.edit_table(|table, (_, col)| {
+            *col += 1;
+            table.insert_column(*col);
+        });
+    }
+
+    /// Deletes the cursor's column, unless it is the only one.
+    pub fn table_delete_column(&mut self) {
+        self.edit_table(|table, (_, col)| {
+            if table.delete_column(*col) {
+                *col = (*col).min(table.columns() - 1);
+            }
+        });
+    }
+
+    /// Applies `change` to the table the cursor is in, with the cursor's
+    /// row and column, then writes it back aligned and puts the cursor at
+    /// the end of the text of the cell it left selected.
+    fn edit_table(&mut self, change: impl FnOnce(&mut Table, &mut (usize, usize))) {
+        let lines = self.lines();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let Some(range) = table::bounds(&lines, self.cursor.line) else {
+            return;
+        };
+        let mut table = Table::parse(&lines[range.clone()]);
+        let row = self.cursor.line - range.start;
+        let col = table::cell_at(lines[self.cursor.line], self.cursor.col);
+        let mut cell = (row, col.min(table.columns() - 1));
+        change(&mut table, &mut cell);
+
+        let aligned = table.render();
+        let start = range.start;
+        self.replace_lines(range, &aligned);
+        self.cursor = Position {
+            line: start + cell.0,
+            col: table.cell_col(cell.0, cell.1),
+        };
+        self.selection = None;
+        self.autocomplete = None;
+        self.table = Some(start);
+    }
+
+    /// Replaces lines `range` with `lines`, as one undo step if that
+    /// changes anything.
+    fn replace_lines(&mut self, range: std::ops::Range<usize>, lines: &[String]) {
+        let start = self.content.line_to_char(range.start);
+        let end = self.line_col_to_char_idx(range.end - 1, usize::MAX);
+        let text = lines.join("\n");
+        if self.content.slice(start..end) == text.as_str() {
+            return;
+        }
+        self.save_undo_snapshot();
+        self.content.remove(start..end);
+        self.content.insert(start, &text);
+        self.dirty = true;
+    }
+
+    /// The buffer's lines without their line breaks.
+    fn lines(&self) -> Vec<String> {
+        self.content
+            .lines()
+            .map(|line| line.to_string().trim_end_matches(['\n', '\r']).to_string())
+            .collect()
+    }
+
+    /// Target of the `[[wiki-link]]` the edit cursor is on or touching.
+    pub fn link_at_cursor(&self) -> Option<String> {
+        if self.cursor.line >= self.content.len_lines() {
+            return None;
+        }
+        let line: Vec<char> = self.content.line(self.cursor.line).chars().collect();
+        let col = self.cursor.col;
+
+        let mut i = 0;
+        while i + 1 < line.len() {
+            if line[i] != '[' || line[i + 1] != '[' {
+                i += 1;
+                continue;
+            }
+            let close = (i + 2..line.len().saturating_sub(1))
+                .find(|&j| line[j] == ']' && line[j + 1] == ']')?;
+            if (i..=close + 2).contains(&col) {
+                let inner: String = line[i + 2..close].iter().collect();
+                let target = inner.split('|').next().unwrap_or(&inner).trim();
+                return (!target.is_empty()).then(|| target.to_string());
+            }
+            i = close + 2;
+        }
+        None
+    }
+
+    /// Moves `line` one heading level deeper (`deeper`) or shallower, wrapping
+    /// through plain text: `###### ` ↔ text ↔ `# `. Returns the new level.
+    pub fn cycle_heading(&mut self, line: usize, deeper: bool) -> usize {
+        let text: String = self.content.line(line).chars().collect();
+        let hashes = text.chars().take_while(|&c| c == '#').count();
+        let rest = text.chars().nth(hashes);
+        let (level, prefix_len) = match rest {
+            Some(' ') if (1..=6).contains(&hashes) => (hashes, hashes + 1),
+            None | Some('\n') | Some('\r') if (1..=6).contains(&hashes) => (hashes, hashes),
+            _ => (0, 0),
+        };
+
+        let new_level = match (deeper, level) {
+            (true, 6) => 0,
+            (true, n) => n + 1,
+            (false, 0) => 6,
+            (false, n) => n - 1,
+        };
+        let prefix = if new_level == 0 {
+            String::new()
+        } else {
+            format!("{} ", "#".repeat(new_level))
+        };
+
+        self.save_undo_snapshot();
+        let start = self.content.line_to_char(line);
+        self.content.remove(start..start + prefix_len);
+        self.content.insert(start, &prefix);
+
+        let delta = prefix.chars().count() as isize - prefix_len as isize;
+        self.shift_columns(line, delta);
+        if self.read_cursor.line == line {
+            self.read_cursor.col = self.read_cursor.col.saturating_add_signed(delta);
+        }
+        self.dirty = true;
+        new_level
+    }
+
     pub fn paste_text_at_read_cursor(&mut self, text: &str) {
         self.save_undo_snapshot();
         // Insert below the current read_cursor line
@@ -712,14 +1450,20 @@ impl ViewerState {
     }
 
     fn check_autocomplete_trigger(&mut self) {
-        // Check if we just typed the second '[' of '[['
+        // Check if we just typed the second character of '[[' or '[@'
         if self.cursor.col >= 2 {
             let char_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
             if char_idx >= 2 {
                 let prev_two = self.content.slice((char_idx - 2)..char_idx).to_string();
-                if prev_two == "[[" {
+                let kind = match prev_two.as_str() {
+                    "[[" => Some(CompletionKind::Note),
+                    "[@" => Some(CompletionKind::Citation),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
                     // Trigger autocomplete
                     self.autocomplete = Some(AutocompleteState {
+                        kind,
                         trigger_pos: Position {
                             line: self.cursor.line,
                             col: self.cursor.col - 2,
@@ -737,6 +1481,7 @@ impl ViewerState {
             // Extract values before mutable operations
             let trigger_line = ac.trigger_pos.line;
             let trigger_col = ac.trigger_pos.col;
+            let kind = ac.kind;
             let trigger_idx = self.line_col_to_char_idx(trigger_line, trigger_col);
             let cursor_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
 
@@ -748,7 +1493,12 @@ impl ViewerState {
                 String::new()
             };
 
-            let should_close = new_query.contains("]]") || self.cursor.line != trigger_line;
+            let ended = match kind {
+                CompletionKind::Note => new_query.contains("]]"),
+                // Cite keys have no spaces
+                CompletionKind::Citation => new_query.contains([']', ';', ' ']),
+            };
+            let should_close = ended || self.cursor.line != trigger_line;
 
             // Now do the mutable operations
             if should_close {
@@ -761,81 +1511,181 @@ impl ViewerState {
 
     pub fn update_autocomplete_matches(&mut self, vault: &crate::core::Vault) {
         if let Some(ref mut ac) = self.autocomplete {
-            ac.matches.clear();
             ac.selected = 0;
-
-            let query_lower = ac.query.to_lowercase();
-
-            // Simple fuzzy matching - collect all notes that contain query chars in order
-            for (path, note) in &vault.notes {
-                let name = note.title.to_lowercase();
-                if query_lower.is_empty() || core::fuzzy_match(&query_lower, &name) {
-                    ac.matches.push((path.clone(), note.title.clone()));
-                }
-            }
-
-            // Sort by relevance (starts with query first, then alphabetically)
-            ac.matches.sort_by(|a, b| {
-                let a_starts = a.1.to_lowercase().starts_with(&query_lower);
-                let b_starts = b.1.to_lowercase().starts_with(&query_lower);
-                match (a_starts, b_starts) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.1.cmp(&b.1),
-                }
-            });
-
-            // Limit to 10 results
-            ac.matches.truncate(10);
+            let query = text::fold(&ac.query);
+            ac.matches = match ac.kind {
+                CompletionKind::Note => note_completions(vault, &query),
+                CompletionKind::Citation => citation_completions(vault, &query),
+            };
         }
     }
 
     pub fn autocomplete_next(&mut self) {
-        if let Some(ref mut ac) = self.autocomplete {
-            if !ac.matches.is_empty() {
-                ac.selected = (ac.selected + 1) % ac.matches.len();
-            }
+        if let Some(ref mut ac) = self.autocomplete
+            && !ac.matches.is_empty()
+        {
+            ac.selected = (ac.selected + 1) % ac.matches.len();
         }
     }
 
     pub fn autocomplete_prev(&mut self) {
-        if let Some(ref mut ac) = self.autocomplete {
-            if !ac.matches.is_empty() {
-                ac.selected = if ac.selected == 0 {
-                    ac.matches.len() - 1
-                } else {
-                    ac.selected - 1
-                };
-            }
+        if let Some(ref mut ac) = self.autocomplete
+            && !ac.matches.is_empty()
+        {
+            ac.selected = if ac.selected == 0 {
+                ac.matches.len() - 1
+            } else {
+                ac.selected - 1
+            };
         }
     }
 
     pub fn autocomplete_accept(&mut self) {
-        if let Some(ac) = self.autocomplete.take() {
-            if let Some((path, _)) = ac.matches.get(ac.selected) {
-                // Remove the [[ and any query text
-                let trigger_idx =
-                    self.line_col_to_char_idx(ac.trigger_pos.line, ac.trigger_pos.col);
-                let cursor_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
-                self.content.remove(trigger_idx..cursor_idx);
-
-                // Insert the completed link
-                let link_name = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Unknown");
-                let completion = format!("[[{}]]", link_name);
-                self.content.insert(trigger_idx, &completion);
-
-                // Move cursor after the ]]
-                self.cursor.line = ac.trigger_pos.line;
-                self.cursor.col = ac.trigger_pos.col + completion.len();
-                self.dirty = true;
+        if let Some(ac) = self.autocomplete.take()
+            && let Some(completion) = ac.matches.get(ac.selected)
+        {
+            // Remove the [[ or [@ and any query text
+            let trigger_idx = self.line_col_to_char_idx(ac.trigger_pos.line, ac.trigger_pos.col);
+            let cursor_idx = self.line_col_to_char_idx(self.cursor.line, self.cursor.col);
+            self.content.remove(trigger_idx..cursor_idx);
+
+            // Insert the completed link or citation
+            let completion = &completion.text;
+            self.content.insert(trigger_idx, completion);
+
+            // Drop the closing brackets already typed or auto-paired after the query
+            let closing = match ac.kind {
+                CompletionKind::Note => "]]",
+                CompletionKind::Citation => "]",
+            };
+            let end = trigger_idx + completion.chars().count();
+            let after = end + closing.len();
+            if after <= self.content.len_chars() && self.content.slice(end..after) == closing {
+                self.content.remove(end..after);
             }
+
+            // Move cursor after the closing bracket
+            self.cursor.line = ac.trigger_pos.line;
+            self.cursor.col = ac.trigger_pos.col + completion.chars().count();
+            self.dirty = true;
         }
     }
 }
 
+/// Notes whose title or an alias contains the folded `query`'s characters
+/// in order, those starting with it first, as `[[wiki-links]]`. At most 10.
+fn note_completions(vault: &crate::core::Vault, query: &str) -> Vec<Completion> {
+    let mut matches: Vec<(&PathBuf, &str, bool)> = vault
+        .notes
+        .iter()
+        .filter_map(|(path, note)| {
+            note.match_name(query)
+                .map(|(name, is_alias)| (path, name, is_alias))
+        })
+        .collect();
+
+    // Sort by relevance (starts with query first, then alphabetically)
+    matches.sort_by(|a, b| {
+        let a_starts = text::fold(a.1).starts_with(query);
+        let b_starts = text::fold(b.1).starts_with(query);
+        match (a_starts, b_starts) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => text::collate(a.1, b.1),
+        }
+    });
+
+    // Limit to 10 results
+    matches.truncate(10);
+    matches
+        .into_iter()
+        .map(|(path, name, is_alias)| {
+            let link_name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown");
+            // An alias stays as the display text of a link to the file
+            let text = if is_alias {
+                format!("[[{}|{}]]", link_name, name)
+            } else {
+                format!("[[{}]]", link_name)
+            };
+            Completion {
+                label: name.to_string(),
+                text,
+                is_alias,
+            }
+        })
+        .collect()
+}
+
+/// Bibliography entries whose key, authors or title contain the folded
+/// `query`, keys starting with it first, as `[@key]` citations. At most 10.
+fn citation_completions(vault: &crate::core::Vault, query: &str) -> Vec<Completion> {
+    let mut matches: Vec<_> = vault
+        .bibliography()
+        .entries()
+        .iter()
+        .filter(|entry| {
+            [entry.key.as_str(), entry.title()]
+                .into_iter()
+                .chain(entry.authors.iter().map(String::as_str))
+                .any(|field| text::fold(field).contains(query))
+        })
+        .collect();
+    // Entries are in key order already; the sort is stable
+    matches.sort_by_key(|entry| !text::fold(&entry.key).starts_with(query));
+    matches.truncate(10);
+    matches
+        .into_iter()
+        .map(|entry| Completion {
+            label: format!("{}  {}", entry.key, entry.label()),
+            text: format!("[@{}]", entry.key),
+            is_alias: false,
+        })
+        .collect()
+}
+
+/// The character auto-inserted after an opening one.
+fn closing_pair(c: char) -> Option<char> {
+    match c {
+        '[' => Some(']'),
+        '(' => Some(')'),
+        '`' => Some('`'),
+        _ => None,
+    }
+}
+
+/// Strips `$N` tab stops out of a snippet body, returning the text and the
+/// char offsets of the stops in visiting order: `$1` to `$9`, then `$0`.
+/// `\$` is a literal dollar.
+fn parse_snippet(body: &str) -> (String, Vec<usize>) {
+    let mut text = String::new();
+    let mut stops: Vec<(u32, usize)> = Vec::new();
+    let mut len = 0;
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next_if_eq(&'$').is_some() {
+            text.push('$');
+            len += 1;
+            continue;
+        }
+        if c == '$'
+            && let Some(n) = chars.peek().and_then(|d| d.to_digit(10))
+        {
+            chars.next();
+            stops.push((if n == 0 { 10 } else { n }, len));
+            continue;
+        }
+        text.push(c);
+        len += 1;
+    }
+
+    stops.sort_by_key(|&(n, _)| n);
+    (text, stops.into_iter().map(|(_, offset)| offset).collect())
+}
+
 fn is_word_separator(ch: char) -> bool {
     matches!(
         ch,
@@ -0,0 +1,77 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::ui::theme::Theme;
+
+/// Keys that may follow `g` in the viewer and what each does.
+const G_CONTINUATIONS: &[(&str, &str)] = &[
+    ("g", "first line"),
+    ("t", "next tab"),
+    ("T", "previous tab"),
+    ("c", "close tab"),
+    ("u", "folder in browser"),
+];
+
+/// A small popup just inside the bottom-right corner of `area` listing what the
+/// pending `g` prefix can be followed by. A pending count is shown in the
+/// title, since `gg` jumps to that line instead.
+pub fn render(frame: &mut Frame, area: Rect, count: usize, t: &Theme) {
+    let key_width = G_CONTINUATIONS
+        .iter()
+        .map(|(k, _)| k.len())
+        .max()
+        .unwrap_or(0);
+    let action_width = G_CONTINUATIONS
+        .iter()
+        .map(|(_, a)| a.len())
+        .max()
+        .unwrap_or(0);
+    let title = match count {
+        0 => " g ".to_string(),
+        n => format!(" {}g ", n),
+    };
+
+    let popup_width = ((key_width + action_width + 6) as u16)
+        .max(title.len() as u16 + 2)
+        .min(area.width);
+    let popup_height = (G_CONTINUATIONS.len() as u16 + 2).min(area.height.saturating_sub(1));
+    let popup_area = Rect::new(
+        area.x + area.width.saturating_sub(popup_width + 2),
+        area.y + area.height.saturating_sub(popup_height + 1),
+        popup_width,
+        popup_height,
+    );
+
+    frame.render_widget(Clear, popup_area);
+
+    let lines: Vec<Line> = G_CONTINUATIONS
+        .iter()
+        .map(|(key, action)| {
+            let action = match (*key, count) {
+                ("g", n) if n > 0 => format!("line {}", n),
+                _ => action.to_string(),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<width$}  ", key, width = key_width),
+                    Style::default().fg(t.aqua).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(action, Style::default().fg(t.fg1)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    frame.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
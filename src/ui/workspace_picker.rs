@@ -0,0 +1,114 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+use crate::ui::theme::Theme;
+
+/// Saved workspaces to restore, and the name being typed while saving the
+/// current one.
+pub struct WorkspacePickerState {
+    pub names: Vec<String>,
+    pub selected: usize,
+    pub naming: Option<String>,
+    list_state: ListState,
+}
+
+impl WorkspacePickerState {
+    pub fn new(names: Vec<String>) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+
+        Self {
+            names,
+            selected: 0,
+            naming: None,
+            list_state,
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.names.len() {
+            self.selected += 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    pub fn selected_name(&self) -> Option<&str> {
+        self.names.get(self.selected).map(String::as_str)
+    }
+
+    /// Replaces the list after a save or delete, keeping the selection in range.
+    pub fn set_names(&mut self, names: Vec<String>) {
+        self.names = names;
+        self.selected = self.selected.min(self.names.len().saturating_sub(1));
+        self.list_state.select(Some(self.selected));
+    }
+}
+
+pub fn render(frame: &mut Frame, area: Rect, state: &WorkspacePickerState, t: &Theme) {
+    let popup_width = 50.min(area.width.saturating_sub(4));
+    let popup_height = (state.names.len().max(1) as u16 + 2).min(area.height.saturating_sub(4));
+
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    frame.render_widget(Clear, popup_area);
+
+    let bottom = match &state.naming {
+        Some(name) => Line::from(vec![
+            Span::styled(" Save as: ", Style::default().fg(t.search_prompt)),
+            Span::styled(format!("{}_ ", name), Style::default().fg(t.fg1)),
+        ]),
+        None => Line::from(Span::styled(
+            " Enter: restore  s: save current  d: delete ",
+            Style::default().fg(t.fg4).add_modifier(Modifier::ITALIC),
+        )),
+    };
+    let block = Block::default()
+        .title(" Workspaces ")
+        .title_bottom(bottom)
+        .borders(Borders::ALL)
+        .border_set(t.borders)
+        .border_style(Style::default().fg(t.border_overlay))
+        .style(Style::default().bg(t.bg0));
+
+    if state.names.is_empty() {
+        let hint = List::new([ListItem::new(Span::styled(
+            "  Nothing saved yet",
+            Style::default().fg(t.empty_hint),
+        ))])
+        .block(block);
+        frame.render_widget(hint, popup_area);
+        return;
+    }
+
+    let items: Vec<ListItem> = state
+        .names
+        .iter()
+        .map(|name| {
+            ListItem::new(Span::styled(
+                format!("  {}", name),
+                Style::default().fg(t.fg1),
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(t.selection_style());
+
+    let mut list_state = state.list_state.clone();
+    frame.render_stateful_widget(list, popup_area, &mut list_state);
+}